@@ -0,0 +1,98 @@
+// Parser throughput and packet-to-LED latency
+//
+// Run with `cargo bench -p g27-led-bridge-core`. The wheel is a `MockWheel`, so the
+// numbers cover the bridge's own work up to the HID write, not the USB transfer.
+
+use std::cell::Cell;
+use std::hint::black_box;
+use std::net::UdpSocket;
+use std::rc::Rc;
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use g27_led_bridge_core::leds::LEDS;
+use g27_led_bridge_core::simulator::encode_packet;
+use g27_led_bridge_core::{BridgeBuilder, GameType, LoopControl, MockWheel, TelemetryFrame, UdpSource};
+
+const IDLE_RPM: f32 = 900.0;
+const MAX_RPM: f32 = 8000.0;
+
+/// Packet of `size` bytes at `rpm`, padded like the larger formats of the game
+fn packet(game_type: GameType, size: usize, rpm: f32) -> Vec<u8> {
+    let mut packet = encode_packet(game_type, rpm, MAX_RPM, IDLE_RPM, true);
+    packet.resize(size, 0);
+    packet
+}
+
+fn parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+    for game_type in GameType::ALL {
+        let parser = game_type.parser();
+        for &size in game_type.packet_sizes() {
+            let packet = packet(game_type, size, 6000.0);
+            let id = BenchmarkId::new(format!("{:?}", game_type), size);
+            group.throughput(Throughput::Bytes(size as u64));
+            group.bench_with_input(id, &packet, |b, packet| b.iter(|| parser.parse_rpm_data(black_box(packet))));
+        }
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("frame");
+    for game_type in GameType::ALL {
+        let parser = game_type.parser();
+        let packet = packet(game_type, game_type.packet_sizes()[0], 6000.0);
+        group.bench_function(format!("{:?}", game_type), |b| {
+            b.iter(|| TelemetryFrame::parse(game_type, parser, black_box(&packet)))
+        });
+    }
+    group.finish();
+}
+
+fn packet_to_led(c: &mut Criterion) {
+    let mut group = c.benchmark_group("packet_to_led");
+    for game_type in GameType::ALL {
+        let parser = game_type.parser();
+        let packet = packet(game_type, game_type.packet_sizes()[0], MAX_RPM);
+
+        // A fresh wheel per run, so every packet changes the LEDs and causes a HID write
+        group.bench_function(format!("{:?}", game_type), |b| {
+            b.iter_batched_ref(
+                || LEDS::new(MockWheel::new()),
+                |leds| leds.update(black_box(&packet), parser),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+/// Latency from a packet arriving on the UDP socket to its LED report being written
+fn udp_to_led(c: &mut Criterion) {
+    let game_type = GameType::ForzaHorizon5;
+    let source = UdpSource::bind("127.0.0.1:0").expect("bind benchmark listener");
+    let target = source.local_addr().expect("listener address");
+    let sender = UdpSocket::bind("127.0.0.1:0").expect("bind benchmark sender");
+
+    // Let the bridge loop run for exactly one packet per call to `run`
+    let pending = Rc::new(Cell::new(false));
+    let control = pending.clone();
+    let mut bridge = BridgeBuilder::new(game_type)
+        .control(move || if control.replace(false) { LoopControl::Run } else { LoopControl::Stop })
+        .build(source, MockWheel::new());
+
+    // Alternate between revs that light different LEDs, so each packet is written
+    let size = game_type.packet_sizes()[0];
+    let packets = [packet(game_type, size, 5000.0), packet(game_type, size, MAX_RPM)];
+    let mut sent = 0usize;
+
+    c.bench_function("udp_to_led", |b| {
+        b.iter(|| {
+            sender.send_to(&packets[sent % 2], target).expect("send benchmark packet");
+            sent += 1;
+            pending.set(true);
+            bridge.run().expect("bridge loop")
+        })
+    });
+}
+
+criterion_group!(benches, parse, packet_to_led, udp_to_led);
+criterion_main!(benches);
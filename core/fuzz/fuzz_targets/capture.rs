@@ -0,0 +1,18 @@
+// Captures come from Wireshark and other tools as often as from the bridge: reading a
+// damaged or hostile file must fail with an error, never panic
+
+#![no_main]
+
+use g27_led_bridge_core::CaptureReader;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|file: &[u8]| {
+    // Port 0 would skip most packets; take the port from the input instead
+    let Some((port, file)) = file.split_first_chunk::<2>() else {
+        return;
+    };
+    let Ok(mut capture) = CaptureReader::new(file, u16::from_le_bytes(*port)) else {
+        return;
+    };
+    while let Ok(Some(_)) = capture.next_packet() {}
+});
@@ -0,0 +1,21 @@
+// Telemetry arrives on an open UDP port: every parser, and the LED mapping fed by
+// it, must handle any packet without panicking
+
+#![no_main]
+
+use g27_led_bridge_core::leds::LEDS;
+use g27_led_bridge_core::{GameType, MockWheel, TelemetryFrame};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|packet: &[u8]| {
+    let _ = GameType::from_packet_size(packet.len());
+
+    for game_type in GameType::ALL {
+        let parser = game_type.parser();
+        let frame = TelemetryFrame::parse(game_type, parser, packet);
+
+        let mut leds = LEDS::new(MockWheel::new());
+        let _ = leds.update_frame(&frame);
+        let _ = leds.update(packet, parser);
+    }
+});
@@ -0,0 +1,14 @@
+// Recordings are shared in bug reports: reading a damaged or hostile file must fail
+// with an error, never panic
+
+#![no_main]
+
+use g27_led_bridge_core::RecordingReader;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|file: &[u8]| {
+    let Ok(mut recording) = RecordingReader::new(file) else {
+        return;
+    };
+    while let Ok(Some(_)) = recording.next_packet() {}
+});
@@ -0,0 +1,517 @@
+// Async bridge loop on tokio (feature "tokio")
+//
+// Same pipeline as `Bridge`, but split into a source task publishing frames on the
+// telemetry bus and an LED task consuming them. Both await a control channel next to
+// their input, so mute and stop requests take effect immediately instead of waiting
+// for the next packet or read timeout, and dropping the control sender stops the loop.
+//
+// The publishers take any `AsyncTelemetrySource`: the UDP listener the games send to,
+// or a TCP stream of length-prefixed packets (`AsyncTcpSource`).
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{debug, error, info, warn};
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::net::{ToSocketAddrs, UdpSocket};
+use tokio::sync::watch;
+
+use crate::bridge::{LoopControl, MAX_PACKET_SIZE};
+use crate::bus::{Subscription, TelemetryBus};
+use crate::capture::Capture;
+use crate::detect::PacketSizeMonitor;
+use crate::forward::ForwardFormat;
+use crate::frame::TelemetryFrame;
+use crate::leds::LEDS;
+use crate::output::LedOutput;
+use crate::recording::{Recorder, ReplaySource};
+use crate::source::SenderFilter;
+use crate::telemetry::{GameType, TelemetryParser};
+use crate::util::{catch_panic, DR2G27Error, DR2G27Result};
+
+/// A provider of raw telemetry packets for the async publishers
+pub trait AsyncTelemetrySource {
+    /// Receive one packet into `buf`, returning its size and who sent it. Must be cancel-safe,
+    /// as the publishers race it against control changes.
+    fn recv_from(&self, buf: &mut [u8]) -> impl Future<Output = io::Result<(usize, SocketAddr)>>;
+
+    /// Senders packets are accepted from, if limited
+    fn sender_filter(&self) -> Option<&SenderFilter>;
+
+    /// Short description for logs, e.g. the listen address
+    fn describe(&self) -> String;
+}
+
+/// Telemetry received over UDP without blocking the runtime
+pub struct AsyncUdpSource {
+    socket: UdpSocket,
+    filter: Option<Arc<SenderFilter>>,
+}
+
+impl AsyncUdpSource {
+    /// Bind a UDP listener
+    pub async fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Ok(Self {
+            socket: UdpSocket::bind(addr).await?,
+            filter: None,
+        })
+    }
+
+    /// Bind a UDP listener with SO_REUSEADDR set, so it can share the port with other programs
+    /// that allow it. On Windows this also takes a port another socket already holds (unless
+    /// that socket claimed it exclusively), and each unicast packet then reaches only one of
+    /// the sockets; multicast and broadcast packets reach all of them.
+    pub async fn bind_shared(addr: SocketAddr) -> io::Result<Self> {
+        Ok(Self {
+            socket: bind_socket(addr, true)?,
+            filter: None,
+        })
+    }
+
+    /// Bind to `port` on every interface and join the multicast `group`, receiving what is sent
+    /// to the group alongside other listeners. IPv4 groups are joined on `interface`, or on the
+    /// system's choice without one; IPv6 groups always use the default interface.
+    /// `reuse_address` shares the port as `bind_shared` does.
+    pub async fn bind_multicast(
+        group: IpAddr,
+        port: u16,
+        interface: Option<IpAddr>,
+        reuse_address: bool,
+    ) -> io::Result<Self> {
+        if !group.is_multicast() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} is not a multicast address", group),
+            ));
+        }
+        let socket = match (group, interface) {
+            (IpAddr::V4(group), None | Some(IpAddr::V4(_))) => {
+                let interface = match interface {
+                    Some(IpAddr::V4(interface)) => interface,
+                    _ => Ipv4Addr::UNSPECIFIED,
+                };
+                let socket = bind_socket((Ipv4Addr::UNSPECIFIED, port).into(), reuse_address)?;
+                socket.join_multicast_v4(group, interface)?;
+                socket
+            }
+            (IpAddr::V6(group), None) => {
+                let socket = bind_socket((Ipv6Addr::UNSPECIFIED, port).into(), reuse_address)?;
+                socket.join_multicast_v6(&group, 0)?;
+                socket
+            }
+            (group, Some(interface)) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("cannot join {} on interface {}", group, interface),
+                ));
+            }
+        };
+        Ok(Self { socket, filter: None })
+    }
+
+    /// Drop packets from senders `filter` does not accept
+    pub fn with_sender_filter(mut self, filter: Arc<SenderFilter>) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Address the listener is bound to
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+}
+
+impl AsyncTelemetrySource for AsyncUdpSource {
+    fn recv_from(&self, buf: &mut [u8]) -> impl Future<Output = io::Result<(usize, SocketAddr)>> {
+        self.socket.recv_from(buf)
+    }
+
+    fn sender_filter(&self) -> Option<&SenderFilter> {
+        self.filter.as_deref()
+    }
+
+    fn describe(&self) -> String {
+        match self.local_addr() {
+            Ok(addr) => format!("UDP {}", addr),
+            Err(_) => "UDP".to_string(),
+        }
+    }
+}
+
+/// UDP socket bound to `addr` for the runtime, with SO_REUSEADDR set before binding if asked
+fn bind_socket(addr: SocketAddr, reuse_address: bool) -> io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::for_address(addr), Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(reuse_address)?;
+    socket.bind(&addr.into())?;
+    socket.set_nonblocking(true)?;
+    UdpSocket::from_std(socket.into())
+}
+
+/// A telemetry-to-LED pipeline driven by a tokio task, created with `BridgeBuilder::build_async`.
+///
+/// Received packets are parsed into frames and published on a `TelemetryBus`; the LED
+/// mapping is one subscriber of that bus, and other consumers can subscribe alongside it.
+pub struct AsyncBridge<O: LedOutput> {
+    pub(crate) game_type: GameType,
+    pub(crate) dump_unknown: bool,
+    pub(crate) source: AsyncUdpSource,
+    pub(crate) bus: TelemetryBus,
+    pub(crate) leds: LEDS<O>,
+}
+
+impl<O: LedOutput> AsyncBridge<O> {
+    /// Bus the frames are published on; subscribe before calling `run` to see every frame
+    pub fn bus(&self) -> &TelemetryBus {
+        &self.bus
+    }
+
+    /// Run until `control` holds `LoopControl::Stop` or its sender is dropped (`Ok`),
+    /// or the socket or output fails (`Err`)
+    pub async fn run(&mut self, control: watch::Receiver<LoopControl>) -> DR2G27Result {
+        let frames = self.bus.subscribe("wheel LEDs");
+        let publish = publish_frames(&self.source, self.game_type, self.dump_unknown, &self.bus, control.clone());
+        let display = drive_leds(&mut self.leds, frames, control);
+        tokio::try_join!(publish, display).map(|_| ())
+    }
+
+    /// Turn the LEDs off, e.g. after the loop stopped for shutdown
+    pub fn clear(&mut self) -> DR2G27Result {
+        self.leds.clear()
+    }
+}
+
+/// Receive packets from `source`, parse them as `game_type` and publish them as frames on `bus`
+/// until `control` holds `LoopControl::Stop` or its sender is dropped. `Mute` is left to consumers.
+/// With `dump_unknown`, packets the game does not send are logged as hex dumps.
+pub async fn publish_frames(
+    source: &impl AsyncTelemetrySource,
+    game_type: GameType,
+    dump_unknown: bool,
+    bus: &TelemetryBus,
+    control: watch::Receiver<LoopControl>,
+) -> DR2G27Result {
+    let (_, game) = watch::channel(game_type);
+    publish_frames_switching(source, game, dump_unknown, bus, control).await
+}
+
+/// Like `publish_frames`, but parses packets as the game currently held by `game`,
+/// so the game can change without rebinding the socket
+pub async fn publish_frames_switching(
+    source: &impl AsyncTelemetrySource,
+    game: watch::Receiver<GameType>,
+    dump_unknown: bool,
+    bus: &TelemetryBus,
+    control: watch::Receiver<LoopControl>,
+) -> DR2G27Result {
+    let sizes = PacketSizeMonitor::new(*game.borrow()).dump_unknown(dump_unknown);
+    receive_frames(source, game, None, sizes, bus, control).await
+}
+
+/// Like `publish_frames_switching`, but switches to the game whose packets keep arriving
+/// and sends it on `game`, so the selection follows whichever game is running
+pub async fn publish_frames_detecting(
+    source: &impl AsyncTelemetrySource,
+    game: &watch::Sender<GameType>,
+    dump_unknown: bool,
+    bus: &TelemetryBus,
+    control: watch::Receiver<LoopControl>,
+) -> DR2G27Result {
+    let sizes = PacketSizeMonitor::detecting(*game.borrow()).dump_unknown(dump_unknown);
+    receive_frames(source, game.subscribe(), Some(game), sizes, bus, control).await
+}
+
+/// Receive loop shared by the publishers; `detected` receives auto-detected games
+async fn receive_frames(
+    source: &impl AsyncTelemetrySource,
+    mut game: watch::Receiver<GameType>,
+    detected: Option<&watch::Sender<GameType>>,
+    mut sizes: PacketSizeMonitor,
+    bus: &TelemetryBus,
+    mut control: watch::Receiver<LoopControl>,
+) -> DR2G27Result {
+    let mut game_type = *game.borrow_and_update();
+    let mut data = vec![0u8; MAX_PACKET_SIZE];
+    // Rejected senders already logged at warning level
+    let mut rejected_senders: HashSet<IpAddr> = HashSet::new();
+
+    info!(
+        "Listening for {} telemetry on {} (expecting {} byte packets)",
+        game_type.game_name(),
+        source.describe(),
+        game_type.expected_packet_size()
+    );
+    info!("Waiting for telemetry data from the game...");
+
+    loop {
+        // Muting is up to each consumer; the source keeps publishing
+        if *control.borrow_and_update() == LoopControl::Stop {
+            return Ok(());
+        }
+
+        // Apply stop requests and game switches before packets that arrived after them
+        tokio::select! {
+            biased;
+            changed = control.changed() => {
+                // Nobody is left to stop the loop
+                if changed.is_err() {
+                    return Ok(());
+                }
+            }
+            Ok(()) = game.changed() => {
+                game_type = *game.borrow_and_update();
+                sizes.set_game(game_type);
+                info!(
+                    "Switched to {} telemetry (expecting {} byte packets)",
+                    game_type.game_name(),
+                    game_type.expected_packet_size()
+                );
+            }
+            received = source.recv_from(&mut data) => {
+                let (received_size, sender) = match received {
+                    Ok(received) => received,
+                    Err(e) => {
+                        error!("Telemetry receive error: {}", e);
+                        return Err(DR2G27Error::Receive(e));
+                    }
+                };
+                if source.sender_filter().is_some_and(|filter| !filter.accepts(sender.ip())) {
+                    if rejected_senders.insert(sender.ip()) {
+                        warn!("Ignoring telemetry from {}, which is not an allowed sender", sender.ip());
+                    } else {
+                        debug!("Ignored {} byte packet from {}", received_size, sender);
+                    }
+                    continue;
+                }
+
+                if let (Some(likely), Some(detected)) = (sizes.observe(&data[..received_size]), detected) {
+                    detected.send_replace(likely);
+                    // Already switched here; do not announce it again as a switch
+                    game.borrow_and_update();
+                    game_type = likely;
+                    sizes.set_game(game_type);
+                    info!(
+                        "Detected {} telemetry (expecting {} byte packets)",
+                        game_type.game_name(),
+                        game_type.expected_packet_size()
+                    );
+                }
+                let packet = &data[..received_size];
+                match check_packet_size(game_type, packet) {
+                    Ok(()) => {
+                        let mut frame = parse_guarded(game_type, packet)?;
+                        frame.sender = Some(sender);
+                        bus.publish(frame);
+                    }
+                    Err(e) => {
+                        debug!("Dropped packet from {}: {}", sender, e);
+                        bus.count_malformed();
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Fail with `ParseError` if `packet` is too short to hold `game_type`'s fields
+fn check_packet_size(game_type: GameType, packet: &[u8]) -> DR2G27Result {
+    let expected_size = game_type.expected_packet_size();
+    if packet.len() < expected_size {
+        return Err(DR2G27Error::ParseError {
+            game: game_type.game_name(),
+            reason: format!("{} bytes, expected at least {}", packet.len(), expected_size),
+        });
+    }
+    Ok(())
+}
+
+/// Parse `packet` as `game_type`, failing instead of unwinding if the parser panics
+fn parse_guarded(game_type: GameType, packet: &[u8]) -> Result<TelemetryFrame, DR2G27Error> {
+    catch_panic("telemetry parser", || TelemetryFrame::parse(game_type, &game_type, packet)).inspect_err(|e| {
+        error!("{} parser failed on a {} byte packet: {}", game_type.game_name(), packet.len(), e)
+    })
+}
+
+/// Map frames from `frames` to `leds`, holding them off while `control` is `Mute`,
+/// until `control` holds `LoopControl::Stop`, its sender is dropped or the bus closes.
+/// Frames that queued up during a slow write are skipped for the newest.
+pub async fn drive_leds<O: LedOutput>(
+    leds: &mut LEDS<O>,
+    mut frames: Subscription,
+    mut control: watch::Receiver<LoopControl>,
+) -> DR2G27Result {
+    loop {
+        let state = *control.borrow_and_update();
+        match state {
+            LoopControl::Stop => return Ok(()),
+            LoopControl::Mute => leds.clear()?,
+            LoopControl::Run => {}
+        }
+
+        tokio::select! {
+            changed = control.changed() => {
+                if changed.is_err() {
+                    return Ok(());
+                }
+            }
+            frame = frames.recv_latest() => match frame {
+                Some(frame) if state == LoopControl::Run => leds.update_frame(&frame)?,
+                Some(_) => {}
+                None => return Ok(()),
+            },
+        }
+    }
+}
+
+/// Write frames from `frames` to `recorder`, pausing while `control` is `Mute`, until
+/// `control` holds `LoopControl::Stop`, its sender is dropped or the bus closes.
+/// Everything recorded is flushed to disk before returning.
+pub async fn record_frames(
+    recorder: &mut Recorder,
+    mut frames: Subscription,
+    mut control: watch::Receiver<LoopControl>,
+) -> DR2G27Result {
+    loop {
+        let state = *control.borrow_and_update();
+        if state == LoopControl::Stop {
+            break;
+        }
+
+        tokio::select! {
+            changed = control.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+            }
+            frame = frames.recv() => match frame {
+                Some(frame) if state == LoopControl::Run => {
+                    recorder.record(&frame).map_err(DR2G27Error::Recording)?;
+                }
+                Some(_) => {}
+                None => break,
+            },
+        }
+    }
+    recorder.flush().map_err(DR2G27Error::Recording)
+}
+
+/// Write the packets of frames from `frames` to `capture` for `duration`, pausing while `control`
+/// is `Mute`, stopping early when `control` holds `LoopControl::Stop`, its sender is dropped or
+/// the bus closes. Everything captured is flushed to disk before returning.
+pub async fn capture_frames(
+    capture: &mut Capture,
+    duration: Duration,
+    mut frames: Subscription,
+    mut control: watch::Receiver<LoopControl>,
+) -> DR2G27Result {
+    let deadline = tokio::time::sleep(duration);
+    tokio::pin!(deadline);
+    loop {
+        let state = *control.borrow_and_update();
+        if state == LoopControl::Stop {
+            break;
+        }
+
+        tokio::select! {
+            () = &mut deadline => break,
+            changed = control.changed() => {
+                if changed.is_err() {
+                    break;
+                }
+            }
+            frame = frames.recv() => match frame {
+                Some(frame) if state == LoopControl::Run => {
+                    capture.capture(&frame).map_err(DR2G27Error::Recording)?;
+                }
+                Some(_) => {}
+                None => break,
+            },
+        }
+    }
+    capture.flush().map_err(DR2G27Error::Recording)
+}
+
+/// Send each frame from `frames` in `format` from `socket` to `target`, pausing while
+/// `control` is `Mute`, until `control` holds `LoopControl::Stop`, its sender is dropped
+/// or the bus closes. Failed sends are logged and skipped, so the receiving bridge can
+/// start after the sender.
+pub async fn forward_frames(
+    socket: &UdpSocket,
+    target: SocketAddr,
+    format: ForwardFormat,
+    mut frames: Subscription,
+    mut control: watch::Receiver<LoopControl>,
+) -> DR2G27Result {
+    let mut failing = false;
+    loop {
+        let state = *control.borrow_and_update();
+        if state == LoopControl::Stop {
+            return Ok(());
+        }
+
+        tokio::select! {
+            changed = control.changed() => {
+                if changed.is_err() {
+                    return Ok(());
+                }
+            }
+            frame = frames.recv() => match frame {
+                Some(frame) if state == LoopControl::Run => match socket.send_to(&format.encode(&frame), target).await {
+                    Ok(_) if failing => {
+                        info!("Forwarding to {} works again", target);
+                        failing = false;
+                    }
+                    Ok(_) => {}
+                    Err(e) if !failing => {
+                        warn!("Cannot forward telemetry to {}: {}", target, e);
+                        failing = true;
+                    }
+                    Err(e) => debug!("Forwarding to {} failed: {}", target, e),
+                },
+                Some(_) => {}
+                None => return Ok(()),
+            },
+        }
+    }
+}
+
+/// Publish the packets of `recording` (a session recording or a packet capture) as frames on
+/// `bus` with their recorded timing, `speed` times as fast, until the recording ends,
+/// `control` holds `LoopControl::Stop` or its sender is dropped. Each packet is parsed as
+/// the game the recording gives for it.
+pub async fn replay_frames(
+    recording: &mut impl ReplaySource,
+    speed: f32,
+    bus: &TelemetryBus,
+    mut control: watch::Receiver<LoopControl>,
+) -> DR2G27Result {
+    let started = tokio::time::Instant::now();
+    let mut current = None;
+
+    while let Some(recorded) = recording.next_packet().map_err(DR2G27Error::Recording)? {
+        tokio::select! {
+            _ = tokio::time::sleep_until(started + recorded.elapsed.div_f32(speed)) => {}
+            _ = control.wait_for(|state| *state == LoopControl::Stop) => return Ok(()),
+        }
+
+        let game_type = recorded.game_type;
+        if current != Some(game_type) {
+            info!("Replaying {} telemetry", game_type.game_name());
+            current = Some(game_type);
+        }
+
+        match check_packet_size(game_type, &recorded.packet) {
+            Ok(()) => {
+                bus.publish(parse_guarded(game_type, &recorded.packet)?);
+            }
+            Err(e) => {
+                debug!("Dropped recorded packet: {}", e);
+                bus.count_malformed();
+            }
+        }
+    }
+    Ok(())
+}
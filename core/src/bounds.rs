@@ -0,0 +1,80 @@
+// Bounded runs (feature "tokio")
+//
+// Rig-check scripts run the bridge for a while, or until some telemetry has arrived,
+// and read the result from the exit code. `RunBounds` says when to stop and
+// `wait_for_bounds` counts the frames on the bus until then.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::bus::Subscription;
+
+/// When a bounded run stops; both `None` runs until stopped some other way
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RunBounds {
+    /// Stop after this long
+    pub duration: Option<Duration>,
+    /// Stop once this many frames have been parsed
+    pub packets: Option<u64>,
+}
+
+impl RunBounds {
+    /// True if either bound is set
+    pub fn is_bounded(&self) -> bool {
+        self.duration.is_some() || self.packets.is_some()
+    }
+}
+
+/// How a bounded run ended
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BoundOutcome {
+    /// The duration passed, or the expected frames arrived
+    Completed { received: u64 },
+    /// The duration passed before `expected` frames arrived
+    MissingPackets { received: u64, expected: u64 },
+}
+
+impl fmt::Display for BoundOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoundOutcome::Completed { received } => write!(f, "received {} telemetry packets", received),
+            BoundOutcome::MissingPackets { received, expected } => {
+                write!(f, "received {} of {} expected telemetry packets", received, expected)
+            }
+        }
+    }
+}
+
+/// Count `frames` until one of `bounds` is reached. Never returns for an unbounded run,
+/// so race it against the other ways of stopping.
+pub async fn wait_for_bounds(bounds: RunBounds, mut frames: Subscription) -> BoundOutcome {
+    let deadline = async {
+        match bounds.duration {
+            Some(duration) => tokio::time::sleep(duration).await,
+            None => std::future::pending().await,
+        }
+    };
+    tokio::pin!(deadline);
+
+    let mut received = 0;
+    let mut bus_open = true;
+    loop {
+        if bounds.packets.is_some_and(|expected| received >= expected) {
+            return BoundOutcome::Completed { received };
+        }
+        tokio::select! {
+            biased;
+            _ = &mut deadline => break,
+            frame = frames.recv(), if bus_open => match frame {
+                Some(_) => received += 1,
+                // Nothing more will arrive; only the deadline can end the run now
+                None => bus_open = false,
+            },
+        }
+    }
+
+    match bounds.packets {
+        Some(expected) if received < expected => BoundOutcome::MissingPackets { received, expected },
+        _ => BoundOutcome::Completed { received },
+    }
+}
@@ -0,0 +1,300 @@
+// Bridge loop: telemetry source -> parser -> LED mapping -> output
+//
+// The loop runs on the caller's thread until the control callback asks it to stop
+// or the source/output fails. The callback is polled between packets and on every
+// source timeout, so embedders can mute the LEDs or stop the loop without a packet.
+// With the "tokio" feature, `BridgeBuilder::spawn` runs the async bridge on a thread
+// of its own instead, bound to the configured address and port. It handles telemetry
+// right away and looks for the G27 in the background.
+
+#[cfg(feature = "tokio")]
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+#[cfg(feature = "tokio")]
+use std::thread;
+
+use log::{debug, error, info};
+#[cfg(feature = "tokio")]
+use tokio::sync::watch;
+
+#[cfg(feature = "tokio")]
+use crate::async_bridge::{AsyncBridge, AsyncUdpSource};
+#[cfg(feature = "tokio")]
+use crate::bus::TelemetryBus;
+#[cfg(feature = "tokio")]
+use crate::device::open_g27;
+#[cfg(feature = "tokio")]
+use crate::handle::{BridgeHandle, BridgeStatus};
+#[cfg(feature = "tokio")]
+use crate::output::{PendingOutput, RetryingWheel, WatchedOutput};
+
+use crate::detect::PacketSizeMonitor;
+use crate::leds::LEDS;
+use crate::output::LedOutput;
+use crate::source::TelemetrySource;
+use crate::telemetry::{GameType, TelemetryParser};
+use crate::util::{DR2G27Error, DR2G27Result};
+
+/// Receive buffer size; large enough for every supported game's packets
+pub(crate) const MAX_PACKET_SIZE: usize = 512;
+
+/// What the bridge loop should do next, decided by the embedder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoopControl {
+    /// Keep mapping telemetry to the LEDs
+    Run,
+    /// Keep receiving but hold the LEDs off
+    Mute,
+    /// Return from `Bridge::run`
+    Stop,
+}
+
+/// Configures a `Bridge`, or with the "tokio" feature spawns one:
+///
+/// ```no_run
+/// # #[cfg(feature = "tokio")] {
+/// use g27_led_bridge_core::{BridgeBuilder, GameType, MockWheel};
+///
+/// let handle = BridgeBuilder::new(GameType::ForzaHorizon5)
+///     .port(9999)
+///     .output(MockWheel::new())
+///     .spawn()?;
+/// let mut frames = handle.frames();
+/// if let Some(frame) = frames.blocking_recv() {
+///     println!("{:.0} / {:.0} RPM", frame.rpm, frame.max_rpm);
+/// }
+/// handle.stop()?;
+/// # }
+/// # Ok::<(), g27_led_bridge_core::DR2G27Error>(())
+/// ```
+pub struct BridgeBuilder {
+    game_type: GameType,
+    dump_unknown: bool,
+    control: Box<dyn FnMut() -> LoopControl>,
+    #[cfg(feature = "tokio")]
+    bus: Option<TelemetryBus>,
+    #[cfg(feature = "tokio")]
+    bind_address: IpAddr,
+    #[cfg(feature = "tokio")]
+    port: Option<u16>,
+    #[cfg(feature = "tokio")]
+    output: Option<Box<dyn LedOutput + Send>>,
+}
+
+impl BridgeBuilder {
+    /// Start building a bridge for `game_type`
+    pub fn new(game_type: GameType) -> Self {
+        Self {
+            game_type,
+            dump_unknown: false,
+            control: Box::new(|| LoopControl::Run),
+            #[cfg(feature = "tokio")]
+            bus: None,
+            #[cfg(feature = "tokio")]
+            bind_address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            #[cfg(feature = "tokio")]
+            port: None,
+            #[cfg(feature = "tokio")]
+            output: None,
+        }
+    }
+
+    /// Change the game whose telemetry format is parsed
+    pub fn game(mut self, game_type: GameType) -> Self {
+        self.game_type = game_type;
+        self
+    }
+
+    /// Log a hex dump of packets the game does not send, for diagnosing a wrong game or an
+    /// unknown format (default: off)
+    pub fn dump_unknown(mut self, enabled: bool) -> Self {
+        self.dump_unknown = enabled;
+        self
+    }
+
+    /// Callback polled between packets to mute or stop the loop (default: always run)
+    pub fn control(mut self, control: impl FnMut() -> LoopControl + 'static) -> Self {
+        self.control = Box::new(control);
+        self
+    }
+
+    /// Publish frames on an existing bus, e.g. one shared across listener restarts (async bridge only)
+    #[cfg(feature = "tokio")]
+    pub fn bus(mut self, bus: TelemetryBus) -> Self {
+        self.bus = Some(bus);
+        self
+    }
+
+    /// Address `spawn` listens on (default: 127.0.0.1)
+    #[cfg(feature = "tokio")]
+    pub fn bind_address(mut self, address: IpAddr) -> Self {
+        self.bind_address = address;
+        self
+    }
+
+    /// UDP port `spawn` listens on (default: the game's default port)
+    #[cfg(feature = "tokio")]
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = Some(port);
+        self
+    }
+
+    /// Output `spawn` drives (default: the first connected G27)
+    #[cfg(feature = "tokio")]
+    pub fn output(mut self, output: impl LedOutput + Send + 'static) -> Self {
+        self.output = Some(Box::new(output));
+        self
+    }
+
+    /// Create the bridge reading from `source` and driving `output`
+    pub fn build<S: TelemetrySource, O: LedOutput>(self, source: S, output: O) -> Bridge<S, O> {
+        Bridge {
+            source,
+            leds: LEDS::new(output),
+            game_type: self.game_type,
+            buffer: vec![0u8; MAX_PACKET_SIZE],
+            sizes: PacketSizeMonitor::new(self.game_type).dump_unknown(self.dump_unknown),
+            control: self.control,
+        }
+    }
+
+    /// Create an async bridge reading from `source` and driving `output`.
+    /// It is controlled through the channel passed to `AsyncBridge::run`; the `control` callback is not used.
+    #[cfg(feature = "tokio")]
+    pub fn build_async<O: LedOutput>(self, source: AsyncUdpSource, output: O) -> AsyncBridge<O> {
+        AsyncBridge {
+            game_type: self.game_type,
+            dump_unknown: self.dump_unknown,
+            source,
+            bus: self.bus.unwrap_or_default(),
+            leds: LEDS::new(output),
+        }
+    }
+
+    /// Bind the listener and run the async bridge on a background thread. Binding happens
+    /// before this returns, so its errors are reported here. Without an `output`, the G27 is
+    /// looked for while telemetry is already handled and lit up once it is found.
+    #[cfg(feature = "tokio")]
+    pub fn spawn(mut self) -> Result<BridgeHandle, DR2G27Error> {
+        let addr = SocketAddr::new(self.bind_address, self.port.unwrap_or(self.game_type.default_port()));
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(DR2G27Error::Spawn)?;
+        let source = runtime
+            .block_on(AsyncUdpSource::bind(addr))
+            .map_err(|source| DR2G27Error::BindFailed { addr, source })?;
+        let local_addr = source.local_addr().unwrap_or(addr);
+        let output = match self.output.take() {
+            Some(output) => output,
+            None => Box::new(PendingOutput::search(
+                || {
+                    let wheel = RetryingWheel::new(open_g27()?).reopening(open_g27);
+                    WatchedOutput::spawn(wheel, WatchedOutput::DEFAULT_TIMEOUT)
+                },
+                PendingOutput::<WatchedOutput>::DEFAULT_INTERVAL,
+            )?),
+        };
+
+        let mut bridge = self.build_async(source, output);
+        let bus = bridge.bus().clone();
+        let (control, control_rx) = watch::channel(LoopControl::Run);
+        let (status_tx, status) = watch::channel(BridgeStatus::Running);
+
+        let thread = thread::Builder::new()
+            .name("g27-led-bridge".to_string())
+            .spawn(move || {
+                let result = runtime.block_on(bridge.run(control_rx));
+                // Leave the LEDs dark however the bridge ended
+                let _ = bridge.clear();
+                status_tx.send_replace(match &result {
+                    Ok(()) => BridgeStatus::Stopped,
+                    Err(e) => BridgeStatus::Failed(e.to_string()),
+                });
+                result
+            })
+            .map_err(DR2G27Error::Spawn)?;
+
+        Ok(BridgeHandle {
+            control,
+            status,
+            bus,
+            local_addr,
+            thread,
+        })
+    }
+}
+
+/// A running telemetry-to-LED pipeline
+pub struct Bridge<S: TelemetrySource, O: LedOutput> {
+    source: S,
+    leds: LEDS<O>,
+    game_type: GameType,
+    /// Receive buffer, reused for every packet
+    buffer: Vec<u8>,
+    sizes: PacketSizeMonitor,
+    control: Box<dyn FnMut() -> LoopControl>,
+}
+
+impl<S: TelemetrySource, O: LedOutput> Bridge<S, O> {
+    /// Run until the control callback returns `LoopControl::Stop` (`Ok`) or the
+    /// source or output fails (`Err`)
+    pub fn run(&mut self) -> DR2G27Result {
+        let expected_size = self.game_type.expected_packet_size();
+
+        info!(
+            "Listening for {} telemetry on {} (expecting {} byte packets)",
+            self.game_type.game_name(),
+            self.source.describe(),
+            expected_size
+        );
+        info!("Waiting for telemetry data from the game...");
+
+        loop {
+            let control = (self.control)();
+            if control == LoopControl::Stop {
+                return Ok(());
+            }
+
+            let mut received_size = match self.source.recv(&mut self.buffer) {
+                Ok(Some(size)) => size,
+                Ok(None) => continue,
+                Err(e) => {
+                    error!("Telemetry receive error: {}", e);
+                    return Err(DR2G27Error::Receive(e));
+                }
+            };
+            // Packets queued behind it are newer; only the newest is worth showing
+            let mut skipped = 0;
+            loop {
+                self.sizes.observe(&self.buffer[..received_size]);
+                match self.source.try_recv(&mut self.buffer) {
+                    Ok(Some(size)) => {
+                        received_size = size;
+                        skipped += 1;
+                    }
+                    Ok(None) => break,
+                    Err(e) => {
+                        error!("Telemetry receive error: {}", e);
+                        return Err(DR2G27Error::Receive(e));
+                    }
+                }
+            }
+            if skipped > 0 {
+                debug!("Skipped {} queued packets for the newest", skipped);
+            }
+
+            if received_size < expected_size {
+                debug!("Received packet too small: {} bytes (expected {})", received_size, expected_size);
+            } else if control == LoopControl::Mute {
+                self.leds.clear()?;
+            } else {
+                self.leds.update(&self.buffer[..received_size], &self.game_type)?;
+            }
+        }
+    }
+
+    /// Turn the LEDs off, e.g. before handing the output to something else
+    pub fn clear(&mut self) -> DR2G27Result {
+        self.leds.clear()
+    }
+}
@@ -0,0 +1,160 @@
+// Telemetry event bus (feature "tokio")
+//
+// The telemetry source publishes every parsed frame once; each consumer (LED
+// controller, recorder, forwarder, ...) holds its own subscription and reads at its
+// own pace. A slow consumer skips the frames it missed instead of holding back the
+// source or the other consumers. Consumers that only show the current state, like the
+// LEDs, take the newest queued frame with `recv_latest` rather than working through a
+// backlog that lags behind the engine. The bus also keeps the latest frame, so an LED
+// output that attaches later (e.g. the wheel found after telemetry started) can show the
+// current state right away with `subscribe_from_latest`.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use log::debug;
+use tokio::sync::broadcast::{self, error::RecvError, error::TryRecvError};
+
+use crate::frame::TelemetryFrame;
+
+/// Publish/subscribe channel for telemetry frames, counting the packets the source could
+/// not turn into one. Clones share the same bus.
+#[derive(Clone)]
+pub struct TelemetryBus {
+    sender: broadcast::Sender<TelemetryFrame>,
+    malformed: Arc<AtomicU64>,
+    latest: Arc<Mutex<Option<TelemetryFrame>>>,
+}
+
+impl TelemetryBus {
+    /// Frames buffered per subscriber before the oldest are dropped
+    pub const DEFAULT_CAPACITY: usize = 64;
+
+    pub fn new() -> Self {
+        Self::with_capacity(Self::DEFAULT_CAPACITY)
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity);
+        Self {
+            sender,
+            malformed: Arc::new(AtomicU64::new(0)),
+            latest: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Send a frame to every current subscriber, returning how many there are
+    pub fn publish(&self, frame: TelemetryFrame) -> usize {
+        if let Ok(mut latest) = self.latest.lock() {
+            *latest = Some(frame.clone());
+        }
+        self.sender.send(frame).unwrap_or(0)
+    }
+
+    /// Receive every frame published from now on; `name` identifies the consumer in logs
+    pub fn subscribe(&self, name: &'static str) -> Subscription {
+        Subscription {
+            name,
+            receiver: self.sender.subscribe(),
+            buffered: None,
+        }
+    }
+
+    /// Like `subscribe`, but starting with the latest frame published so far if it arrived
+    /// within `max_age`
+    pub fn subscribe_from_latest(&self, name: &'static str, max_age: Duration) -> Subscription {
+        let mut subscription = self.subscribe(name);
+        subscription.buffered = self.latest().filter(|frame| frame.received_at.elapsed() <= max_age);
+        subscription
+    }
+
+    /// The most recently published frame
+    pub fn latest(&self) -> Option<TelemetryFrame> {
+        self.latest.lock().ok().and_then(|latest| latest.clone())
+    }
+
+    /// Count a received packet that could not be parsed, e.g. one too short for the game
+    pub fn count_malformed(&self) {
+        self.malformed.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Packets counted by `count_malformed` so far
+    pub fn malformed(&self) -> u64 {
+        self.malformed.load(Ordering::Relaxed)
+    }
+
+    /// Number of active subscriptions
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+impl Default for TelemetryBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// One consumer's view of a `TelemetryBus`
+pub struct Subscription {
+    name: &'static str,
+    receiver: broadcast::Receiver<TelemetryFrame>,
+    /// Frame from before the subscription, handed out first
+    buffered: Option<TelemetryFrame>,
+}
+
+impl Subscription {
+    /// Next frame, or `None` once every bus handle has been dropped
+    pub async fn recv(&mut self) -> Option<TelemetryFrame> {
+        if let Some(frame) = self.buffered.take() {
+            return Some(frame);
+        }
+        loop {
+            match self.receiver.recv().await {
+                Ok(frame) => return Some(frame),
+                Err(RecvError::Lagged(missed)) => {
+                    debug!("Telemetry consumer '{}' fell behind, skipped {} frames", self.name, missed);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    /// Like `recv`, but skips to the newest frame when more are queued behind the next one
+    pub async fn recv_latest(&mut self) -> Option<TelemetryFrame> {
+        let mut latest = self.recv().await?;
+        let mut skipped = 0;
+        loop {
+            match self.receiver.try_recv() {
+                Ok(frame) => {
+                    latest = frame;
+                    skipped += 1;
+                }
+                Err(TryRecvError::Lagged(missed)) => skipped += missed,
+                // A closed bus still hands out `latest` first
+                Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+            }
+        }
+        if skipped > 0 {
+            debug!("Telemetry consumer '{}' skipped {} queued frames for the newest", self.name, skipped);
+        }
+        Some(latest)
+    }
+
+    /// Like `recv`, for threads outside the tokio runtime (panics if called from async code)
+    pub fn blocking_recv(&mut self) -> Option<TelemetryFrame> {
+        if let Some(frame) = self.buffered.take() {
+            return Some(frame);
+        }
+        loop {
+            match self.receiver.blocking_recv() {
+                Ok(frame) => return Some(frame),
+                Err(RecvError::Lagged(missed)) => {
+                    debug!("Telemetry consumer '{}' fell behind, skipped {} frames", self.name, missed);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
@@ -0,0 +1,635 @@
+// Packet captures in the pcapng format
+//
+// A capture holds received telemetry as the UDP datagrams it arrived in, so it opens in
+// Wireshark and other network tools, can be attached to parser bug reports and added to
+// the test fixtures. Each packet is written with its receive time, the sender's address
+// and the listen address, wrapped in IP and UDP headers (link type "raw IP"), with the
+// game it was parsed as in the packet comment.
+//
+// `CaptureReader` reads the UDP packets to one port back from pcap and pcapng files,
+// whether written here or by Wireshark and tcpdump, so they can be replayed. It reads
+// Ethernet, loopback, Linux "cooked" and raw IP captures; fragmented packets and IPv6
+// extension headers are skipped.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use log::debug;
+
+use crate::frame::TelemetryFrame;
+use crate::recording::{RecordedPacket, ReplaySource};
+use crate::telemetry::{GameType, TelemetryParser};
+
+/// File extension of captures
+pub const CAPTURE_EXTENSION: &str = "pcapng";
+
+/// Other file extension of captures, for classic pcap files
+pub const PCAP_EXTENSION: &str = "pcap";
+
+const SECTION_HEADER_BLOCK: u32 = 0x0A0D_0D0A;
+const INTERFACE_DESCRIPTION_BLOCK: u32 = 1;
+const SIMPLE_PACKET_BLOCK: u32 = 3;
+const ENHANCED_PACKET_BLOCK: u32 = 6;
+const BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+/// Classic pcap files with timestamps in microseconds and in nanoseconds
+const PCAP_MAGIC_MICROS: u32 = 0xA1B2_C3D4;
+const PCAP_MAGIC_NANOS: u32 = 0xA1B2_3C4D;
+/// Larger blocks are taken for a damaged file rather than allocated
+const MAX_BLOCK_SIZE: usize = 16 * 1024 * 1024;
+
+/// BSD loopback: the address family, in the capturing machine's byte order
+const LINKTYPE_NULL: u16 = 0;
+const LINKTYPE_ETHERNET: u16 = 1;
+/// Packets start with an IPv4 or IPv6 header
+const LINKTYPE_RAW: u16 = 101;
+/// OpenBSD loopback: the address family in big-endian
+const LINKTYPE_LOOP: u16 = 108;
+const LINKTYPE_LINUX_SLL: u16 = 113;
+const LINKTYPE_IPV4: u16 = 228;
+const LINKTYPE_IPV6: u16 = 229;
+const LINKTYPE_LINUX_SLL2: u16 = 276;
+
+const ETHERTYPE_VLAN: u16 = 0x8100;
+
+const OPT_END: u16 = 0;
+const OPT_COMMENT: u16 = 1;
+const OPT_SHB_USERAPPL: u16 = 4;
+const OPT_IF_TSRESOL: u16 = 9;
+
+const IP_PROTOCOL_UDP: u8 = 17;
+const UDP_HEADER_LEN: usize = 8;
+
+/// Writes packets as UDP datagrams in the pcapng format, with timestamps in microseconds
+pub struct PcapngWriter<W: Write> {
+    writer: W,
+}
+
+impl<W: Write> PcapngWriter<W> {
+    /// Write the section header and the one interface all packets are captured on
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        let mut section = Vec::new();
+        section.extend_from_slice(&BYTE_ORDER_MAGIC.to_le_bytes());
+        // Format version 1.0, then an unknown section length
+        section.extend_from_slice(&1u16.to_le_bytes());
+        section.extend_from_slice(&0u16.to_le_bytes());
+        section.extend_from_slice(&(-1i64).to_le_bytes());
+        let application = concat!("G27 LED Bridge ", env!("CARGO_PKG_VERSION"));
+        push_option(&mut section, OPT_SHB_USERAPPL, application.as_bytes());
+        push_option(&mut section, OPT_END, &[]);
+        write_block(&mut writer, SECTION_HEADER_BLOCK, &section)?;
+
+        let mut interface = Vec::new();
+        interface.extend_from_slice(&LINKTYPE_RAW.to_le_bytes());
+        // Reserved, then no snapshot length limit
+        interface.extend_from_slice(&0u16.to_le_bytes());
+        interface.extend_from_slice(&0u32.to_le_bytes());
+        write_block(&mut writer, INTERFACE_DESCRIPTION_BLOCK, &interface)?;
+
+        Ok(Self { writer })
+    }
+
+    /// Append `payload` as a datagram from `from` to `to`, received at `timestamp`
+    pub fn write_packet(
+        &mut self,
+        timestamp: SystemTime,
+        from: SocketAddr,
+        to: SocketAddr,
+        payload: &[u8],
+        comment: Option<&str>,
+    ) -> io::Result<()> {
+        let datagram = udp_datagram(from, to, payload)?;
+        let micros = timestamp.duration_since(UNIX_EPOCH).unwrap_or_default().as_micros() as u64;
+
+        let mut packet = Vec::with_capacity(datagram.len() + 64);
+        // Interface 0, timestamp as high and low 32 bits, captured and original length
+        packet.extend_from_slice(&0u32.to_le_bytes());
+        packet.extend_from_slice(&((micros >> 32) as u32).to_le_bytes());
+        packet.extend_from_slice(&(micros as u32).to_le_bytes());
+        packet.extend_from_slice(&(datagram.len() as u32).to_le_bytes());
+        packet.extend_from_slice(&(datagram.len() as u32).to_le_bytes());
+        packet.extend_from_slice(&datagram);
+        pad(&mut packet);
+        if let Some(comment) = comment {
+            push_option(&mut packet, OPT_COMMENT, comment.as_bytes());
+            push_option(&mut packet, OPT_END, &[]);
+        }
+        write_block(&mut self.writer, ENHANCED_PACKET_BLOCK, &packet)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    /// The writer the capture went to
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+/// Captures the packets of frames to a pcapng file, as datagrams sent to the listen address
+pub struct Capture {
+    packets: PcapngWriter<BufWriter<File>>,
+    listen_addr: SocketAddr,
+    /// Wall clock time matching `started`, as frames only carry an `Instant`
+    started: (Instant, SystemTime),
+    captured: u64,
+}
+
+impl Capture {
+    /// Create `output` for packets received on `listen_addr`
+    pub fn create(output: &Path, listen_addr: SocketAddr) -> io::Result<Self> {
+        Ok(Self {
+            packets: PcapngWriter::new(BufWriter::new(File::create(output)?))?,
+            listen_addr,
+            started: (Instant::now(), SystemTime::now()),
+            captured: 0,
+        })
+    }
+
+    pub fn capture(&mut self, frame: &TelemetryFrame) -> io::Result<()> {
+        let (started, wall_clock) = self.started;
+        let timestamp = match frame.received_at.checked_duration_since(started) {
+            Some(elapsed) => wall_clock + elapsed,
+            None => wall_clock - started.duration_since(frame.received_at),
+        };
+        // Packets without a known sender, e.g. replayed ones, come from the unspecified address
+        let unspecified = match self.listen_addr {
+            SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        };
+        let from = frame.sender.unwrap_or(SocketAddr::new(unspecified, 0));
+        let game = frame.game_type.game_name();
+        self.packets.write_packet(timestamp, from, self.listen_addr, &frame.packet, Some(game))?;
+        self.captured += 1;
+        Ok(())
+    }
+
+    /// Number of packets captured so far
+    pub fn captured(&self) -> u64 {
+        self.captured
+    }
+
+    /// Write everything buffered to disk
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.packets.flush()
+    }
+}
+
+/// Byte order of a capture, from its file or section header
+#[derive(Debug, Clone, Copy)]
+enum ByteOrder {
+    Little,
+    Big,
+}
+
+impl ByteOrder {
+    fn u16(self, bytes: &[u8]) -> u16 {
+        let bytes = [bytes[0], bytes[1]];
+        match self {
+            ByteOrder::Little => u16::from_le_bytes(bytes),
+            ByteOrder::Big => u16::from_be_bytes(bytes),
+        }
+    }
+
+    fn u32(self, bytes: &[u8]) -> u32 {
+        let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+        match self {
+            ByteOrder::Little => u32::from_le_bytes(bytes),
+            ByteOrder::Big => u32::from_be_bytes(bytes),
+        }
+    }
+}
+
+/// Link layer and timestamp unit of the interface packets were captured on
+#[derive(Debug, Clone, Copy)]
+struct Interface {
+    link_type: u16,
+    ticks_per_second: u64,
+}
+
+impl Interface {
+    fn timestamp(self, ticks: u64) -> Duration {
+        let nanos = u128::from(ticks % self.ticks_per_second) * 1_000_000_000 / u128::from(self.ticks_per_second);
+        Duration::from_secs(ticks / self.ticks_per_second) + Duration::from_nanos(nanos as u64)
+    }
+}
+
+enum Format {
+    /// Classic pcap, with one interface for the whole file
+    Pcap(Interface),
+    /// pcapng, with the interfaces described in the current section
+    Pcapng(Vec<Interface>),
+}
+
+/// Reads the UDP packets to one port back from a pcap or pcapng capture
+pub struct CaptureReader<R: Read> {
+    reader: R,
+    order: ByteOrder,
+    format: Format,
+    port: u16,
+    /// Game of every packet; without one it is detected from each packet's size
+    game_type: Option<GameType>,
+    /// Capture time of the first packet read, which replays start from
+    first: Option<Duration>,
+    /// Capture time of the last packet, for packet blocks that have none
+    last: Duration,
+    matched: u64,
+}
+
+impl CaptureReader<BufReader<File>> {
+    /// Open the capture at `path`, reading the packets sent to `port`
+    pub fn open(path: &Path, port: u16) -> io::Result<Self> {
+        Self::new(BufReader::new(File::open(path)?), port)
+    }
+}
+
+impl<R: Read> CaptureReader<R> {
+    /// Check the file header; fails if `reader` does not hold a pcap or pcapng capture
+    pub fn new(mut reader: R, port: u16) -> io::Result<Self> {
+        let not_a_capture = || io::Error::new(ErrorKind::InvalidData, "not a pcap or pcapng capture");
+        let truncated = |e: io::Error| match e.kind() {
+            ErrorKind::UnexpectedEof => not_a_capture(),
+            _ => e,
+        };
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic).map_err(truncated)?;
+
+        let (order, format) = if u32::from_le_bytes(magic) == SECTION_HEADER_BLOCK {
+            let mut length = [0u8; 4];
+            reader.read_exact(&mut length).map_err(truncated)?;
+            (read_section_header(&mut reader, length).map_err(truncated)?, Format::Pcapng(Vec::new()))
+        } else {
+            let (order, ticks_per_second) = match (u32::from_le_bytes(magic), u32::from_be_bytes(magic)) {
+                (PCAP_MAGIC_MICROS, _) => (ByteOrder::Little, 1_000_000),
+                (_, PCAP_MAGIC_MICROS) => (ByteOrder::Big, 1_000_000),
+                (PCAP_MAGIC_NANOS, _) => (ByteOrder::Little, 1_000_000_000),
+                (_, PCAP_MAGIC_NANOS) => (ByteOrder::Big, 1_000_000_000),
+                _ => return Err(not_a_capture()),
+            };
+            // Version, time zone, accuracy and snapshot length, then the link type
+            let mut header = [0u8; 20];
+            reader.read_exact(&mut header).map_err(truncated)?;
+            let link_type = order.u32(&header[16..20]) as u16;
+            (order, Format::Pcap(Interface { link_type, ticks_per_second }))
+        };
+
+        Ok(Self {
+            reader,
+            order,
+            format,
+            port,
+            game_type: None,
+            first: None,
+            last: Duration::ZERO,
+            matched: 0,
+        })
+    }
+
+    /// Parse every packet as `game_type` instead of detecting the game from its size
+    pub fn with_game(mut self, game_type: GameType) -> Self {
+        self.game_type = Some(game_type);
+        self
+    }
+
+    /// Number of packets to the port read so far
+    pub fn matched(&self) -> u64 {
+        self.matched
+    }
+
+    /// Next packet to the port, or `None` at the end of the capture. Packets of a game
+    /// that cannot be told from their size are skipped.
+    pub fn next_packet(&mut self) -> io::Result<Option<RecordedPacket>> {
+        while let Some((timestamp, link_type, frame)) = self.next_frame()? {
+            let Some((port, payload)) = udp_payload(link_type, &frame) else {
+                continue;
+            };
+            if port != self.port {
+                continue;
+            }
+            self.matched += 1;
+            let Some(game_type) = self.game_type.or_else(|| GameType::from_packet_size(payload.len())) else {
+                debug!("Skipping {} byte packet of no known game", payload.len());
+                continue;
+            };
+            let first = *self.first.get_or_insert(timestamp);
+            return Ok(Some(RecordedPacket {
+                elapsed: timestamp.saturating_sub(first),
+                game_type,
+                packet: payload.to_vec(),
+            }));
+        }
+        Ok(None)
+    }
+
+    /// Next captured frame with its capture time and link type; a capture cut short ends
+    /// at its last complete packet
+    fn next_frame(&mut self) -> io::Result<Option<(Duration, u16, Vec<u8>)>> {
+        let interfaces = match &mut self.format {
+            Format::Pcap(interface) => {
+                let interface = *interface;
+                let mut header = [0u8; 16];
+                if !read_complete(&mut self.reader, &mut header)? {
+                    return Ok(None);
+                }
+                let seconds = self.order.u32(&header[0..4]);
+                let fraction = self.order.u32(&header[4..8]);
+                let mut frame = vec![0u8; block_size(self.order.u32(&header[8..12]))?];
+                if !read_complete(&mut self.reader, &mut frame)? {
+                    return Ok(None);
+                }
+                let timestamp = Duration::from_secs(seconds.into()) + interface.timestamp(fraction.into());
+                return Ok(Some((timestamp, interface.link_type, frame)));
+            }
+            Format::Pcapng(interfaces) => interfaces,
+        };
+
+        loop {
+            let mut header = [0u8; 8];
+            if !read_complete(&mut self.reader, &mut header)? {
+                return Ok(None);
+            }
+            let block_type = self.order.u32(&header[0..4]);
+            if block_type == SECTION_HEADER_BLOCK {
+                // A new section may use another byte order and describes its own interfaces
+                match read_section_header(&mut self.reader, [header[4], header[5], header[6], header[7]]) {
+                    Ok(order) => self.order = order,
+                    Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+                    Err(e) => return Err(e),
+                }
+                interfaces.clear();
+                continue;
+            }
+
+            let length = block_size(self.order.u32(&header[4..8]))?;
+            if length < 12 || length % 4 != 0 {
+                return Err(io::Error::new(ErrorKind::InvalidData, format!("invalid {} byte block", length)));
+            }
+            // The body, then the length again
+            let mut block = vec![0u8; length - 8];
+            if !read_complete(&mut self.reader, &mut block)? {
+                return Ok(None);
+            }
+            let body = &block[..length - 12];
+
+            match block_type {
+                INTERFACE_DESCRIPTION_BLOCK if body.len() >= 8 => {
+                    interfaces.push(Interface {
+                        link_type: self.order.u16(&body[0..2]),
+                        ticks_per_second: timestamp_resolution(self.order, &body[8..]),
+                    });
+                }
+                ENHANCED_PACKET_BLOCK if body.len() >= 20 => {
+                    let Some(interface) = interfaces.get(self.order.u32(&body[0..4]) as usize) else {
+                        continue;
+                    };
+                    let (high, low) = (self.order.u32(&body[4..8]), self.order.u32(&body[8..12]));
+                    let ticks = (u64::from(high) << 32) | u64::from(low);
+                    let captured = self.order.u32(&body[12..16]) as usize;
+                    let Some(frame) = body.get(20..20 + captured) else {
+                        continue;
+                    };
+                    self.last = interface.timestamp(ticks);
+                    return Ok(Some((self.last, interface.link_type, frame.to_vec())));
+                }
+                SIMPLE_PACKET_BLOCK if body.len() >= 4 => {
+                    let Some(interface) = interfaces.first() else {
+                        continue;
+                    };
+                    // Original length, then the packet padded to four bytes
+                    let captured = (self.order.u32(&body[0..4]) as usize).min(body.len() - 4);
+                    return Ok(Some((self.last, interface.link_type, body[4..4 + captured].to_vec())));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<R: Read> ReplaySource for CaptureReader<R> {
+    fn next_packet(&mut self) -> io::Result<Option<RecordedPacket>> {
+        CaptureReader::next_packet(self)
+    }
+}
+
+/// Rest of a section header block after its type and `length`, returning the section's
+/// byte order
+fn read_section_header(reader: &mut impl Read, length: [u8; 4]) -> io::Result<ByteOrder> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    let order = if u32::from_le_bytes(magic) == BYTE_ORDER_MAGIC {
+        ByteOrder::Little
+    } else if u32::from_be_bytes(magic) == BYTE_ORDER_MAGIC {
+        ByteOrder::Big
+    } else {
+        return Err(io::Error::new(ErrorKind::InvalidData, "pcapng section has no byte order magic"));
+    };
+    let length = block_size(order.u32(&length))?;
+    if length < 28 || length % 4 != 0 {
+        return Err(io::Error::new(ErrorKind::InvalidData, "invalid pcapng section header"));
+    }
+    let rest = (length - 12) as u64;
+    if io::copy(&mut reader.take(rest), &mut io::sink())? < rest {
+        return Err(ErrorKind::UnexpectedEof.into());
+    }
+    Ok(order)
+}
+
+fn block_size(length: u32) -> io::Result<usize> {
+    let length = length as usize;
+    if length > MAX_BLOCK_SIZE {
+        return Err(io::Error::new(ErrorKind::InvalidData, format!("{} byte block is too large", length)));
+    }
+    Ok(length)
+}
+
+/// Fill `buf`, returning false if the capture ends first
+fn read_complete(reader: &mut impl Read, buf: &mut [u8]) -> io::Result<bool> {
+    match reader.read_exact(buf) {
+        Ok(()) => Ok(true),
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+            debug!("Capture ended");
+            Ok(false)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Timestamp units per second from an interface's options; microseconds unless `if_tsresol`
+/// gives a power of ten or, with its top bit set, of two
+fn timestamp_resolution(order: ByteOrder, mut options: &[u8]) -> u64 {
+    while options.len() >= 4 {
+        let code = order.u16(&options[0..2]);
+        let length = usize::from(order.u16(&options[2..4]));
+        let Some(value) = options.get(4..4 + length) else {
+            break;
+        };
+        match code {
+            OPT_END => break,
+            OPT_IF_TSRESOL if length == 1 => {
+                let exponent = u32::from(value[0] & 0x7F);
+                let base: u64 = if value[0] & 0x80 == 0 { 10 } else { 2 };
+                if let Some(resolution) = base.checked_pow(exponent) {
+                    return resolution;
+                }
+            }
+            _ => {}
+        }
+        options = options.get((4 + length).next_multiple_of(4)..).unwrap_or_default();
+    }
+    1_000_000
+}
+
+/// Destination port and payload of the UDP datagram in `frame`, if it holds one
+fn udp_payload(link_type: u16, frame: &[u8]) -> Option<(u16, &[u8])> {
+    let ip = match link_type {
+        LINKTYPE_RAW | LINKTYPE_IPV4 | LINKTYPE_IPV6 => frame,
+        // The IP version tells the address families apart
+        LINKTYPE_NULL | LINKTYPE_LOOP => frame.get(4..)?,
+        LINKTYPE_ETHERNET => {
+            let mut offset = 12;
+            while be_u16(frame, offset)? == ETHERTYPE_VLAN {
+                offset += 4;
+            }
+            frame.get(offset + 2..)?
+        }
+        LINKTYPE_LINUX_SLL => frame.get(16..)?,
+        LINKTYPE_LINUX_SLL2 => frame.get(20..)?,
+        _ => return None,
+    };
+
+    let udp = match ip.first()? >> 4 {
+        4 => {
+            let header_length = usize::from(ip[0] & 0x0F) * 4;
+            let total_length = usize::from(be_u16(ip, 2)?);
+            // Only an unfragmented packet holds the whole datagram
+            let fragment = be_u16(ip, 6)? & 0x3FFF;
+            if *ip.get(9)? != IP_PROTOCOL_UDP || fragment != 0 {
+                return None;
+            }
+            ip.get(header_length..total_length.min(ip.len()))?
+        }
+        6 => {
+            if *ip.get(6)? != IP_PROTOCOL_UDP {
+                return None;
+            }
+            let payload_length = usize::from(be_u16(ip, 4)?);
+            ip.get(40..(40 + payload_length).min(ip.len()))?
+        }
+        _ => return None,
+    };
+    let length = usize::from(be_u16(udp, 4)?);
+    Some((be_u16(udp, 2)?, udp.get(UDP_HEADER_LEN..length.min(udp.len()))?))
+}
+
+fn be_u16(bytes: &[u8], offset: usize) -> Option<u16> {
+    Some(u16::from_be_bytes([*bytes.get(offset)?, *bytes.get(offset + 1)?]))
+}
+
+/// Block of `block_type` around `body`, with its total length before and after it
+fn write_block(writer: &mut impl Write, block_type: u32, body: &[u8]) -> io::Result<()> {
+    let length = (body.len() + 12) as u32;
+    writer.write_all(&block_type.to_le_bytes())?;
+    writer.write_all(&length.to_le_bytes())?;
+    writer.write_all(body)?;
+    writer.write_all(&length.to_le_bytes())
+}
+
+fn push_option(block: &mut Vec<u8>, code: u16, value: &[u8]) {
+    block.extend_from_slice(&code.to_le_bytes());
+    block.extend_from_slice(&(value.len() as u16).to_le_bytes());
+    block.extend_from_slice(value);
+    pad(block);
+}
+
+/// Pad `block` to a multiple of four bytes
+fn pad(block: &mut Vec<u8>) {
+    block.resize(block.len().next_multiple_of(4), 0);
+}
+
+/// IP and UDP headers followed by `payload`. Senders seen through an IPv6 socket keep their
+/// IPv4 address; a datagram mixing IPv4 and IPv6 is written as IPv6 with mapped addresses.
+fn udp_datagram(from: SocketAddr, to: SocketAddr, payload: &[u8]) -> io::Result<Vec<u8>> {
+    let udp_length = u16::try_from(UDP_HEADER_LEN + payload.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "packet too large to capture"))?;
+    let mut udp = Vec::with_capacity(usize::from(udp_length));
+    udp.extend_from_slice(&from.port().to_be_bytes());
+    udp.extend_from_slice(&to.port().to_be_bytes());
+    udp.extend_from_slice(&udp_length.to_be_bytes());
+    udp.extend_from_slice(&[0, 0]);
+    udp.extend_from_slice(payload);
+
+    let mut datagram = Vec::with_capacity(udp.len() + 40);
+    match (from.ip().to_canonical(), to.ip().to_canonical()) {
+        (IpAddr::V4(source), IpAddr::V4(destination)) => {
+            let mut pseudo_header = Vec::with_capacity(12);
+            pseudo_header.extend_from_slice(&source.octets());
+            pseudo_header.extend_from_slice(&destination.octets());
+            pseudo_header.extend_from_slice(&[0, IP_PROTOCOL_UDP]);
+            pseudo_header.extend_from_slice(&udp_length.to_be_bytes());
+            set_udp_checksum(&mut udp, &pseudo_header);
+
+            let total_length = (20 + udp.len()) as u16;
+            // Version 4 with a 20 byte header, total length, no fragmentation, TTL 64
+            datagram.extend_from_slice(&[0x45, 0]);
+            datagram.extend_from_slice(&total_length.to_be_bytes());
+            datagram.extend_from_slice(&[0, 0, 0x40, 0, 64, IP_PROTOCOL_UDP, 0, 0]);
+            datagram.extend_from_slice(&source.octets());
+            datagram.extend_from_slice(&destination.octets());
+            let header_checksum = checksum(&[&datagram]);
+            datagram[10..12].copy_from_slice(&header_checksum.to_be_bytes());
+        }
+        (source, destination) => {
+            let source = ipv6(source);
+            let destination = ipv6(destination);
+            let mut pseudo_header = Vec::with_capacity(40);
+            pseudo_header.extend_from_slice(&source.octets());
+            pseudo_header.extend_from_slice(&destination.octets());
+            pseudo_header.extend_from_slice(&u32::from(udp_length).to_be_bytes());
+            pseudo_header.extend_from_slice(&[0, 0, 0, IP_PROTOCOL_UDP]);
+            set_udp_checksum(&mut udp, &pseudo_header);
+
+            // Version 6, payload length, next header UDP, hop limit 64
+            datagram.extend_from_slice(&[0x60, 0, 0, 0]);
+            datagram.extend_from_slice(&udp_length.to_be_bytes());
+            datagram.extend_from_slice(&[IP_PROTOCOL_UDP, 64]);
+            datagram.extend_from_slice(&source.octets());
+            datagram.extend_from_slice(&destination.octets());
+        }
+    }
+    datagram.extend_from_slice(&udp);
+    Ok(datagram)
+}
+
+fn ipv6(address: IpAddr) -> Ipv6Addr {
+    match address {
+        IpAddr::V4(address) => address.to_ipv6_mapped(),
+        IpAddr::V6(address) => address,
+    }
+}
+
+fn set_udp_checksum(udp: &mut [u8], pseudo_header: &[u8]) {
+    // A computed checksum of zero is sent as all ones, as zero means "none"
+    let sum = match checksum(&[pseudo_header, udp]) {
+        0 => 0xFFFF,
+        sum => sum,
+    };
+    udp[6..8].copy_from_slice(&sum.to_be_bytes());
+}
+
+/// Internet checksum (RFC 1071) over `parts` as one run of bytes; parts other than the last
+/// must have an even length
+fn checksum(parts: &[&[u8]]) -> u16 {
+    let mut sum = 0u32;
+    for part in parts {
+        for word in part.chunks(2) {
+            sum += u32::from(u16::from_be_bytes([word[0], word.get(1).copied().unwrap_or(0)]));
+        }
+    }
+    while sum > 0xFFFF {
+        sum = (sum & 0xFFFF) + (sum >> 16);
+    }
+    !(sum as u16)
+}
@@ -0,0 +1,104 @@
+// Wrong-game detection from packet sizes
+//
+// Each game sends packets of a few fixed sizes. A steady stream of packets that do
+// not fit the selected game usually means the wrong game is selected (or the game's
+// telemetry format option is off), so the user gets a hint naming the likely game.
+// With auto-detection on, the monitor names the game to switch to instead. Packets
+// that do not fit are also dumped for diagnosis while `--dump-unknown` is on.
+
+use log::warn;
+
+use crate::dump::UnknownPacketDumps;
+use crate::telemetry::GameType;
+
+/// Watches received packet sizes for the selected game
+pub struct PacketSizeMonitor {
+    game_type: GameType,
+    mismatches: u32,
+    warned: bool,
+    detecting: bool,
+    /// Dumps of the packets that do not fit, while `--dump-unknown` is on
+    dumps: Option<UnknownPacketDumps>,
+}
+
+impl PacketSizeMonitor {
+    /// Consecutive mismatched packets before warning; games send dozens per second
+    const THRESHOLD: u32 = 10;
+
+    pub fn new(game_type: GameType) -> Self {
+        Self {
+            game_type,
+            mismatches: 0,
+            warned: false,
+            detecting: false,
+            dumps: None,
+        }
+    }
+
+    /// Monitor that reports the game to switch to rather than warning about it
+    pub fn detecting(game_type: GameType) -> Self {
+        Self {
+            detecting: true,
+            ..Self::new(game_type)
+        }
+    }
+
+    /// Log a hex dump of packets that do not fit, rate-limited
+    pub fn dump_unknown(mut self, enabled: bool) -> Self {
+        self.dumps = enabled.then(UnknownPacketDumps::new);
+        self
+    }
+
+    /// Start over for a newly selected game
+    pub fn set_game(&mut self, game_type: GameType) {
+        *self = Self {
+            detecting: self.detecting,
+            dumps: self.dumps.take(),
+            ..Self::new(game_type)
+        };
+    }
+
+    /// Record one packet's size, warning once per game if sizes keep not matching.
+    /// When detecting, returns the game the packets belong to instead of warning.
+    pub fn observe(&mut self, packet: &[u8]) -> Option<GameType> {
+        let size = packet.len();
+        if self.game_type.packet_sizes().contains(&size) {
+            self.mismatches = 0;
+            return None;
+        }
+        if let Some(dumps) = &mut self.dumps {
+            dumps.log(self.game_type, packet);
+        }
+
+        self.mismatches = self.mismatches.saturating_add(1);
+        if self.mismatches < Self::THRESHOLD {
+            return None;
+        }
+        let likely = GameType::from_packet_size(size);
+        if self.detecting && likely.is_some() {
+            return likely;
+        }
+        if self.warned {
+            return None;
+        }
+        self.warned = true;
+
+        let selected = self.game_type.parser().game_name();
+        match likely {
+            Some(likely) => warn!(
+                "Receiving {} byte packets, which look like {} telemetry but {} is selected. Select {} to use them",
+                size,
+                likely.parser().game_name(),
+                selected,
+                likely.parser().game_name()
+            ),
+            None => warn!(
+                "Receiving {} byte packets, which {} does not send (expected {:?}). Check the game's telemetry settings",
+                size,
+                selected,
+                self.game_type.packet_sizes()
+            ),
+        }
+        None
+    }
+}
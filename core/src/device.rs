@@ -0,0 +1,89 @@
+// Wheel HID access
+//
+// `WheelDevice` is the slice of HID the bridge needs: writing output reports to the
+// wheel. `HidDevice` implements it for real hardware; `MockWheel` keeps the reports
+// instead, so the packet-to-LED path can be tested on machines without a G27.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+
+use hidapi::{HidApi, HidDevice, HidError};
+
+use crate::util::{DR2G27Error, G27_PID, G27_VID};
+
+/// Something output reports can be written to, like the G27
+pub trait WheelDevice {
+    /// Send one output report; the first byte is the report ID. Returns the bytes written.
+    fn write_report(&self, report: &[u8]) -> Result<usize, HidError>;
+}
+
+impl WheelDevice for HidDevice {
+    fn write_report(&self, report: &[u8]) -> Result<usize, HidError> {
+        self.write(report)
+    }
+}
+
+/// Open the first connected G27
+pub fn open_g27() -> Result<HidDevice, DR2G27Error> {
+    HidApi::new()
+        .map_err(DR2G27Error::HidInit)?
+        .open(G27_VID, G27_PID)
+        .map_err(DR2G27Error::hid_open)
+}
+
+/// Stand-in wheel that records every report written to it.
+/// Clones share the same record, so a test can keep one while the bridge owns another.
+#[derive(Clone, Default)]
+pub struct MockWheel {
+    reports: Arc<Mutex<Vec<Vec<u8>>>>,
+    disconnected: Arc<AtomicBool>,
+    failing_writes: Arc<AtomicU32>,
+}
+
+impl MockWheel {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every report written so far, oldest first
+    pub fn reports(&self) -> Vec<Vec<u8>> {
+        self.reports.lock().map(|reports| reports.clone()).unwrap_or_default()
+    }
+
+    /// LED states written so far, decoded from the G27 LED reports among them
+    pub fn led_states(&self) -> Vec<u8> {
+        self.reports()
+            .iter()
+            .filter(|report| report.starts_with(&[0x00, 0xF8, 0x12]))
+            .filter_map(|report| report.get(3).copied())
+            .collect()
+    }
+
+    /// Fail every further write, as if the wheel was unplugged
+    pub fn disconnect(&self) {
+        self.disconnected.store(true, Ordering::Relaxed);
+    }
+
+    /// Fail the next `count` writes, as a wheel does while its driver briefly misbehaves
+    pub fn fail_writes(&self, count: u32) {
+        self.failing_writes.store(count, Ordering::Relaxed);
+    }
+}
+
+impl WheelDevice for MockWheel {
+    fn write_report(&self, report: &[u8]) -> Result<usize, HidError> {
+        let failing = self
+            .failing_writes
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |count| count.checked_sub(1))
+            .is_ok();
+        if self.disconnected.load(Ordering::Relaxed) || failing {
+            return Err(HidError::HidApiError {
+                message: "mock wheel disconnected".to_string(),
+            });
+        }
+        if let Ok(mut reports) = self.reports.lock() {
+            reports.push(report.to_vec());
+        }
+        Ok(report.len())
+    }
+}
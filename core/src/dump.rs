@@ -0,0 +1,93 @@
+// Hex dumps of unrecognized packets
+//
+// A packet of a size the selected game never sends is either another game's or a format
+// the bridge does not know yet. With dumps switched on (`--dump-unknown`, passed to the
+// publishers and `BridgeBuilder::dump_unknown`), such packets are logged as a hex dump, at
+// most one every `DUMP_INTERVAL`, with the 4-byte words that read as plausible
+// little-endian floats next to each line, since games send nearly all values as f32.
+// Values in the range of engine speeds are marked as RPM candidates; their offsets are
+// what a parser for the format needs.
+
+use std::fmt::Write as _;
+use std::time::{Duration, Instant};
+
+use log::info;
+
+use crate::telemetry::{GameType, TelemetryParser};
+
+/// Shortest time between two dumps; games send dozens of packets per second
+pub const DUMP_INTERVAL: Duration = Duration::from_secs(5);
+/// Bytes per line of a dump
+const BYTES_PER_LINE: usize = 16;
+/// Floats outside this magnitude are most likely integers, flags or padding
+const PLAUSIBLE_FLOATS: std::ops::RangeInclusive<f32> = 0.001..=1_000_000.0;
+/// Floats in this range could be the engine speed
+const RPM_CANDIDATES: std::ops::RangeInclusive<f32> = 300.0..=25_000.0;
+
+/// `packet` as lines of offset, hex bytes and candidate floats, offsets in decimal like
+/// the parsers use them
+pub fn hex_dump(packet: &[u8]) -> String {
+    let mut dump = String::new();
+    for (line, bytes) in packet.chunks(BYTES_PER_LINE).enumerate() {
+        let offset = line * BYTES_PER_LINE;
+        let _ = write!(dump, "{:5} ", offset);
+        for byte in bytes {
+            let _ = write!(dump, " {:02x}", byte);
+        }
+        let padding = (BYTES_PER_LINE - bytes.len()) * 3;
+        let _ = write!(dump, "{:padding$}", "");
+        for (word, value) in bytes.chunks_exact(4).enumerate() {
+            let value = f32::from_le_bytes([value[0], value[1], value[2], value[3]]);
+            if value.is_finite() && PLAUSIBLE_FLOATS.contains(&value.abs()) {
+                let rpm = if RPM_CANDIDATES.contains(&value) { " (rpm?)" } else { "" };
+                let _ = write!(dump, "  @{}: {}{}", offset + word * 4, value, rpm);
+            }
+        }
+        dump.truncate(dump.trim_end().len());
+        dump.push('\n');
+    }
+    dump
+}
+
+/// Rate-limited dumps of the packets one publisher could not match to its game
+#[derive(Debug, Default)]
+pub struct UnknownPacketDumps {
+    last_dump: Option<Instant>,
+    skipped: u64,
+}
+
+impl UnknownPacketDumps {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Log `packet` received for `game_type` if the last dump is long enough ago
+    pub fn log(&mut self, game_type: GameType, packet: &[u8]) {
+        if let Some(dump) = self.dump(game_type, packet, Instant::now()) {
+            info!("{}", dump.trim_end());
+        }
+    }
+
+    /// Dump of `packet` received at `now`, unless one was made less than `DUMP_INTERVAL` before
+    pub fn dump(&mut self, game_type: GameType, packet: &[u8], now: Instant) -> Option<String> {
+        if self.last_dump.is_some_and(|last| now.saturating_duration_since(last) < DUMP_INTERVAL) {
+            self.skipped += 1;
+            return None;
+        }
+        self.last_dump = Some(now);
+
+        let mut dump = format!(
+            "Unrecognized {} byte packet for {} (expecting {:?} bytes)",
+            packet.len(),
+            game_type.game_name(),
+            game_type.packet_sizes()
+        );
+        if self.skipped > 0 {
+            let _ = write!(dump, ", {} more since the last dump", self.skipped);
+            self.skipped = 0;
+        }
+        dump.push_str(":\n");
+        dump.push_str(&hex_dump(packet));
+        Some(dump)
+    }
+}
@@ -0,0 +1,79 @@
+// Formats the forwarder can send
+//
+// Each forwarding destination gets telemetry in the format it asks for: the packet
+// as the game sent it, the parsed frame as one JSON object per datagram, or the frame
+// re-encoded as another game's packet for tools that only understand that game, such
+// as a motion rig reading Forza's format while DiRT Rally 2.0 runs. Re-encoded packets
+// only carry what frames hold (engine state, speed and gear); the rest is zero.
+
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+use serde_json::json;
+
+use crate::frame::TelemetryFrame;
+use crate::simulator::encode_frame;
+use crate::telemetry::GameType;
+
+/// What a forwarding destination receives for each frame
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ForwardFormat {
+    /// The packet unchanged
+    #[default]
+    Raw,
+    /// The parsed frame as JSON, the same for every game
+    Json,
+    /// A packet in this game's format
+    Game(GameType),
+}
+
+impl ForwardFormat {
+    /// Datagram to send for `frame`; packets already in the requested game's format go
+    /// out unchanged
+    pub fn encode<'a>(&self, frame: &'a TelemetryFrame) -> Cow<'a, [u8]> {
+        match self {
+            ForwardFormat::Raw => Cow::Borrowed(&frame.packet),
+            ForwardFormat::Game(game_type) if *game_type == frame.game_type => Cow::Borrowed(&frame.packet),
+            ForwardFormat::Game(game_type) => Cow::Owned(encode_frame(*game_type, frame)),
+            ForwardFormat::Json => Cow::Owned(
+                json!({
+                    "game": frame.game_type,
+                    "rpm": frame.rpm,
+                    "max_rpm": frame.max_rpm,
+                    "idle_rpm": frame.idle_rpm,
+                    "race_active": frame.race_active,
+                    "speed": frame.speed,
+                    "gear": frame.gear,
+                })
+                .to_string()
+                .into_bytes(),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ForwardFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ForwardFormat::Raw => f.write_str("raw"),
+            ForwardFormat::Json => f.write_str("json"),
+            ForwardFormat::Game(game_type) => f.write_str(&game_type.short_name().to_lowercase()),
+        }
+    }
+}
+
+impl FromStr for ForwardFormat {
+    type Err = String;
+
+    /// `raw`, `json` or a game name as `--game` takes it, e.g. `fh5`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "raw" => Ok(ForwardFormat::Raw),
+            "json" => Ok(ForwardFormat::Json),
+            name => GameType::parse_game_name(name)
+                .map(ForwardFormat::Game)
+                .ok_or_else(|| format!("unknown format '{}'; use raw, json or a game such as fh5 or dr2", s)),
+        }
+    }
+}
@@ -0,0 +1,51 @@
+// Normalized telemetry frames
+//
+// A frame is one packet after parsing: the game-agnostic values the LED mapping
+// needs, plus the raw bytes so recorders and forwarders can pass packets on unchanged.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::telemetry::{GameType, TelemetryParser};
+
+/// One telemetry packet, normalized across games
+#[derive(Debug, Clone)]
+pub struct TelemetryFrame {
+    /// Game the packet was parsed as
+    pub game_type: GameType,
+    pub rpm: f32,
+    pub max_rpm: f32,
+    pub idle_rpm: f32,
+    /// False in menus, replays and pauses
+    pub race_active: bool,
+    /// Vehicle speed in m/s, if the packet format carries it
+    pub speed: Option<f32>,
+    /// Current gear (-1 reverse, 0 neutral), if the packet format carries it
+    pub gear: Option<i8>,
+    /// Packet exactly as received
+    pub packet: Arc<[u8]>,
+    pub received_at: Instant,
+    /// Address the packet came from, `None` for replayed and simulated packets
+    pub sender: Option<SocketAddr>,
+}
+
+impl TelemetryFrame {
+    /// Parse `packet` with `parser`, the parser for `game_type` (or `game_type` itself)
+    pub fn parse<P: TelemetryParser + ?Sized>(game_type: GameType, parser: &P, packet: &[u8]) -> Self {
+        let (rpm, max_rpm, idle_rpm, race_active) = parser.parse_rpm_data(packet);
+        let (speed, gear) = parser.parse_motion_data(packet);
+        Self {
+            game_type,
+            rpm,
+            max_rpm,
+            idle_rpm,
+            race_active,
+            speed,
+            gear,
+            packet: Arc::from(packet),
+            received_at: Instant::now(),
+            sender: None,
+        }
+    }
+}
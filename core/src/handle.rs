@@ -0,0 +1,88 @@
+// Bridge running on a background thread (feature "tokio")
+//
+// `BridgeBuilder::spawn` starts the async bridge on a thread with its own tokio
+// runtime, for embedders that just want the LEDs driven next to their own app
+// (dashboards, overlays). The handle stops it, reports how it is doing and hands out
+// subscriptions to the parsed frames.
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::thread::JoinHandle;
+
+use tokio::sync::watch;
+
+use crate::bridge::LoopControl;
+use crate::bus::{Subscription, TelemetryBus};
+use crate::util::{panic_message, DR2G27Error, DR2G27Result};
+
+/// State of a spawned bridge
+#[derive(Debug, Clone, PartialEq)]
+pub enum BridgeStatus {
+    /// Listening and driving the output
+    Running,
+    /// Stopped on request; the LEDs were turned off
+    Stopped,
+    /// The socket or output failed; the bridge does not restart on its own
+    Failed(String),
+}
+
+impl fmt::Display for BridgeStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BridgeStatus::Running => write!(f, "Running"),
+            BridgeStatus::Stopped => write!(f, "Stopped"),
+            BridgeStatus::Failed(error) => write!(f, "Failed: {}", error),
+        }
+    }
+}
+
+/// Controls a bridge started with `BridgeBuilder::spawn`.
+/// Dropping the handle stops the bridge without waiting for it.
+pub struct BridgeHandle {
+    pub(crate) control: watch::Sender<LoopControl>,
+    pub(crate) status: watch::Receiver<BridgeStatus>,
+    pub(crate) bus: TelemetryBus,
+    pub(crate) local_addr: SocketAddr,
+    pub(crate) thread: JoinHandle<DR2G27Result>,
+}
+
+impl BridgeHandle {
+    /// Current state of the bridge
+    pub fn status(&self) -> BridgeStatus {
+        self.status.borrow().clone()
+    }
+
+    /// Address the listener is bound to, e.g. to find the port picked for port 0
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// Frames published from now on; `name` identifies the consumer in logs
+    pub fn subscribe(&self, name: &'static str) -> Subscription {
+        self.bus.subscribe(name)
+    }
+
+    /// Frames published from now on
+    pub fn frames(&self) -> Subscription {
+        self.subscribe("embedder")
+    }
+
+    /// Hold the LEDs off (`true`) or show telemetry again (`false`); frames keep flowing
+    pub fn set_muted(&self, muted: bool) {
+        let control = if muted { LoopControl::Mute } else { LoopControl::Run };
+        // A finished bridge has nothing left to mute
+        let _ = self.control.send(control);
+    }
+
+    /// Stop the bridge, turn the LEDs off and wait for its thread to end.
+    /// Returns the error the bridge failed with, if it failed before being stopped.
+    pub fn stop(self) -> DR2G27Result {
+        let _ = self.control.send(LoopControl::Stop);
+        self.thread.join().unwrap_or_else(|payload| {
+            Err(DR2G27Error::Panicked {
+                component: "bridge thread",
+                message: panic_message(payload.as_ref()),
+            })
+        })
+    }
+}
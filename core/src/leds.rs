@@ -0,0 +1,268 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::frame::TelemetryFrame;
+use crate::output::LedOutput;
+use crate::rpm::RPM;
+use crate::telemetry::TelemetryParser;
+use crate::util::{catch_panic, DR2G27Result};
+
+/// Share of the bar's range, in percent, from which the whole bar flashes with `shift_flash`
+const SHIFT_FLASH_PERCENTAGE: u8 = 95;
+
+/// How long the flashing bar stays lit, and then dark
+const SHIFT_FLASH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Where in the rev range the LED bar lights up
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LedProfile {
+    /// From 30% of the way from idle to max RPM, for shifting early
+    Relaxed,
+    /// From halfway between idle and max RPM
+    #[default]
+    Standard,
+    /// From 70% of the way, keeping the bar for the top of the rev range
+    Aggressive,
+}
+
+impl LedProfile {
+    pub const ALL: [LedProfile; 3] = [LedProfile::Relaxed, LedProfile::Standard, LedProfile::Aggressive];
+
+    /// Name used on the command line and in settings
+    pub fn name(&self) -> &'static str {
+        match self {
+            LedProfile::Relaxed => "relaxed",
+            LedProfile::Standard => "standard",
+            LedProfile::Aggressive => "aggressive",
+        }
+    }
+
+    /// Position between idle (0.0) and max RPM (1.0) where the first LEDs light up
+    fn bar_start(&self) -> f32 {
+        match self {
+            LedProfile::Relaxed => 0.3,
+            LedProfile::Standard => 0.5,
+            LedProfile::Aggressive => 0.7,
+        }
+    }
+}
+
+impl fmt::Display for LedProfile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for LedProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|profile| profile.name() == s.to_lowercase())
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(LedProfile::name).collect();
+                format!("unknown LED profile '{}', expected one of: {}", s, names.join(", "))
+            })
+    }
+}
+
+/// Order the LEDs light up in as the revs rise
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FillPattern {
+    /// From the outer green LEDs in to the red ones
+    #[default]
+    Progressive,
+    /// From the red LEDs out to the green ones
+    CenterOut,
+    /// A single LED pair moving in from the outside
+    Dot,
+}
+
+impl FillPattern {
+    pub const ALL: [FillPattern; 3] = [FillPattern::Progressive, FillPattern::CenterOut, FillPattern::Dot];
+
+    /// Name used on the command line and in settings
+    pub fn name(&self) -> &'static str {
+        match self {
+            FillPattern::Progressive => "progressive",
+            FillPattern::CenterOut => "center-out",
+            FillPattern::Dot => "dot",
+        }
+    }
+
+    /// LED state for `level` of 5 lit steps; 0 is off
+    fn led_state(&self, level: u8) -> u8 {
+        match (self, level.min(5)) {
+            (_, 0) => 0,
+            (FillPattern::Progressive, level) => (1 << level) - 1,
+            (FillPattern::CenterOut, level) => 31 & !((1 << (5 - level)) - 1),
+            (FillPattern::Dot, level) => 1 << (level - 1),
+        }
+    }
+}
+
+impl fmt::Display for FillPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for FillPattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|pattern| pattern.name() == s.to_lowercase())
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(FillPattern::name).collect();
+                format!("unknown fill pattern '{}', expected one of: {}", s, names.join(", "))
+            })
+    }
+}
+
+/// How RPM is shown on the LED bar
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LedStyle {
+    pub profile: LedProfile,
+    pub pattern: FillPattern,
+    /// Flash the whole bar close to max RPM, as a shift light
+    pub shift_flash: bool,
+}
+
+impl LedStyle {
+    /// How far `rpm` is into the part of the rev range the bar shows, in percent; `None` below it
+    fn bar_percentage(&self, rpm: f32, max_rpm: f32, idle_rpm: f32) -> Option<u8> {
+        match idle_rpm + (max_rpm - idle_rpm) * self.profile.bar_start() {
+            range_start if rpm < range_start || range_start == 0.0 => None,
+            range_start => Some(((rpm - range_start) / (max_rpm - range_start) * 100_f32) as u8),
+        }
+    }
+
+    /// Whether `rpm` is at the top of the bar's range, where `shift_flash` flashes the bar.
+    /// Answered with `shift_flash` off too, for outputs with a shift light of their own.
+    pub fn in_shift_range(&self, rpm: f32, max_rpm: f32, idle_rpm: f32) -> bool {
+        self.bar_percentage(rpm, max_rpm, idle_rpm)
+            .is_some_and(|percentage| percentage >= SHIFT_FLASH_PERCENTAGE)
+    }
+}
+
+impl fmt::Display for LedStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} profile, {} pattern", self.profile, self.pattern)?;
+        if self.shift_flash {
+            write!(f, ", shift flash")?;
+        }
+        Ok(())
+    }
+}
+
+/// Maps RPM telemetry to an LED bar state and writes changes to an output
+pub struct LEDS<O: LedOutput> {
+    output: O,
+    rpm: RPM,
+    style: LedStyle,
+    state: u8,
+    /// Start of the shift flash's on/off rhythm
+    flash_clock: Instant,
+}
+
+impl<O: LedOutput> LEDS<O> {
+    pub fn new(output: O) -> Self {
+        LEDS {
+            output,
+            rpm: RPM::new(),
+            style: LedStyle::default(),
+            state: 0,
+            flash_clock: Instant::now(),
+        }
+    }
+
+    /// Bitmask of the LEDs currently lit
+    pub fn state(&self) -> u8 {
+        self.state
+    }
+
+    /// Show RPM in `style` from the next update on
+    pub fn set_style(&mut self, style: LedStyle) {
+        self.style = style;
+    }
+
+    fn percentage_to_level(percentage: u8) -> u8 {
+        match percentage {
+            u8::MIN..=20 => 1,
+            21..=40 => 2,
+            41..=60 => 3,
+            61..=80 => 4,
+            81..=u8::MAX => 5,
+        }
+    }
+
+    fn new_led_state(&self) -> u8 {
+        let (rpm_current, rpm_max, rpm_idle) = self.rpm.state();
+        match self.style.bar_percentage(rpm_current, rpm_max, rpm_idle) {
+            None => 0,
+            Some(percentage) if self.style.shift_flash && percentage >= SHIFT_FLASH_PERCENTAGE => self.flash_state(),
+            Some(percentage) => self.style.pattern.led_state(Self::percentage_to_level(percentage)),
+        }
+    }
+
+    /// All LEDs or none, alternating every `SHIFT_FLASH_INTERVAL`
+    fn flash_state(&self) -> u8 {
+        let intervals = self.flash_clock.elapsed().as_millis() / SHIFT_FLASH_INTERVAL.as_millis();
+        if intervals.is_multiple_of(2) {
+            FillPattern::Progressive.led_state(5)
+        } else {
+            0
+        }
+    }
+
+    fn update_device_and_state(&mut self, new_state: u8) -> DR2G27Result {
+        // After a panic the output's state is unknown, so the next update writes again
+        catch_panic("LED output", || self.output.set_state(new_state))??;
+        self.state = new_state;
+
+        Ok(())
+    }
+
+    /// Turn all LEDs off
+    pub fn clear(&mut self) -> DR2G27Result {
+        if self.state != 0 {
+            self.update_device_and_state(0)?;
+        }
+
+        Ok(())
+    }
+
+    /// Feed one telemetry packet and update the LEDs if their state changed
+    pub fn update<P: TelemetryParser + ?Sized>(&mut self, data: &[u8], parser: &P) -> DR2G27Result {
+        catch_panic("telemetry parser", || self.rpm.update(data, parser))?;
+        self.refresh()
+    }
+
+    /// Feed one parsed frame and update the LEDs if their state changed
+    pub fn update_frame(&mut self, frame: &TelemetryFrame) -> DR2G27Result {
+        self.rpm.update_frame(frame);
+        self.refresh()
+    }
+
+    fn refresh(&mut self) -> DR2G27Result {
+        if !self.rpm.is_stale() && self.rpm.is_race_active() {
+            let new_state = self.new_led_state();
+            if new_state != self.state {
+                self.update_device_and_state(new_state)?;
+            }
+        } else if self.state != 0 {
+            self.update_device_and_state(0)?;
+        }
+
+        Ok(())
+    }
+}
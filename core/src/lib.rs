@@ -0,0 +1,98 @@
+//! Core of G27 LED Bridge: racing game telemetry to Logitech G27 RPM LEDs.
+//!
+//! The pipeline is built from three pieces:
+//! - a [`TelemetrySource`] delivering raw packets (e.g. [`UdpSource`]),
+//! - a [`TelemetryParser`] chosen by [`GameType`] that extracts RPM data,
+//! - a [`LedOutput`] showing the resulting LED state (implemented for the G27's `HidDevice`,
+//!   and for [`MockWheel`], which records the reports for tests without hardware).
+//!
+//! [`BridgeBuilder`] ties them together into a [`Bridge`] loop:
+//!
+//! ```no_run
+//! use g27_led_bridge_core::{BridgeBuilder, GameType, UdpSource, G27_PID, G27_VID};
+//!
+//! let wheel = hidapi::HidApi::new()?.open(G27_VID, G27_PID)?;
+//! let source = UdpSource::bind(("127.0.0.1", GameType::ForzaHorizon5.default_port()))?;
+//! let mut bridge = BridgeBuilder::new(GameType::ForzaHorizon5).build(source, wheel);
+//! bridge.run().ok();
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! Modules:
+//! - `bridge`, `source`, `telemetry`, `output`, `leds`, `rpm`: the pipeline above
+//! - `device`: opening the G27 and a mock wheel for tests
+//! - `frame`, `detect`: parsed telemetry frames and spotting another game's packets by size
+//! - `recording`, `capture`: session recordings and pcapng captures, written and replayed
+//! - `forward`: formats for relaying packets to another bridge
+//! - `session`, `stats`: race summaries and packet statistics
+//! - `shared_memory`: games that publish telemetry in shared memory
+//! - `simulator`, `dump`: synthetic packets and hex dumps of unrecognized ones
+//! - `zip`: zip archives for diagnostic reports
+//! - `util`: errors and wheel IDs
+//!
+//! With the `tokio` feature:
+//! - `async_bridge`: `BridgeBuilder::build_async` and the async publishers
+//! - `bus`: a broadcast bus of parsed frames for consumers next to the LEDs
+//! - `handle`: running the bridge on its own thread with a handle to control it
+//! - `tcp`: length-prefixed packets over TCP
+//! - `bounds`: ending a run after a time or packet limit
+
+#[cfg(feature = "tokio")]
+pub mod async_bridge;
+pub mod bridge;
+#[cfg(feature = "tokio")]
+pub mod bounds;
+#[cfg(feature = "tokio")]
+pub mod bus;
+pub mod capture;
+pub mod detect;
+pub mod device;
+pub mod dump;
+pub mod frame;
+pub mod forward;
+#[cfg(feature = "tokio")]
+pub mod handle;
+pub mod leds;
+pub mod output;
+pub mod recording;
+pub mod rpm;
+pub mod session;
+pub mod shared_memory;
+pub mod simulator;
+pub mod source;
+pub mod stats;
+#[cfg(feature = "tokio")]
+pub mod tcp;
+pub mod telemetry;
+pub mod util;
+pub mod zip;
+
+#[cfg(feature = "tokio")]
+pub use async_bridge::{
+    capture_frames, drive_leds, forward_frames, publish_frames, publish_frames_detecting, publish_frames_switching,
+    record_frames, replay_frames, AsyncBridge, AsyncTelemetrySource, AsyncUdpSource,
+};
+#[cfg(feature = "tokio")]
+pub use bounds::{wait_for_bounds, BoundOutcome, RunBounds};
+pub use bridge::{Bridge, BridgeBuilder, LoopControl};
+#[cfg(feature = "tokio")]
+pub use bus::{Subscription, TelemetryBus};
+pub use capture::{Capture, CaptureReader, PcapngWriter};
+pub use device::{open_g27, MockWheel, WheelDevice};
+pub use dump::{hex_dump, UnknownPacketDumps};
+pub use forward::ForwardFormat;
+pub use frame::TelemetryFrame;
+pub use leds::{FillPattern, LedProfile, LedStyle};
+#[cfg(feature = "tokio")]
+pub use handle::{BridgeHandle, BridgeStatus};
+pub use output::{LedOutput, PendingOutput, RetryingWheel, WatchedOutput};
+pub use recording::{Recorder, RecordingReader, ReplaySource};
+pub use session::{SessionEnd, SessionSummary, SessionTracker};
+pub use shared_memory::{SharedLayout, SharedMemoryReader, SharedRegion};
+pub use source::{SenderFilter, TelemetrySource, UdpSource};
+pub use stats::{PacketStats, PacketStatsSummary};
+#[cfg(feature = "tokio")]
+pub use tcp::{AsyncTcpSource, LengthPrefix};
+pub use telemetry::{FieldType, GameType, TelemetryField, TelemetryParser};
+pub use util::{DR2G27Error, DR2G27Result, WheelOpenFailure, G27_PID, G27_VID};
+pub use zip::ZipWriter;
@@ -0,0 +1,356 @@
+// LED output abstraction
+//
+// An output receives the LED bar state computed by `LEDS` as a bitmask:
+// [GREEN_1, GREEN_2, ORANGE_4, ORANGE_8, RED_16]. The G27 wheel (or a `MockWheel`
+// standing in for it) is the built-in implementation; embedders can add their own
+// (strips, on-screen widgets, ...).
+//
+// A single failed HID write is often a hiccup of the Logitech driver rather than an
+// unplugged wheel. `RetryingWheel` retries it a few times with a growing delay and then
+// reopens the device, and only reports the wheel lost when that fails too.
+//
+// Some driver states make a write block for good. `WatchedOutput` writes on a thread of
+// its own, so a hung write cannot hold up the task that receives telemetry, and reports
+// the output lost once a write has been running for longer than its timeout.
+//
+// Telemetry does not wait for the wheel: `PendingOutput` looks for it on a thread while
+// the bridge already runs, and shows the latest LED state as soon as it is opened.
+
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use hidapi::{HidDevice, HidError};
+use log::{debug, info, warn};
+
+use crate::device::{MockWheel, WheelDevice};
+use crate::util::{catch_panic, DR2G27Error, DR2G27Result};
+
+/// A destination for RPM LED state
+pub trait LedOutput {
+    /// Show `state`, a bitmask of lit LEDs (0 = all off, 31 = all on)
+    fn set_state(&mut self, state: u8) -> DR2G27Result;
+}
+
+/// HID report that sets the G27 RPM LEDs
+pub const fn g27_led_payload(state: u8) -> [u8; 8] {
+    [0x00, 0xF8, 0x12, state, 0x00, 0x00, 0x00, 0x01]
+}
+
+/// Show `state` on the RPM LEDs of a G27 behind `device`
+pub fn write_g27_leds(device: &impl WheelDevice, state: u8) -> DR2G27Result {
+    device.write_report(&g27_led_payload(state)).map_err(DR2G27Error::HidWrite)?;
+    Ok(())
+}
+
+impl LedOutput for HidDevice {
+    fn set_state(&mut self, state: u8) -> DR2G27Result {
+        write_g27_leds(self, state)
+    }
+}
+
+impl LedOutput for MockWheel {
+    fn set_state(&mut self, state: u8) -> DR2G27Result {
+        write_g27_leds(self, state)
+    }
+}
+
+/// Opens the wheel again after writes to it kept failing
+type Reopen<D> = Box<dyn FnMut() -> Result<D, DR2G27Error> + Send>;
+
+/// G27 LED output that rides out failed writes: it retries them with a doubling delay, then
+/// reopens the device, before reporting `DR2G27Error::HidWrite`
+pub struct RetryingWheel<D: WheelDevice> {
+    device: D,
+    reopen: Option<Reopen<D>>,
+    retries: u32,
+    first_delay: Duration,
+}
+
+impl<D: WheelDevice> RetryingWheel<D> {
+    /// Retries of a failed write before the device is reopened
+    pub const DEFAULT_RETRIES: u32 = 3;
+    /// Wait before the first retry; each further one waits twice as long
+    pub const DEFAULT_FIRST_DELAY: Duration = Duration::from_millis(5);
+
+    pub fn new(device: D) -> Self {
+        Self {
+            device,
+            reopen: None,
+            retries: Self::DEFAULT_RETRIES,
+            first_delay: Self::DEFAULT_FIRST_DELAY,
+        }
+    }
+
+    /// Open the device with `reopen` once the retries failed, e.g. `open_g27`
+    pub fn reopening(mut self, reopen: impl FnMut() -> Result<D, DR2G27Error> + Send + 'static) -> Self {
+        self.reopen = Some(Box::new(reopen));
+        self
+    }
+
+    /// Retry a failed write `retries` times, waiting `first_delay` before the first retry
+    pub fn with_retries(mut self, retries: u32, first_delay: Duration) -> Self {
+        self.retries = retries;
+        self.first_delay = first_delay;
+        self
+    }
+
+    /// Retries, then the reopened device; the error of the last write if none of them worked
+    fn recover(&mut self, report: &[u8], mut error: HidError) -> Result<(), HidError> {
+        let mut delay = self.first_delay;
+        for retry in 1..=self.retries {
+            debug!("G27 write failed ({}), retry {} of {} in {:?}", error, retry, self.retries, delay);
+            thread::sleep(delay);
+            delay *= 2;
+            match self.device.write_report(report) {
+                Ok(_) => return Ok(()),
+                Err(e) => error = e,
+            }
+        }
+        let Some(reopen) = &mut self.reopen else {
+            return Err(error);
+        };
+        match reopen() {
+            Ok(device) => {
+                self.device = device;
+                self.device.write_report(report)?;
+                info!("Reopened the G27 after its writes kept failing ({})", error);
+                Ok(())
+            }
+            Err(e) => {
+                debug!("Reopening the G27 failed: {}", e);
+                Err(error)
+            }
+        }
+    }
+}
+
+impl<D: WheelDevice> LedOutput for RetryingWheel<D> {
+    fn set_state(&mut self, state: u8) -> DR2G27Result {
+        let report = g27_led_payload(state);
+        match self.device.write_report(&report) {
+            Ok(_) => Ok(()),
+            Err(e) => self.recover(&report, e).map_err(DR2G27Error::HidWrite),
+        }
+    }
+}
+
+impl<O: LedOutput + ?Sized> LedOutput for Box<O> {
+    fn set_state(&mut self, state: u8) -> DR2G27Result {
+        (**self).set_state(state)
+    }
+}
+
+/// What the caller and the writer thread of a `WatchedOutput` share
+#[derive(Default)]
+struct Writer {
+    /// Newest state not picked up by the writer yet; older ones are skipped
+    pending: Option<u8>,
+    /// When the write in progress started
+    writing_since: Option<Instant>,
+    /// Failure of the last write, reported by the next `set_state`
+    error: Option<DR2G27Error>,
+    /// Set when the `WatchedOutput` is dropped; the thread ends once nothing is pending
+    closed: bool,
+}
+
+/// LED output written on a thread of its own, with a watchdog: `set_state` hands the state
+/// to the thread without waiting for the write, and fails with `DR2G27Error::HidWriteTimeout`
+/// once a write has been running for longer than the timeout. Write errors are reported by
+/// the following `set_state`.
+pub struct WatchedOutput {
+    writer: Arc<(Mutex<Writer>, Condvar)>,
+    timeout: Duration,
+}
+
+impl WatchedOutput {
+    /// Longest time a write may take before the output counts as lost
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(500);
+
+    /// Write to `output` on a new thread
+    pub fn spawn(output: impl LedOutput + Send + 'static, timeout: Duration) -> Result<Self, DR2G27Error> {
+        Self::spawn_with(output, timeout, || {})
+    }
+
+    /// Write to `output` on a new thread that runs `setup` first, e.g. to raise its priority
+    pub fn spawn_with(
+        output: impl LedOutput + Send + 'static,
+        timeout: Duration,
+        setup: impl FnOnce() + Send + 'static,
+    ) -> Result<Self, DR2G27Error> {
+        let writer = Arc::new((Mutex::new(Writer::default()), Condvar::new()));
+        let shared = writer.clone();
+        thread::Builder::new()
+            .name("g27-led-writer".to_string())
+            .spawn(move || {
+                setup();
+                write_states(output, &shared)
+            })
+            .map_err(DR2G27Error::Spawn)?;
+        Ok(Self { writer, timeout })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Writer> {
+        self.writer.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl LedOutput for WatchedOutput {
+    fn set_state(&mut self, state: u8) -> DR2G27Result {
+        let mut writer = self.lock();
+        if writer.writing_since.is_some_and(|since| since.elapsed() > self.timeout) {
+            return Err(DR2G27Error::HidWriteTimeout(self.timeout));
+        }
+        if let Some(error) = writer.error.take() {
+            return Err(error);
+        }
+        writer.pending = Some(state);
+        self.writer.1.notify_one();
+        Ok(())
+    }
+}
+
+impl Drop for WatchedOutput {
+    /// Give the last state, e.g. the LEDs turned off at exit, up to the timeout to be written
+    fn drop(&mut self) {
+        let written = &self.writer.1;
+        let mut writer = self.lock();
+        writer.closed = true;
+        written.notify_all();
+        // Already hung; waiting longer would only hold up the caller
+        if writer.writing_since.is_some_and(|since| since.elapsed() > self.timeout) {
+            return;
+        }
+        let deadline = Instant::now() + self.timeout;
+        while writer.pending.is_some() || writer.writing_since.is_some() {
+            let Some(left) = deadline.checked_duration_since(Instant::now()) else {
+                warn!("The wheel did not take the last LED state within {:?}", self.timeout);
+                return;
+            };
+            writer = written.wait_timeout(writer, left).unwrap_or_else(|poisoned| poisoned.into_inner()).0;
+        }
+    }
+}
+
+/// What the caller and the search thread of a `PendingOutput` share
+struct Pending<O> {
+    /// Latest state, written to the output once it is opened
+    state: u8,
+    output: Option<O>,
+    /// Set when the `PendingOutput` is dropped; stops the search
+    closed: bool,
+}
+
+/// LED output that is opened in the background: a thread calls `open` every `interval` until
+/// it succeeds, while `set_state` only remembers the state. Once open, the output shows the
+/// latest state and takes every further one.
+pub struct PendingOutput<O> {
+    pending: Arc<(Mutex<Pending<O>>, Condvar)>,
+}
+
+impl<O: LedOutput + Send + 'static> PendingOutput<O> {
+    /// How often the G27 is looked for by default
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Start looking for the output with `open`
+    pub fn search(
+        mut open: impl FnMut() -> Result<O, DR2G27Error> + Send + 'static,
+        interval: Duration,
+    ) -> Result<Self, DR2G27Error> {
+        let pending = Arc::new((
+            Mutex::new(Pending {
+                state: 0,
+                output: None,
+                closed: false,
+            }),
+            Condvar::new(),
+        ));
+        let shared = pending.clone();
+        thread::Builder::new()
+            .name("g27-led-search".to_string())
+            .spawn(move || {
+                let (lock, closed) = &*shared;
+                loop {
+                    match open() {
+                        Ok(mut output) => {
+                            let mut pending = lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                            if pending.closed {
+                                return;
+                            }
+                            info!("LED output opened");
+                            if let Err(e) = output.set_state(pending.state) {
+                                warn!("LED output failed right after opening: {}", e);
+                            }
+                            pending.output = Some(output);
+                            return;
+                        }
+                        Err(e) => debug!("LED output not available yet: {}", e),
+                    }
+                    let pending = lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                    let pending = closed
+                        .wait_timeout_while(pending, interval, |pending| !pending.closed)
+                        .unwrap_or_else(|poisoned| poisoned.into_inner())
+                        .0;
+                    if pending.closed {
+                        return;
+                    }
+                }
+            })
+            .map_err(DR2G27Error::Spawn)?;
+        Ok(Self { pending })
+    }
+
+    /// Whether the output has been opened
+    pub fn is_open(&self) -> bool {
+        self.lock().output.is_some()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Pending<O>> {
+        self.pending.0.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl<O: LedOutput + Send + 'static> LedOutput for PendingOutput<O> {
+    fn set_state(&mut self, state: u8) -> DR2G27Result {
+        let mut pending = self.lock();
+        pending.state = state;
+        match pending.output.as_mut() {
+            Some(output) => output.set_state(state),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<O> Drop for PendingOutput<O> {
+    fn drop(&mut self) {
+        let (lock, closed) = &*self.pending;
+        lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).closed = true;
+        closed.notify_all();
+    }
+}
+
+/// Writer thread of a `WatchedOutput`: write pending states until it is dropped. A hung write
+/// keeps the thread, which ends if the write ever returns.
+fn write_states(mut output: impl LedOutput, writer: &(Mutex<Writer>, Condvar)) {
+    let (lock, changed) = writer;
+    let mut shared = lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    loop {
+        let Some(state) = shared.pending.take() else {
+            if shared.closed {
+                return;
+            }
+            shared = changed.wait(shared).unwrap_or_else(|poisoned| poisoned.into_inner());
+            continue;
+        };
+        shared.writing_since = Some(Instant::now());
+        drop(shared);
+        let result = catch_panic("LED output", || output.set_state(state)).and_then(|written| written);
+        shared = lock.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        shared.writing_since = None;
+        if let Err(e) = result {
+            shared.error = Some(e);
+        }
+        // Wakes a `WatchedOutput` waiting for its last state to be written
+        changed.notify_all();
+    }
+}
@@ -0,0 +1,276 @@
+// Telemetry session recordings
+//
+// A `.g27rec` file holds the raw packets of a session, so it can be analysed later,
+// attached to a bug report, or fed through the current parsers again:
+//
+//   header: "G27REC" magic, format version (1 byte)
+//   record: time since the recording started in microseconds (u64 LE),
+//           game the packet was parsed as (1 byte), packet length (u16 LE), packet bytes
+//
+// Parsed frames can be written alongside as CSV or JSON lines for spreadsheets and scripts.
+// A recording cut short, e.g. by a crash, reads back up to its last complete packet.
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, ErrorKind, Read, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use log::debug;
+
+use crate::frame::TelemetryFrame;
+use crate::telemetry::GameType;
+
+/// File extension of recordings
+pub const RECORDING_EXTENSION: &str = "g27rec";
+
+const MAGIC: &[u8; 6] = b"G27REC";
+const FORMAT_VERSION: u8 = 1;
+
+/// Byte identifying `game_type` in a recording; must never change for an existing game
+fn game_id(game_type: GameType) -> u8 {
+    match game_type {
+        GameType::DirtRally2 => 1,
+        GameType::ForzaHorizon5 => 2,
+    }
+}
+
+/// Game identified by `id` in a recording
+fn game_from_id(id: u8) -> io::Result<GameType> {
+    match id {
+        1 => Ok(GameType::DirtRally2),
+        2 => Ok(GameType::ForzaHorizon5),
+        _ => Err(io::Error::new(ErrorKind::InvalidData, format!("unknown game id {} in recording", id))),
+    }
+}
+
+/// Writes raw packets in the `.g27rec` format
+pub struct RecordingWriter<W: Write> {
+    writer: W,
+    started: Instant,
+}
+
+impl<W: Write> RecordingWriter<W> {
+    /// Write the header; packet times are measured from now
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[FORMAT_VERSION])?;
+        Ok(Self {
+            writer,
+            started: Instant::now(),
+        })
+    }
+
+    /// Append the packet of `frame`
+    pub fn write_frame(&mut self, frame: &TelemetryFrame) -> io::Result<()> {
+        let length = u16::try_from(frame.packet.len())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "packet too large to record"))?;
+        let elapsed = frame.received_at.saturating_duration_since(self.started).as_micros() as u64;
+
+        self.writer.write_all(&elapsed.to_le_bytes())?;
+        self.writer.write_all(&[game_id(frame.game_type)])?;
+        self.writer.write_all(&length.to_le_bytes())?;
+        self.writer.write_all(&frame.packet)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// One packet read back from a recording
+#[derive(Debug, Clone)]
+pub struct RecordedPacket {
+    /// Time since the recording started
+    pub elapsed: Duration,
+    /// Game the packet was parsed as while recording
+    pub game_type: GameType,
+    pub packet: Vec<u8>,
+}
+
+/// Packets to replay, in the order they arrived and with their times
+pub trait ReplaySource {
+    /// Next packet, or `None` at the end
+    fn next_packet(&mut self) -> io::Result<Option<RecordedPacket>>;
+}
+
+/// Reads raw packets back from the `.g27rec` format
+pub struct RecordingReader<R: Read> {
+    reader: R,
+}
+
+impl RecordingReader<BufReader<File>> {
+    /// Open the recording at `path`
+    pub fn open(path: &Path) -> io::Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> RecordingReader<R> {
+    /// Check the header; fails if `reader` does not hold a recording this version can read
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let mut header = [0u8; MAGIC.len() + 1];
+        let not_a_recording = || io::Error::new(ErrorKind::InvalidData, "not a G27 LED Bridge recording");
+        reader.read_exact(&mut header).map_err(|e| match e.kind() {
+            ErrorKind::UnexpectedEof => not_a_recording(),
+            _ => e,
+        })?;
+        if header[..MAGIC.len()] != MAGIC[..] {
+            return Err(not_a_recording());
+        }
+        let version = header[MAGIC.len()];
+        if version != FORMAT_VERSION {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("recording format version {} is not supported", version),
+            ));
+        }
+        Ok(Self { reader })
+    }
+
+    /// Next packet, or `None` at the end of the recording
+    pub fn next_packet(&mut self) -> io::Result<Option<RecordedPacket>> {
+        let mut record = [0u8; 11];
+        if !self.read_complete(&mut record)? {
+            return Ok(None);
+        }
+        let [elapsed @ .., game_id, len_lo, len_hi] = record;
+        let mut packet = vec![0u8; u16::from_le_bytes([len_lo, len_hi]) as usize];
+        if !self.read_complete(&mut packet)? {
+            return Ok(None);
+        }
+
+        Ok(Some(RecordedPacket {
+            elapsed: Duration::from_micros(u64::from_le_bytes(elapsed)),
+            game_type: game_from_id(game_id)?,
+            packet,
+        }))
+    }
+
+    /// Fill `buf`, returning false if the recording ends first
+    fn read_complete(&mut self, buf: &mut [u8]) -> io::Result<bool> {
+        match self.reader.read_exact(buf) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                debug!("Recording ended");
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl<R: Read> ReplaySource for RecordingReader<R> {
+    fn next_packet(&mut self) -> io::Result<Option<RecordedPacket>> {
+        RecordingReader::next_packet(self)
+    }
+}
+
+/// Text format for parsed frames
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FrameFormat {
+    Csv,
+    JsonLines,
+}
+
+impl FrameFormat {
+    /// Format matching the extension of `path`: `.csv`, or `.json`/`.jsonl` for JSON lines
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        match extension.as_str() {
+            "csv" => Some(FrameFormat::Csv),
+            "json" | "jsonl" => Some(FrameFormat::JsonLines),
+            _ => None,
+        }
+    }
+}
+
+/// Writes parsed frames, one per line
+pub struct FrameLogWriter<W: Write> {
+    writer: W,
+    format: FrameFormat,
+    started: Instant,
+}
+
+impl<W: Write> FrameLogWriter<W> {
+    /// Write the CSV header row if needed; frame times are measured from now
+    pub fn new(mut writer: W, format: FrameFormat) -> io::Result<Self> {
+        if format == FrameFormat::Csv {
+            writeln!(writer, "time_ms,game,rpm,max_rpm,idle_rpm,race_active")?;
+        }
+        Ok(Self {
+            writer,
+            format,
+            started: Instant::now(),
+        })
+    }
+
+    pub fn write_frame(&mut self, frame: &TelemetryFrame) -> io::Result<()> {
+        let time_ms = frame.received_at.saturating_duration_since(self.started).as_secs_f64() * 1000.0;
+        match self.format {
+            FrameFormat::Csv => writeln!(
+                self.writer,
+                "{:.3},{:?},{},{},{},{}",
+                time_ms, frame.game_type, frame.rpm, frame.max_rpm, frame.idle_rpm, frame.race_active
+            ),
+            FrameFormat::JsonLines => writeln!(
+                self.writer,
+                r#"{{"time_ms":{:.3},"game":"{:?}","rpm":{},"max_rpm":{},"idle_rpm":{},"race_active":{}}}"#,
+                time_ms, frame.game_type, frame.rpm, frame.max_rpm, frame.idle_rpm, frame.race_active
+            ),
+        }
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Records frames to a `.g27rec` file, and optionally their parsed values to a frame log
+pub struct Recorder {
+    packets: RecordingWriter<BufWriter<File>>,
+    frames: Option<FrameLogWriter<BufWriter<File>>>,
+    recorded: u64,
+}
+
+impl Recorder {
+    /// Create `output`, and `frame_log` in the format its extension names
+    pub fn create(output: &Path, frame_log: Option<&Path>) -> io::Result<Self> {
+        let frames = match frame_log {
+            Some(path) => {
+                let format = FrameFormat::from_path(path).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "frame log must end in .csv, .json or .jsonl")
+                })?;
+                Some(FrameLogWriter::new(BufWriter::new(File::create(path)?), format)?)
+            }
+            None => None,
+        };
+        Ok(Self {
+            packets: RecordingWriter::new(BufWriter::new(File::create(output)?))?,
+            frames,
+            recorded: 0,
+        })
+    }
+
+    pub fn record(&mut self, frame: &TelemetryFrame) -> io::Result<()> {
+        self.packets.write_frame(frame)?;
+        if let Some(frames) = &mut self.frames {
+            frames.write_frame(frame)?;
+        }
+        self.recorded += 1;
+        Ok(())
+    }
+
+    /// Number of packets recorded so far
+    pub fn recorded(&self) -> u64 {
+        self.recorded
+    }
+
+    /// Write everything buffered to disk
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.packets.flush()?;
+        if let Some(frames) = &mut self.frames {
+            frames.flush()?;
+        }
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
-use crate::common::telemetry::TelemetryParser;
+use crate::frame::TelemetryFrame;
+use crate::telemetry::TelemetryParser;
 
 #[derive(Default)]
 pub struct RPM {
@@ -38,9 +39,16 @@ impl RPM {
         (self.current, self.max, self.idle)
     }
 
-    pub fn update(&mut self, data: &[u8], parser: &dyn TelemetryParser) {
+    pub fn update<P: TelemetryParser + ?Sized>(&mut self, data: &[u8], parser: &P) {
         let (current, max, idle, is_race_active) = parser.parse_rpm_data(data);
-        
+        self.set(current, max, idle, is_race_active);
+    }
+
+    pub fn update_frame(&mut self, frame: &TelemetryFrame) {
+        self.set(frame.rpm, frame.max_rpm, frame.idle_rpm, frame.race_active);
+    }
+
+    fn set(&mut self, current: f32, max: f32, idle: f32, is_race_active: bool) {
         if (self.current, self.max, self.idle, self.is_race_active) == (current, max, idle, is_race_active) {
             self.increment_staleness();
         } else {
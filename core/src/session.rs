@@ -0,0 +1,192 @@
+// Race session statistics
+//
+// A session runs from the first frame with `race_active` set until the game reports the
+// race is over (menus, replays, pause) or its telemetry stops. `SessionTracker` follows
+// the frames and hands back a `SessionSummary` when a session ends: how long it lasted,
+// the highest RPM, the time spent in the shift range of the LED bar and the packets
+// received and estimated lost on the way. Sessions shorter than `MIN_SESSION`, such as a
+// flicker of `race_active` while a menu loads, are not reported.
+
+use std::fmt;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::frame::TelemetryFrame;
+use crate::leds::LedStyle;
+use crate::stats::{Arrival, IntervalTracker};
+use crate::telemetry::{GameType, TelemetryParser};
+
+/// Shorter sessions are not reported
+pub const MIN_SESSION: Duration = Duration::from_secs(5);
+
+/// Why a session ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionEnd {
+    /// The game stopped reporting an active race
+    RaceEnded,
+    /// Telemetry stopped arriving, or another game's did
+    TelemetryStopped,
+}
+
+/// Statistics of one finished race session
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionSummary {
+    pub game_type: GameType,
+    /// Wall clock time of the first frame
+    pub started: SystemTime,
+    /// From the first to the last frame of the session
+    pub duration: Duration,
+    pub max_rpm: f32,
+    /// Time the RPM spent where the LED bar's shift range starts
+    pub above_shift: Duration,
+    pub packets: u64,
+    /// Packets estimated lost from the gaps between those received
+    pub dropped: u64,
+    pub end: SessionEnd,
+}
+
+impl SessionSummary {
+    /// Column names of `csv_row`
+    pub const CSV_HEADER: &'static str = "started_unix,game,duration_s,max_rpm,above_shift_s,packets,dropped,end";
+
+    /// The summary as a line of a CSV history file, without the line break
+    pub fn csv_row(&self) -> String {
+        let started = self.started.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+        let end = match self.end {
+            SessionEnd::RaceEnded => "race_ended",
+            SessionEnd::TelemetryStopped => "telemetry_stopped",
+        };
+        format!(
+            "{},{:?},{:.1},{:.0},{:.1},{},{},{}",
+            started,
+            self.game_type,
+            self.duration.as_secs_f64(),
+            self.max_rpm,
+            self.above_shift.as_secs_f64(),
+            self.packets,
+            self.dropped,
+            end
+        )
+    }
+}
+
+impl fmt::Display for SessionSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let seconds = self.duration.as_secs();
+        write!(
+            f,
+            "{} session of {}:{:02}, max {:.0} RPM, {:.1} s in the shift range, {} packets, {} dropped",
+            self.game_type.game_name(),
+            seconds / 60,
+            seconds % 60,
+            self.max_rpm,
+            self.above_shift.as_secs_f64(),
+            self.packets,
+            self.dropped
+        )?;
+        if self.end == SessionEnd::TelemetryStopped {
+            write!(f, " (telemetry stopped)")?;
+        }
+        Ok(())
+    }
+}
+
+/// The session in progress
+#[derive(Debug)]
+struct Session {
+    game_type: GameType,
+    started: SystemTime,
+    first_arrival: Instant,
+    last_arrival: Instant,
+    /// Whether the last frame was in the shift range, which counts until the next one
+    in_shift_range: bool,
+    max_rpm: f32,
+    above_shift: Duration,
+    packets: u64,
+    dropped: u64,
+    /// Gaps and pauses between packets, as `PacketStats` tells them apart
+    intervals: IntervalTracker,
+}
+
+/// Follows the frames of a bus and reports each race session as it ends
+#[derive(Debug, Default)]
+pub struct SessionTracker {
+    session: Option<Session>,
+}
+
+impl SessionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count `frame`, with `style` deciding what is the shift range. Returns the summary of
+    /// the session this frame ended, if any.
+    pub fn observe(&mut self, frame: &TelemetryFrame, style: &LedStyle) -> Option<SessionSummary> {
+        let ended = match &self.session {
+            Some(_) if !frame.race_active => Some(SessionEnd::RaceEnded),
+            Some(session) if session.game_type != frame.game_type => Some(SessionEnd::TelemetryStopped),
+            _ => None,
+        };
+        let summary = ended.and_then(|end| self.end(end));
+        if !frame.race_active {
+            return summary;
+        }
+
+        let in_shift_range = style.in_shift_range(frame.rpm, frame.max_rpm, frame.idle_rpm);
+        let arrival = frame.received_at;
+        let session = self.session.get_or_insert_with(|| Session {
+            game_type: frame.game_type,
+            started: SystemTime::now(),
+            first_arrival: arrival,
+            last_arrival: arrival,
+            in_shift_range,
+            max_rpm: 0.0,
+            above_shift: Duration::ZERO,
+            packets: 0,
+            dropped: 0,
+            intervals: IntervalTracker::default(),
+        });
+        session.packets += 1;
+        session.max_rpm = session.max_rpm.max(frame.rpm);
+
+        session.last_arrival = arrival;
+        // Pauses count as neither time in the shift range nor lost packets
+        let interval = match session.intervals.observe(arrival) {
+            Arrival::First => Duration::ZERO,
+            Arrival::Regular { interval, .. } => interval,
+            Arrival::Gap { interval, missing } => {
+                session.dropped += missing;
+                interval
+            }
+        };
+        if session.in_shift_range {
+            session.above_shift += interval;
+        }
+        session.in_shift_range = in_shift_range;
+        summary
+    }
+
+    /// End the session because telemetry stopped arriving
+    pub fn telemetry_stopped(&mut self) -> Option<SessionSummary> {
+        self.end(SessionEnd::TelemetryStopped)
+    }
+
+    /// Whether a race session is in progress
+    pub fn in_session(&self) -> bool {
+        self.session.is_some()
+    }
+
+    fn end(&mut self, end: SessionEnd) -> Option<SessionSummary> {
+        let session = self.session.take()?;
+        let duration = session.last_arrival.saturating_duration_since(session.first_arrival);
+        (duration >= MIN_SESSION).then_some(SessionSummary {
+            game_type: session.game_type,
+            started: session.started,
+            duration,
+            max_rpm: session.max_rpm,
+            above_shift: session.above_shift,
+            packets: session.packets,
+            dropped: session.dropped,
+            end,
+        })
+    }
+}
@@ -0,0 +1,218 @@
+// Shared memory telemetry
+//
+// Some sims publish telemetry in a named shared memory block instead of sending packets:
+// iRacing, rFactor 2 (through its shared memory plugin) and ETS2/ATS (through the SCS
+// telemetry plugin). This is the reader their sources build on, so a game only declares
+// its layout and none of them needs unsafe code of its own:
+// - `SharedLayout` describes a game's block: its name, the layout versions the type
+//   matches and, for games that write them, the update counters around each update
+// - `SharedMemoryReader` copies a snapshot of the block into the layout type. A snapshot
+//   taken while the game was mid-update (a torn read: the counters differ) is taken
+//   again, and a block too short for the layout or with another version is an error
+//   instead of garbage RPM
+// - `SharedRegion` is the memory read from: the mapped block on Windows, or any byte
+//   buffer, e.g. to test a layout against a saved snapshot
+
+use std::marker::PhantomData;
+use std::mem;
+use std::ptr;
+
+use crate::util::DR2G27Error;
+
+/// A game's shared memory block.
+///
+/// # Safety
+///
+/// Implementors must be `#[repr(C)]` (or `#[repr(C, packed)]`) structs of integers, floats
+/// and arrays of them, for which every bit pattern is a valid value: snapshots are copied
+/// byte for byte from memory another process writes.
+pub unsafe trait SharedLayout: Copy {
+    /// Name the game maps the block under, e.g. `$rFactor2SMMP_Telemetry$`
+    const NAME: &'static str;
+    /// Layout versions this type matches
+    const VERSIONS: &'static [u32];
+
+    /// Layout version the game wrote into this snapshot
+    fn version(&self) -> u32;
+
+    /// Counters the game bumps before and after each update; they differ in a snapshot taken
+    /// mid-update. `None` for games that do not write them.
+    fn sequence(&self) -> Option<(u32, u32)> {
+        None
+    }
+}
+
+/// Memory a `SharedMemoryReader` takes snapshots of
+pub trait SharedRegion {
+    /// Size of the region in bytes
+    fn len(&self) -> usize;
+
+    /// Copy the start of the region into `buffer`, which is at most `len` bytes long
+    fn read(&self, buffer: &mut [u8]);
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl SharedRegion for [u8] {
+    fn len(&self) -> usize {
+        <[u8]>::len(self)
+    }
+
+    fn read(&self, buffer: &mut [u8]) {
+        buffer.copy_from_slice(&self[..buffer.len()]);
+    }
+}
+
+impl SharedRegion for Vec<u8> {
+    fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    fn read(&self, buffer: &mut [u8]) {
+        self.as_slice().read(buffer)
+    }
+}
+
+/// Reads consistent snapshots of layout `T` from a shared memory region
+pub struct SharedMemoryReader<T, R> {
+    region: R,
+    retries: u32,
+    buffer: Vec<u8>,
+    layout: PhantomData<T>,
+}
+
+impl<T: SharedLayout, R: SharedRegion> SharedMemoryReader<T, R> {
+    /// Snapshots taken again after a torn read before `read` gives up
+    pub const DEFAULT_RETRIES: u32 = 8;
+
+    /// Read `T` from `region`, which must be at least as large as `T`
+    pub fn new(region: R) -> Result<Self, DR2G27Error> {
+        let size = mem::size_of::<T>();
+        if region.len() < size {
+            return Err(DR2G27Error::SharedMemoryRead {
+                name: T::NAME,
+                reason: format!("the block holds {} bytes, the layout needs {}", region.len(), size),
+            });
+        }
+        Ok(Self {
+            region,
+            retries: Self::DEFAULT_RETRIES,
+            buffer: vec![0; size],
+            layout: PhantomData,
+        })
+    }
+
+    /// Take `retries` more snapshots after a torn read before giving up
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// A snapshot the game did not change while it was copied
+    pub fn read(&mut self) -> Result<T, DR2G27Error> {
+        for _ in 0..=self.retries {
+            let snapshot = self.snapshot();
+            if snapshot.sequence().is_some_and(|(begin, end)| begin != end) {
+                continue;
+            }
+            let version = snapshot.version();
+            if !T::VERSIONS.contains(&version) {
+                return Err(DR2G27Error::SharedMemoryRead {
+                    name: T::NAME,
+                    reason: format!("unsupported layout version {} (expected {:?})", version, T::VERSIONS),
+                });
+            }
+            return Ok(snapshot);
+        }
+        Err(DR2G27Error::SharedMemoryRead {
+            name: T::NAME,
+            reason: format!("the game kept updating it during {} reads", self.retries + 1),
+        })
+    }
+
+    fn snapshot(&mut self) -> T {
+        self.region.read(&mut self.buffer);
+        // SAFETY: the buffer holds size_of::<T>() bytes, and `SharedLayout` types are valid
+        // for any bit pattern; read_unaligned does not rely on the Vec's alignment
+        unsafe { ptr::read_unaligned(self.buffer.as_ptr().cast::<T>()) }
+    }
+}
+
+#[cfg(windows)]
+impl<T: SharedLayout> SharedMemoryReader<T, MappedRegion> {
+    /// Map the game's block; fails while the game (or its plugin) is not running
+    pub fn open() -> Result<Self, DR2G27Error> {
+        Self::new(MappedRegion::open(T::NAME, mem::size_of::<T>())?)
+    }
+}
+
+/// A named shared memory block mapped read-only into this process
+#[cfg(windows)]
+pub struct MappedRegion {
+    mapping: winapi::um::winnt::HANDLE,
+    view: *const u8,
+    len: usize,
+}
+
+// The view is only ever read, and stays mapped until the region is dropped
+#[cfg(windows)]
+unsafe impl Send for MappedRegion {}
+
+#[cfg(windows)]
+impl MappedRegion {
+    /// Map the first `len` bytes of the block called `name`
+    pub fn open(name: &'static str, len: usize) -> Result<Self, DR2G27Error> {
+        use std::io;
+        use std::os::windows::ffi::OsStrExt;
+
+        use winapi::shared::minwindef::FALSE;
+        use winapi::um::handleapi::CloseHandle;
+        use winapi::um::memoryapi::{MapViewOfFile, OpenFileMappingW, FILE_MAP_READ};
+
+        let wide: Vec<u16> = std::ffi::OsStr::new(name).encode_wide().chain(Some(0)).collect();
+        let open_error = |source| DR2G27Error::SharedMemoryOpen { name, source };
+        let mapping = unsafe { OpenFileMappingW(FILE_MAP_READ, FALSE, wide.as_ptr()) };
+        if mapping.is_null() {
+            return Err(open_error(io::Error::last_os_error()));
+        }
+        let view = unsafe { MapViewOfFile(mapping, FILE_MAP_READ, 0, 0, len) };
+        if view.is_null() {
+            let error = io::Error::last_os_error();
+            unsafe { CloseHandle(mapping) };
+            return Err(open_error(error));
+        }
+        Ok(Self {
+            mapping,
+            view: view as *const u8,
+            len,
+        })
+    }
+}
+
+#[cfg(windows)]
+impl SharedRegion for MappedRegion {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn read(&self, buffer: &mut [u8]) {
+        let len = buffer.len().min(self.len);
+        // SAFETY: the view is `self.len` bytes long and mapped while `self` lives
+        unsafe { ptr::copy_nonoverlapping(self.view, buffer.as_mut_ptr(), len) }
+    }
+}
+
+#[cfg(windows)]
+impl Drop for MappedRegion {
+    fn drop(&mut self) {
+        use winapi::um::handleapi::CloseHandle;
+        use winapi::um::memoryapi::UnmapViewOfFile;
+
+        unsafe {
+            UnmapViewOfFile(self.view as _);
+            CloseHandle(self.mapping);
+        }
+    }
+}
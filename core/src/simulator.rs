@@ -0,0 +1,184 @@
+// Synthetic telemetry
+//
+// Builds packets in each game's wire format from a scripted RPM pattern, so the
+// listener, parsers and LED mapping can be demoed and tested without a game running.
+// The forwarder uses the same encoding to pass one game's telemetry on as another's.
+
+use std::f32::consts::TAU;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::frame::TelemetryFrame;
+use crate::telemetry::{DirtRally2Parser, ForzaHorizon5Parser, GameType, TelemetryField};
+
+/// Scripted engine behaviour to simulate
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Pattern {
+    /// Rev from idle to the limiter and back every 4 seconds
+    RevSweep,
+    /// Accelerate through the gears, shifting at the limiter
+    Shifts,
+    /// Bounce off the rev limiter
+    Redline,
+    /// Engine idling in a race
+    Idle,
+    /// In the menus: no race running, so the LEDs stay off
+    Menu,
+}
+
+impl Pattern {
+    pub const ALL: [Pattern; 5] = [
+        Pattern::RevSweep,
+        Pattern::Shifts,
+        Pattern::Redline,
+        Pattern::Idle,
+        Pattern::Menu,
+    ];
+
+    /// Name used on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Pattern::RevSweep => "rev-sweep",
+            Pattern::Shifts => "shifts",
+            Pattern::Redline => "redline",
+            Pattern::Idle => "idle",
+            Pattern::Menu => "menu",
+        }
+    }
+
+    /// Position between idle (0.0) and max RPM (1.0) after `seconds`, or `None` outside a race
+    fn rev_fraction(&self, seconds: f32) -> Option<f32> {
+        match self {
+            Pattern::RevSweep => {
+                let phase = seconds % 4.0 / 4.0;
+                Some(1.0 - (2.0 * phase - 1.0).abs())
+            }
+            Pattern::Shifts => Some(0.55 + 0.45 * (seconds % 1.5 / 1.5)),
+            Pattern::Redline => Some(0.97 + 0.03 * (seconds * 10.0 * TAU).sin()),
+            Pattern::Idle => Some(0.02 * (seconds * 2.0 * TAU).sin().abs()),
+            Pattern::Menu => None,
+        }
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|pattern| pattern.name() == s.to_lowercase())
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(Pattern::name).collect();
+                format!("unknown pattern '{}', expected one of: {}", s, names.join(", "))
+            })
+    }
+}
+
+/// Generates the packets a game would send while its engine follows a `Pattern`
+#[derive(Debug, Clone)]
+pub struct Simulator {
+    pub game_type: GameType,
+    pub pattern: Pattern,
+    pub idle_rpm: f32,
+    pub max_rpm: f32,
+}
+
+impl Simulator {
+    pub const DEFAULT_IDLE_RPM: f32 = 900.0;
+    pub const DEFAULT_MAX_RPM: f32 = 8000.0;
+
+    pub fn new(game_type: GameType, pattern: Pattern) -> Self {
+        Self {
+            game_type,
+            pattern,
+            idle_rpm: Self::DEFAULT_IDLE_RPM,
+            max_rpm: Self::DEFAULT_MAX_RPM,
+        }
+    }
+
+    /// Packet for `elapsed` time into the simulation
+    pub fn packet_at(&self, elapsed: Duration) -> Vec<u8> {
+        match self.pattern.rev_fraction(elapsed.as_secs_f32()) {
+            Some(fraction) => {
+                let rpm = self.idle_rpm + (self.max_rpm - self.idle_rpm) * fraction;
+                encode_packet(self.game_type, rpm, self.max_rpm, self.idle_rpm, true)
+            }
+            None => encode_packet(self.game_type, 0.0, 0.0, 0.0, false),
+        }
+    }
+}
+
+/// Build a packet in `game_type`'s format carrying the given engine state; all other
+/// fields are zero
+pub fn encode_packet(game_type: GameType, rpm: f32, max_rpm: f32, idle_rpm: f32, race_active: bool) -> Vec<u8> {
+    let mut packet = vec![0u8; game_type.packet_sizes()[0]];
+    let mut put = |offset: usize, bytes: [u8; 4]| packet[offset..offset + 4].copy_from_slice(&bytes);
+
+    match game_type {
+        // DiRT Rally 2.0 has no race flag; a zero max RPM reads as not racing
+        GameType::DirtRally2 if race_active => {
+            put(DirtRally2Parser::RPM.offset, rpm.to_le_bytes());
+            put(DirtRally2Parser::MAX_RPM.offset, max_rpm.to_le_bytes());
+            put(DirtRally2Parser::IDLE_RPM.offset, idle_rpm.to_le_bytes());
+        }
+        GameType::DirtRally2 => {}
+        GameType::ForzaHorizon5 => {
+            put(ForzaHorizon5Parser::IS_RACE_ON.offset, i32::from(race_active).to_le_bytes());
+            put(ForzaHorizon5Parser::MAX_RPM.offset, max_rpm.to_le_bytes());
+            put(ForzaHorizon5Parser::IDLE_RPM.offset, idle_rpm.to_le_bytes());
+            put(ForzaHorizon5Parser::RPM.offset, rpm.to_le_bytes());
+        }
+    }
+    packet
+}
+
+/// Size of Forza Horizon 4/5's "Dash" packet, which carries speed and gear after the "Sled" part
+const FORZA_DASH_SIZE: usize = 324;
+
+/// Build a packet in `game_type`'s format carrying `frame`'s engine state, speed and gear,
+/// whichever game `frame` came from; all other fields are zero. Forza gets its "Dash" format,
+/// which has no neutral, so neutral goes out as first gear.
+pub fn encode_frame(game_type: GameType, frame: &TelemetryFrame) -> Vec<u8> {
+    let mut packet = encode_packet(game_type, frame.rpm, frame.max_rpm, frame.idle_rpm, frame.race_active);
+    match game_type {
+        GameType::DirtRally2 => {
+            if let Some(speed) = frame.speed {
+                put_f32(&mut packet, DirtRally2Parser::SPEED, speed);
+            }
+            // DiRT Rally 2.0 sends the gear as a float, with 10 for reverse
+            if let Some(gear) = frame.gear {
+                let gear = if gear < 0 { 10.0 } else { f32::from(gear) };
+                put_f32(&mut packet, DirtRally2Parser::GEAR, gear);
+            }
+        }
+        GameType::ForzaHorizon5 => {
+            packet.resize(FORZA_DASH_SIZE, 0);
+            if let Some(speed) = frame.speed {
+                // Straight ahead, so the "Sled" velocity gives the same speed
+                put_f32(&mut packet, ForzaHorizon5Parser::VELOCITY_Z, speed);
+                put_f32(&mut packet, ForzaHorizon5Parser::DASH_SPEED, speed);
+            }
+            if let Some(gear) = frame.gear {
+                packet[ForzaHorizon5Parser::DASH_GEAR.offset] = match gear {
+                    gear if gear < 0 => 0,
+                    0 => 1,
+                    gear => gear as u8,
+                };
+            }
+        }
+    }
+    packet
+}
+
+/// Write `value` where `field` is read from
+fn put_f32(packet: &mut [u8], field: TelemetryField, value: f32) {
+    packet[field.offset..field.offset + 4].copy_from_slice(&value.to_le_bytes());
+}
@@ -0,0 +1,127 @@
+// Telemetry source abstraction
+//
+// A source delivers raw game packets to the bridge loop. Receiving must not block
+// indefinitely: returning `Ok(None)` on a timeout gives the loop a chance to react
+// to control changes (settings, shutdown) while the game is silent. Packets that
+// queued up while the loop was busy writing the LEDs are taken with `try_recv`, so the
+// loop can skip to the newest instead of lagging behind the engine.
+//
+// A `SenderFilter` limits a listener bound to all interfaces to the machines that
+// should be sending telemetry, so stray LAN traffic cannot drive the LEDs.
+
+use std::io::{self, ErrorKind};
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+use std::time::Duration;
+
+/// A provider of raw telemetry packets
+pub trait TelemetrySource {
+    /// Receive one packet into `buf`, returning its size, or `None` if nothing arrived in time
+    fn recv(&mut self, buf: &mut [u8]) -> io::Result<Option<usize>>;
+
+    /// Receive a packet that is already queued into `buf` without waiting, `None` if there
+    /// is none. Sources that cannot tell never report one.
+    fn try_recv(&mut self, _buf: &mut [u8]) -> io::Result<Option<usize>> {
+        Ok(None)
+    }
+
+    /// Short description for logs, e.g. the listen address
+    fn describe(&self) -> String;
+}
+
+/// Telemetry received over UDP, as sent by the games
+pub struct UdpSource {
+    socket: UdpSocket,
+}
+
+impl UdpSource {
+    /// Default time `recv` waits before returning `None`; bounds how long a stop
+    /// or mute request can go unnoticed while the game is silent
+    pub const DEFAULT_READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+    /// Bind a UDP listener with the default read timeout
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        Self::bind_with_timeout(addr, Self::DEFAULT_READ_TIMEOUT)
+    }
+
+    /// Bind a UDP listener whose `recv` gives up after `read_timeout`
+    pub fn bind_with_timeout(addr: impl ToSocketAddrs, read_timeout: Duration) -> io::Result<Self> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_read_timeout(Some(read_timeout))?;
+        Ok(Self { socket })
+    }
+
+    /// Address the listener is bound to
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+}
+
+impl TelemetrySource for UdpSource {
+    fn recv(&mut self, buf: &mut [u8]) -> io::Result<Option<usize>> {
+        match self.socket.recv(buf) {
+            Ok(size) => Ok(Some(size)),
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn try_recv(&mut self, buf: &mut [u8]) -> io::Result<Option<usize>> {
+        self.socket.set_nonblocking(true)?;
+        let received = self.recv(buf);
+        self.socket.set_nonblocking(false)?;
+        received
+    }
+
+    fn describe(&self) -> String {
+        match self.local_addr() {
+            Ok(addr) => format!("UDP {}", addr),
+            Err(_) => "UDP".to_string(),
+        }
+    }
+}
+
+/// Sender addresses packets are accepted from, counting the packets dropped from anyone else.
+/// Empty accepts every sender. Shared between a listener and whoever reports on it, and
+/// changeable while the listener runs.
+#[derive(Debug, Default)]
+pub struct SenderFilter {
+    allowed: RwLock<Vec<IpAddr>>,
+    rejected: AtomicU64,
+}
+
+impl SenderFilter {
+    pub fn new(allowed: Vec<IpAddr>) -> Self {
+        Self {
+            allowed: RwLock::new(allowed),
+            rejected: AtomicU64::new(0),
+        }
+    }
+
+    /// Replace the allowed senders; the rejected count keeps going
+    pub fn set_allowed(&self, allowed: Vec<IpAddr>) {
+        if let Ok(mut current) = self.allowed.write() {
+            *current = allowed;
+        }
+    }
+
+    /// Whether a packet from `sender` is accepted, counting it if not. IPv4 senders seen
+    /// through an IPv6 socket (`::ffff:a.b.c.d`) match their IPv4 address.
+    pub fn accepts(&self, sender: IpAddr) -> bool {
+        let sender = sender.to_canonical();
+        let accepted = self
+            .allowed
+            .read()
+            .map_or(true, |allowed| allowed.is_empty() || allowed.iter().any(|ip| ip.to_canonical() == sender));
+        if !accepted {
+            self.rejected.fetch_add(1, Ordering::Relaxed);
+        }
+        accepted
+    }
+
+    /// Packets dropped so far
+    pub fn rejected(&self) -> u64 {
+        self.rejected.load(Ordering::Relaxed)
+    }
+}
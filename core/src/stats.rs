@@ -0,0 +1,194 @@
+// Packet rate and timing statistics
+//
+// Games send telemetry at a steady rate, about 60 packets per second with the
+// recommended settings. `PacketStats` follows a session's rate over the last second,
+// the jitter between arrivals (smoothed like RTP's interarrival jitter, RFC 3550), the
+// gaps where packets went missing and the packets that could not be parsed. A rate that
+// stays well below the game's points to its telemetry frequency being set too low.
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::frame::TelemetryFrame;
+use crate::telemetry::{GameType, TelemetryParser};
+
+/// Longer silences are the game pausing or sitting in a menu, not lost packets
+pub(crate) const PAUSE: Duration = Duration::from_secs(2);
+/// A packet arriving this many average intervals after the previous one ends a gap
+pub(crate) const GAP_FACTOR: f64 = 3.0;
+/// Weight of the newest interval in the running averages, as in RFC 3550
+pub(crate) const SMOOTHING: f64 = 1.0 / 16.0;
+/// Seconds in a row the rate must stay below half the game's before it is reported
+const LOW_RATE_SECONDS: u32 = 5;
+
+/// A session's packet statistics, as `monitor`, the tooltip and the status API show them
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PacketStatsSummary {
+    pub packets: u64,
+    /// Packets received in the last second
+    pub packets_per_second: f32,
+    /// Average deviation of the time between packets from its mean
+    pub jitter_ms: f32,
+    /// Times packets stopped for several intervals without the game pausing
+    pub gaps: u64,
+    pub longest_gap_ms: f32,
+    /// Packets that could not be parsed, e.g. too short for the game
+    pub malformed: u64,
+}
+
+impl fmt::Display for PacketStatsSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} packets, {:.0} packets/s, jitter {:.1} ms, {} gaps",
+            self.packets, self.packets_per_second, self.jitter_ms, self.gaps
+        )?;
+        if self.gaps > 0 {
+            write!(f, " (longest {:.0} ms)", self.longest_gap_ms)?;
+        }
+        write!(f, ", {} malformed", self.malformed)
+    }
+}
+
+/// How a packet arrived relative to the one before it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Arrival {
+    /// The first packet, or the first after a pause
+    First,
+    /// `interval` after the previous packet, `deviation` seconds off the average interval
+    /// once there is one
+    Regular { interval: Duration, deviation: Option<f64> },
+    /// `interval` after the previous packet, long enough that about `missing` packets were lost
+    Gap { interval: Duration, missing: u64 },
+}
+
+/// Smoothed time between packet arrivals, telling the usual spacing from gaps and pauses
+#[derive(Debug, Default)]
+pub(crate) struct IntervalTracker {
+    last_arrival: Option<Instant>,
+    /// Smoothed time between packets, in seconds
+    mean: Option<f64>,
+}
+
+impl IntervalTracker {
+    pub(crate) fn observe(&mut self, arrival: Instant) -> Arrival {
+        let Some(last_arrival) = self.last_arrival.replace(arrival) else {
+            return Arrival::First;
+        };
+        let interval = arrival.saturating_duration_since(last_arrival);
+        if interval >= PAUSE {
+            return Arrival::First;
+        }
+        let seconds = interval.as_secs_f64();
+        let Some(mean) = self.mean else {
+            // Packets read in one go arrive together and say nothing about the rate
+            if seconds > 0.0 {
+                self.mean = Some(seconds);
+            }
+            return Arrival::Regular { interval, deviation: None };
+        };
+        // Gaps move the mean too, so it follows a lasting change of rate
+        self.mean = Some(mean + (seconds - mean) * SMOOTHING);
+        if seconds > mean * GAP_FACTOR {
+            let missing = ((seconds / mean).round() as u64).saturating_sub(1);
+            return Arrival::Gap { interval, missing };
+        }
+        Arrival::Regular {
+            interval,
+            deviation: Some((seconds - mean).abs()),
+        }
+    }
+}
+
+/// Running packet statistics of one session, fed every frame and closed every second
+#[derive(Debug)]
+pub struct PacketStats {
+    summary: PacketStatsSummary,
+    window_started: Instant,
+    window_packets: u32,
+    intervals: IntervalTracker,
+    /// Smoothed deviation from the mean interval, in seconds
+    jitter: f64,
+    game_type: Option<GameType>,
+    low_rate_seconds: u32,
+    low_rate_reported: bool,
+}
+
+impl PacketStats {
+    pub fn new() -> Self {
+        Self {
+            summary: PacketStatsSummary::default(),
+            window_started: Instant::now(),
+            window_packets: 0,
+            intervals: IntervalTracker::default(),
+            jitter: 0.0,
+            game_type: None,
+            low_rate_seconds: 0,
+            low_rate_reported: false,
+        }
+    }
+
+    /// Count a received frame
+    pub fn observe(&mut self, frame: &TelemetryFrame) {
+        self.summary.packets += 1;
+        self.window_packets += 1;
+        self.game_type = Some(frame.game_type);
+
+        match self.intervals.observe(frame.received_at) {
+            // Gaps do not count towards the jitter
+            Arrival::Gap { interval, .. } => {
+                self.summary.gaps += 1;
+                self.summary.longest_gap_ms = self.summary.longest_gap_ms.max(interval.as_secs_f32() * 1000.0);
+            }
+            Arrival::Regular { deviation: Some(deviation), .. } => {
+                self.jitter += (deviation - self.jitter) * SMOOTHING;
+                self.summary.jitter_ms = (self.jitter * 1000.0) as f32;
+            }
+            Arrival::Regular { deviation: None, .. } | Arrival::First => {}
+        }
+    }
+
+    /// Close the current second at `now`, taking the number of malformed packets so far
+    /// (e.g. `TelemetryBus::malformed`)
+    pub fn tick(&mut self, now: Instant, malformed: u64) {
+        let elapsed = now.saturating_duration_since(self.window_started).as_secs_f32().max(0.001);
+        self.summary.packets_per_second = self.window_packets as f32 / elapsed;
+        self.summary.malformed = malformed;
+
+        let low = self.game_type.is_some_and(|game| self.summary.packets_per_second < game.packet_rate() / 2.0);
+        // No packets at all is a paused or closed game, not a slow one
+        self.low_rate_seconds = if low && self.window_packets > 0 { self.low_rate_seconds + 1 } else { 0 };
+        self.window_packets = 0;
+        self.window_started = now;
+    }
+
+    pub fn summary(&self) -> PacketStatsSummary {
+        self.summary
+    }
+
+    /// Explanation of a rate that has stayed too low for the game for several seconds,
+    /// once per session
+    pub fn low_rate_warning(&mut self) -> Option<String> {
+        let game_type = self.game_type?;
+        if self.low_rate_reported || self.low_rate_seconds < LOW_RATE_SECONDS {
+            return None;
+        }
+        self.low_rate_reported = true;
+        Some(format!(
+            "{} telemetry arrives at only {:.0} packets/s instead of about {:.0}, so the LEDs lag behind the engine; {}",
+            game_type.game_name(),
+            self.summary.packets_per_second,
+            game_type.packet_rate(),
+            game_type.packet_rate_hint()
+        ))
+    }
+}
+
+impl Default for PacketStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,210 @@
+// Telemetry over TCP (feature "tokio")
+//
+// Some tools relay telemetry over TCP instead of UDP datagrams. A stream has no packet
+// boundaries, so each packet is sent behind its length as a little-endian integer
+// (`LengthPrefix`). The payload is the same packet the game sends over UDP and goes
+// through the same parsers.
+//
+// The source either listens for a connection or connects out, and keeps doing so when
+// the connection drops. A task of its own reads the stream, so a packet that is half
+// received when the publisher is interrupted is not lost.
+
+use std::io::{self, ErrorKind};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, Mutex};
+use tokio::task::JoinHandle;
+
+use crate::async_bridge::AsyncTelemetrySource;
+use crate::bridge::MAX_PACKET_SIZE;
+use crate::source::SenderFilter;
+
+/// Packets read ahead of the publisher
+const BACKLOG: usize = 64;
+
+/// Size of the length in front of each packet, little-endian
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LengthPrefix {
+    U16,
+    #[default]
+    U32,
+}
+
+impl LengthPrefix {
+    async fn read(self, reader: &mut (impl AsyncReadExt + Unpin)) -> io::Result<usize> {
+        Ok(match self {
+            LengthPrefix::U16 => reader.read_u16_le().await?.into(),
+            LengthPrefix::U32 => reader.read_u32_le().await? as usize,
+        })
+    }
+
+    /// `packet` with its length in front, as a sender writes it to the stream
+    pub fn frame(self, packet: &[u8]) -> Vec<u8> {
+        let mut framed = Vec::with_capacity(packet.len() + 4);
+        match self {
+            LengthPrefix::U16 => framed.extend_from_slice(&(packet.len() as u16).to_le_bytes()),
+            LengthPrefix::U32 => framed.extend_from_slice(&(packet.len() as u32).to_le_bytes()),
+        }
+        framed.extend_from_slice(packet);
+        framed
+    }
+}
+
+/// Length-prefixed telemetry packets received over TCP
+pub struct AsyncTcpSource {
+    packets: Mutex<mpsc::Receiver<(Vec<u8>, SocketAddr)>>,
+    local_addr: Option<SocketAddr>,
+    description: String,
+    filter: Option<Arc<SenderFilter>>,
+    reader: JoinHandle<()>,
+}
+
+impl AsyncTcpSource {
+    /// Listen on `addr` and read packets from one connection at a time; the next connection
+    /// is accepted once the current one closes
+    pub async fn listen(addr: SocketAddr, prefix: LengthPrefix) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr).await?;
+        let local_addr = listener.local_addr()?;
+        let (sender, packets) = mpsc::channel(BACKLOG);
+        let reader = tokio::spawn(async move {
+            loop {
+                let (stream, peer) = match listener.accept().await {
+                    Ok(accepted) => accepted,
+                    Err(e) => {
+                        debug!("Telemetry connection failed: {}", e);
+                        continue;
+                    }
+                };
+                info!("Telemetry connection from {}", peer);
+                match read_packets(stream, peer, prefix, &sender).await {
+                    Ok(()) if sender.is_closed() => return,
+                    Ok(()) => info!("Telemetry connection from {} closed", peer),
+                    Err(e) => warn!("Telemetry connection from {} dropped: {}", peer, e),
+                }
+            }
+        });
+        Ok(Self::new(packets, Some(local_addr), format!("TCP {}", local_addr), reader))
+    }
+
+    /// Connect to `addr` (`host:port`) and read packets from it, connecting again
+    /// `retry_delay` after the connection fails or closes
+    pub fn connect(addr: String, prefix: LengthPrefix, retry_delay: Duration) -> Self {
+        let description = format!("TCP connection to {}", addr);
+        let (sender, packets) = mpsc::channel(BACKLOG);
+        let reader = tokio::spawn(async move {
+            // Failed attempts already logged at warning level
+            let mut failing = false;
+            loop {
+                match TcpStream::connect(&addr).await.and_then(|stream| Ok((stream.peer_addr()?, stream))) {
+                    Ok((peer, stream)) => {
+                        info!("Connected to telemetry at {}", peer);
+                        failing = false;
+                        match read_packets(stream, peer, prefix, &sender).await {
+                            Ok(()) if sender.is_closed() => return,
+                            Ok(()) => info!("Telemetry connection to {} closed", peer),
+                            Err(e) => warn!("Telemetry connection to {} dropped: {}", peer, e),
+                        }
+                    }
+                    Err(e) if !failing => {
+                        warn!("Cannot connect to telemetry at {}: {} (retrying)", addr, e);
+                        failing = true;
+                    }
+                    Err(e) => debug!("Connecting to {} failed: {}", addr, e),
+                }
+                tokio::time::sleep(retry_delay).await;
+            }
+        });
+        Self::new(packets, None, description, reader)
+    }
+
+    fn new(
+        packets: mpsc::Receiver<(Vec<u8>, SocketAddr)>,
+        local_addr: Option<SocketAddr>,
+        description: String,
+        reader: JoinHandle<()>,
+    ) -> Self {
+        Self {
+            packets: Mutex::new(packets),
+            local_addr,
+            description,
+            filter: None,
+            reader,
+        }
+    }
+
+    /// Drop packets from senders `filter` does not accept
+    pub fn with_sender_filter(mut self, filter: Arc<SenderFilter>) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Address a listening source is bound to, `None` for one that connects out
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.local_addr
+    }
+}
+
+impl AsyncTelemetrySource for AsyncTcpSource {
+    async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        let (packet, sender) = self
+            .packets
+            .lock()
+            .await
+            .recv()
+            .await
+            .ok_or_else(|| io::Error::new(ErrorKind::BrokenPipe, "TCP reader stopped"))?;
+        let size = packet.len().min(buf.len());
+        buf[..size].copy_from_slice(&packet[..size]);
+        Ok((size, sender))
+    }
+
+    fn sender_filter(&self) -> Option<&SenderFilter> {
+        self.filter.as_deref()
+    }
+
+    fn describe(&self) -> String {
+        self.description.clone()
+    }
+}
+
+impl Drop for AsyncTcpSource {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+/// Send the packets on `stream` to `packets` until the peer closes it (`Ok`) or sends
+/// something that is not a length-prefixed packet (`Err`)
+async fn read_packets(
+    stream: TcpStream,
+    peer: SocketAddr,
+    prefix: LengthPrefix,
+    packets: &mpsc::Sender<(Vec<u8>, SocketAddr)>,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    loop {
+        let length = match prefix.read(&mut reader).await {
+            Ok(length) => length,
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(e),
+        };
+        if length > MAX_PACKET_SIZE {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                format!("{} byte packet is too large; is the length prefix {:?}?", length, prefix),
+            ));
+        }
+        let mut packet = vec![0u8; length];
+        reader.read_exact(&mut packet).await?;
+        if packets.send((packet, peer)).await.is_err() {
+            return Ok(());
+        }
+    }
+}
@@ -0,0 +1,396 @@
+// Telemetry parsing module for multi-game support
+// 
+// Extends the original DR2G27 architecture by Aely0 with:
+// - Trait-based telemetry parsing
+// - Forza Horizon 5 support
+// - Game-agnostic RPM extraction
+// - Declarative field maps: each parser reads its packets through the `TelemetryField`s it
+//   lists in `fields()`, which the `schema` command prints, so the documented offsets are
+//   the ones parsed
+
+use std::convert::TryFrom;
+use serde::{Deserialize, Serialize};
+
+/// Trait for parsing telemetry data from different racing games
+pub trait TelemetryParser {
+    /// Parse telemetry data and return (current_rpm, max_rpm, idle_rpm, is_race_active)
+    fn parse_rpm_data(&self, data: &[u8]) -> (f32, f32, f32, bool);
+    
+    /// Parse (speed in m/s, gear) where the packet format carries them; gear is -1 for
+    /// reverse and 0 for neutral
+    fn parse_motion_data(&self, _data: &[u8]) -> (Option<f32>, Option<i8>) {
+        (None, None)
+    }
+    
+    /// Get the expected packet size for this game's telemetry
+    fn expected_packet_size(&self) -> usize;
+    
+    /// Get the game name for logging
+    fn game_name(&self) -> &'static str;
+    
+    /// Fields the parser reads, in packet order
+    fn fields(&self) -> &'static [TelemetryField];
+}
+
+/// How a field's bytes are read, all little-endian
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FieldType {
+    F32,
+    I32,
+    U8,
+}
+
+impl FieldType {
+    /// Rust name of the type, as in the JSON schema
+    pub fn name(&self) -> &'static str {
+        match self {
+            FieldType::F32 => "f32",
+            FieldType::I32 => "i32",
+            FieldType::U8 => "u8",
+        }
+    }
+
+    /// Size in bytes
+    pub fn size(&self) -> usize {
+        match self {
+            FieldType::F32 | FieldType::I32 => 4,
+            FieldType::U8 => 1,
+        }
+    }
+}
+
+/// A value at a fixed offset of a game's packets
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct TelemetryField {
+    pub name: &'static str,
+    pub offset: usize,
+    #[serde(rename = "type")]
+    pub field_type: FieldType,
+    pub unit: &'static str,
+    /// Packet sizes that carry the field; empty if all of the game's do
+    pub packet_sizes: &'static [usize],
+    pub description: &'static str,
+}
+
+impl TelemetryField {
+    const fn new(name: &'static str, offset: usize, field_type: FieldType, unit: &'static str) -> Self {
+        Self { name, offset, field_type, unit, packet_sizes: &[], description: "" }
+    }
+
+    const fn describe(self, description: &'static str) -> Self {
+        Self { description, ..self }
+    }
+
+    const fn only_in(self, packet_sizes: &'static [usize]) -> Self {
+        Self { packet_sizes, ..self }
+    }
+
+    fn f32(&self, data: &[u8]) -> Option<f32> {
+        f32_at(data, self.offset)
+    }
+
+    fn i32(&self, data: &[u8]) -> Option<i32> {
+        i32_at(data, self.offset)
+    }
+
+    fn u8(&self, data: &[u8]) -> Option<u8> {
+        u8_at(data, self.offset)
+    }
+}
+
+/// Parser output for packets that carry no usable RPM data
+const NO_RPM_DATA: (f32, f32, f32, bool) = (0.0, 0.0, 0.0, false);
+
+/// Little-endian f32 at `offset`, or `None` if the packet ends before it.
+/// Packets come from an open UDP port, so parsers must never index past the end.
+fn f32_at(data: &[u8], offset: usize) -> Option<f32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(f32::from_le_bytes(<[u8; 4]>::try_from(bytes).ok()?))
+}
+
+/// Byte at `offset`, or `None` if the packet ends before it
+fn u8_at(data: &[u8], offset: usize) -> Option<u8> {
+    data.get(offset).copied()
+}
+
+/// Little-endian i32 at `offset`, or `None` if the packet ends before it
+fn i32_at(data: &[u8], offset: usize) -> Option<i32> {
+    let bytes = data.get(offset..offset.checked_add(4)?)?;
+    Some(i32::from_le_bytes(<[u8; 4]>::try_from(bytes).ok()?))
+}
+
+/// DiRT Rally 2.0 telemetry parser
+pub struct DirtRally2Parser;
+
+impl DirtRally2Parser {
+    pub const SPEED: TelemetryField = TelemetryField::new("speed", 28, FieldType::F32, "m/s");
+    pub const GEAR: TelemetryField = TelemetryField::new("gear", 132, FieldType::F32, "gear")
+        .describe("0 is neutral, 10 (or -1 in older titles) reverse");
+    pub const RPM: TelemetryField = TelemetryField::new("rpm", 148, FieldType::F32, "rpm");
+    pub const MAX_RPM: TelemetryField = TelemetryField::new("max_rpm", 252, FieldType::F32, "rpm")
+        .describe("Rev limit; 0 when not driving, which reads as no race");
+    pub const IDLE_RPM: TelemetryField = TelemetryField::new("idle_rpm", 256, FieldType::F32, "rpm");
+    pub const FIELDS: &'static [TelemetryField] = &[Self::SPEED, Self::GEAR, Self::RPM, Self::MAX_RPM, Self::IDLE_RPM];
+}
+
+impl TelemetryParser for DirtRally2Parser {
+    fn parse_rpm_data(&self, data: &[u8]) -> (f32, f32, f32, bool) {
+        if data.len() < self.expected_packet_size() {
+            return NO_RPM_DATA;
+        }
+        
+        let (Some(current_rpm), Some(max_rpm), Some(idle_rpm)) =
+            (Self::RPM.f32(data), Self::MAX_RPM.f32(data), Self::IDLE_RPM.f32(data))
+        else {
+            return NO_RPM_DATA;
+        };
+        
+        // For DiRT Rally 2.0, assume race is active if we're receiving valid RPM data
+        let is_race_active = max_rpm > 0.0 && current_rpm >= 0.0;
+        
+        (current_rpm, max_rpm, idle_rpm, is_race_active)
+    }
+    
+    fn parse_motion_data(&self, data: &[u8]) -> (Option<f32>, Option<i8>) {
+        if data.len() < self.expected_packet_size() {
+            return (None, None);
+        }
+        
+        // The gear is a float: 0 is neutral and reverse is reported as 10 (or -1 by older titles)
+        let gear = Self::GEAR.f32(data).filter(|gear| gear.is_finite()).map(|gear| match gear {
+            gear if !(0.0..10.0).contains(&gear) => -1,
+            gear => gear as i8,
+        });
+        (Self::SPEED.f32(data), gear)
+    }
+    
+    fn expected_packet_size(&self) -> usize {
+        264 // DiRT Rally 2.0 packet size
+    }
+    
+    fn game_name(&self) -> &'static str {
+        "DiRT Rally 2.0"
+    }
+    
+    fn fields(&self) -> &'static [TelemetryField] {
+        Self::FIELDS
+    }
+}
+
+/// Forza Horizon 5 telemetry parser
+pub struct ForzaHorizon5Parser;
+
+impl ForzaHorizon5Parser {
+    pub const IS_RACE_ON: TelemetryField = TelemetryField::new("is_race_on", 0, FieldType::I32, "flag")
+        .describe("1 while driving, 0 in menus and replays");
+    pub const MAX_RPM: TelemetryField = TelemetryField::new("max_rpm", 8, FieldType::F32, "rpm");
+    pub const IDLE_RPM: TelemetryField = TelemetryField::new("idle_rpm", 12, FieldType::F32, "rpm");
+    pub const RPM: TelemetryField = TelemetryField::new("rpm", 16, FieldType::F32, "rpm");
+    pub const VELOCITY_X: TelemetryField = TelemetryField::new("velocity_x", 32, FieldType::F32, "m/s")
+        .describe("Speed is the length of the velocity in \"Sled\" packets");
+    pub const VELOCITY_Y: TelemetryField = TelemetryField::new("velocity_y", 36, FieldType::F32, "m/s");
+    pub const VELOCITY_Z: TelemetryField = TelemetryField::new("velocity_z", 40, FieldType::F32, "m/s");
+    // The "Dash" fields follow the "Sled" part; FH4/FH5 insert 12 bytes in between
+    pub const DASH_SPEED_FM7: TelemetryField =
+        TelemetryField::new("speed", 244, FieldType::F32, "m/s").only_in(&[311]);
+    pub const DASH_GEAR_FM7: TelemetryField = TelemetryField::new("gear", 307, FieldType::U8, "gear")
+        .only_in(&[311])
+        .describe("0 is reverse");
+    pub const DASH_SPEED: TelemetryField = TelemetryField::new("speed", 256, FieldType::F32, "m/s").only_in(&[324]);
+    pub const DASH_GEAR: TelemetryField = TelemetryField::new("gear", 319, FieldType::U8, "gear")
+        .only_in(&[324])
+        .describe("0 is reverse");
+    pub const FIELDS: &'static [TelemetryField] = &[
+        Self::IS_RACE_ON,
+        Self::MAX_RPM,
+        Self::IDLE_RPM,
+        Self::RPM,
+        Self::VELOCITY_X,
+        Self::VELOCITY_Y,
+        Self::VELOCITY_Z,
+        Self::DASH_SPEED_FM7,
+        Self::DASH_SPEED,
+        Self::DASH_GEAR_FM7,
+        Self::DASH_GEAR,
+    ];
+}
+
+impl TelemetryParser for ForzaHorizon5Parser {
+    fn parse_rpm_data(&self, data: &[u8]) -> (f32, f32, f32, bool) {
+        if data.len() < self.expected_packet_size() {
+            return NO_RPM_DATA;
+        }
+        
+        // Check if race is active (IsRaceOn flag)
+        let is_race_on = Self::IS_RACE_ON.i32(data) == Some(1);
+        
+        if !is_race_on {
+            return NO_RPM_DATA;
+        }
+        
+        let (Some(max_rpm), Some(idle_rpm), Some(current_rpm)) =
+            (Self::MAX_RPM.f32(data), Self::IDLE_RPM.f32(data), Self::RPM.f32(data))
+        else {
+            return NO_RPM_DATA;
+        };
+        
+        (current_rpm, max_rpm, idle_rpm, is_race_on)
+    }
+    
+    fn parse_motion_data(&self, data: &[u8]) -> (Option<f32>, Option<i8>) {
+        if data.len() < self.expected_packet_size() || Self::IS_RACE_ON.i32(data) != Some(1) {
+            return (None, None);
+        }
+        
+        // "Dash" packets carry speed and gear; "Sled" packets only have the velocity vector
+        let dash = [(Self::DASH_SPEED_FM7, Self::DASH_GEAR_FM7), (Self::DASH_SPEED, Self::DASH_GEAR)]
+            .into_iter()
+            .find(|(speed, _)| speed.packet_sizes.contains(&data.len()));
+        let Some((speed, gear)) = dash else {
+            let velocity = (Self::VELOCITY_X.f32(data), Self::VELOCITY_Y.f32(data), Self::VELOCITY_Z.f32(data));
+            let speed = match velocity {
+                (Some(x), Some(y), Some(z)) => Some((x * x + y * y + z * z).sqrt()),
+                _ => None,
+            };
+            return (speed, None);
+        };
+        let gear = gear.u8(data).map(|gear| if gear == 0 { -1 } else { gear.min(i8::MAX as u8) as i8 });
+        (speed.f32(data), gear)
+    }
+    
+    fn expected_packet_size(&self) -> usize {
+        232 // Forza "Sled" format packet size (smaller than "Dash" format)
+    }
+    
+    fn game_name(&self) -> &'static str {
+        "Forza Horizon 5"
+    }
+    
+    fn fields(&self) -> &'static [TelemetryField] {
+        Self::FIELDS
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum GameType {
+    DirtRally2,
+    ForzaHorizon5,
+}
+
+impl GameType {
+    /// All supported games, in menu order
+    pub const ALL: [GameType; 2] = [GameType::DirtRally2, GameType::ForzaHorizon5];
+
+    /// Parser for this game's packets. Parsers are stateless, so this hands out a
+    /// shared instance instead of allocating one.
+    pub fn parser(&self) -> &'static dyn TelemetryParser {
+        match self {
+            GameType::DirtRally2 => &DirtRally2Parser,
+            GameType::ForzaHorizon5 => &ForzaHorizon5Parser,
+        }
+    }
+    
+    /// Sizes of the packets this game sends, in bytes
+    pub fn packet_sizes(&self) -> &'static [usize] {
+        match self {
+            GameType::DirtRally2 => &[264],
+            // "Sled", "Dash" (FM7) and "Dash" (FH4/FH5) formats
+            GameType::ForzaHorizon5 => &[232, 311, 324],
+        }
+    }
+
+    /// Game whose packets have exactly `size` bytes, if any
+    pub fn from_packet_size(size: usize) -> Option<GameType> {
+        Self::ALL
+            .into_iter()
+            .find(|game| game.packet_sizes().contains(&size))
+    }
+
+    /// Packets per second the game sends with the recommended settings
+    pub fn packet_rate(&self) -> f32 {
+        match self {
+            GameType::DirtRally2 => 60.0,
+            GameType::ForzaHorizon5 => 60.0,
+        }
+    }
+
+    /// What to check when packets arrive much slower than `packet_rate`
+    pub fn packet_rate_hint(&self) -> &'static str {
+        match self {
+            GameType::DirtRally2 => "set delay=\"1\" on the udp line of hardware_settings_config.xml",
+            GameType::ForzaHorizon5 => {
+                "Forza always sends 60, so packets are lost on the way, e.g. over Wi-Fi or a busy relay"
+            }
+        }
+    }
+
+    pub fn default_port(&self) -> u16 {
+        match self {
+            GameType::DirtRally2 => 20777,
+            GameType::ForzaHorizon5 => 9999, // Common Forza port
+        }
+    }
+
+    /// Abbreviation for places with little room, such as the tray tooltip
+    pub fn short_name(&self) -> &'static str {
+        match self {
+            GameType::DirtRally2 => "DR2",
+            GameType::ForzaHorizon5 => "FH5",
+        }
+    }
+
+    /// Names accepted by `parse_game_name` (and `--game`), the full name first
+    pub fn aliases(&self) -> &'static [&'static str] {
+        match self {
+            GameType::DirtRally2 => &["dirt-rally-2", "dr2", "dirt"],
+            GameType::ForzaHorizon5 => &["forza-horizon-5", "fh5", "forza"],
+        }
+    }
+
+    pub fn parse_game_name(s: &str) -> Option<GameType> {
+        let name = s.to_lowercase();
+        Self::ALL.into_iter().find(|game| game.aliases().contains(&name.as_str()))
+    }
+}
+
+/// A game parses its own packets, dispatching statically to its parser; the bridge
+/// loops use this instead of going through `parser()` for every packet
+impl TelemetryParser for GameType {
+    fn parse_rpm_data(&self, data: &[u8]) -> (f32, f32, f32, bool) {
+        match self {
+            GameType::DirtRally2 => DirtRally2Parser.parse_rpm_data(data),
+            GameType::ForzaHorizon5 => ForzaHorizon5Parser.parse_rpm_data(data),
+        }
+    }
+
+    fn parse_motion_data(&self, data: &[u8]) -> (Option<f32>, Option<i8>) {
+        match self {
+            GameType::DirtRally2 => DirtRally2Parser.parse_motion_data(data),
+            GameType::ForzaHorizon5 => ForzaHorizon5Parser.parse_motion_data(data),
+        }
+    }
+
+    fn expected_packet_size(&self) -> usize {
+        match self {
+            GameType::DirtRally2 => DirtRally2Parser.expected_packet_size(),
+            GameType::ForzaHorizon5 => ForzaHorizon5Parser.expected_packet_size(),
+        }
+    }
+
+    fn game_name(&self) -> &'static str {
+        self.parser().game_name()
+    }
+    
+    fn fields(&self) -> &'static [TelemetryField] {
+        self.parser().fields()
+    }
+}
+
+impl std::str::FromStr for GameType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_game_name(s).ok_or(())
+    }
+}
\ No newline at end of file
@@ -0,0 +1,196 @@
+use std::any::Any;
+use std::cell::Cell;
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::Duration;
+
+use hidapi::HidError;
+use thiserror::Error;
+
+// [GREEN_1, GREEN_2, ORANGE_4, ORANGE_8, RED_16]
+
+pub const G27_VID: u16 = 1133; // Vendor ID
+pub const G27_PID: u16 = 49819; // Product ID
+
+pub type DR2G27Result = Result<(), DR2G27Error>;
+
+/// Everything that can stop the bridge, with enough context to tell the user what to fix
+#[derive(Debug, Error)]
+pub enum DR2G27Error {
+    #[error("cannot listen on {addr}: {source}")]
+    BindFailed {
+        addr: SocketAddr,
+        #[source]
+        source: io::Error,
+    },
+    #[error("telemetry receive failed: {0}")]
+    Receive(#[source] io::Error),
+    #[error("cannot access USB HID devices: {0}")]
+    HidInit(#[source] HidError),
+    #[error("cannot open the G27 ({failure}): {source}")]
+    HidOpen {
+        failure: WheelOpenFailure,
+        #[source]
+        source: HidError,
+    },
+    #[error("G27 connection lost: {0}")]
+    HidWrite(#[source] HidError),
+    #[error("G27 stopped responding: an LED write took longer than {0:?}")]
+    HidWriteTimeout(Duration),
+    #[error("invalid {game} packet: {reason}")]
+    ParseError { game: &'static str, reason: String },
+    #[error("{0}")]
+    ConfigError(String),
+    #[error("{service} connection to {addr} failed: {source}")]
+    Connection {
+        service: &'static str,
+        addr: String,
+        #[source]
+        source: io::Error,
+    },
+    #[error("recording file error: {0}")]
+    Recording(#[source] io::Error),
+    #[error("cannot start the bridge thread: {0}")]
+    Spawn(#[source] io::Error),
+    #[error("{component} crashed: {message}")]
+    Panicked { component: &'static str, message: String },
+    #[error("cannot open shared memory '{name}' (is the game running?): {source}")]
+    SharedMemoryOpen {
+        name: &'static str,
+        #[source]
+        source: io::Error,
+    },
+    #[error("cannot read shared memory '{name}': {reason}")]
+    SharedMemoryRead { name: &'static str, reason: String },
+}
+
+impl DR2G27Error {
+    /// `HidOpen` for `source`, with the likely reason behind it
+    pub fn hid_open(source: HidError) -> Self {
+        DR2G27Error::HidOpen {
+            failure: WheelOpenFailure::from_error(&source),
+            source,
+        }
+    }
+}
+
+/// Why a G27 that is plugged in could not be opened
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WheelOpenFailure {
+    /// Windows refused access, usually because Logitech G HUB or Logitech Gaming Software
+    /// holds the wheel exclusively
+    AccessDenied,
+    /// Another program has the wheel open and does not share it
+    InUse,
+    /// The wheel went away between finding and opening it
+    Disconnected,
+    Other,
+}
+
+impl WheelOpenFailure {
+    /// Tell the failure from the error hidapi returned; Windows reports it as a system error
+    /// code or only as the text of one
+    pub fn from_error(error: &HidError) -> Self {
+        match error {
+            HidError::IoError { error } => match (error.raw_os_error(), error.kind()) {
+                (Some(5), _) | (_, io::ErrorKind::PermissionDenied) => WheelOpenFailure::AccessDenied,
+                (Some(32), _) => WheelOpenFailure::InUse,
+                (Some(2 | 1167), _) | (_, io::ErrorKind::NotFound) => WheelOpenFailure::Disconnected,
+                _ => Self::from_message(&error.to_string()),
+            },
+            HidError::HidApiError { message } => Self::from_message(message),
+            _ => WheelOpenFailure::Other,
+        }
+    }
+
+    fn from_message(message: &str) -> Self {
+        let message = message.to_lowercase();
+        let mentions = |phrases: &[&str]| phrases.iter().any(|phrase| message.contains(phrase));
+        if mentions(&["access is denied", "permission denied"]) {
+            WheelOpenFailure::AccessDenied
+        } else if mentions(&["used by another process", "busy"]) {
+            WheelOpenFailure::InUse
+        } else if mentions(&["cannot find", "not connected", "no such device"]) {
+            WheelOpenFailure::Disconnected
+        } else {
+            WheelOpenFailure::Other
+        }
+    }
+
+    /// What to try, most likely fix first
+    pub fn hints(self) -> &'static [&'static str] {
+        match self {
+            WheelOpenFailure::AccessDenied => &[
+                "Quit Logitech G HUB or Logitech Gaming Software from its tray icon",
+                "Close other apps that drive the wheel LEDs, such as SimHub",
+                "Unplug the wheel and plug it back in",
+            ],
+            WheelOpenFailure::InUse => &[
+                "Close other apps that drive the wheel LEDs, such as SimHub",
+                "Make sure the bridge is not running twice, e.g. as the service and in the tray",
+                "Quit Logitech G HUB or Logitech Gaming Software from its tray icon",
+            ],
+            WheelOpenFailure::Disconnected => &[
+                "Check the USB cable, or try another USB port",
+                "Connect the wheel directly instead of through an unpowered hub",
+            ],
+            WheelOpenFailure::Other => &[
+                "Unplug the wheel and plug it back in",
+                "Quit Logitech G HUB or Logitech Gaming Software from its tray icon",
+                "Restart Windows if the wheel stays unavailable",
+            ],
+        }
+    }
+}
+
+impl fmt::Display for WheelOpenFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            WheelOpenFailure::AccessDenied => "access denied, another app has the wheel",
+            WheelOpenFailure::InUse => "the wheel is in use by another app",
+            WheelOpenFailure::Disconnected => "the wheel disconnected",
+            WheelOpenFailure::Other => "the wheel did not respond",
+        })
+    }
+}
+
+thread_local! {
+    /// `catch_unwind` calls in progress on this thread
+    static CATCHING: Cell<u32> = const { Cell::new(0) };
+}
+
+/// `panic::catch_unwind` that lets panic hooks know the panic is handled, see `panic_is_caught`
+pub fn catch_unwind<T>(f: impl FnOnce() -> T) -> std::thread::Result<T> {
+    CATCHING.with(|catching| catching.set(catching.get() + 1));
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING.with(|catching| catching.set(catching.get() - 1));
+    result
+}
+
+/// Whether a panic on this thread is about to be caught by `catch_unwind` and handled as an
+/// error, rather than ending the thread
+pub fn panic_is_caught() -> bool {
+    CATCHING.with(|catching| catching.get() > 0)
+}
+
+/// Run `f`, turning a panic into an `Err` with the panic message, so a bug in one
+/// parser or output fails its component instead of the whole bridge thread
+pub fn catch_panic<T>(component: &'static str, f: impl FnOnce() -> T) -> Result<T, DR2G27Error> {
+    catch_unwind(f).map_err(|payload| DR2G27Error::Panicked {
+        component,
+        message: panic_message(payload.as_ref()),
+    })
+}
+
+/// Text of a panic payload from `catch_unwind` or a panic hook
+pub fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
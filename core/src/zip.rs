@@ -0,0 +1,82 @@
+// Zip archives written in memory
+//
+// Just enough of the format for bundling a few text files, such as diagnostic reports:
+// every entry is deflated, named in UTF-8 and dated 2000-01-01, and the archive has no
+// comment, no ZIP64 records and at most 65535 entries of up to 4 GiB each.
+
+use std::io::{self, Write};
+
+/// A deflated zip archive built in memory
+#[derive(Default)]
+pub struct ZipWriter {
+    data: Vec<u8>,
+    central_directory: Vec<u8>,
+    entries: u16,
+}
+
+impl ZipWriter {
+    /// MS-DOS date of the entries; callers that need the time put it in the archive's name
+    const DOS_DATE: u16 = (2000 - 1980) << 9 | 1 << 5 | 1;
+
+    /// Deflate `contents` into an entry called `name`
+    pub fn add(&mut self, name: &str, contents: &[u8]) -> io::Result<()> {
+        let mut encoder = flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(contents)?;
+        let compressed = encoder.finish()?;
+        let mut crc = flate2::Crc::new();
+        crc.update(contents);
+
+        let offset = self.data.len() as u32;
+        // Local file header: version 2.0, UTF-8 names, deflate
+        let mut header = Vec::new();
+        header.extend_from_slice(&0x0403_4b50u32.to_le_bytes());
+        header.extend_from_slice(&20u16.to_le_bytes());
+        Self::put_entry_fields(&mut header, name, crc.sum(), compressed.len(), contents.len());
+        header.extend_from_slice(&0u16.to_le_bytes());
+        header.extend_from_slice(name.as_bytes());
+        self.data.extend_from_slice(&header);
+        self.data.extend_from_slice(&compressed);
+
+        // Central directory entry pointing back at it
+        let mut entry = Vec::new();
+        entry.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+        entry.extend_from_slice(&20u16.to_le_bytes());
+        entry.extend_from_slice(&20u16.to_le_bytes());
+        Self::put_entry_fields(&mut entry, name, crc.sum(), compressed.len(), contents.len());
+        // Extra field, comment, disk number, internal and external attributes
+        entry.extend_from_slice(&[0; 12]);
+        entry.extend_from_slice(&offset.to_le_bytes());
+        entry.extend_from_slice(name.as_bytes());
+        self.central_directory.extend_from_slice(&entry);
+        self.entries += 1;
+        Ok(())
+    }
+
+    /// Flags, method, time, date, CRC, sizes and name length, shared by both headers
+    fn put_entry_fields(out: &mut Vec<u8>, name: &str, crc: u32, compressed: usize, size: usize) {
+        out.extend_from_slice(&0x0800u16.to_le_bytes());
+        out.extend_from_slice(&8u16.to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes());
+        out.extend_from_slice(&Self::DOS_DATE.to_le_bytes());
+        out.extend_from_slice(&crc.to_le_bytes());
+        out.extend_from_slice(&(compressed as u32).to_le_bytes());
+        out.extend_from_slice(&(size as u32).to_le_bytes());
+        out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+    }
+
+    /// The finished archive, with the central directory after the entries
+    pub fn finish(mut self) -> Vec<u8> {
+        let directory_offset = self.data.len() as u32;
+        let directory_size = self.central_directory.len() as u32;
+        self.data.append(&mut self.central_directory);
+        // End of central directory record
+        self.data.extend_from_slice(&0x0605_4b50u32.to_le_bytes());
+        self.data.extend_from_slice(&[0; 4]);
+        self.data.extend_from_slice(&self.entries.to_le_bytes());
+        self.data.extend_from_slice(&self.entries.to_le_bytes());
+        self.data.extend_from_slice(&directory_size.to_le_bytes());
+        self.data.extend_from_slice(&directory_offset.to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes());
+        self.data
+    }
+}
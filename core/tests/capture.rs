@@ -0,0 +1,112 @@
+// Packet captures: pcapng files written by the bridge, and pcap files as Wireshark and
+// tcpdump write them, read back as the UDP packets to one port
+
+use std::net::SocketAddr;
+use std::time::{Duration, UNIX_EPOCH};
+
+use g27_led_bridge_core::{CaptureReader, GameType, PcapngWriter};
+
+mod common;
+use common::fixture;
+
+const PORT: u16 = 5300;
+
+/// Classic little-endian pcap of Ethernet frames, with `packets` as (microseconds, UDP payload)
+/// sent to `port`
+fn ethernet_pcap(port: u16, packets: &[(u64, &[u8])]) -> Vec<u8> {
+    let mut file = Vec::new();
+    file.extend_from_slice(&0xA1B2_C3D4u32.to_le_bytes());
+    file.extend_from_slice(&[2, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+    file.extend_from_slice(&65535u32.to_le_bytes());
+    file.extend_from_slice(&1u32.to_le_bytes());
+    for (micros, payload) in packets {
+        let mut frame = vec![0u8; 12];
+        frame.extend_from_slice(&[0x08, 0x00]);
+        // IPv4 header without checksum, 192.168.1.20 to 192.168.1.10
+        frame.extend_from_slice(&[0x45, 0]);
+        frame.extend_from_slice(&((20 + 8 + payload.len()) as u16).to_be_bytes());
+        frame.extend_from_slice(&[0, 0, 0x40, 0, 64, 17, 0, 0, 192, 168, 1, 20, 192, 168, 1, 10]);
+        frame.extend_from_slice(&50000u16.to_be_bytes());
+        frame.extend_from_slice(&port.to_be_bytes());
+        frame.extend_from_slice(&((8 + payload.len()) as u16).to_be_bytes());
+        frame.extend_from_slice(&[0, 0]);
+        frame.extend_from_slice(payload);
+
+        file.extend_from_slice(&((micros / 1_000_000) as u32).to_le_bytes());
+        file.extend_from_slice(&((micros % 1_000_000) as u32).to_le_bytes());
+        file.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        file.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+        file.extend_from_slice(&frame);
+    }
+    file
+}
+
+#[test]
+fn written_captures_read_back_by_port() {
+    let dash = fixture("fh5/dash_race.bin");
+    let sled = fixture("fh5/sled_race.bin");
+    let sender: SocketAddr = "127.0.0.1:50000".parse().unwrap();
+    let started = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+    let mut writer = PcapngWriter::new(Vec::new()).unwrap();
+    writer.write_packet(started, sender, SocketAddr::from(([127, 0, 0, 1], PORT)), &dash, None).unwrap();
+    writer.write_packet(started, sender, "127.0.0.1:9999".parse().unwrap(), &dash, None).unwrap();
+    let later = started + Duration::from_millis(250);
+    writer.write_packet(later, sender, "[::1]:5300".parse().unwrap(), &sled, Some("sled")).unwrap();
+    let file = writer.into_inner();
+
+    let mut capture = CaptureReader::new(&file[..], PORT).unwrap();
+    let first = capture.next_packet().unwrap().expect("packet to the port");
+    assert_eq!((first.elapsed, first.game_type, first.packet), (Duration::ZERO, GameType::ForzaHorizon5, dash));
+    let second = capture.next_packet().unwrap().expect("IPv6 packet to the port");
+    assert_eq!((second.elapsed, second.packet), (Duration::from_millis(250), sled));
+    assert!(capture.next_packet().unwrap().is_none());
+    assert_eq!(capture.matched(), 2);
+}
+
+#[test]
+fn ethernet_pcap_packets_keep_their_timing() {
+    let dash = fixture("fh5/dash_race.bin");
+    let dr2 = fixture("dr2/stage_high_revs.bin");
+    let file = ethernet_pcap(PORT, &[(10_000_000, &dash), (10_016_667, &dash), (10_033_333, &dr2)]);
+
+    let mut capture = CaptureReader::new(&file[..], PORT).unwrap();
+    let mut packets = Vec::new();
+    while let Some(packet) = capture.next_packet().unwrap() {
+        packets.push((packet.elapsed, packet.game_type));
+    }
+    assert_eq!(
+        packets,
+        [
+            (Duration::ZERO, GameType::ForzaHorizon5),
+            (Duration::from_micros(16_667), GameType::ForzaHorizon5),
+            (Duration::from_micros(33_333), GameType::DirtRally2),
+        ]
+    );
+}
+
+#[test]
+fn chosen_game_overrides_detection() {
+    let dash = fixture("fh5/dash_race.bin");
+    let file = ethernet_pcap(PORT, &[(0, &dash)]);
+
+    let mut capture = CaptureReader::new(&file[..], PORT).unwrap().with_game(GameType::DirtRally2);
+    assert_eq!(capture.next_packet().unwrap().unwrap().game_type, GameType::DirtRally2);
+}
+
+#[test]
+fn capture_cut_short_ends_at_last_complete_packet() {
+    let dash = fixture("fh5/dash_race.bin");
+    let file = ethernet_pcap(PORT, &[(0, &dash), (1_000, &dash)]);
+
+    let mut capture = CaptureReader::new(&file[..file.len() - 10], PORT).unwrap();
+    assert!(capture.next_packet().unwrap().is_some());
+    assert!(capture.next_packet().unwrap().is_none());
+}
+
+#[test]
+fn other_files_are_not_captures() {
+    for file in [&b"G27REC\x01"[..], b"", b"\xD4\xC3\xB2"] {
+        assert!(CaptureReader::new(file, PORT).is_err());
+    }
+}
@@ -0,0 +1,15 @@
+// Helpers shared by the integration tests; each test crate uses only some of them
+#![allow(dead_code)]
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// tests/fixtures, holding captured packets and their expected values (see its README)
+pub fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("fixtures")
+}
+
+/// Packet from tests/fixtures
+pub fn fixture(name: &str) -> Vec<u8> {
+    fs::read(fixtures_dir().join(name)).unwrap_or_else(|e| panic!("read fixture {}: {}", name, e))
+}
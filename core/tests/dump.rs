@@ -0,0 +1,39 @@
+// Hex dumps of packets that do not match the selected game
+
+use std::time::{Duration, Instant};
+
+use g27_led_bridge_core::dump::DUMP_INTERVAL;
+use g27_led_bridge_core::{hex_dump, GameType, UnknownPacketDumps};
+
+#[test]
+fn dump_shows_offsets_bytes_and_candidate_floats() {
+    let mut packet = vec![0u8; 20];
+    packet[4..8].copy_from_slice(&7500.0f32.to_le_bytes());
+    packet[8..12].copy_from_slice(&0.5f32.to_le_bytes());
+    packet[16..20].copy_from_slice(&1u32.to_le_bytes());
+
+    let dump = hex_dump(&packet);
+    let lines: Vec<_> = dump.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("    0  00 00 00 00 00 60 ea 45 00 00 00 3f"), "{}", lines[0]);
+    assert!(lines[0].ends_with(" 00  @4: 7500 (rpm?)  @8: 0.5"), "{}", lines[0]);
+    // An integer 1 reads as a denormal float, which is no candidate
+    assert_eq!(lines[1].trim_end(), "   16  01 00 00 00");
+}
+
+#[test]
+fn dumps_are_rate_limited_and_count_the_packets_in_between() {
+    let started = Instant::now();
+    let mut dumps = UnknownPacketDumps::new();
+    let packet = [0u8; 300];
+
+    let first = dumps.dump(GameType::ForzaHorizon5, &packet, started).expect("first packet dumped");
+    assert!(first.starts_with("Unrecognized 300 byte packet for Forza"), "{}", first);
+    for packet_number in 1..=3 {
+        let at = started + Duration::from_millis(packet_number * 100);
+        assert_eq!(dumps.dump(GameType::ForzaHorizon5, &packet, at), None);
+    }
+
+    let next = dumps.dump(GameType::ForzaHorizon5, &packet, started + DUMP_INTERVAL).expect("dumped again");
+    assert!(next.contains(", 3 more since the last dump:"), "{}", next);
+}
@@ -0,0 +1,130 @@
+// Parser regression tests against the packets in tests/fixtures (see its README)
+
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::Path;
+
+use g27_led_bridge_core::{ForwardFormat, GameType, TelemetryFrame};
+
+mod common;
+use common::fixtures_dir;
+
+/// One line of fixtures/packets.txt
+struct Golden {
+    file: String,
+    game_type: GameType,
+    expected: (f32, f32, f32, bool),
+}
+
+fn load_manifest() -> Vec<Golden> {
+    let manifest = fs::read_to_string(fixtures_dir().join("packets.txt")).expect("read packets.txt");
+    manifest
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let columns: Vec<&str> = line.split_whitespace().collect();
+            let [file, game, rpm, max_rpm, idle_rpm, race_active] = columns[..] else {
+                panic!("packets.txt: expected 6 columns in '{}'", line);
+            };
+            let number = |value: &str| -> f32 {
+                value.parse().unwrap_or_else(|_| panic!("packets.txt: bad number '{}' in '{}'", value, line))
+            };
+            Golden {
+                file: file.to_string(),
+                game_type: GameType::parse_game_name(game)
+                    .unwrap_or_else(|| panic!("packets.txt: unknown game '{}' in '{}'", game, line)),
+                expected: (
+                    number(rpm),
+                    number(max_rpm),
+                    number(idle_rpm),
+                    race_active.parse().unwrap_or_else(|_| panic!("packets.txt: bad flag in '{}'", line)),
+                ),
+            }
+        })
+        .collect()
+}
+
+/// Packet files below `dir`, relative to the fixtures directory with `/` separators
+fn packet_files(dir: &Path, found: &mut BTreeSet<String>) {
+    for entry in fs::read_dir(dir).expect("read fixtures directory") {
+        let path = entry.expect("read fixtures entry").path();
+        if path.is_dir() {
+            packet_files(&path, found);
+        } else if path.extension().is_some_and(|extension| extension == "bin") {
+            let relative = path.strip_prefix(fixtures_dir()).expect("fixture below fixtures directory");
+            let parts: Vec<_> = relative.iter().map(|part| part.to_string_lossy()).collect();
+            found.insert(parts.join("/"));
+        }
+    }
+}
+
+#[test]
+fn parsers_match_golden_packets() {
+    let mut failures = Vec::new();
+
+    for golden in load_manifest() {
+        let packet = fs::read(fixtures_dir().join(&golden.file))
+            .unwrap_or_else(|e| panic!("read fixture {}: {}", golden.file, e));
+        let parsed = golden.game_type.parser().parse_rpm_data(&packet);
+        if parsed != golden.expected {
+            failures.push(format!(
+                "{} ({} bytes): parsed {:?}, expected {:?}",
+                golden.file,
+                packet.len(),
+                parsed,
+                golden.expected
+            ));
+        }
+    }
+
+    assert!(failures.is_empty(), "golden packet mismatches:\n{}", failures.join("\n"));
+}
+
+#[test]
+fn every_fixture_is_listed() {
+    let listed: BTreeSet<String> = load_manifest().into_iter().map(|golden| golden.file).collect();
+    let mut found = BTreeSet::new();
+    packet_files(&fixtures_dir(), &mut found);
+
+    let unlisted: Vec<_> = found.difference(&listed).collect();
+    assert!(unlisted.is_empty(), "fixtures missing from packets.txt: {:?}", unlisted);
+}
+
+#[test]
+fn every_game_has_fixtures_for_each_packet_size() {
+    let manifest = load_manifest();
+    for game_type in GameType::ALL {
+        for size in game_type.packet_sizes() {
+            let covered = manifest.iter().any(|golden| {
+                golden.game_type == game_type
+                    && fs::metadata(fixtures_dir().join(&golden.file)).is_ok_and(|meta| meta.len() == *size as u64)
+            });
+            assert!(covered, "no {} byte fixture for {:?}", size, game_type);
+        }
+    }
+}
+
+#[test]
+fn reencoded_packets_parse_the_same_in_every_game() {
+    for golden in load_manifest() {
+        let packet = fs::read(fixtures_dir().join(&golden.file)).expect("read fixture");
+        let frame = TelemetryFrame::parse(golden.game_type, &golden.game_type, &packet);
+        for game_type in GameType::ALL {
+            let encoded = ForwardFormat::Game(game_type).encode(&frame);
+            let reparsed = TelemetryFrame::parse(game_type, &game_type, &encoded);
+            let context = format!("{} as {:?}", golden.file, game_type);
+            assert_eq!(reparsed.rpm, frame.rpm, "{}", context);
+            assert_eq!(reparsed.max_rpm, frame.max_rpm, "{}", context);
+            assert_eq!(reparsed.idle_rpm, frame.idle_rpm, "{}", context);
+            assert_eq!(reparsed.race_active, frame.race_active, "{}", context);
+            if let (Some(speed), Some(reparsed)) = (frame.speed, reparsed.speed) {
+                assert!((speed - reparsed).abs() < 0.01, "{}: speed {} became {}", context, speed, reparsed);
+            }
+            // Forza has no neutral, and DiRT Rally 2.0's tenth gear is reverse
+            if frame.gear.is_some_and(|gear| gear != 0 && gear < 10) {
+                assert_eq!(reparsed.gear, frame.gear, "{}", context);
+            }
+        }
+    }
+}
@@ -0,0 +1,490 @@
+// End-to-end tests: captured packets sent over loopback UDP (and TCP) to the async bridge,
+// asserting the LED states it writes to a mock wheel
+
+use std::fs;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use g27_led_bridge_core::leds::LEDS;
+use g27_led_bridge_core::simulator::encode_packet;
+use g27_led_bridge_core::{
+    capture_frames, drive_leds, forward_frames, publish_frames, publish_frames_detecting, publish_frames_switching,
+    wait_for_bounds, AsyncTcpSource, AsyncUdpSource, BoundOutcome, BridgeBuilder, BridgeStatus, Capture, DR2G27Error,
+    DR2G27Result, ForwardFormat, GameType, LengthPrefix, LoopControl, MockWheel, RunBounds, SenderFilter, TelemetryBus,
+    TelemetryFrame, TelemetryParser,
+};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+mod common;
+use common::fixture;
+
+/// How long to wait for the bridge to write the expected LED states
+const LED_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Time between packets sent with `Harness::send_paced`, a little faster than 60 per second
+const PACKET_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A listener and LED task wired up like the tray app, fed from a UDP socket of its own
+struct Harness {
+    sender: UdpSocket,
+    target: SocketAddr,
+    bus: TelemetryBus,
+    game: Arc<watch::Sender<GameType>>,
+    control: watch::Sender<LoopControl>,
+    listener: JoinHandle<DR2G27Result>,
+}
+
+impl Harness {
+    async fn start(game_type: GameType) -> Self {
+        Self::spawn(game_type, false, None).await
+    }
+
+    /// Like `start`, but the listener detects the game from the packets
+    async fn start_detecting(game_type: GameType) -> Self {
+        Self::spawn(game_type, true, None).await
+    }
+
+    /// Like `start`, but the listener only accepts the senders `filter` allows
+    async fn start_filtered(game_type: GameType, filter: Arc<SenderFilter>) -> Self {
+        Self::spawn(game_type, false, Some(filter)).await
+    }
+
+    async fn spawn(game_type: GameType, detect: bool, filter: Option<Arc<SenderFilter>>) -> Self {
+        let mut source = AsyncUdpSource::bind("127.0.0.1:0").await.expect("bind listener");
+        if let Some(filter) = filter {
+            source = source.with_sender_filter(filter);
+        }
+        let target = source.local_addr().expect("listener address");
+        let sender = UdpSocket::bind("127.0.0.1:0").await.expect("bind sender");
+        let bus = TelemetryBus::new();
+        let game = Arc::new(watch::channel(game_type).0);
+        let (control, control_rx) = watch::channel(LoopControl::Run);
+
+        let listener_bus = bus.clone();
+        let listener_game = game.clone();
+        let listener = tokio::spawn(async move {
+            if detect {
+                publish_frames_detecting(&source, &listener_game, false, &listener_bus, control_rx).await
+            } else {
+                publish_frames_switching(&source, listener_game.subscribe(), false, &listener_bus, control_rx).await
+            }
+        });
+
+        Self {
+            sender,
+            target,
+            bus,
+            game,
+            control,
+            listener,
+        }
+    }
+
+    /// Drive `wheel` from the bus, as the wheel component does after (re)connecting
+    fn connect(&self, wheel: &MockWheel) -> JoinHandle<DR2G27Result> {
+        let mut leds = LEDS::new(wheel.clone());
+        let frames = self.bus.subscribe("wheel LEDs");
+        let control = self.control.subscribe();
+        tokio::spawn(async move { drive_leds(&mut leds, frames, control).await })
+    }
+
+    async fn send(&self, packets: &[Vec<u8>]) {
+        for packet in packets {
+            self.sender.send_to(packet, self.target).await.expect("send packet");
+        }
+    }
+
+    /// Send `packets` spaced out like a game does, so the LEDs show each one instead of
+    /// skipping to the newest
+    async fn send_paced(&self, packets: &[Vec<u8>]) {
+        for packet in packets {
+            self.send(std::slice::from_ref(packet)).await;
+            tokio::time::sleep(PACKET_INTERVAL).await;
+        }
+    }
+
+    fn switch_game(&self, game_type: GameType) {
+        self.game.send_replace(game_type);
+    }
+
+    /// Stop the listener and return how it ended
+    async fn stop(self) -> DR2G27Result {
+        self.control.send_replace(LoopControl::Stop);
+        self.listener.await.expect("listener task")
+    }
+}
+
+/// Wait until `wheel` has been written exactly `expected`, failing with what it got instead
+async fn expect_leds(wheel: &MockWheel, expected: &[u8]) {
+    let waited = tokio::time::timeout(LED_TIMEOUT, async {
+        while wheel.led_states().len() < expected.len() {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await;
+    assert!(waited.is_ok(), "timed out waiting for LED states {:?}, got {:?}", expected, wheel.led_states());
+    assert_eq!(wheel.led_states(), expected);
+}
+
+#[tokio::test]
+async fn captured_session_drives_leds() {
+    let harness = Harness::start(GameType::DirtRally2).await;
+    let wheel = MockWheel::new();
+    let _display = harness.connect(&wheel);
+
+    harness
+        .send_paced(&[fixture("dr2/stage_idle.bin"), fixture("dr2/stage_high_revs.bin"), fixture("dr2/menu.bin")])
+        .await;
+
+    // Idle keeps the LEDs dark, so the first write is the high revs
+    expect_leds(&wheel, &[15, 0]).await;
+    harness.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn switching_games_changes_the_parser() {
+    let harness = Harness::start(GameType::DirtRally2).await;
+    let wheel = MockWheel::new();
+    let _display = harness.connect(&wheel);
+
+    // Forza "Sled" packets are too small for DiRT Rally 2.0, so they only count while Forza is selected
+    harness.send(&[fixture("fh5/sled_race.bin"), fixture("dr2/stage_high_revs.bin")]).await;
+    expect_leds(&wheel, &[15]).await;
+
+    harness.switch_game(GameType::ForzaHorizon5);
+    harness.send(&[fixture("fh5/dash_race.bin")]).await;
+    expect_leds(&wheel, &[15, 31]).await;
+
+    harness.switch_game(GameType::DirtRally2);
+    harness.send(&[fixture("fh5/sled_race.bin"), fixture("dr2/menu.bin")]).await;
+    expect_leds(&wheel, &[15, 31, 0]).await;
+    harness.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn detecting_switches_to_the_game_sending_packets() {
+    let harness = Harness::start_detecting(GameType::DirtRally2).await;
+    let wheel = MockWheel::new();
+    let _display = harness.connect(&wheel);
+
+    // Too small for DiRT Rally 2.0 until enough of them arrive to switch to Forza
+    harness.send(&vec![fixture("fh5/sled_race.bin"); 9]).await;
+    harness.send(&[fixture("fh5/dash_race.bin")]).await;
+    expect_leds(&wheel, &[31]).await;
+    assert_eq!(*harness.game.borrow(), GameType::ForzaHorizon5);
+    harness.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn repeated_packets_are_stale() {
+    let harness = Harness::start(GameType::ForzaHorizon5).await;
+    let wheel = MockWheel::new();
+    let _display = harness.connect(&wheel);
+
+    // A paused game repeats its last packet; the LEDs go dark until the values change
+    let frozen = vec![fixture("fh5/dash_race.bin"); 8];
+    harness.send_paced(&frozen).await;
+    harness.send_paced(&[fixture("fh5/sled_race.bin")]).await;
+
+    expect_leds(&wheel, &[31, 0, 15]).await;
+    harness.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn reconnected_wheel_resumes_without_restarting_listener() {
+    let harness = Harness::start(GameType::ForzaHorizon5).await;
+    let unplugged = MockWheel::new();
+    let display = harness.connect(&unplugged);
+
+    harness.send(&[fixture("fh5/dash_race.bin")]).await;
+    expect_leds(&unplugged, &[31]).await;
+
+    unplugged.disconnect();
+    harness.send(&[fixture("fh5/sled_race.bin")]).await;
+    let result = tokio::time::timeout(LED_TIMEOUT, display).await.expect("LED task to fail");
+    assert!(matches!(result.expect("LED task"), Err(DR2G27Error::HidWrite(_))));
+
+    let replugged = MockWheel::new();
+    let _display = harness.connect(&replugged);
+    harness.send(&[fixture("fh5/dash_race.bin")]).await;
+
+    expect_leds(&replugged, &[31]).await;
+    assert!(!harness.listener.is_finished());
+    harness.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn packets_from_other_senders_are_dropped_and_counted() {
+    let filter = Arc::new(SenderFilter::new(vec!["192.0.2.7".parse().unwrap()]));
+    let harness = Harness::start_filtered(GameType::DirtRally2, filter.clone()).await;
+    let wheel = MockWheel::new();
+    let _display = harness.connect(&wheel);
+
+    harness.send(&[fixture("dr2/stage_high_revs.bin")]).await;
+    let rejected = tokio::time::timeout(LED_TIMEOUT, async {
+        while filter.rejected() == 0 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await;
+    assert!(rejected.is_ok(), "packet from 127.0.0.1 was not rejected");
+    assert!(wheel.led_states().is_empty());
+
+    // A new allowlist applies without rebinding
+    filter.set_allowed(vec![Ipv4Addr::LOCALHOST.into()]);
+    harness.send(&[fixture("dr2/stage_high_revs.bin")]).await;
+    expect_leds(&wheel, &[15]).await;
+    assert_eq!(filter.rejected(), 1);
+    harness.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn multicast_needs_a_multicast_group() {
+    let unicast = AsyncUdpSource::bind_multicast(Ipv4Addr::LOCALHOST.into(), 0, None, false).await;
+    assert_eq!(unicast.err().map(|e| e.kind()), Some(std::io::ErrorKind::InvalidInput));
+}
+
+#[tokio::test]
+async fn shared_listeners_bind_the_same_port() {
+    let first = AsyncUdpSource::bind_shared("127.0.0.1:0".parse().unwrap()).await.expect("bind first listener");
+    let addr = first.local_addr().expect("listener address");
+    let second = AsyncUdpSource::bind_shared(addr).await.expect("bind second listener");
+    assert_eq!(second.local_addr().expect("second listener address"), addr);
+}
+
+#[tokio::test]
+async fn tcp_packets_drive_leds_across_connections() {
+    let source = AsyncTcpSource::listen("127.0.0.1:0".parse().unwrap(), LengthPrefix::U16).await.expect("listen");
+    let addr = source.local_addr().expect("listener address");
+    let bus = TelemetryBus::new();
+    let (control, control_rx) = watch::channel(LoopControl::Run);
+    let wheel = MockWheel::new();
+    let mut leds = LEDS::new(wheel.clone());
+    let (frames, display_control) = (bus.subscribe("wheel LEDs"), control.subscribe());
+    let _display = tokio::spawn(async move { drive_leds(&mut leds, frames, display_control).await });
+    let listener_bus = bus.clone();
+    let listener = tokio::spawn(async move {
+        publish_frames(&source, GameType::ForzaHorizon5, false, &listener_bus, control_rx).await
+    });
+
+    // A packet split across writes, then one on a new connection once the first closes
+    let framed = LengthPrefix::U16.frame(&fixture("fh5/dash_race.bin"));
+    let mut first = TcpStream::connect(addr).await.expect("connect");
+    first.write_all(&framed[..100]).await.expect("send first part");
+    first.write_all(&framed[100..]).await.expect("send rest");
+    drop(first);
+    let mut second = TcpStream::connect(addr).await.expect("reconnect");
+    second.write_all(&LengthPrefix::U16.frame(&fixture("fh5/sled_race.bin"))).await.expect("send packet");
+
+    expect_leds(&wheel, &[31, 15]).await;
+    control.send_replace(LoopControl::Stop);
+    listener.await.expect("listener task").unwrap();
+}
+
+#[tokio::test]
+async fn forwarded_packets_arrive_unchanged() {
+    let harness = Harness::start(GameType::ForzaHorizon5).await;
+    let relay = UdpSocket::bind("127.0.0.1:0").await.expect("bind relay");
+    let receiver = UdpSocket::bind("127.0.0.1:0").await.expect("bind receiver");
+    let target = receiver.local_addr().expect("receiver address");
+    let frames = harness.bus.subscribe("forwarder");
+    let control = harness.control.subscribe();
+    let _forwarder =
+        tokio::spawn(async move { forward_frames(&relay, target, ForwardFormat::Raw, frames, control).await });
+
+    let packet = fixture("fh5/dash_race.bin");
+    harness.send(std::slice::from_ref(&packet)).await;
+    let mut received = vec![0u8; 1024];
+    let size = tokio::time::timeout(LED_TIMEOUT, receiver.recv(&mut received))
+        .await
+        .expect("forwarded packet")
+        .expect("receive forwarded packet");
+    assert_eq!(&received[..size], &packet[..]);
+    harness.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn forwarded_frames_take_each_destination_format() {
+    let harness = Harness::start(GameType::DirtRally2).await;
+    let mut receivers = Vec::new();
+    for format in [ForwardFormat::Json, ForwardFormat::Game(GameType::ForzaHorizon5)] {
+        let relay = UdpSocket::bind("127.0.0.1:0").await.expect("bind relay");
+        let receiver = UdpSocket::bind("127.0.0.1:0").await.expect("bind receiver");
+        let target = receiver.local_addr().expect("receiver address");
+        let frames = harness.bus.subscribe("forwarder");
+        let control = harness.control.subscribe();
+        tokio::spawn(async move { forward_frames(&relay, target, format, frames, control).await });
+        receivers.push(receiver);
+    }
+
+    harness.send(&[fixture("dr2/stage_high_revs.bin")]).await;
+    let mut received = Vec::new();
+    for receiver in &receivers {
+        let mut buffer = vec![0u8; 1024];
+        let size = tokio::time::timeout(LED_TIMEOUT, receiver.recv(&mut buffer))
+            .await
+            .expect("forwarded packet")
+            .expect("receive forwarded packet");
+        buffer.truncate(size);
+        received.push(buffer);
+    }
+    let json: serde_json::Value = serde_json::from_slice(&received[0]).expect("JSON frame");
+    assert_eq!(json["game"], "DirtRally2");
+    assert!(json["rpm"].as_f64().is_some_and(|rpm| rpm > 0.0), "unexpected JSON: {}", json);
+    assert_eq!(received[1].len(), 324, "Forza \"Dash\" packet");
+    let (rpm, max_rpm, _, race_active) = GameType::ForzaHorizon5.parse_rpm_data(&received[1]);
+    assert!(race_active && rpm > 0.0 && max_rpm > rpm);
+    harness.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn leds_skip_to_the_newest_of_a_burst() {
+    let harness = Harness::start(GameType::DirtRally2).await;
+    let wheel = MockWheel::new();
+    let _display = harness.connect(&wheel);
+
+    // Sent faster than the LED task runs, as when a wheel write stalls
+    harness
+        .send(&[fixture("dr2/stage_idle.bin"), fixture("dr2/menu.bin"), fixture("dr2/stage_high_revs.bin")])
+        .await;
+
+    expect_leds(&wheel, &[15]).await;
+    harness.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn latest_frame_skips_the_queued_ones() {
+    let bus = TelemetryBus::new();
+    let mut frames = bus.subscribe("wheel LEDs");
+    for rpm in [5000.0, 6000.0, 7000.0] {
+        let packet = encode_packet(GameType::ForzaHorizon5, rpm, 8000.0, 900.0, true);
+        bus.publish(TelemetryFrame::parse(GameType::ForzaHorizon5, &GameType::ForzaHorizon5, &packet));
+    }
+    drop(bus);
+
+    assert_eq!(frames.recv_latest().await.map(|frame| frame.rpm), Some(7000.0));
+    assert!(frames.recv_latest().await.is_none());
+}
+
+#[tokio::test]
+async fn late_subscriber_starts_from_the_latest_frame() {
+    let bus = TelemetryBus::new();
+    for rpm in [5000.0, 6000.0] {
+        let packet = encode_packet(GameType::ForzaHorizon5, rpm, 8000.0, 900.0, true);
+        bus.publish(TelemetryFrame::parse(GameType::ForzaHorizon5, &GameType::ForzaHorizon5, &packet));
+    }
+    let mut late = bus.subscribe_from_latest("wheel LEDs", Duration::from_secs(1));
+    let mut stale = bus.subscribe_from_latest("stale", Duration::ZERO);
+    drop(bus);
+
+    assert_eq!(late.recv_latest().await.map(|frame| frame.rpm), Some(6000.0));
+    assert!(late.recv_latest().await.is_none());
+    assert!(stale.recv().await.is_none());
+}
+
+/// Blocks of a pcapng file as (type, body)
+fn pcapng_blocks(file: &[u8]) -> Vec<(u32, &[u8])> {
+    let mut blocks = Vec::new();
+    let mut rest = file;
+    while !rest.is_empty() {
+        let block_type = u32::from_le_bytes(rest[0..4].try_into().unwrap());
+        let length = u32::from_le_bytes(rest[4..8].try_into().unwrap()) as usize;
+        assert_eq!(rest[length - 4..length], rest[4..8], "block length repeated at its end");
+        blocks.push((block_type, &rest[8..length - 4]));
+        rest = &rest[length..];
+    }
+    blocks
+}
+
+#[tokio::test]
+async fn captured_packets_keep_payload_and_sender() {
+    let harness = Harness::start(GameType::ForzaHorizon5).await;
+    let path = std::env::temp_dir().join(format!("g27-loopback-{}.pcapng", std::process::id()));
+    let mut capture = Capture::create(&path, harness.target).expect("create capture");
+    let frames = harness.bus.subscribe("capture");
+    let control = harness.control.subscribe();
+    let capturing =
+        tokio::spawn(async move { capture_frames(&mut capture, Duration::from_millis(300), frames, control).await });
+
+    let packet = fixture("fh5/dash_race.bin");
+    harness.send(std::slice::from_ref(&packet)).await;
+    capturing.await.unwrap().unwrap();
+    let (sender, target) = (harness.sender.local_addr().unwrap(), harness.target);
+    harness.stop().await.unwrap();
+
+    let file = fs::read(&path).expect("read capture");
+    let _ = fs::remove_file(&path);
+    let blocks = pcapng_blocks(&file);
+    let types: Vec<u32> = blocks.iter().map(|(block_type, _)| *block_type).collect();
+    assert_eq!(types, [0x0A0D_0D0A, 1, 6], "section header, interface and one packet");
+
+    // Enhanced packet: interface, timestamp, lengths, then an IPv4 header and the UDP header
+    let body = blocks[2].1;
+    let captured = u32::from_le_bytes(body[12..16].try_into().unwrap()) as usize;
+    let datagram = &body[20..20 + captured];
+    assert_eq!(datagram[0], 0x45);
+    assert_eq!(u16::from_be_bytes([datagram[20], datagram[21]]), sender.port());
+    assert_eq!(u16::from_be_bytes([datagram[22], datagram[23]]), target.port());
+    assert_eq!(&datagram[28..], &packet[..]);
+}
+
+#[tokio::test]
+async fn bounded_run_ends_after_expected_packets() {
+    let harness = Harness::start(GameType::ForzaHorizon5).await;
+    let bounds = RunBounds {
+        duration: Some(LED_TIMEOUT),
+        packets: Some(2),
+    };
+    let waiting = tokio::spawn(wait_for_bounds(bounds, harness.bus.subscribe("run bounds")));
+
+    harness.send(&[fixture("fh5/dash_race.bin"), fixture("fh5/sled_race.bin")]).await;
+    let outcome = tokio::time::timeout(LED_TIMEOUT, waiting).await.expect("bounds reached").unwrap();
+    assert_eq!(outcome, BoundOutcome::Completed { received: 2 });
+    harness.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn bounded_run_reports_missing_packets() {
+    let harness = Harness::start(GameType::ForzaHorizon5).await;
+    let bounds = RunBounds {
+        duration: Some(Duration::from_millis(200)),
+        packets: Some(5),
+    };
+    let waiting = tokio::spawn(wait_for_bounds(bounds, harness.bus.subscribe("run bounds")));
+
+    harness.send(&[fixture("fh5/dash_race.bin")]).await;
+    let outcome = waiting.await.unwrap();
+    assert_eq!(outcome, BoundOutcome::MissingPackets { received: 1, expected: 5 });
+    harness.stop().await.unwrap();
+}
+
+#[test]
+fn spawned_bridge_is_stopped_through_its_handle() {
+    let wheel = MockWheel::new();
+    let handle = BridgeBuilder::new(GameType::ForzaHorizon5)
+        .port(0)
+        .output(wheel.clone())
+        .spawn()
+        .expect("spawn bridge");
+    assert_eq!(handle.status(), BridgeStatus::Running);
+
+    let mut frames = handle.frames();
+    let sender = std::net::UdpSocket::bind("127.0.0.1:0").expect("bind sender");
+    sender.send_to(&fixture("fh5/dash_race.bin"), handle.local_addr()).expect("send packet");
+
+    let frame = frames.blocking_recv().expect("frame from the bridge");
+    assert_eq!(frame.game_type, GameType::ForzaHorizon5);
+    assert!(frame.race_active);
+
+    // The frame reached this subscriber; give the LED subscriber time to catch up
+    let deadline = Instant::now() + LED_TIMEOUT;
+    while wheel.led_states().is_empty() && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    handle.stop().unwrap();
+    assert_eq!(wheel.led_states(), vec![31, 0]);
+}
@@ -0,0 +1,337 @@
+// Packet-to-LED path against a mock wheel, so it runs without a G27 attached
+
+use std::collections::VecDeque;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use g27_led_bridge_core::leds::{FillPattern, LedProfile, LedStyle, LEDS};
+use g27_led_bridge_core::simulator::encode_packet;
+use g27_led_bridge_core::{
+    BridgeBuilder, DR2G27Error, DR2G27Result, GameType, LedOutput, LoopControl, MockWheel, PendingOutput,
+    RetryingWheel, TelemetrySource, WatchedOutput,
+};
+
+const IDLE_RPM: f32 = 900.0;
+const MAX_RPM: f32 = 8000.0;
+
+fn racing(game_type: GameType, rpm: f32) -> Vec<u8> {
+    encode_packet(game_type, rpm, MAX_RPM, IDLE_RPM, true)
+}
+
+/// Hands out a fixed list of packets, then nothing
+struct ScriptedSource {
+    packets: VecDeque<Vec<u8>>,
+    /// Whether the packets are all queued at once, as after a slow LED write
+    queued: bool,
+}
+
+impl TelemetrySource for ScriptedSource {
+    fn recv(&mut self, buf: &mut [u8]) -> io::Result<Option<usize>> {
+        Ok(self.packets.pop_front().map(|packet| {
+            buf[..packet.len()].copy_from_slice(&packet);
+            packet.len()
+        }))
+    }
+
+    fn try_recv(&mut self, buf: &mut [u8]) -> io::Result<Option<usize>> {
+        if !self.queued {
+            return Ok(None);
+        }
+        self.recv(buf)
+    }
+
+    fn describe(&self) -> String {
+        "script".to_string()
+    }
+}
+
+#[test]
+fn rising_rpm_lights_leds_progressively() {
+    let wheel = MockWheel::new();
+    let mut leds = LEDS::new(wheel.clone());
+    let parser = GameType::ForzaHorizon5.parser();
+
+    for rpm in [IDLE_RPM, 5000.0, 6000.0, 7000.0, MAX_RPM] {
+        leds.update(&racing(GameType::ForzaHorizon5, rpm), parser).unwrap();
+    }
+
+    // Idle stays dark, so the first write is the first LED
+    assert_eq!(wheel.led_states(), vec![1, 7, 15, 31]);
+    assert_eq!(wheel.reports()[0], vec![0x00, 0xF8, 0x12, 1, 0x00, 0x00, 0x00, 0x01]);
+}
+
+#[test]
+fn center_out_pattern_lights_the_red_leds_first() {
+    let wheel = MockWheel::new();
+    let mut leds = LEDS::new(wheel.clone());
+    leds.set_style(LedStyle {
+        pattern: FillPattern::CenterOut,
+        ..LedStyle::default()
+    });
+    let parser = GameType::ForzaHorizon5.parser();
+
+    for rpm in [IDLE_RPM, 5000.0, 6000.0, 7000.0, MAX_RPM] {
+        leds.update(&racing(GameType::ForzaHorizon5, rpm), parser).unwrap();
+    }
+
+    assert_eq!(wheel.led_states(), vec![16, 28, 30, 31]);
+}
+
+#[test]
+fn aggressive_profile_lights_leds_later() {
+    let wheel = MockWheel::new();
+    let mut leds = LEDS::new(wheel.clone());
+    leds.set_style(LedStyle {
+        profile: LedProfile::Aggressive,
+        ..LedStyle::default()
+    });
+    let parser = GameType::ForzaHorizon5.parser();
+
+    for rpm in [IDLE_RPM, 5000.0, 6000.0, 7000.0, MAX_RPM] {
+        leds.update(&racing(GameType::ForzaHorizon5, rpm), parser).unwrap();
+    }
+
+    // 5000 RPM already lights the standard profile's first LED
+    assert_eq!(wheel.led_states(), vec![1, 7, 31]);
+}
+
+#[test]
+fn shift_flash_blinks_the_whole_bar_near_max_rpm() {
+    let wheel = MockWheel::new();
+    let mut leds = LEDS::new(wheel.clone());
+    leds.set_style(LedStyle {
+        shift_flash: true,
+        ..LedStyle::default()
+    });
+    let parser = GameType::ForzaHorizon5.parser();
+
+    // Slightly different RPM values keep the telemetry from turning stale
+    for step in 0..30 {
+        leds.update(&racing(GameType::ForzaHorizon5, MAX_RPM - step as f32), parser).unwrap();
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    let states = wheel.led_states();
+    assert_eq!(states[..2], [31, 0]);
+    assert!(states.iter().all(|state| *state == 31 || *state == 0), "{:?}", states);
+}
+
+#[test]
+fn shift_range_is_the_top_of_the_bar() {
+    let standard = LedStyle::default();
+    assert!(standard.in_shift_range(MAX_RPM, MAX_RPM, IDLE_RPM));
+    assert!(!standard.in_shift_range(MAX_RPM - 500.0, MAX_RPM, IDLE_RPM));
+    // Without telemetry there is no range to be in
+    assert!(!standard.in_shift_range(0.0, 0.0, 0.0));
+}
+
+#[test]
+fn leaving_the_race_turns_leds_off() {
+    let wheel = MockWheel::new();
+    let mut leds = LEDS::new(wheel.clone());
+    let parser = GameType::ForzaHorizon5.parser();
+
+    leds.update(&racing(GameType::ForzaHorizon5, MAX_RPM), parser).unwrap();
+    let menu = encode_packet(GameType::ForzaHorizon5, 0.0, 0.0, 0.0, false);
+    leds.update(&menu, parser).unwrap();
+
+    assert_eq!(wheel.led_states(), vec![31, 0]);
+}
+
+#[test]
+fn frozen_telemetry_turns_leds_off() {
+    let wheel = MockWheel::new();
+    let mut leds = LEDS::new(wheel.clone());
+    let parser = GameType::DirtRally2.parser();
+    let packet = racing(GameType::DirtRally2, MAX_RPM);
+
+    // A paused game repeats the same packet
+    for _ in 0..10 {
+        leds.update(&packet, parser).unwrap();
+    }
+
+    assert_eq!(wheel.led_states(), vec![31, 0]);
+}
+
+#[test]
+fn unplugged_wheel_reports_write_error() {
+    let wheel = MockWheel::new();
+    let mut leds = LEDS::new(wheel.clone());
+    let parser = GameType::ForzaHorizon5.parser();
+    wheel.disconnect();
+
+    let result = leds.update(&racing(GameType::ForzaHorizon5, MAX_RPM), parser);
+
+    assert!(matches!(result, Err(DR2G27Error::HidWrite(_))));
+    assert!(wheel.reports().is_empty());
+}
+
+#[test]
+fn failed_writes_are_retried() {
+    let wheel = MockWheel::new();
+    let mut output = RetryingWheel::new(wheel.clone()).with_retries(3, Duration::from_millis(1));
+
+    wheel.fail_writes(2);
+    output.set_state(7).unwrap();
+    assert_eq!(wheel.led_states(), vec![7]);
+
+    wheel.fail_writes(4);
+    assert!(matches!(output.set_state(31), Err(DR2G27Error::HidWrite(_))));
+}
+
+#[test]
+fn wheel_is_reopened_when_retries_fail() {
+    let unplugged = MockWheel::new();
+    let replugged = MockWheel::new();
+    let reopened = replugged.clone();
+    let mut output = RetryingWheel::new(unplugged.clone())
+        .with_retries(1, Duration::from_millis(1))
+        .reopening(move || Ok(reopened.clone()));
+
+    unplugged.disconnect();
+    output.set_state(31).unwrap();
+    output.set_state(15).unwrap();
+    assert_eq!(replugged.led_states(), vec![31, 15]);
+}
+
+/// Output whose writes block until the test drops the sender, like a hung driver
+struct HangingOutput(mpsc::Receiver<()>);
+
+impl LedOutput for HangingOutput {
+    fn set_state(&mut self, _state: u8) -> DR2G27Result {
+        let _ = self.0.recv();
+        Ok(())
+    }
+}
+
+#[test]
+fn watched_output_writes_on_its_thread() {
+    let wheel = MockWheel::new();
+    let mut output = WatchedOutput::spawn(wheel.clone(), WatchedOutput::DEFAULT_TIMEOUT).unwrap();
+    output.set_state(7).unwrap();
+    output.set_state(0).unwrap();
+    // Dropping waits for the last state, so the wheel is left dark
+    drop(output);
+    assert_eq!(wheel.led_states().last(), Some(&0));
+
+    let unplugged = MockWheel::new();
+    unplugged.disconnect();
+    let mut output = WatchedOutput::spawn(unplugged, WatchedOutput::DEFAULT_TIMEOUT).unwrap();
+    output.set_state(7).unwrap();
+    thread::sleep(Duration::from_millis(50));
+    assert!(matches!(output.set_state(15), Err(DR2G27Error::HidWrite(_))));
+}
+
+#[test]
+fn pending_output_shows_the_latest_state_once_found() {
+    let wheel = MockWheel::new();
+    let plugged = Arc::new(AtomicBool::new(false));
+    let mut output = {
+        let (wheel, plugged) = (wheel.clone(), plugged.clone());
+        PendingOutput::search(
+            move || {
+                if plugged.load(Ordering::SeqCst) {
+                    Ok(wheel.clone())
+                } else {
+                    Err(DR2G27Error::ConfigError("unplugged".to_string()))
+                }
+            },
+            Duration::from_millis(5),
+        )
+        .unwrap()
+    };
+    output.set_state(3).unwrap();
+    output.set_state(7).unwrap();
+    assert!(!output.is_open());
+
+    plugged.store(true, Ordering::SeqCst);
+    let deadline = Instant::now() + Duration::from_secs(1);
+    while !output.is_open() && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(5));
+    }
+    assert_eq!(wheel.led_states(), vec![7]);
+    output.set_state(15).unwrap();
+    assert_eq!(wheel.led_states(), vec![7, 15]);
+}
+
+#[test]
+fn hung_write_trips_the_watchdog() {
+    let (release, hang) = mpsc::channel();
+    let mut output = WatchedOutput::spawn(HangingOutput(hang), Duration::from_millis(20)).unwrap();
+
+    let started = Instant::now();
+    output.set_state(31).unwrap();
+    thread::sleep(Duration::from_millis(50));
+    assert!(matches!(output.set_state(0), Err(DR2G27Error::HidWriteTimeout(_))));
+    // Neither call waited for the write
+    assert!(started.elapsed() < Duration::from_millis(200));
+    drop(release);
+}
+
+#[test]
+fn panicking_output_reports_error() {
+    struct Buggy;
+
+    impl LedOutput for Buggy {
+        fn set_state(&mut self, _state: u8) -> DR2G27Result {
+            panic!("buggy output")
+        }
+    }
+
+    let mut leds = LEDS::new(Buggy);
+    let result = leds.update(&racing(GameType::ForzaHorizon5, MAX_RPM), GameType::ForzaHorizon5.parser());
+
+    assert!(matches!(result, Err(DR2G27Error::Panicked { component: "LED output", .. })));
+}
+
+#[test]
+fn bridge_drives_mock_wheel_from_source() {
+    let packets: VecDeque<_> = [5000.0, 6000.0, MAX_RPM]
+        .into_iter()
+        .map(|rpm| racing(GameType::DirtRally2, rpm))
+        .collect();
+    let mut polls = packets.len() + 1;
+    let wheel = MockWheel::new();
+
+    let mut bridge = BridgeBuilder::new(GameType::DirtRally2)
+        .control(move || {
+            polls -= 1;
+            if polls == 0 {
+                LoopControl::Stop
+            } else {
+                LoopControl::Run
+            }
+        })
+        .build(ScriptedSource { packets, queued: false }, wheel.clone());
+    bridge.run().unwrap();
+    bridge.clear().unwrap();
+
+    assert_eq!(wheel.led_states(), vec![1, 7, 31, 0]);
+}
+
+#[test]
+fn bridge_skips_queued_packets_for_the_newest() {
+    let packets: VecDeque<_> = [5000.0, 6000.0, MAX_RPM]
+        .into_iter()
+        .map(|rpm| racing(GameType::DirtRally2, rpm))
+        .collect();
+    let mut polls = 2;
+    let wheel = MockWheel::new();
+
+    let mut bridge = BridgeBuilder::new(GameType::DirtRally2)
+        .control(move || {
+            polls -= 1;
+            if polls == 0 {
+                LoopControl::Stop
+            } else {
+                LoopControl::Run
+            }
+        })
+        .build(ScriptedSource { packets, queued: true }, wheel.clone());
+    bridge.run().unwrap();
+
+    assert_eq!(wheel.led_states(), vec![31]);
+}
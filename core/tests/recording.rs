@@ -0,0 +1,136 @@
+// Session recordings: frames written by `Recorder` read back with `RecordingReader`, and
+// the parsed values in the CSV and JSON lines frame logs written alongside
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use g27_led_bridge_core::simulator::encode_packet;
+use g27_led_bridge_core::{GameType, Recorder, RecordingReader, TelemetryFrame};
+
+/// Offsets of the recorded frames from the first one
+const OFFSETS_MS: [u64; 3] = [0, 50, 120];
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("g27-recording-{}-{}", std::process::id(), name))
+}
+
+/// Frames alternating between the games, received `OFFSETS_MS` after `start`
+fn frames(start: Instant) -> Vec<TelemetryFrame> {
+    let games = [GameType::ForzaHorizon5, GameType::DirtRally2, GameType::ForzaHorizon5];
+    games
+        .into_iter()
+        .zip(OFFSETS_MS)
+        .enumerate()
+        .map(|(i, (game, offset))| {
+            let packet = encode_packet(game, 3000.0 + 1000.0 * i as f32, 8000.0, 900.0, true);
+            let mut frame = TelemetryFrame::parse(game, &game, &packet);
+            frame.received_at = start + Duration::from_millis(offset);
+            frame
+        })
+        .collect()
+}
+
+/// Record `frames` starting now, with a frame log at `frame_log`; returns the frames and the raw recording
+fn record(name: &str, frame_log: &Path) -> (Vec<TelemetryFrame>, Vec<u8>) {
+    let output = temp_path(&format!("{}.g27rec", name));
+    let mut recorder = Recorder::create(&output, Some(frame_log)).expect("create recorder");
+    let frames = frames(Instant::now());
+    for frame in &frames {
+        recorder.record(frame).expect("record frame");
+    }
+    assert_eq!(recorder.recorded(), frames.len() as u64);
+    recorder.flush().expect("flush recorder");
+    drop(recorder);
+
+    let recording = fs::read(&output).expect("read recording");
+    let _ = fs::remove_file(&output);
+    (frames, recording)
+}
+
+/// Milliseconds since the first entry, from absolute times
+fn relative(times: &[f64]) -> Vec<f64> {
+    times.iter().map(|time| time - times[0]).collect()
+}
+
+fn assert_offsets(times_ms: &[f64]) {
+    for (time, expected) in relative(times_ms).iter().zip(OFFSETS_MS) {
+        assert!((time - expected as f64).abs() < 0.01, "frame at {} ms, expected {} ms", time, expected);
+    }
+}
+
+#[test]
+fn recorded_packets_read_back_with_payload_game_and_timing() {
+    let frame_log = temp_path("packets.csv");
+    let (frames, recording) = record("packets", &frame_log);
+    let _ = fs::remove_file(&frame_log);
+
+    let mut reader = RecordingReader::new(recording.as_slice()).expect("valid header");
+    let mut packets = Vec::new();
+    while let Some(packet) = reader.next_packet().expect("read packet") {
+        packets.push(packet);
+    }
+
+    assert_eq!(packets.len(), frames.len());
+    for (packet, frame) in packets.iter().zip(&frames) {
+        assert_eq!(packet.game_type, frame.game_type);
+        assert_eq!(packet.packet, &*frame.packet);
+    }
+    assert!(packets[0].elapsed < Duration::from_secs(1), "first packet at {:?}", packets[0].elapsed);
+    let elapsed: Vec<f64> = packets.iter().map(|packet| packet.elapsed.as_secs_f64() * 1000.0).collect();
+    assert_offsets(&elapsed);
+
+    // A recording cut off inside the last packet reads back up to the one before
+    let mut reader = RecordingReader::new(&recording[..recording.len() - 10]).expect("valid header");
+    let mut complete = 0;
+    while reader.next_packet().expect("read packet").is_some() {
+        complete += 1;
+    }
+    assert_eq!(complete, frames.len() - 1);
+}
+
+#[test]
+fn csv_frame_log_holds_the_parsed_values() {
+    let frame_log = temp_path("frames.csv");
+    let (frames, _) = record("csv", &frame_log);
+    let log = fs::read_to_string(&frame_log).expect("read frame log");
+    let _ = fs::remove_file(&frame_log);
+
+    let mut lines = log.lines();
+    assert_eq!(lines.next(), Some("time_ms,game,rpm,max_rpm,idle_rpm,race_active"));
+    let rows: Vec<Vec<&str>> = lines.map(|line| line.split(',').collect()).collect();
+    assert_eq!(rows.len(), frames.len());
+    for (row, frame) in rows.iter().zip(&frames) {
+        let expected = [
+            format!("{:?}", frame.game_type),
+            frame.rpm.to_string(),
+            "8000".to_string(),
+            "900".to_string(),
+            "true".to_string(),
+        ];
+        assert_eq!(row[1..], expected);
+    }
+    let times: Vec<f64> = rows.iter().map(|row| row[0].parse().expect("time_ms")).collect();
+    assert_offsets(&times);
+}
+
+#[test]
+fn json_lines_frame_log_holds_the_parsed_values() {
+    let frame_log = temp_path("frames.jsonl");
+    let (frames, _) = record("jsonl", &frame_log);
+    let log = fs::read_to_string(&frame_log).expect("read frame log");
+    let _ = fs::remove_file(&frame_log);
+
+    let rows: Vec<serde_json::Value> =
+        log.lines().map(|line| serde_json::from_str(line).expect("one JSON object per line")).collect();
+    assert_eq!(rows.len(), frames.len());
+    for (row, frame) in rows.iter().zip(&frames) {
+        assert_eq!(row["game"], format!("{:?}", frame.game_type));
+        assert_eq!(row["rpm"], frame.rpm as f64);
+        assert_eq!(row["max_rpm"], 8000.0);
+        assert_eq!(row["idle_rpm"], 900.0);
+        assert_eq!(row["race_active"], true);
+    }
+    let times: Vec<f64> = rows.iter().map(|row| row["time_ms"].as_f64().expect("time_ms")).collect();
+    assert_offsets(&times);
+}
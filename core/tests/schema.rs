@@ -0,0 +1,35 @@
+// The field maps the parsers read through, as the schema command documents them
+
+use g27_led_bridge_core::simulator::encode_packet;
+use g27_led_bridge_core::{GameType, TelemetryParser};
+
+#[test]
+fn fields_fit_the_packets_that_carry_them() {
+    for game in GameType::ALL {
+        let mut last_offset = 0;
+        for field in game.fields() {
+            assert!(field.offset >= last_offset, "{} fields out of packet order at {}", game.game_name(), field.name);
+            last_offset = field.offset;
+
+            let sizes = if field.packet_sizes.is_empty() { game.packet_sizes() } else { field.packet_sizes };
+            for size in sizes {
+                assert!(game.packet_sizes().contains(size), "{}: {} is not one of the game's sizes", field.name, size);
+                assert!(field.offset + field.field_type.size() <= *size, "{} ends past {} bytes", field.name, size);
+            }
+        }
+    }
+}
+
+#[test]
+fn parsers_read_the_documented_rpm_offsets() {
+    for game in GameType::ALL {
+        let packet = encode_packet(game, 5000.0, 8000.0, 900.0, true);
+        for (name, expected) in [("rpm", 5000.0f32), ("max_rpm", 8000.0), ("idle_rpm", 900.0)] {
+            let field = game.fields().iter().find(|field| field.name == name).expect("field documented");
+            let value = f32::from_le_bytes(packet[field.offset..field.offset + 4].try_into().unwrap());
+            assert_eq!(value, expected, "{} {}", game.game_name(), name);
+        }
+        let (rpm, max_rpm, idle_rpm, _) = game.parse_rpm_data(&packet);
+        assert_eq!((rpm, max_rpm, idle_rpm), (5000.0, 8000.0, 900.0));
+    }
+}
@@ -0,0 +1,66 @@
+// Race session summaries over simulated arrival times
+
+use std::time::{Duration, Instant};
+
+use g27_led_bridge_core::simulator::encode_packet;
+use g27_led_bridge_core::{GameType, LedStyle, SessionEnd, SessionTracker, TelemetryFrame};
+
+/// A Forza frame `at_ms` after `started`, racing unless `race_active` is false
+fn frame(started: Instant, at_ms: u64, rpm: f32, race_active: bool) -> TelemetryFrame {
+    let packet = encode_packet(GameType::ForzaHorizon5, rpm, 8000.0, 800.0, race_active);
+    let mut frame = TelemetryFrame::parse(GameType::ForzaHorizon5, &GameType::ForzaHorizon5, &packet);
+    frame.received_at = started + Duration::from_millis(at_ms);
+    frame
+}
+
+#[test]
+fn race_end_reports_the_session() {
+    let started = Instant::now();
+    let style = LedStyle::default();
+    let mut tracker = SessionTracker::new();
+    // Ten seconds at 50 packets per second, at the limiter for the last two
+    for packet in 0..=500u64 {
+        let rpm = if packet >= 400 { 7950.0 } else { 4000.0 };
+        assert_eq!(tracker.observe(&frame(started, packet * 20, rpm, true), &style), None);
+    }
+    assert!(tracker.in_session());
+
+    let summary = tracker.observe(&frame(started, 10_020, 0.0, false), &style).expect("session ended");
+    assert!(!tracker.in_session());
+    assert_eq!(summary.end, SessionEnd::RaceEnded);
+    assert_eq!(summary.duration, Duration::from_secs(10));
+    assert_eq!(summary.max_rpm, 7950.0);
+    assert_eq!(summary.above_shift, Duration::from_secs(2));
+    assert_eq!((summary.packets, summary.dropped), (501, 0));
+    assert!(summary.csv_row().ends_with(",10.0,7950,2.0,501,0,race_ended"), "{}", summary.csv_row());
+}
+
+#[test]
+fn gaps_are_counted_as_dropped_packets_but_pauses_are_not() {
+    let started = Instant::now();
+    let style = LedStyle::default();
+    let mut tracker = SessionTracker::new();
+    let mut at = 0;
+    // 100 ms with four packets missing, then a 3 s pause
+    for interval_ms in [20; 10].into_iter().chain([100]).chain([20; 10]).chain([3000]).chain([20; 200]) {
+        at += interval_ms;
+        tracker.observe(&frame(started, at, 4000.0, true), &style);
+    }
+
+    let summary = tracker.telemetry_stopped().expect("session ended");
+    assert_eq!(summary.end, SessionEnd::TelemetryStopped);
+    assert_eq!(summary.dropped, 4);
+}
+
+#[test]
+fn short_sessions_are_not_reported() {
+    let started = Instant::now();
+    let style = LedStyle::default();
+    let mut tracker = SessionTracker::new();
+    tracker.observe(&frame(started, 0, 3000.0, true), &style);
+    tracker.observe(&frame(started, 1000, 3000.0, true), &style);
+
+    assert_eq!(tracker.observe(&frame(started, 1020, 0.0, false), &style), None);
+    assert!(!tracker.in_session());
+    assert_eq!(tracker.telemetry_stopped(), None);
+}
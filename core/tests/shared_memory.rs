@@ -0,0 +1,98 @@
+// Shared memory snapshots against in-memory blocks, including ones the "game" changes mid-read
+
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+
+use g27_led_bridge_core::{DR2G27Error, SharedLayout, SharedMemoryReader, SharedRegion};
+
+/// Layout of a made-up game that brackets each update with counters, like rFactor 2
+#[derive(Debug, Clone, Copy)]
+#[repr(C)]
+struct RigTelemetry {
+    update_begin: u32,
+    update_end: u32,
+    layout_version: u32,
+    rpm: f32,
+}
+
+unsafe impl SharedLayout for RigTelemetry {
+    const NAME: &'static str = "$RigTelemetry$";
+    const VERSIONS: &'static [u32] = &[2, 3];
+
+    fn version(&self) -> u32 {
+        self.layout_version
+    }
+
+    fn sequence(&self) -> Option<(u32, u32)> {
+        Some((self.update_begin, self.update_end))
+    }
+}
+
+fn block(update_begin: u32, update_end: u32, layout_version: u32, rpm: f32) -> Vec<u8> {
+    [update_begin.to_ne_bytes(), update_end.to_ne_bytes(), layout_version.to_ne_bytes(), rpm.to_ne_bytes()].concat()
+}
+
+/// Shows the next snapshot on every read, like a block the game keeps writing
+struct Updating {
+    snapshots: RefCell<VecDeque<Vec<u8>>>,
+    reads: Cell<usize>,
+}
+
+impl Updating {
+    fn new(snapshots: impl IntoIterator<Item = Vec<u8>>) -> Self {
+        Self {
+            snapshots: RefCell::new(snapshots.into_iter().collect()),
+            reads: Cell::new(0),
+        }
+    }
+}
+
+impl SharedRegion for &Updating {
+    fn len(&self) -> usize {
+        self.snapshots.borrow().front().map_or(0, Vec::len)
+    }
+
+    fn read(&self, buffer: &mut [u8]) {
+        self.reads.set(self.reads.get() + 1);
+        let mut snapshots = self.snapshots.borrow_mut();
+        let snapshot = if snapshots.len() > 1 { snapshots.pop_front().unwrap() } else { snapshots[0].clone() };
+        buffer.copy_from_slice(&snapshot[..buffer.len()]);
+    }
+}
+
+#[test]
+fn snapshot_is_copied_from_the_block() {
+    let mut reader = SharedMemoryReader::<RigTelemetry, _>::new(block(7, 7, 3, 6500.0)).unwrap();
+    let telemetry = reader.read().unwrap();
+    assert_eq!(telemetry.rpm, 6500.0);
+    assert_eq!(telemetry.version(), 3);
+}
+
+#[test]
+fn torn_snapshot_is_read_again() {
+    let region = Updating::new([block(8, 7, 3, 1234.0), block(8, 8, 3, 6600.0)]);
+    let mut reader = SharedMemoryReader::<RigTelemetry, _>::new(&region).unwrap();
+    assert_eq!(reader.read().unwrap().rpm, 6600.0);
+    assert_eq!(region.reads.get(), 2);
+}
+
+#[test]
+fn reader_gives_up_when_every_snapshot_is_torn() {
+    let region = Updating::new([block(9, 8, 3, 1234.0)]);
+    let mut reader = SharedMemoryReader::<RigTelemetry, _>::new(&region).unwrap().with_retries(2);
+    assert!(matches!(reader.read(), Err(DR2G27Error::SharedMemoryRead { .. })));
+    assert_eq!(region.reads.get(), 3);
+}
+
+#[test]
+fn unknown_layout_version_is_rejected() {
+    let mut reader = SharedMemoryReader::<RigTelemetry, _>::new(block(1, 1, 4, 6500.0)).unwrap();
+    let error = reader.read().unwrap_err();
+    assert!(error.to_string().contains("unsupported layout version 4"), "{}", error);
+}
+
+#[test]
+fn block_shorter_than_the_layout_is_rejected() {
+    let short = block(1, 1, 3, 6500.0)[..12].to_vec();
+    assert!(SharedMemoryReader::<RigTelemetry, _>::new(short).is_err());
+}
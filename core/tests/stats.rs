@@ -0,0 +1,75 @@
+// Packet statistics over simulated arrival times
+
+use std::time::{Duration, Instant};
+
+use g27_led_bridge_core::{GameType, PacketStats, TelemetryFrame};
+
+const FORZA_SLED: usize = 232;
+
+/// A Forza packet that arrived at `at`
+fn frame_at(at: Instant) -> TelemetryFrame {
+    let mut frame = TelemetryFrame::parse(GameType::ForzaHorizon5, &GameType::ForzaHorizon5, &[0u8; FORZA_SLED]);
+    frame.received_at = at;
+    frame
+}
+
+#[test]
+fn steady_packets_have_little_jitter_and_no_gaps() {
+    let started = Instant::now();
+    let mut stats = PacketStats::new();
+    for packet in 0..60u32 {
+        // 60 per second, alternating a millisecond early and late
+        let wobble = if packet % 2 == 0 { 1000 } else { 0 };
+        stats.observe(&frame_at(started + Duration::from_micros(u64::from(packet) * 16_667 + wobble)));
+    }
+    stats.tick(started + Duration::from_secs(1), 2);
+
+    let summary = stats.summary();
+    assert_eq!(summary.packets, 60);
+    assert!((summary.packets_per_second - 60.0).abs() < 0.5, "rate {}", summary.packets_per_second);
+    assert!(summary.jitter_ms > 0.5 && summary.jitter_ms < 2.5, "jitter {}", summary.jitter_ms);
+    assert_eq!((summary.gaps, summary.malformed), (0, 2));
+}
+
+#[test]
+fn missing_packets_count_as_a_gap_but_pauses_do_not() {
+    let started = Instant::now();
+    let mut stats = PacketStats::new();
+    let mut at = started;
+    for interval_ms in [16, 16, 16, 16, 100, 16, 16, 3000, 16, 16] {
+        at += Duration::from_millis(interval_ms);
+        stats.observe(&frame_at(at));
+    }
+
+    let summary = stats.summary();
+    assert_eq!(summary.gaps, 1);
+    assert_eq!(summary.longest_gap_ms, 100.0);
+}
+
+#[test]
+fn low_rate_is_reported_once_after_a_few_seconds() {
+    let started = Instant::now();
+    let mut stats = PacketStats::new();
+    let mut warnings = Vec::new();
+    for second in 0..10u64 {
+        for packet in 0..10u64 {
+            stats.observe(&frame_at(started + Duration::from_millis(second * 1000 + packet * 100)));
+        }
+        stats.tick(started + Duration::from_secs(second + 1), 0);
+        warnings.extend(stats.low_rate_warning());
+    }
+
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(warnings[0].contains("10 packets/s"), "{}", warnings[0]);
+}
+
+#[test]
+fn silence_is_not_a_low_rate() {
+    let started = Instant::now();
+    let mut stats = PacketStats::new();
+    stats.observe(&frame_at(started));
+    for second in 1..10 {
+        stats.tick(started + Duration::from_secs(second), 0);
+        assert_eq!(stats.low_rate_warning(), None);
+    }
+}
@@ -0,0 +1,36 @@
+// Telling why the G27 could not be opened from the HID error, for the tray's troubleshooting hints
+
+use std::io;
+
+use g27_led_bridge_core::{DR2G27Error, WheelOpenFailure};
+use hidapi::HidError;
+
+fn message(text: &str) -> HidError {
+    HidError::HidApiError { message: text.to_string() }
+}
+
+#[test]
+fn windows_error_codes_map_to_failures() {
+    let os_error = |code| HidError::IoError { error: io::Error::from_raw_os_error(code) };
+    assert_eq!(WheelOpenFailure::from_error(&os_error(5)), WheelOpenFailure::AccessDenied);
+    assert_eq!(WheelOpenFailure::from_error(&os_error(32)), WheelOpenFailure::InUse);
+    assert_eq!(WheelOpenFailure::from_error(&os_error(1167)), WheelOpenFailure::Disconnected);
+}
+
+#[test]
+fn hidapi_messages_map_to_failures() {
+    let denied = message("Failed to open a device with path '\\\\?\\hid#vid_046d': Access is denied.");
+    assert_eq!(WheelOpenFailure::from_error(&denied), WheelOpenFailure::AccessDenied);
+    let in_use = message("The process cannot access the file because it is being used by another process.");
+    assert_eq!(WheelOpenFailure::from_error(&in_use), WheelOpenFailure::InUse);
+    assert_eq!(WheelOpenFailure::from_error(&message("hid_open failed")), WheelOpenFailure::Other);
+    assert_eq!(WheelOpenFailure::from_error(&HidError::HidApiErrorEmpty), WheelOpenFailure::Other);
+}
+
+#[test]
+fn open_error_names_the_failure_and_offers_hints() {
+    let error = DR2G27Error::hid_open(message("Access is denied."));
+    assert!(error.to_string().contains("another app has the wheel"), "{}", error);
+    assert!(matches!(error, DR2G27Error::HidOpen { failure: WheelOpenFailure::AccessDenied, .. }));
+    assert!(WheelOpenFailure::AccessDenied.hints()[0].contains("G HUB"));
+}
@@ -0,0 +1,90 @@
+// Zip archives: the records `ZipWriter` writes parsed back by offset, as unzip tools read them
+
+use std::io::Read;
+
+use g27_led_bridge_core::ZipWriter;
+
+const ENTRIES: [(&str, &[u8]); 3] = [
+    ("report.txt", b"G27 LED Bridge diagnostic report\nwheel: connected\n"),
+    ("settings.toml", b"port = 20777\n\n[integrations.mqtt]\npassword = \"********\"\n"),
+    ("logs/g27-led-bridge.log", b""),
+];
+
+fn u16_at(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(data[offset..offset + 2].try_into().unwrap())
+}
+
+fn u32_at(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = flate2::Crc::new();
+    crc.update(data);
+    crc.sum()
+}
+
+fn archive() -> Vec<u8> {
+    let mut zip = ZipWriter::default();
+    for (name, contents) in ENTRIES {
+        zip.add(name, contents).expect("add entry");
+    }
+    zip.finish()
+}
+
+#[test]
+fn end_record_points_at_the_central_directory() {
+    let data = archive();
+    let end = data.len() - 22;
+    assert_eq!(u32_at(&data, end), 0x0605_4b50, "end of central directory signature");
+    assert_eq!(u16_at(&data, end + 8), ENTRIES.len() as u16, "entries on this disk");
+    assert_eq!(u16_at(&data, end + 10), ENTRIES.len() as u16, "entries in total");
+    assert_eq!(u16_at(&data, end + 20), 0, "comment length");
+
+    let directory_size = u32_at(&data, end + 12) as usize;
+    let directory_offset = u32_at(&data, end + 16) as usize;
+    assert_eq!(directory_offset + directory_size, end, "directory ends where the end record starts");
+
+    let mut offset = directory_offset;
+    for (name, _) in ENTRIES {
+        assert_eq!(u32_at(&data, offset), 0x0201_4b50, "central directory signature for {}", name);
+        let name_len = u16_at(&data, offset + 28) as usize;
+        assert_eq!(&data[offset + 46..offset + 46 + name_len], name.as_bytes());
+        offset += 46 + name_len;
+    }
+    assert_eq!(offset, end);
+}
+
+#[test]
+fn entries_inflate_to_their_contents_with_matching_crcs() {
+    let data = archive();
+    let end = data.len() - 22;
+    let mut directory = u32_at(&data, end + 16) as usize;
+
+    for (name, contents) in ENTRIES {
+        let local = u32_at(&data, directory + 42) as usize;
+        assert_eq!(u32_at(&data, local), 0x0403_4b50, "local header signature for {}", name);
+        assert_eq!(u16_at(&data, local + 6), 0x0800, "UTF-8 name flag for {}", name);
+        assert_eq!(u16_at(&data, local + 8), 8, "deflate method for {}", name);
+        // CRC, sizes and name length match between the local header and the directory entry
+        assert_eq!(data[local + 14..local + 28], data[directory + 16..directory + 30], "{}", name);
+
+        let crc = u32_at(&data, local + 14);
+        let compressed_size = u32_at(&data, local + 18) as usize;
+        let size = u32_at(&data, local + 22) as usize;
+        let name_len = u16_at(&data, local + 26) as usize;
+        let extra_len = u16_at(&data, local + 28) as usize;
+        assert_eq!(&data[local + 30..local + 30 + name_len], name.as_bytes());
+        assert_eq!(crc, crc32(contents), "CRC of {}", name);
+        assert_eq!(size, contents.len(), "size of {}", name);
+
+        let start = local + 30 + name_len + extra_len;
+        let mut inflated = Vec::new();
+        flate2::read::DeflateDecoder::new(&data[start..start + compressed_size])
+            .read_to_end(&mut inflated)
+            .expect("inflate entry");
+        assert_eq!(inflated, contents, "contents of {}", name);
+
+        directory += 46 + name_len;
+    }
+}
@@ -0,0 +1,117 @@
+// Run-at-Windows-startup management for G27 LED Bridge
+//
+// Mirrors the persisted `autostart` setting into the per-user Run registry key
+// (HKCU\Software\Microsoft\Windows\CurrentVersion\Run), so no admin rights are needed.
+// The entry points at the current executable and keeps portable mode if active.
+
+use log::error;
+
+use crate::common::paths;
+
+#[cfg_attr(not(windows), allow(dead_code))]
+const RUN_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
+#[cfg_attr(not(windows), allow(dead_code))]
+const VALUE_NAME: &str = "G27 LED Bridge";
+
+/// Command line registered to run at login
+fn startup_command() -> Result<String, Box<dyn std::error::Error>> {
+    let exe = std::env::current_exe()?;
+    let mut command = format!("\"{}\"", exe.display());
+    if paths::is_portable() {
+        command.push_str(" --portable");
+    }
+    Ok(command)
+}
+
+/// Create or remove the startup entry to match `enabled`
+pub fn set_enabled(enabled: bool) -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(windows)]
+    {
+        if enabled {
+            registry::set_value(RUN_KEY, VALUE_NAME, &startup_command()?)?;
+            log::info!("Registered to start with Windows");
+        } else {
+            registry::delete_value(RUN_KEY, VALUE_NAME)?;
+            log::info!("Removed from Windows startup");
+        }
+        Ok(())
+    }
+
+    #[cfg(not(windows))]
+    {
+        if enabled {
+            let _ = startup_command()?;
+            return Err("Start with Windows is only supported on Windows".into());
+        }
+        Ok(())
+    }
+}
+
+/// Apply the setting, reporting failures on the console
+pub fn sync(enabled: bool) {
+    if let Err(e) = set_enabled(enabled) {
+        error!("Failed to update Windows startup entry: {}", e);
+    }
+}
+
+#[cfg(windows)]
+mod registry {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr::null_mut;
+
+    use winapi::shared::minwindef::HKEY;
+    use winapi::shared::winerror::{ERROR_FILE_NOT_FOUND, ERROR_SUCCESS};
+    use winapi::um::winnt::{KEY_SET_VALUE, REG_SZ};
+    use winapi::um::winreg::{RegCloseKey, RegDeleteValueW, RegOpenKeyExW, RegSetValueExW, HKEY_CURRENT_USER};
+
+    fn wide(s: &str) -> Vec<u16> {
+        OsStr::new(s).encode_wide().chain(std::iter::once(0)).collect()
+    }
+
+    fn check(status: i32, action: &str) -> Result<(), String> {
+        if status as u32 == ERROR_SUCCESS {
+            Ok(())
+        } else {
+            Err(format!("{} failed: {}", action, std::io::Error::from_raw_os_error(status)))
+        }
+    }
+
+    /// Open a key under HKCU for writing, run `f`, then close it
+    fn with_key<T>(path: &str, f: impl FnOnce(HKEY) -> Result<T, String>) -> Result<T, String> {
+        let mut key: HKEY = null_mut();
+        let status = unsafe { RegOpenKeyExW(HKEY_CURRENT_USER, wide(path).as_ptr(), 0, KEY_SET_VALUE, &mut key) };
+        check(status, "Opening registry key")?;
+        let result = f(key);
+        unsafe { RegCloseKey(key) };
+        result
+    }
+
+    pub fn set_value(path: &str, name: &str, value: &str) -> Result<(), String> {
+        with_key(path, |key| {
+            let data = wide(value);
+            let status = unsafe {
+                RegSetValueExW(
+                    key,
+                    wide(name).as_ptr(),
+                    0,
+                    REG_SZ,
+                    data.as_ptr() as *const u8,
+                    (data.len() * 2) as u32,
+                )
+            };
+            check(status, "Writing registry value")
+        })
+    }
+
+    pub fn delete_value(path: &str, name: &str) -> Result<(), String> {
+        with_key(path, |key| {
+            let status = unsafe { RegDeleteValueW(key, wide(name).as_ptr()) };
+            // Already absent is fine
+            if status as u32 == ERROR_FILE_NOT_FOUND {
+                return Ok(());
+            }
+            check(status, "Deleting registry value")
+        })
+    }
+}
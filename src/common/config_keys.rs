@@ -0,0 +1,150 @@
+// Dotted-key access to settings for the `config get/set/list` commands
+//
+// Keys use TOML dotted-key syntax against the settings file layout:
+// - `port`, `bind_address`, `retry.wheel_search_ms`
+// - `profiles."Rally rig".game_type` (quote names containing spaces or dots)
+//
+// Values are parsed as TOML, falling back to a plain string, and the result is
+// validated the same way as an imported file before it is accepted.
+// Secrets are masked when listing, but can still be read with an explicit `get`.
+
+use serde::Deserialize;
+use toml::{Table, Value};
+
+use crate::common::integrations::mask_secrets;
+use crate::common::settings::AppSettings;
+use crate::common::telemetry::GameType;
+use crate::common::validation::{self, SettingsReport};
+
+/// List every setting as `(dotted key, TOML value)` pairs, sorted by key
+pub fn list(settings: &AppSettings) -> Result<Vec<(String, String)>, String> {
+    let mut table = to_table(settings)?;
+    mask_secrets(&mut table);
+    let mut entries = Vec::new();
+    flatten(&table, "", &mut entries);
+    Ok(entries)
+}
+
+/// Read a single setting, or a whole section when the key names a table
+pub fn get(settings: &AppSettings, key: &str) -> Result<String, String> {
+    let path = parse_key(key)?;
+    let table = to_table(settings)?;
+
+    let mut current = &table;
+    for (i, segment) in path.iter().enumerate() {
+        match current.get(segment) {
+            Some(Value::Table(next)) if i + 1 < path.len() => current = next,
+            Some(Value::Table(section)) => {
+                let mut section = section.clone();
+                mask_secrets(&mut section);
+                return toml::to_string_pretty(&section).map_err(|e| e.to_string());
+            }
+            Some(value) if i + 1 == path.len() => return Ok(value.to_string()),
+            _ => break,
+        }
+    }
+
+    Err(format!("Unknown setting '{}'. Use 'config list' to see available keys", key))
+}
+
+/// Return a copy of `settings` with `key` set to `value`, rejecting unknown keys and invalid values.
+/// Top-level changes are copied into the active profile, like changes from the tray.
+pub fn set(settings: &AppSettings, key: &str, value: &str) -> Result<AppSettings, String> {
+    let path = parse_key(key)?;
+    let mut table = to_table(settings)?;
+    let value = parse_value(&path, value);
+
+    let (last, parents) = path.split_last().ok_or("Empty setting key")?;
+    let mut current = &mut table;
+    for segment in parents {
+        current = match current
+            .entry(segment.clone())
+            .or_insert_with(|| Value::Table(Table::new()))
+        {
+            Value::Table(next) => next,
+            _ => return Err(format!("'{}' is not a section", segment)),
+        };
+    }
+    current.insert(last.clone(), value);
+
+    let mut report = SettingsReport::default();
+    validation::check_unknown_keys(&table, &mut report);
+    if !report.warnings.is_empty() {
+        return Err(report.warnings.join("\n"));
+    }
+
+    let mut updated = AppSettings::deserialize(Value::Table(table))
+        .map_err(|e| format!("Invalid value for '{}': {}", key, e.message()))?;
+    validation::repair(&mut updated, &mut report);
+    if !report.is_empty() {
+        // Repair messages describe the fallback value, which is not applied here
+        let problems: Vec<&str> = report
+            .errors
+            .iter()
+            .chain(&report.warnings)
+            .map(|problem| problem.split(" (using").next().unwrap_or(problem))
+            .collect();
+        return Err(problems.join("\n"));
+    }
+
+    // A profile's own keys, or the choice of profile, must not be overwritten by the top-level values
+    if path.first().is_some_and(|key| key != "profiles" && key != "active_profile") {
+        updated.sync_active_profile();
+    }
+    Ok(updated)
+}
+
+fn to_table(settings: &AppSettings) -> Result<Table, String> {
+    Table::try_from(settings).map_err(|e| e.to_string())
+}
+
+/// Split a TOML dotted key into its segments, honouring quoted names
+fn parse_key(key: &str) -> Result<Vec<String>, String> {
+    let parsed: Table = toml::from_str(&format!("{} = 0", key))
+        .map_err(|_| format!("Invalid setting key '{}'", key))?;
+
+    let mut path = Vec::new();
+    let mut current = &parsed;
+    while let Some((segment, value)) = current.iter().next() {
+        path.push(segment.clone());
+        match value {
+            Value::Table(next) => current = next,
+            _ => break,
+        }
+    }
+    Ok(path)
+}
+
+/// Parse a command-line value as TOML, accepting bare strings and game aliases
+fn parse_value(path: &[String], value: &str) -> Value {
+    if path.last().is_some_and(|key| key == "game_type") {
+        if let Some(game) = GameType::parse_game_name(value) {
+            return Value::String(format!("{:?}", game));
+        }
+    }
+
+    toml::from_str::<Table>(&format!("value = {}", value))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| Value::String(value.to_string()))
+}
+
+fn flatten(table: &Table, prefix: &str, entries: &mut Vec<(String, String)>) {
+    for (key, value) in table {
+        let key = format!("{}{}", prefix, format_key(key));
+        match value {
+            Value::Table(section) => flatten(section, &format!("{}.", key), entries),
+            value => entries.push((key, value.to_string())),
+        }
+    }
+}
+
+fn format_key(key: &str) -> String {
+    let bare = !key.is_empty()
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if bare {
+        key.to_string()
+    } else {
+        format!("{:?}", key)
+    }
+}
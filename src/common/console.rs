@@ -0,0 +1,164 @@
+// Console control events for `--console` mode
+//
+// Without a handler, Ctrl+C, Ctrl+Break and closing the console window end the process
+// on the spot, possibly with the LEDs still lit or the settings file half written.
+// `handle_close` asks the app to exit like its Exit menu entry instead. Windows ends
+// the process as soon as the handler returns from a close event, so the handler waits
+// for `finished` first, within the few seconds Windows allows.
+//
+// With `[exit] console_close = "minimize_to_tray"` the window's close button is disabled
+// instead, and a minimized window is hidden until Show Console in the tray brings it back.
+// Consoles without a window of their own, such as Windows Terminal tabs, still close.
+
+use std::sync::{Condvar, Mutex, OnceLock};
+use std::time::Duration;
+
+/// Called from the handler thread when the console asks the app to exit
+static REQUEST_EXIT: OnceLock<Mutex<Box<dyn Fn() + Send>>> = OnceLock::new();
+
+/// Set by `finished`, for a close event waiting to let the process end
+static FINISHED: (Mutex<bool>, Condvar) = (Mutex::new(false), Condvar::new());
+
+/// Longest a close event waits for shutdown; Windows ends the process after about 5 seconds
+#[cfg_attr(not(windows), allow(dead_code))]
+const CLOSE_WAIT: Duration = Duration::from_millis(4500);
+
+/// Call `request_exit` on Ctrl+C, Ctrl+Break, or when the console window is closed or the
+/// user logs off. Later calls are ignored.
+pub fn handle_close(request_exit: impl Fn() + Send + 'static) {
+    if REQUEST_EXIT.set(Mutex::new(Box::new(request_exit))).is_ok() {
+        handler::install();
+    }
+}
+
+/// Shutdown is complete; a closed console window may now end the process
+pub fn finished() {
+    let (done, changed) = &FINISHED;
+    if let Ok(mut done) = done.lock() {
+        *done = true;
+    }
+    changed.notify_all();
+}
+
+/// Enable the console window's close button, or disable it so the window can only be minimized
+pub fn set_close_enabled(enabled: bool) {
+    window::set_close_enabled(enabled);
+}
+
+/// Hide the console window if it is minimized
+pub fn hide_if_minimized() {
+    window::hide_if_minimized();
+}
+
+/// Show a hidden or minimized console window and bring it to the front
+pub fn show() {
+    window::show();
+}
+
+#[cfg_attr(not(windows), allow(dead_code))]
+fn request_exit() {
+    if let Some(Ok(request_exit)) = REQUEST_EXIT.get().map(Mutex::lock) {
+        request_exit();
+    }
+}
+
+/// Block until `finished` is called or `timeout` passes
+#[cfg_attr(not(windows), allow(dead_code))]
+fn wait_finished(timeout: Duration) {
+    let (done, changed) = &FINISHED;
+    if let Ok(done) = done.lock() {
+        let _ = changed.wait_timeout_while(done, timeout, |done| !*done);
+    }
+}
+
+#[cfg(windows)]
+mod handler {
+    use winapi::shared::minwindef::{BOOL, DWORD, FALSE, TRUE};
+    use winapi::um::wincon::{CTRL_BREAK_EVENT, CTRL_CLOSE_EVENT, CTRL_C_EVENT, CTRL_LOGOFF_EVENT, CTRL_SHUTDOWN_EVENT};
+
+    use super::{request_exit, wait_finished, CLOSE_WAIT};
+
+    pub fn install() {
+        use winapi::um::consoleapi::SetConsoleCtrlHandler;
+
+        if unsafe { SetConsoleCtrlHandler(Some(on_control), TRUE) } == 0 {
+            log::warn!("Cannot handle Ctrl+C and closing the console: {}", std::io::Error::last_os_error());
+        }
+    }
+
+    unsafe extern "system" fn on_control(event: DWORD) -> BOOL {
+        match event {
+            // The process keeps running; the tray loop exits once it notices
+            CTRL_C_EVENT | CTRL_BREAK_EVENT => {
+                log::info!("Ctrl+C pressed - shutting down");
+                request_exit();
+                TRUE
+            }
+            // The process ends when this returns
+            CTRL_CLOSE_EVENT | CTRL_LOGOFF_EVENT | CTRL_SHUTDOWN_EVENT => {
+                log::info!("Console closing - shutting down");
+                request_exit();
+                wait_finished(CLOSE_WAIT);
+                TRUE
+            }
+            _ => FALSE,
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod handler {
+    pub fn install() {}
+}
+
+#[cfg(windows)]
+mod window {
+    use winapi::um::wincon::GetConsoleWindow;
+    use winapi::um::winuser::{
+        DeleteMenu, DrawMenuBar, GetSystemMenu, IsIconic, IsWindowVisible, SetForegroundWindow, ShowWindow,
+        MF_BYCOMMAND, SC_CLOSE, SW_HIDE, SW_RESTORE,
+    };
+
+    pub fn set_close_enabled(enabled: bool) {
+        unsafe {
+            let console = GetConsoleWindow();
+            if console.is_null() {
+                return;
+            }
+            // Resetting the system menu brings the close entry and button back
+            let menu = GetSystemMenu(console, if enabled { 1 } else { 0 });
+            if !enabled && !menu.is_null() {
+                DeleteMenu(menu, SC_CLOSE as u32, MF_BYCOMMAND);
+            }
+            DrawMenuBar(console);
+        }
+    }
+
+    pub fn hide_if_minimized() {
+        unsafe {
+            let console = GetConsoleWindow();
+            if !console.is_null() && IsWindowVisible(console) != 0 && IsIconic(console) != 0 {
+                ShowWindow(console, SW_HIDE);
+            }
+        }
+    }
+
+    pub fn show() {
+        unsafe {
+            let console = GetConsoleWindow();
+            if !console.is_null() {
+                ShowWindow(console, SW_RESTORE);
+                SetForegroundWindow(console);
+            }
+        }
+    }
+}
+
+#[cfg(not(windows))]
+mod window {
+    pub fn set_close_enabled(_enabled: bool) {}
+
+    pub fn hide_if_minimized() {}
+
+    pub fn show() {}
+}
@@ -0,0 +1,218 @@
+// Crash reports for G27 LED Bridge
+//
+// A panic that nothing catches, or a Windows exception such as an access violation in a
+// driver, ends the bridge without a trace in tray mode - it simply disappears. `install`
+// turns that into something a bug report can use:
+// - A crash report in crashes\ in the data directory: version, thread, panic message or
+//   exception code, a backtrace and the most recent log lines
+// - A minidump next to it when `crash_dumps = true` in settings.toml (Windows only)
+// - A notification naming the report
+//
+// Panics a component catches and reports as an error (shown as "crashed" in the tray
+// status) are only logged; the component restarts and no report is written.
+
+use std::backtrace::Backtrace;
+use std::fmt::Write as _;
+use std::fs;
+use std::panic;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::common::i18n::{tr, tr_with};
+use crate::common::logging;
+use crate::common::notifications::Notification;
+use crate::common::paths;
+use crate::common::util::{panic_is_caught, panic_message};
+
+/// Log lines at the end of a crash report
+const LOG_LINES_IN_REPORT: usize = 100;
+
+/// Write a minidump with each crash report
+static MINIDUMPS: AtomicBool = AtomicBool::new(false);
+
+/// Write crash reports for panics nothing catches and, on Windows, for unhandled
+/// exceptions; call once at startup after `logging::init`
+pub fn install() {
+    let log_panic = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        log_panic(info);
+        if panic_is_caught() {
+            return;
+        }
+        let thread = std::thread::current();
+        let location = info.location().map(|location| format!(" at {}", location)).unwrap_or_default();
+        let cause = format!(
+            "Panic in thread '{}'{}: {}",
+            thread.name().unwrap_or("unnamed"),
+            location,
+            panic_message(info.payload())
+        );
+        report(&cause, None);
+    }));
+
+    #[cfg(windows)]
+    windows::install();
+}
+
+/// Also write a minidump with each crash report; applied when settings are loaded
+pub fn set_minidumps(enabled: bool) {
+    MINIDUMPS.store(enabled, Ordering::Relaxed);
+}
+
+/// Write the crash report and dump for `cause` and tell the user where they are.
+/// `exception` is the Windows exception being handled, if any.
+fn report(cause: &str, exception: Option<ExceptionPointers>) {
+    log::logger().flush();
+
+    let path = match new_report_path() {
+        Ok(path) => path,
+        Err(e) => {
+            eprintln!("# Cannot create the crashes directory: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = fs::write(&path, report_text(cause)) {
+        eprintln!("# Cannot write crash report {:?}: {}", path, e);
+        return;
+    }
+    if MINIDUMPS.load(Ordering::Relaxed) {
+        write_minidump(&path.with_extension("dmp"), exception);
+    }
+    eprintln!("# Crash report written to {:?}", path);
+
+    Notification {
+        title: tr("G27 LED Bridge crashed"),
+        message: tr_with("Please attach the crash report to your bug report: {}", &path.display().to_string()),
+    }
+    .show_now();
+}
+
+/// New file in the crashes folder, named after the current time
+fn new_report_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let crashed = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    Ok(paths::crashes_dir()?.join(format!("crash-{}.txt", crashed)))
+}
+
+fn report_text(cause: &str) -> String {
+    let mut text = String::new();
+    let _ = writeln!(text, "G27 LED Bridge {} crashed", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(text, "{} {}", std::env::consts::OS, std::env::consts::ARCH);
+    let _ = writeln!(text);
+    let _ = writeln!(text, "{}", cause);
+    let _ = writeln!(text);
+    let _ = writeln!(text, "Backtrace:");
+    let _ = writeln!(text, "{}", Backtrace::force_capture());
+    let _ = writeln!(text, "Recent log:");
+    let lines = logging::recent_lines();
+    for line in &lines[lines.len().saturating_sub(LOG_LINES_IN_REPORT)..] {
+        let _ = writeln!(text, "{}", line);
+    }
+    text
+}
+
+#[cfg(windows)]
+type ExceptionPointers = *mut winapi::um::winnt::EXCEPTION_POINTERS;
+
+#[cfg(not(windows))]
+type ExceptionPointers = ();
+
+#[cfg(windows)]
+fn write_minidump(path: &std::path::Path, exception: Option<ExceptionPointers>) {
+    windows::write_minidump(path, exception)
+}
+
+#[cfg(not(windows))]
+fn write_minidump(_path: &std::path::Path, _exception: Option<ExceptionPointers>) {}
+
+#[cfg(windows)]
+mod windows {
+    use std::fs::File;
+    use std::os::windows::io::AsRawHandle;
+    use std::path::Path;
+
+    use winapi::shared::minwindef::{BOOL, DWORD, FALSE, LONG};
+    use winapi::um::errhandlingapi::SetUnhandledExceptionFilter;
+    use winapi::um::processthreadsapi::{GetCurrentProcess, GetCurrentProcessId, GetCurrentThreadId};
+    use winapi::um::winnt::{EXCEPTION_POINTERS, HANDLE};
+    use winapi::vc::excpt::EXCEPTION_CONTINUE_SEARCH;
+
+    use super::{report, ExceptionPointers};
+
+    /// MiniDumpNormal: stacks of all threads, without the heap
+    const MINIDUMP_NORMAL: u32 = 0;
+
+    /// MINIDUMP_EXCEPTION_INFORMATION, which dbghelp.h packs to 4 bytes
+    #[repr(C, packed(4))]
+    struct MinidumpExceptionInformation {
+        thread_id: DWORD,
+        exception_pointers: *mut EXCEPTION_POINTERS,
+        client_pointers: BOOL,
+    }
+
+    // winapi does not declare MiniDumpWriteDump
+    #[link(name = "dbghelp")]
+    extern "system" {
+        fn MiniDumpWriteDump(
+            process: HANDLE,
+            process_id: DWORD,
+            file: HANDLE,
+            dump_type: u32,
+            exception: *const MinidumpExceptionInformation,
+            user_stream: *const std::ffi::c_void,
+            callback: *const std::ffi::c_void,
+        ) -> BOOL;
+    }
+
+    pub(super) fn install() {
+        unsafe {
+            SetUnhandledExceptionFilter(Some(on_unhandled_exception));
+        }
+    }
+
+    /// Report the exception, then let Windows end the process as it would have
+    unsafe extern "system" fn on_unhandled_exception(exception: *mut EXCEPTION_POINTERS) -> LONG {
+        let (code, address) = match exception.as_ref().and_then(|pointers| pointers.ExceptionRecord.as_ref()) {
+            Some(record) => (record.ExceptionCode, record.ExceptionAddress as usize),
+            None => (0, 0),
+        };
+        let thread = std::thread::current();
+        let cause = format!(
+            "Unhandled exception 0x{:08X} at 0x{:X} in thread '{}'",
+            code,
+            address,
+            thread.name().unwrap_or("unnamed")
+        );
+        report(&cause, Some(exception));
+        EXCEPTION_CONTINUE_SEARCH
+    }
+
+    pub(super) fn write_minidump(path: &Path, exception: Option<ExceptionPointers>) {
+        let file = match File::create(path) {
+            Ok(file) => file,
+            Err(e) => {
+                eprintln!("# Cannot create minidump {:?}: {}", path, e);
+                return;
+            }
+        };
+        let information = exception.map(|exception_pointers| MinidumpExceptionInformation {
+            thread_id: unsafe { GetCurrentThreadId() },
+            exception_pointers,
+            client_pointers: FALSE,
+        });
+        let written = unsafe {
+            MiniDumpWriteDump(
+                GetCurrentProcess(),
+                GetCurrentProcessId(),
+                file.as_raw_handle() as HANDLE,
+                MINIDUMP_NORMAL,
+                information.as_ref().map_or(std::ptr::null(), |information| information as *const _),
+                std::ptr::null(),
+                std::ptr::null(),
+            )
+        };
+        if written == FALSE {
+            eprintln!("# Cannot write minidump {:?}: {}", path, std::io::Error::last_os_error());
+        }
+    }
+}
@@ -0,0 +1,341 @@
+// Dashboard window for G27 LED Bridge
+//
+// One window for what otherwise takes the console, the tray menu and Notepad:
+// - Bridge status as reported by the running tray app or service over the status pipe
+// - Live RPM, gear and the wheel's LED bar as the bridge lights it
+// - The everyday settings with Save and Revert; the running bridge picks up the saved
+//   file like any other edit, and the settings file keeps everything else
+//
+// winit allows one event loop per process and the tray owns it, so the tray starts the
+// dashboard as a process of its own (`g27-led-bridge dashboard`). Builds without the
+// `dashboard` feature leave egui out and report that the window is unavailable.
+
+/// Show the dashboard until the user closes it
+pub fn run() -> Result<(), String> {
+    #[cfg(feature = "dashboard")]
+    {
+        window::run()
+    }
+
+    #[cfg(not(feature = "dashboard"))]
+    {
+        Err("This build has no dashboard; build with the `dashboard` feature to get it".to_string())
+    }
+}
+
+#[cfg(feature = "dashboard")]
+mod window {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use eframe::egui::{self, Color32, RichText};
+    use log::{debug, warn};
+
+    use crate::common::autostart;
+    use crate::common::i18n::{self, tr, Language};
+    use crate::common::ipc::{self, InstanceStatus, LiveReadout};
+    use crate::common::leds::{FillPattern, LedProfile};
+    use crate::common::settings::AppSettings;
+    use crate::common::settings_dialog::DialogValues;
+    use crate::common::systray::pattern_label;
+    use crate::common::telemetry::GameType;
+
+    /// How often the running bridge is asked for its status
+    const POLL_INTERVAL: Duration = Duration::from_millis(200);
+    /// Spacing of the LED bar, and the size of each LED
+    const LED_SPACING: f32 = 36.0;
+    const LED_RADIUS: f32 = 12.0;
+
+    pub fn run() -> Result<(), String> {
+        i18n::set_language(AppSettings::load().language);
+        let options = eframe::NativeOptions {
+            viewport: egui::ViewportBuilder::default()
+                .with_inner_size([420.0, 680.0])
+                .with_min_inner_size([360.0, 400.0]),
+            ..Default::default()
+        };
+        eframe::run_native(
+            tr("G27 LED Bridge Dashboard"),
+            options,
+            Box::new(|cc| Box::new(Dashboard::new(cc))),
+        )
+        .map_err(|e| e.to_string())
+    }
+
+    struct Dashboard {
+        /// Settings shown in the form
+        form: AppSettings,
+        /// Port and bind address as typed, checked on save
+        port: String,
+        bind_address: String,
+        /// Latest answer of the running bridge, `None` while none is running
+        status: Arc<Mutex<Option<InstanceStatus>>>,
+        /// Outcome of the last save
+        message: Option<Result<String, String>>,
+    }
+
+    impl Dashboard {
+        fn new(cc: &eframe::CreationContext<'_>) -> Self {
+            let status = Arc::new(Mutex::new(None));
+            let poll_status = status.clone();
+            let ctx = cc.egui_ctx.clone();
+            std::thread::spawn(move || poll(poll_status, ctx));
+
+            let mut dashboard = Self {
+                form: AppSettings::default(),
+                port: String::new(),
+                bind_address: String::new(),
+                status,
+                message: None,
+            };
+            dashboard.revert();
+            dashboard
+        }
+
+        /// Fill the form from the settings file
+        fn revert(&mut self) {
+            self.form = AppSettings::load();
+            self.port = self.form.port.to_string();
+            self.bind_address = self.form.bind_address.to_string();
+        }
+
+        /// Write the form over the settings file as it is now, keeping whatever else changed in it
+        fn save(&mut self) -> Result<(), String> {
+            let game_index = GameType::ALL.iter().position(|game| *game == self.form.game_type).unwrap_or_default();
+            let values = DialogValues::parse(game_index, &self.port, &self.bind_address)?;
+
+            let mut settings = AppSettings::load();
+            settings.auto_detect_game = self.form.auto_detect_game;
+            settings.leds = self.form.leds;
+            settings.tray.led_mirror = self.form.tray.led_mirror;
+            settings.notifications = self.form.notifications;
+            settings.language = self.form.language;
+            settings.check_for_updates = self.form.check_for_updates;
+            if settings.autostart != self.form.autostart {
+                settings.autostart = self.form.autostart;
+                autostart::sync(settings.autostart);
+            }
+            // Saves once for everything above
+            settings.set_listener(values.game_type, values.port, values.bind_address);
+
+            self.form = settings;
+            Ok(())
+        }
+
+        fn settings_section(&mut self, ui: &mut egui::Ui) {
+            egui::Grid::new("settings").num_columns(2).spacing([16.0, 6.0]).show(ui, |ui| {
+                ui.label(tr("Game:"));
+                egui::ComboBox::from_id_source("game")
+                    .selected_text(self.form.game_type.parser().game_name())
+                    .show_ui(ui, |ui| {
+                        for game in GameType::ALL {
+                            ui.selectable_value(&mut self.form.game_type, game, game.parser().game_name());
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("");
+                ui.checkbox(&mut self.form.auto_detect_game, tr("Auto-detect"));
+                ui.end_row();
+
+                ui.label(tr("UDP port:"));
+                ui.text_edit_singleline(&mut self.port);
+                ui.end_row();
+
+                ui.label(tr("Bind address:"));
+                ui.text_edit_singleline(&mut self.bind_address);
+                ui.end_row();
+
+                ui.label(tr("LED profile:"));
+                egui::ComboBox::from_id_source("led_profile")
+                    .selected_text(self.form.leds.profile.name())
+                    .show_ui(ui, |ui| {
+                        for profile in LedProfile::ALL {
+                            ui.selectable_value(&mut self.form.leds.profile, profile, profile.name());
+                        }
+                    });
+                ui.end_row();
+
+                ui.label(tr("LED pattern:"));
+                egui::ComboBox::from_id_source("led_pattern")
+                    .selected_text(tr(pattern_label(self.form.leds.pattern)))
+                    .show_ui(ui, |ui| {
+                        for pattern in FillPattern::ALL {
+                            ui.selectable_value(&mut self.form.leds.pattern, pattern, tr(pattern_label(pattern)));
+                        }
+                    });
+                ui.end_row();
+
+                ui.label("");
+                ui.checkbox(&mut self.form.leds.shift_flash, tr("Shift Flash"));
+                ui.end_row();
+
+                ui.label("");
+                ui.checkbox(&mut self.form.tray.led_mirror, tr("Mirror the LEDs in the tray icon"));
+                ui.end_row();
+
+                ui.label("");
+                ui.checkbox(&mut self.form.notifications, tr("Notifications"));
+                ui.end_row();
+
+                ui.label("");
+                ui.checkbox(&mut self.form.autostart, tr("Start with Windows"));
+                ui.end_row();
+
+                ui.label("");
+                ui.checkbox(&mut self.form.check_for_updates, tr("Check for updates at startup"));
+                ui.end_row();
+
+                ui.label(tr("Language:"));
+                egui::ComboBox::from_id_source("language")
+                    .selected_text(self.form.language.native_name())
+                    .show_ui(ui, |ui| {
+                        for language in Language::ALL {
+                            ui.selectable_value(&mut self.form.language, language, language.native_name());
+                        }
+                    });
+                ui.end_row();
+            });
+
+            ui.add_space(8.0);
+            ui.horizontal(|ui| {
+                if ui.button(tr("Save")).clicked() {
+                    self.message = Some(
+                        self.save()
+                            .map(|_| tr("Saved. The running bridge applies the changes automatically.").to_string()),
+                    );
+                }
+                if ui.button(tr("Revert")).clicked() {
+                    self.revert();
+                    self.message = None;
+                }
+            });
+            match &self.message {
+                Some(Ok(message)) => {
+                    ui.label(RichText::new(message).color(Color32::from_rgb(0, 160, 0)));
+                }
+                Some(Err(message)) => {
+                    ui.label(RichText::new(message).color(Color32::RED));
+                }
+                None => {}
+            }
+        }
+    }
+
+    impl eframe::App for Dashboard {
+        fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+            let status = self.status.lock().ok().and_then(|status| status.clone());
+            egui::CentralPanel::default().show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    ui.heading(tr("Status"));
+                    status_section(ui, status.as_ref());
+                    ui.separator();
+                    ui.heading(tr("Telemetry"));
+                    telemetry_section(ui, status.as_ref().and_then(|status| status.live));
+                    ui.separator();
+                    ui.heading(tr("Settings"));
+                    self.settings_section(ui);
+                });
+            });
+        }
+    }
+
+    /// Ask the running bridge for its status every `POLL_INTERVAL` until the window closes
+    fn poll(status: Arc<Mutex<Option<InstanceStatus>>>, ctx: egui::Context) {
+        let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+            Ok(runtime) => runtime,
+            Err(e) => {
+                warn!("Cannot query the running bridge: {}", e);
+                return;
+            }
+        };
+        loop {
+            let latest = runtime.block_on(ipc::query_status()).unwrap_or_else(|e| {
+                debug!("Status query failed: {}", e);
+                None
+            });
+            match status.lock() {
+                Ok(mut status) => *status = latest,
+                Err(_) => return,
+            }
+            ctx.request_repaint();
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    }
+
+    fn status_section(ui: &mut egui::Ui, status: Option<&InstanceStatus>) {
+        let Some(status) = status else {
+            ui.label(tr("The bridge is not running."));
+            return;
+        };
+        egui::Grid::new("status").num_columns(2).spacing([16.0, 4.0]).show(ui, |ui| {
+            ui.label(tr("State:"));
+            ui.label(tr(&status.state));
+            ui.end_row();
+
+            ui.label(tr("Game:"));
+            ui.label(status.game.parser().game_name());
+            ui.end_row();
+
+            ui.label(tr("Listening on:"));
+            ui.label(status.listen_address.to_string());
+            ui.end_row();
+
+            ui.label("G27:");
+            ui.label(if status.wheel_connected { tr("Connected") } else { tr("Not found") });
+            ui.end_row();
+
+            ui.label(tr("Packet rate:"));
+            ui.label(format!("{:.1}/s", status.packets_per_second));
+            ui.end_row();
+
+            if status.rejected_packets > 0 {
+                ui.label(tr("Rejected packets:"));
+                ui.label(status.rejected_packets.to_string());
+                ui.end_row();
+            }
+
+            ui.label(tr("Last error:"));
+            ui.label(status.last_error.as_deref().unwrap_or("-"));
+            ui.end_row();
+        });
+    }
+
+    fn telemetry_section(ui: &mut egui::Ui, live: Option<LiveReadout>) {
+        let Some(live) = live else {
+            ui.label(tr("Waiting for telemetry"));
+            led_bar(ui, 0);
+            return;
+        };
+        let gear = match live.gear {
+            Some(-1) => "R".to_string(),
+            Some(0) => "N".to_string(),
+            Some(gear) => gear.to_string(),
+            None => "-".to_string(),
+        };
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(format!("{:.0} rpm", live.rpm.max(0.0))).size(28.0).strong());
+            ui.add_space(16.0);
+            ui.label(RichText::new(format!("{} {}", tr("Gear"), gear)).size(20.0));
+        });
+        let fraction = if live.max_rpm > 0.0 { live.rpm / live.max_rpm } else { 0.0 };
+        ui.add(egui::ProgressBar::new(fraction.clamp(0.0, 1.0)).text(format!("{:.0} / {:.0}", live.rpm, live.max_rpm)));
+        led_bar(ui, live.leds);
+    }
+
+    /// The wheel's five LEDs, lit from `leds` in the wheel's colors
+    fn led_bar(ui: &mut egui::Ui, leds: u8) {
+        let (rect, _) = ui.allocate_exact_size(egui::vec2(LED_SPACING * 5.0, LED_SPACING), egui::Sense::hover());
+        let painter = ui.painter_at(rect);
+        for led in 0..5u8 {
+            let center = rect.left_center() + egui::vec2(LED_SPACING * (f32::from(led) + 0.5), 0.0);
+            let color = match (led, leds & (1 << led) != 0) {
+                (_, false) => Color32::from_gray(64),
+                (0 | 1, true) => Color32::from_rgb(0, 255, 0),
+                (2 | 3, true) => Color32::from_rgb(255, 165, 0),
+                (_, true) => Color32::from_rgb(255, 0, 0),
+            };
+            painter.circle_filled(center, LED_RADIUS, color);
+        }
+    }
+}
@@ -0,0 +1,199 @@
+// Diagnostic reports for G27 LED Bridge
+//
+// "Generate Diagnostic Report" in the tray and `doctor --report` bundle what a bug report
+// otherwise has to ask for into one zip file in the data directory, ready to attach to a
+// GitHub issue:
+// - report.txt: version, Windows version, installed Logitech software, the Logitech HID
+//   devices and what the running bridge reports, including the sizes of its last packets
+// - settings.toml with the integration passwords and keys masked
+// - the current log file
+//
+// `doctor` without `--report` prints report.txt instead. The archive is written by the
+// core crate's small `ZipWriter` rather than another dependency for a single file format.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use g27_led_bridge_core::{ZipWriter, G27_PID, G27_VID};
+
+use crate::common::integrations::mask_secrets;
+use crate::common::ipc::{self, InstanceStatus};
+use crate::common::logging;
+use crate::common::paths;
+use crate::common::settings::AppSettings;
+
+/// Folders of the Logitech software that switches the G27 to native mode
+#[cfg(windows)]
+const LOGITECH_SOFTWARE: &[(&str, &str)] = &[
+    ("Logitech Gaming Software", r"C:\Program Files\Logitech Gaming Software"),
+    ("Logitech G HUB", r"C:\Program Files\LGHUB"),
+];
+
+/// Write the diagnostic report to `path`, or to a new file in the data directory.
+/// Returns where it was written.
+pub fn write_report(path: Option<&Path>) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => {
+            let created = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+            paths::data_dir()?.join(format!("diagnostics-{}.zip", created))
+        }
+    };
+
+    let mut zip = ZipWriter::default();
+    zip.add("report.txt", summary().as_bytes())?;
+    let settings = masked_settings().unwrap_or_else(|e| format!("# Cannot read settings.toml: {}\n", e));
+    zip.add("settings.toml", settings.as_bytes())?;
+    if let Some(log_file) = logging::current_file() {
+        match fs::read(&log_file) {
+            Ok(log) => zip.add(&format!("logs/{}", file_name(&log_file)), &log)?,
+            Err(e) => log::warn!("Cannot add log file {:?} to the report: {}", log_file, e),
+        }
+    }
+    fs::write(&path, zip.finish())?;
+    Ok(path)
+}
+
+/// Everything report.txt says about this PC and the running bridge
+pub fn summary() -> String {
+    let mut text = String::new();
+    let _ = writeln!(text, "G27 LED Bridge {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(text, "OS:        {} ({} {})", os_version(), std::env::consts::OS, std::env::consts::ARCH);
+    let _ = writeln!(text, "Logitech:  {}", logitech_software());
+    let _ = writeln!(text, "Portable:  {}", if paths::is_portable() { "yes" } else { "no" });
+    let _ = writeln!(text);
+
+    let _ = writeln!(text, "Logitech HID devices:");
+    match hidapi::HidApi::new() {
+        Ok(hid) => {
+            let mut found = false;
+            for device in hid.device_list().filter(|device| device.vendor_id() == G27_VID) {
+                found = true;
+                let _ = writeln!(
+                    text,
+                    "  {} [{:04x}:{:04x}]{} - {}",
+                    device.product_string().unwrap_or("(unknown product)"),
+                    device.vendor_id(),
+                    device.product_id(),
+                    if device.product_id() == G27_PID { " G27" } else { "" },
+                    device.path().to_string_lossy()
+                );
+            }
+            if !found {
+                let _ = writeln!(text, "  none");
+            }
+        }
+        Err(e) => {
+            let _ = writeln!(text, "  cannot list devices: {}", e);
+        }
+    }
+    let _ = writeln!(text);
+
+    let _ = writeln!(text, "Running bridge:");
+    match query_running_bridge() {
+        Ok(Some(status)) => {
+            let _ = writeln!(text, "  {} - {}", status.mode, status.state);
+            let _ = writeln!(text, "  game {:?} on {}", status.game, status.listen_address);
+            let _ = writeln!(text, "  wheel {}", if status.wheel_connected { "connected" } else { "not connected" });
+            let _ = writeln!(
+                text,
+                "  {:.1} packets/s, {} gaps, {} malformed, {} rejected",
+                status.packets_per_second, status.packet_gaps, status.malformed_packets, status.rejected_packets
+            );
+            let _ = writeln!(text, "  last packet sizes: {:?}", status.recent_packet_sizes);
+            let _ = writeln!(text, "  last error: {}", status.last_error.as_deref().unwrap_or("-"));
+        }
+        Ok(None) => {
+            let _ = writeln!(text, "  not running");
+        }
+        Err(e) => {
+            let _ = writeln!(text, "  cannot query it: {}", e);
+        }
+    }
+    text
+}
+
+fn query_running_bridge() -> Result<Option<InstanceStatus>, Box<dyn std::error::Error>> {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+    Ok(runtime.block_on(ipc::query_status())?)
+}
+
+/// The settings file with its secrets masked
+fn masked_settings() -> Result<String, Box<dyn std::error::Error>> {
+    let mut settings: toml::Table = fs::read_to_string(AppSettings::config_path()?)?.parse()?;
+    mask_secrets(&mut settings);
+    Ok(toml::to_string_pretty(&settings)?)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map_or_else(|| "log.txt".to_string(), |name| name.to_string_lossy().into_owned())
+}
+
+/// Windows edition, version and build, e.g. "Windows 10 Pro 22H2 (build 19045)"
+fn os_version() -> String {
+    #[cfg(windows)]
+    {
+        let product = registry_string("ProductName").unwrap_or_else(|| "Windows".to_string());
+        let version = registry_string("DisplayVersion").or_else(|| registry_string("ReleaseId")).unwrap_or_default();
+        let build = registry_string("CurrentBuild").unwrap_or_default();
+        format!("{} {} (build {})", product, version, build)
+    }
+
+    #[cfg(not(windows))]
+    {
+        std::env::consts::OS.to_string()
+    }
+}
+
+/// A text value of HKLM\SOFTWARE\Microsoft\Windows NT\CurrentVersion
+#[cfg(windows)]
+fn registry_string(name: &str) -> Option<String> {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::shared::winerror::ERROR_SUCCESS;
+    use winapi::um::winreg::{RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ};
+
+    let wide = |text: &str| -> Vec<u16> { OsStr::new(text).encode_wide().chain(std::iter::once(0)).collect() };
+    let mut buffer = [0u16; 256];
+    let mut size = std::mem::size_of_val(&buffer) as u32;
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            wide(r"SOFTWARE\Microsoft\Windows NT\CurrentVersion").as_ptr(),
+            wide(name).as_ptr(),
+            RRF_RT_REG_SZ,
+            std::ptr::null_mut(),
+            buffer.as_mut_ptr() as *mut _,
+            &mut size,
+        )
+    };
+    if status != ERROR_SUCCESS as i32 {
+        return None;
+    }
+    let len = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    Some(String::from_utf16_lossy(&buffer[..len]))
+}
+
+/// Which Logitech software is installed
+fn logitech_software() -> String {
+    #[cfg(windows)]
+    {
+        let installed: Vec<_> = LOGITECH_SOFTWARE
+            .iter()
+            .filter(|(_, folder)| Path::new(folder).exists())
+            .map(|(name, _)| *name)
+            .collect();
+        if installed.is_empty() {
+            "none found".to_string()
+        } else {
+            installed.join(", ")
+        }
+    }
+
+    #[cfg(not(windows))]
+    {
+        "not checked".to_string()
+    }
+}
@@ -0,0 +1,58 @@
+// Process exit codes
+//
+// Launcher scripts and Playnite/Steam wrappers tell failures apart by the exit code,
+// so the numbers are part of the command-line interface: add new codes, never
+// renumber existing ones. The README lists them for users.
+
+use std::error::Error;
+
+use crate::common::util::DR2G27Error;
+use g27_led_bridge_core::BoundOutcome;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    /// Finished normally, or exited from the tray
+    Success = 0,
+    /// Any failure without a code of its own
+    Failure = 1,
+    /// No G27 found with `--require-wheel` or for a timed LED test
+    WheelNotFound = 2,
+    /// The telemetry port could not be bound, usually because another program uses it
+    BindFailed = 3,
+    /// Unknown profile or game, or a rejected `config` change or import
+    InvalidSettings = 4,
+    /// USB HID access or opening the G27 failed
+    DeviceOpenFailed = 5,
+    /// A `--packets` run ended before all the packets arrived
+    MissingPackets = 6,
+}
+
+impl ExitCode {
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+
+    /// End the process with this code
+    pub fn exit(self) -> ! {
+        std::process::exit(self.code())
+    }
+
+    /// Code for an error returned by a command
+    pub fn for_error(error: &(dyn Error + 'static)) -> Self {
+        match error.downcast_ref::<DR2G27Error>() {
+            Some(DR2G27Error::BindFailed { .. }) => ExitCode::BindFailed,
+            Some(DR2G27Error::HidInit(_) | DR2G27Error::HidOpen { .. }) => ExitCode::DeviceOpenFailed,
+            Some(DR2G27Error::ConfigError(_)) => ExitCode::InvalidSettings,
+            _ => ExitCode::Failure,
+        }
+    }
+}
+
+impl From<BoundOutcome> for ExitCode {
+    fn from(outcome: BoundOutcome) -> Self {
+        match outcome {
+            BoundOutcome::Completed { .. } => ExitCode::Success,
+            BoundOutcome::MissingPackets { .. } => ExitCode::MissingPackets,
+        }
+    }
+}
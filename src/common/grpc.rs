@@ -0,0 +1,235 @@
+// gRPC API for local applications
+//
+// A typed, versioned integration point beyond forwarding raw UDP. `proto/g27_bridge.proto`
+// defines the `g27bridge.v1.TelemetryService`:
+// - SubscribeTelemetry streams every parsed frame with the LED bar it lights
+// - GetStatus returns what `g27-led-bridge status` shows for the running bridge
+// - SetProfile switches to a named profile
+//
+// Served on 127.0.0.1:<port> while `[grpc] enabled` is set. tonic and the generated code
+// are only built with the `grpc` feature (which needs `protoc`); builds without it keep
+// the settings and report that the API is unavailable.
+//
+// The bridge's status lives on its own task, so the service asks for it over a channel
+// rather than sharing it with tonic's connection tasks.
+
+use std::cell::Cell;
+use std::net::{Ipv4Addr, SocketAddr};
+
+use serde::{Deserialize, Serialize};
+
+use g27_led_bridge_core::TelemetryBus;
+
+use crate::common::ipc::InstanceStatus;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct GrpcSettings {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl GrpcSettings {
+    pub const KNOWN_KEYS: &'static [&'static str] = &["enabled", "port"];
+
+    pub fn listen_addr(&self) -> SocketAddr {
+        SocketAddr::new(Ipv4Addr::LOCALHOST.into(), self.port)
+    }
+}
+
+impl Default for GrpcSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 50051,
+        }
+    }
+}
+
+/// Serve the API on `addr` until dropped, streaming frames from `bus` with the LED bar in
+/// `leds` and answering with `status` and `set_profile`
+pub async fn serve(
+    addr: SocketAddr,
+    bus: &TelemetryBus,
+    leds: &Cell<u8>,
+    status: &impl Fn() -> InstanceStatus,
+    set_profile: &impl Fn(&str) -> Result<(), String>,
+) -> Result<(), String> {
+    #[cfg(feature = "grpc")]
+    {
+        server::serve(addr, bus, leds, status, set_profile).await
+    }
+
+    #[cfg(not(feature = "grpc"))]
+    {
+        let _ = (addr, bus, leds, status, set_profile);
+        Err("this build has no gRPC API; build with the `grpc` feature to get it".to_string())
+    }
+}
+
+#[cfg(feature = "grpc")]
+mod server {
+    use std::cell::Cell;
+    use std::net::SocketAddr;
+    use std::pin::Pin;
+
+    use log::info;
+    use tokio::net::TcpListener;
+    use tokio::sync::{broadcast, mpsc, oneshot};
+    use tokio_stream::wrappers::{BroadcastStream, TcpListenerStream};
+    use tokio_stream::{Stream, StreamExt};
+    use tonic::{Request, Response, Status};
+
+    use g27_led_bridge_core::{TelemetryBus, TelemetryFrame};
+
+    use crate::common::ipc::InstanceStatus;
+
+    mod proto {
+        tonic::include_proto!("g27bridge.v1");
+    }
+
+    use proto::telemetry_service_server::{TelemetryService, TelemetryServiceServer};
+
+    /// Frames a subscriber may fall behind by before it skips ahead
+    const SUBSCRIBER_BACKLOG: usize = 64;
+
+    const STOPPING: &str = "the bridge is stopping";
+
+    /// Questions for the bridge's task, answered on `reply`
+    enum Query {
+        Status {
+            reply: oneshot::Sender<InstanceStatus>,
+        },
+        SetProfile {
+            name: String,
+            reply: oneshot::Sender<Result<(), String>>,
+        },
+    }
+
+    struct Service {
+        frames: broadcast::Sender<proto::TelemetryFrame>,
+        queries: mpsc::Sender<Query>,
+    }
+
+    impl Service {
+        async fn ask<T>(&self, query: impl FnOnce(oneshot::Sender<T>) -> Query) -> Result<T, Status> {
+            let (reply, answer) = oneshot::channel();
+            self.queries.send(query(reply)).await.map_err(|_| Status::unavailable(STOPPING))?;
+            answer.await.map_err(|_| Status::unavailable(STOPPING))
+        }
+    }
+
+    #[tonic::async_trait]
+    impl TelemetryService for Service {
+        type SubscribeTelemetryStream = Pin<Box<dyn Stream<Item = Result<proto::TelemetryFrame, Status>> + Send>>;
+
+        async fn subscribe_telemetry(
+            &self,
+            _request: Request<proto::SubscribeTelemetryRequest>,
+        ) -> Result<Response<Self::SubscribeTelemetryStream>, Status> {
+            // A subscriber that falls behind skips frames rather than slowing the bridge down
+            let frames = BroadcastStream::new(self.frames.subscribe())
+                .filter_map(|frame| frame.ok().map(Ok::<_, Status>));
+            Ok(Response::new(Box::pin(frames)))
+        }
+
+        async fn get_status(
+            &self,
+            _request: Request<proto::GetStatusRequest>,
+        ) -> Result<Response<proto::BridgeStatus>, Status> {
+            let status = self.ask(|reply| Query::Status { reply }).await?;
+            Ok(Response::new(status_message(status)))
+        }
+
+        async fn set_profile(
+            &self,
+            request: Request<proto::SetProfileRequest>,
+        ) -> Result<Response<proto::SetProfileResponse>, Status> {
+            let name = request.into_inner().name;
+            self.ask(|reply| Query::SetProfile { name, reply })
+                .await?
+                .map_err(Status::not_found)?;
+            Ok(Response::new(proto::SetProfileResponse {}))
+        }
+    }
+
+    fn frame_message(frame: &TelemetryFrame, leds: u8) -> proto::TelemetryFrame {
+        proto::TelemetryFrame {
+            game: format!("{:?}", frame.game_type),
+            rpm: frame.rpm,
+            max_rpm: frame.max_rpm,
+            idle_rpm: frame.idle_rpm,
+            race_active: frame.race_active,
+            speed: frame.speed,
+            gear: frame.gear.map(i32::from),
+            leds: leds.into(),
+        }
+    }
+
+    fn status_message(status: InstanceStatus) -> proto::BridgeStatus {
+        proto::BridgeStatus {
+            mode: status.mode,
+            game: format!("{:?}", status.game),
+            listen_address: status.listen_address.to_string(),
+            state: status.state,
+            wheel_connected: status.wheel_connected,
+            paused: status.paused,
+            packets_per_second: status.packets_per_second,
+            rejected_packets: status.rejected_packets,
+            last_error: status.last_error,
+            jitter_ms: status.jitter_ms,
+            packet_gaps: status.packet_gaps,
+            longest_gap_ms: status.longest_gap_ms,
+            malformed_packets: status.malformed_packets,
+        }
+    }
+
+    pub async fn serve(
+        addr: SocketAddr,
+        bus: &TelemetryBus,
+        leds: &Cell<u8>,
+        status: &impl Fn() -> InstanceStatus,
+        set_profile: &impl Fn(&str) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let listener = TcpListener::bind(addr).await.map_err(|e| e.to_string())?;
+        info!("gRPC API listening on {}", addr);
+
+        let (frames_tx, _) = broadcast::channel(SUBSCRIBER_BACKLOG);
+        let (queries_tx, mut queries) = mpsc::channel(8);
+        let service = Service {
+            frames: frames_tx.clone(),
+            queries: queries_tx,
+        };
+        let server = tonic::transport::Server::builder()
+            .add_service(TelemetryServiceServer::new(service))
+            .serve_with_incoming(TcpListenerStream::new(listener));
+
+        let answer = async {
+            while let Some(query) = queries.recv().await {
+                match query {
+                    Query::Status { reply } => {
+                        let _ = reply.send(status());
+                    }
+                    Query::SetProfile { name, reply } => {
+                        let _ = reply.send(set_profile(&name));
+                    }
+                }
+            }
+        };
+        let mut frames = bus.subscribe("gRPC");
+        let publish = async {
+            while let Some(frame) = frames.recv().await {
+                // Nothing to convert while nobody is subscribed
+                if frames_tx.receiver_count() > 0 {
+                    let _ = frames_tx.send(frame_message(&frame, leds.get()));
+                }
+            }
+        };
+
+        tokio::select! {
+            result = server => result.map_err(|e| e.to_string()),
+            () = answer => Ok(()),
+            () = publish => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,198 @@
+// Global hotkeys for G27 LED Bridge
+//
+// A hotkey such as "Ctrl+Alt+P" is stored as text in settings.toml and parsed into
+// modifier flags and a virtual-key code. `listen` registers it with Windows on its own
+// thread, which runs a message loop and calls back on every press until dropped.
+
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+const MOD_ALT: u32 = 0x1;
+const MOD_CONTROL: u32 = 0x2;
+const MOD_SHIFT: u32 = 0x4;
+const MOD_WIN: u32 = 0x8;
+
+/// Modifier names in display order, with their `RegisterHotKey` flags
+const MODIFIERS: [(&str, u32); 4] = [("Ctrl", MOD_CONTROL), ("Alt", MOD_ALT), ("Shift", MOD_SHIFT), ("Win", MOD_WIN)];
+
+/// Named keys besides letters, digits and F1-F24, with their virtual-key codes
+const NAMED_KEYS: [(&str, u32); 5] =
+    [("Pause", 0x13), ("ScrollLock", 0x91), ("Insert", 0x2D), ("Home", 0x24), ("End", 0x23)];
+
+/// A key with modifiers, e.g. "Ctrl+Alt+P"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Hotkey {
+    modifiers: u32,
+    key: u32,
+}
+
+impl Hotkey {
+    /// Virtual-key code of a key name, ignoring case
+    fn key_code(name: &str) -> Option<u32> {
+        let upper = name.to_ascii_uppercase();
+        if let [c @ (b'A'..=b'Z' | b'0'..=b'9')] = upper.as_bytes() {
+            return Some(u32::from(*c));
+        }
+        if let Some(number @ 1..=24) = upper.strip_prefix('F').and_then(|number| number.parse::<u32>().ok()) {
+            return Some(0x70 + number - 1);
+        }
+        NAMED_KEYS
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, code)| *code)
+    }
+
+    fn key_name(&self) -> String {
+        match self.key {
+            0x30..=0x39 | 0x41..=0x5A => char::from_u32(self.key).map(String::from).unwrap_or_default(),
+            0x70..=0x87 => format!("F{}", self.key - 0x70 + 1),
+            code => NAMED_KEYS
+                .iter()
+                .find(|(_, named)| *named == code)
+                .map(|(name, _)| name.to_string())
+                .unwrap_or_else(|| format!("0x{:02X}", code)),
+        }
+    }
+}
+
+impl FromStr for Hotkey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let key = parts.pop().filter(|key| !key.is_empty()).ok_or_else(|| format!("hotkey '{}' has no key", s))?;
+        let key = Self::key_code(key).ok_or_else(|| {
+            format!(
+                "unknown key '{}' in hotkey '{}' (use A-Z, 0-9, F1-F24, Pause, ScrollLock, Insert, Home or End)",
+                key, s
+            )
+        })?;
+
+        let mut modifiers = 0;
+        for part in parts {
+            let part = if part.eq_ignore_ascii_case("Control") { "Ctrl" } else { part };
+            let flag = MODIFIERS
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(part))
+                .map(|(_, flag)| *flag)
+                .ok_or_else(|| format!("unknown modifier '{}' in hotkey '{}' (use Ctrl, Alt, Shift or Win)", part, s))?;
+            modifiers |= flag;
+        }
+        Ok(Self { modifiers, key })
+    }
+}
+
+impl TryFrom<String> for Hotkey {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Hotkey> for String {
+    fn from(hotkey: Hotkey) -> Self {
+        hotkey.to_string()
+    }
+}
+
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (name, flag) in MODIFIERS {
+            if self.modifiers & flag != 0 {
+                write!(f, "{}+", name)?;
+            }
+        }
+        f.write_str(&self.key_name())
+    }
+}
+
+/// Registration of a hotkey; pressing it stops calling back once this is dropped
+#[cfg_attr(not(windows), allow(dead_code))]
+pub struct HotkeyListener {
+    #[cfg(windows)]
+    thread_id: u32,
+}
+
+/// Call `on_press` every time `hotkey` is pressed, in any application.
+/// Returns `None` if the hotkey cannot be registered, e.g. because another program uses it.
+pub fn listen(hotkey: Hotkey, on_press: impl Fn() + Send + 'static) -> Option<HotkeyListener> {
+    #[cfg(windows)]
+    {
+        win32::listen(hotkey, on_press)
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = on_press;
+        log::warn!("Global hotkeys are only available on Windows; {} is not registered", hotkey);
+        None
+    }
+}
+
+#[cfg(windows)]
+impl Drop for HotkeyListener {
+    fn drop(&mut self) {
+        use winapi::um::winuser::{PostThreadMessageW, WM_QUIT};
+
+        unsafe { PostThreadMessageW(self.thread_id, WM_QUIT, 0, 0) };
+    }
+}
+
+#[cfg(windows)]
+mod win32 {
+    use std::sync::mpsc;
+    use std::thread;
+
+    use winapi::um::processthreadsapi::GetCurrentThreadId;
+    use winapi::um::winuser::{GetMessageW, RegisterHotKey, UnregisterHotKey, MOD_NOREPEAT, MSG, WM_HOTKEY};
+
+    use super::{Hotkey, HotkeyListener};
+
+    const HOTKEY_ID: i32 = 1;
+
+    pub fn listen(hotkey: Hotkey, on_press: impl Fn() + Send + 'static) -> Option<HotkeyListener> {
+        let (registered_tx, registered_rx) = mpsc::channel();
+        thread::spawn(move || unsafe {
+            // The hotkey belongs to this thread, whose message queue receives the presses
+            let registered = RegisterHotKey(
+                std::ptr::null_mut(),
+                HOTKEY_ID,
+                hotkey.modifiers | MOD_NOREPEAT as u32,
+                hotkey.key,
+            ) != 0;
+            let thread_id = if registered {
+                Ok(GetCurrentThreadId())
+            } else {
+                Err(std::io::Error::last_os_error())
+            };
+            let _ = registered_tx.send(thread_id);
+            if !registered {
+                return;
+            }
+
+            let mut msg: MSG = std::mem::zeroed();
+            while GetMessageW(&mut msg, std::ptr::null_mut(), 0, 0) > 0 {
+                if msg.message == WM_HOTKEY {
+                    on_press();
+                }
+            }
+            UnregisterHotKey(std::ptr::null_mut(), HOTKEY_ID);
+        });
+
+        match registered_rx.recv() {
+            Ok(Ok(thread_id)) => {
+                log::info!("Registered hotkey {}", hotkey);
+                Some(HotkeyListener { thread_id })
+            }
+            Ok(Err(e)) => {
+                log::warn!("Cannot register hotkey {} - another program may be using it: {}", hotkey, e);
+                None
+            }
+            Err(_) => None,
+        }
+    }
+}
@@ -0,0 +1,244 @@
+// Local HTTP API for stream decks and automation tools
+//
+// Off by default. While `[http_api] enabled` is set, the bridge answers on
+// http://127.0.0.1:<port>:
+// - GET  /status          game, listen address, wheel, packet rate, jitter, gaps and last
+//                          error, as reported to `g27-led-bridge status`
+// - POST /pause           turn the LEDs off until resumed, like the tray's Pause
+// - POST /resume
+// - POST /profile/<name>  switch to a named profile (URL-encoded, e.g. Forza%20couch)
+// - GET  /                the phone dashboard: the LED bar, RPM and gear in large type,
+//                          refreshed from /status, for a phone mounted on the rig
+//
+// Responses are JSON apart from the dashboard page; failures carry {"error": "..."}.
+// The API only listens on localhost as anything that can reach it can control the
+// bridge. `lan_dashboard` opens it to the network for phones, but only the page and
+// /status answer other devices.
+
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::time::Duration;
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Longest request accepted; none of the endpoints take a body
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// Longest wait for a client to send its request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Page served at `/`; it polls /status, so it needs nothing but the API
+const PHONE_DASHBOARD: &str = include_str!("phone_dashboard.html");
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct HttpApiSettings {
+    pub enabled: bool,
+    pub port: u16,
+    /// Listen on all interfaces so phones can open the dashboard; commands still only
+    /// answer clients on this PC
+    pub lan_dashboard: bool,
+}
+
+impl HttpApiSettings {
+    pub const KNOWN_KEYS: &'static [&'static str] = &["enabled", "port", "lan_dashboard"];
+
+    pub fn listen_addr(&self) -> SocketAddr {
+        let ip = if self.lan_dashboard { Ipv4Addr::UNSPECIFIED } else { Ipv4Addr::LOCALHOST };
+        SocketAddr::new(ip.into(), self.port)
+    }
+}
+
+impl Default for HttpApiSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: 8766,
+            lan_dashboard: false,
+        }
+    }
+}
+
+/// What a client asked the bridge to do
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Status,
+    Pause,
+    Resume,
+    /// Switch to the named profile
+    Profile(String),
+    /// The phone dashboard page
+    Dashboard,
+}
+
+impl Command {
+    /// Whether devices other than this PC may send it
+    fn read_only(&self) -> bool {
+        matches!(self, Command::Status | Command::Dashboard)
+    }
+}
+
+/// Answer to a request, JSON unless it is the dashboard page
+#[derive(Debug, Clone)]
+pub struct Response {
+    status: u16,
+    body: String,
+    content_type: &'static str,
+    /// Method the endpoint takes, for 405 Method Not Allowed
+    allow: Option<&'static str>,
+}
+
+impl Response {
+    pub fn ok(body: &impl Serialize) -> Self {
+        match serde_json::to_string(body) {
+            Ok(body) => Self {
+                status: 200,
+                body,
+                content_type: "application/json",
+                allow: None,
+            },
+            Err(e) => Self::error(500, &e.to_string()),
+        }
+    }
+
+    pub fn error(status: u16, message: &str) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message }).to_string(),
+            content_type: "application/json",
+            allow: None,
+        }
+    }
+
+    pub fn dashboard() -> Self {
+        Self {
+            status: 200,
+            body: PHONE_DASHBOARD.to_string(),
+            content_type: "text/html; charset=utf-8",
+            allow: None,
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            403 => "Forbidden",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// Answer requests on `listener` with `handle`, one connection at a time, until dropped
+pub async fn serve(listener: &TcpListener, handle: &impl Fn(Command) -> Response) {
+    loop {
+        let (mut stream, peer) = match listener.accept().await {
+            Ok(accepted) => accepted,
+            Err(e) => {
+                debug!("HTTP connection failed: {}", e);
+                continue;
+            }
+        };
+        if let Err(e) = answer(&mut stream, peer, handle).await {
+            debug!("HTTP client {} went away: {}", peer, e);
+        }
+    }
+}
+
+/// Read one request, route it and write the response; the connection is closed after it
+async fn answer(stream: &mut TcpStream, peer: SocketAddr, handle: &impl Fn(Command) -> Response) -> io::Result<()> {
+    let request = tokio::time::timeout(REQUEST_TIMEOUT, read_request(stream))
+        .await
+        .map_err(|_| io::Error::from(io::ErrorKind::TimedOut))??;
+    let request_line = request.lines().next().unwrap_or_default();
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or_default(), parts.next().unwrap_or_default());
+    debug!("HTTP {} {}", method, target);
+
+    let response = match route(method, target) {
+        Ok(command) if !command.read_only() && !peer.ip().is_loopback() => {
+            Response::error(403, "only programs on the bridge's PC can control it")
+        }
+        Ok(command) => handle(command),
+        Err(response) => response,
+    };
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        response.status,
+        response.reason(),
+        response.content_type,
+        response.body.len(),
+    );
+    if let Some(method) = response.allow {
+        head.push_str(&format!("Allow: {}\r\n", method));
+    }
+    head.push_str("\r\n");
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(response.body.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+/// Request line and headers, up to the blank line
+async fn read_request(stream: &mut TcpStream) -> io::Result<String> {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        if request.len() > MAX_REQUEST_BYTES {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "request too long"));
+        }
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        request.extend_from_slice(&buffer[..read]);
+    }
+    Ok(String::from_utf8_lossy(&request).into_owned())
+}
+
+/// Command for a request, or the error response for one the API does not offer
+fn route(method: &str, target: &str) -> Result<Command, Response> {
+    let path = target.split('?').next().unwrap_or_default();
+    let (command, expected) = match path {
+        "/" => (Command::Dashboard, "GET"),
+        "/status" => (Command::Status, "GET"),
+        "/pause" => (Command::Pause, "POST"),
+        "/resume" => (Command::Resume, "POST"),
+        _ => match path.strip_prefix("/profile/") {
+            Some(name) => {
+                let name = percent_decode(name).ok_or_else(|| Response::error(400, "invalid profile name"))?;
+                (Command::Profile(name), "POST")
+            }
+            None => return Err(Response::error(404, "unknown endpoint; try /status")),
+        },
+    };
+    if method != expected {
+        return Err(Response {
+            allow: Some(expected),
+            ..Response::error(405, &format!("use {} {}", expected, path))
+        });
+    }
+    Ok(command)
+}
+
+/// Decode %XX escapes in a path segment, `None` if they do not form UTF-8
+fn percent_decode(segment: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(segment.len());
+    let mut rest = segment.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok().filter(|name| !name.is_empty())
+}
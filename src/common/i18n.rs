@@ -0,0 +1,588 @@
+// Translations for G27 LED Bridge
+//
+// The tray menu, dialogs and notifications are written in English and translated where
+// they are shown:
+// - `tr("Show Log")` returns the text in the active language, or the English text when
+//   there is no translation (names, addresses, error details from the system)
+// - Texts with a value use a `{}` placeholder, filled in by `tr_with`
+// - `language` in settings.toml picks the language; "auto" follows the Windows
+//   display language and falls back to English
+//
+// Log messages and console output stay in English so they can be read in bug reports.
+// Menu entries are created at startup, so a new language applies fully after a restart.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+/// Language setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum Language {
+    /// The Windows display language, if translated
+    #[default]
+    #[serde(rename = "auto")]
+    Auto,
+    #[serde(rename = "en")]
+    English,
+    #[serde(rename = "de")]
+    German,
+    #[serde(rename = "fr")]
+    French,
+    #[serde(rename = "es")]
+    Spanish,
+}
+
+impl Language {
+    pub const ALL: [Language; 5] =
+        [Language::Auto, Language::English, Language::German, Language::French, Language::Spanish];
+
+    /// Code used in settings.toml
+    pub fn code(self) -> &'static str {
+        match self {
+            Language::Auto => "auto",
+            Language::English => "en",
+            Language::German => "de",
+            Language::French => "fr",
+            Language::Spanish => "es",
+        }
+    }
+
+    /// Name of the language in that language, for choosing one
+    pub fn native_name(self) -> &'static str {
+        match self {
+            Language::Auto => tr("Windows language"),
+            Language::English => "English",
+            Language::German => "Deutsch",
+            Language::French => "Français",
+            Language::Spanish => "Español",
+        }
+    }
+
+    /// The language to show, with `Auto` resolved
+    fn resolve(self) -> Language {
+        match self {
+            Language::Auto => system_language(),
+            language => language,
+        }
+    }
+
+    /// Column of this language in `TRANSLATIONS`, or `None` for English
+    fn column(self) -> Option<usize> {
+        match self {
+            Language::German => Some(0),
+            Language::French => Some(1),
+            Language::Spanish => Some(2),
+            Language::Auto | Language::English => None,
+        }
+    }
+}
+
+/// Column of the language in use plus one, or 0 for English
+static ACTIVE: AtomicU8 = AtomicU8::new(0);
+
+/// Use `language` for every text translated from now on
+pub fn set_language(language: Language) {
+    let active = language.resolve().column().map_or(0, |column| column as u8 + 1);
+    ACTIVE.store(active, Ordering::Relaxed);
+}
+
+/// `text` in the active language, or `text` itself without a translation
+pub fn tr(text: &str) -> &str {
+    let Some(column) = (ACTIVE.load(Ordering::Relaxed) as usize).checked_sub(1) else {
+        return text;
+    };
+    TRANSLATIONS
+        .iter()
+        .find(|(english, _)| *english == text)
+        .map_or(text, |(_, translated)| translated[column])
+}
+
+/// `text` translated, with its `{}` placeholder replaced by `value`
+pub fn tr_with(text: &str, value: &str) -> String {
+    tr(text).replacen("{}", value, 1)
+}
+
+/// The Windows display language if it is translated, otherwise English
+fn system_language() -> Language {
+    #[cfg(windows)]
+    {
+        use winapi::um::winnls::GetUserDefaultUILanguage;
+
+        // The low 10 bits of a language ID are the primary language
+        match unsafe { GetUserDefaultUILanguage() } & 0x3FF {
+            0x07 => Language::German,
+            0x0C => Language::French,
+            0x0A => Language::Spanish,
+            _ => Language::English,
+        }
+    }
+
+    #[cfg(not(windows))]
+    {
+        Language::English
+    }
+}
+
+/// English text with its German, French and Spanish translations
+const TRANSLATIONS: &[(&str, [&str; 3])] = &[
+    // Tray menu
+    ("Select Game", ["Spiel auswählen", "Choisir le jeu", "Seleccionar juego"]),
+    ("Auto-detect", ["Automatisch erkennen", "Détection automatique", "Detección automática"]),
+    ("Profiles", ["Profile", "Profils", "Perfiles"]),
+    ("Edit Profiles...", ["Profile bearbeiten...", "Modifier les profils...", "Editar perfiles..."]),
+    ("Outputs", ["Ausgaben", "Sorties", "Salidas"]),
+    ("LED Style", ["LED-Stil", "Style des LED", "Estilo de LED"]),
+    ("Progressive", ["Fortlaufend", "Progressif", "Progresivo"]),
+    ("Center-out", ["Von der Mitte aus", "Depuis le centre", "Desde el centro"]),
+    ("Dot", ["Punkt", "Point", "Punto"]),
+    ("Shift Flash", ["Schaltblitz", "Flash de passage de rapport", "Destello de cambio"]),
+    ("Icon Theme", ["Symboldesign", "Thème de l'icône", "Tema del icono"]),
+    ("Automatic", ["Automatisch", "Automatique", "Automático"]),
+    ("Dark Taskbar", ["Dunkle Taskleiste", "Barre des tâches sombre", "Barra de tareas oscura"]),
+    ("Light Taskbar", ["Helle Taskleiste", "Barre des tâches claire", "Barra de tareas clara"]),
+    ("High Contrast", ["Hoher Kontrast", "Contraste élevé", "Contraste alto"]),
+    ("Start Recording", ["Aufnahme starten", "Démarrer l'enregistrement", "Iniciar grabación"]),
+    ("Stop Recording", ["Aufnahme beenden", "Arrêter l'enregistrement", "Detener grabación"]),
+    (
+        "Capture Packets (30 s)",
+        ["Pakete mitschneiden (30 s)", "Capturer les paquets (30 s)", "Capturar paquetes (30 s)"],
+    ),
+    ("Capturing Packets...", ["Pakete werden mitgeschnitten...", "Capture des paquets...", "Capturando paquetes..."]),
+    ("Pause LED Bridge", ["LED Bridge pausieren", "Mettre LED Bridge en pause", "Pausar LED Bridge"]),
+    ("Dashboard...", ["Dashboard...", "Tableau de bord...", "Panel..."]),
+    ("Settings...", ["Einstellungen...", "Paramètres...", "Configuración..."]),
+    ("Change Port...", ["Port ändern...", "Changer de port...", "Cambiar puerto..."]),
+    (
+        "Edit Settings File...",
+        [
+            "Einstellungsdatei bearbeiten...",
+            "Modifier le fichier de paramètres...",
+            "Editar archivo de configuración...",
+        ],
+    ),
+    ("Open Data Folder", ["Datenordner öffnen", "Ouvrir le dossier des données", "Abrir carpeta de datos"]),
+    ("Reload Settings", ["Einstellungen neu laden", "Recharger les paramètres", "Recargar configuración"]),
+    ("Export Settings...", ["Einstellungen exportieren...", "Exporter les paramètres...", "Exportar configuración..."]),
+    ("Import Settings...", ["Einstellungen importieren...", "Importer les paramètres...", "Importar configuración..."]),
+    ("Start with Windows", ["Mit Windows starten", "Lancer avec Windows", "Iniciar con Windows"]),
+    ("Show Log", ["Protokoll anzeigen", "Afficher le journal", "Mostrar registro"]),
+    ("Show Console", ["Konsole anzeigen", "Afficher la console", "Mostrar consola"]),
+    (
+        "Generate Diagnostic Report",
+        ["Diagnosebericht erstellen", "Générer un rapport de diagnostic", "Generar informe de diagnóstico"],
+    ),
+    (
+        "Check for Updates...",
+        ["Nach Updates suchen...", "Rechercher des mises à jour...", "Buscar actualizaciones..."],
+    ),
+    ("About G27 LED Bridge", ["Über G27 LED Bridge", "À propos de G27 LED Bridge", "Acerca de G27 LED Bridge"]),
+    ("Exit G27 LED Bridge", ["G27 LED Bridge beenden", "Quitter G27 LED Bridge", "Salir de G27 LED Bridge"]),
+    (
+        "(update available)",
+        ["(Update verfügbar)", "(mise à jour disponible)", "(actualización disponible)"],
+    ),
+    // Tray status lines and tooltip
+    ("Active", ["Aktiv", "Actif", "Activo"]),
+    ("(auto-detected)", ["(automatisch erkannt)", "(détecté automatiquement)", "(detectado automáticamente)"]),
+    ("Listening", ["Empfang auf", "Écoute", "Escuchando"]),
+    ("Status", ["Status", "État", "Estado"]),
+    ("Telemetry", ["Telemetrie", "Télémétrie", "Telemetría"]),
+    ("G27 wheel LEDs", ["G27-Lenkrad-LEDs", "LED du volant G27", "LED del volante G27"]),
+    ("Checking...", ["Wird geprüft...", "Vérification...", "Comprobando..."]),
+    ("Off", ["Aus", "Désactivé", "Desactivado"]),
+    ("Not configured", ["Nicht eingerichtet", "Non configuré", "Sin configurar"]),
+    ("Handled by the service", ["Vom Dienst gesteuert", "Géré par le service", "Gestionado por el servicio"]),
+    (
+        "Running as Windows service",
+        ["Läuft als Windows-Dienst", "Exécuté comme service Windows", "Ejecutándose como servicio de Windows"],
+    ),
+    ("Starting...", ["Wird gestartet...", "Démarrage...", "Iniciando..."]),
+    ("Stopped", ["Angehalten", "Arrêté", "Detenido"]),
+    ("Error", ["Fehler", "Erreur", "Error"]),
+    ("retrying in", ["neuer Versuch in", "nouvel essai dans", "reintento en"]),
+    ("Searching...", ["Suche...", "Recherche...", "Buscando..."]),
+    ("Connection failed", ["Verbindung fehlgeschlagen", "Échec de la connexion", "Error de conexión"]),
+    ("Not found", ["Nicht gefunden", "Introuvable", "No encontrado"]),
+    ("Connected", ["Verbunden", "Connecté", "Conectado"]),
+    ("Paused", ["Pausiert", "En pause", "En pausa"]),
+    ("Asleep", ["Ruhezustand", "En veille", "En suspensión"]),
+    ("Paused - port released", ["Pausiert - Port freigegeben", "En pause - port libéré", "En pausa - puerto liberado"]),
+    ("Searching for wheel", ["Suche Lenkrad", "Recherche du volant", "Buscando el volante"]),
+    (
+        "Wheel connected, starting listener",
+        [
+            "Lenkrad verbunden, Empfang wird gestartet",
+            "Volant connecté, démarrage de l'écoute",
+            "Volante conectado, iniciando escucha",
+        ],
+    ),
+    ("Waiting for telemetry", ["Warte auf Telemetrie", "En attente de télémétrie", "Esperando telemetría"]),
+    ("Receiving telemetry", ["Telemetrie wird empfangen", "Réception de la télémétrie", "Recibiendo telemetría"]),
+    ("Telemetry stopped", ["Telemetrie unterbrochen", "Télémétrie interrompue", "Telemetría detenida"]),
+    (
+        "G27 LED Bridge - Racing Game Telemetry",
+        [
+            "G27 LED Bridge - Telemetrie aus Rennspielen",
+            "G27 LED Bridge - Télémétrie de jeux de course",
+            "G27 LED Bridge - Telemetría de juegos de carreras",
+        ],
+    ),
+    // About and message boxes
+    (
+        "Multi-game telemetry bridge for Logitech G27 Racing Wheel",
+        [
+            "Telemetrie-Brücke für mehrere Spiele zum Logitech G27 Lenkrad",
+            "Pont de télémétrie multi-jeux pour le volant Logitech G27",
+            "Puente de telemetría multijuego para el volante Logitech G27",
+        ],
+    ),
+    ("Supported Games:", ["Unterstützte Spiele:", "Jeux pris en charge :", "Juegos compatibles:"]),
+    (
+        "Based on DR2G27 by Aely0",
+        ["Basiert auf DR2G27 von Aely0", "Basé sur DR2G27 par Aely0", "Basado en DR2G27 de Aely0"],
+    ),
+    (
+        "Extended by Rajitha Perera",
+        ["Erweitert von Rajitha Perera", "Étendu par Rajitha Perera", "Ampliado por Rajitha Perera"],
+    ),
+    ("MIT License", ["MIT-Lizenz", "Licence MIT", "Licencia MIT"]),
+    (
+        "Failed to export settings:",
+        [
+            "Einstellungen konnten nicht exportiert werden:",
+            "Impossible d'exporter les paramètres :",
+            "No se pudo exportar la configuración:",
+        ],
+    ),
+    (
+        "Failed to import settings:",
+        [
+            "Einstellungen konnten nicht importiert werden:",
+            "Impossible d'importer les paramètres :",
+            "No se pudo importar la configuración:",
+        ],
+    ),
+    (
+        "Failed to start recording:",
+        [
+            "Aufnahme konnte nicht gestartet werden:",
+            "Impossible de démarrer l'enregistrement :",
+            "No se pudo iniciar la grabación:",
+        ],
+    ),
+    (
+        "Failed to start capture:",
+        [
+            "Mitschnitt konnte nicht gestartet werden:",
+            "Impossible de démarrer la capture :",
+            "No se pudo iniciar la captura:",
+        ],
+    ),
+    (
+        "Stop the bridge and turn the wheel LEDs off?",
+        [
+            "Bridge beenden und die Lenkrad-LEDs ausschalten?",
+            "Arrêter le pont et éteindre les LED du volant ?",
+            "¿Detener el puente y apagar los LED del volante?",
+        ],
+    ),
+    (
+        "Diagnostic report saved. Attach it to your bug report:",
+        [
+            "Diagnosebericht gespeichert. Hängen Sie ihn an Ihren Fehlerbericht an:",
+            "Rapport de diagnostic enregistré. Joignez-le à votre rapport de bug :",
+            "Informe de diagnóstico guardado. Adjúntelo a su informe de error:",
+        ],
+    ),
+    (
+        "Show it in Explorer?",
+        ["Im Explorer anzeigen?", "L'afficher dans l'Explorateur ?", "¿Mostrarlo en el Explorador?"],
+    ),
+    (
+        "Failed to write the diagnostic report:",
+        [
+            "Der Diagnosebericht konnte nicht geschrieben werden:",
+            "Impossible d'écrire le rapport de diagnostic :",
+            "No se pudo escribir el informe de diagnóstico:",
+        ],
+    ),
+    (
+        "Failed to open the data folder:",
+        [
+            "Der Datenordner konnte nicht geöffnet werden:",
+            "Impossible d'ouvrir le dossier des données :",
+            "No se pudo abrir la carpeta de datos:",
+        ],
+    ),
+    (
+        "Failed to open the dashboard:",
+        [
+            "Das Dashboard konnte nicht geöffnet werden:",
+            "Impossible d'ouvrir le tableau de bord :",
+            "No se pudo abrir el panel:",
+        ],
+    ),
+    (
+        "Could not check for updates:",
+        [
+            "Die Suche nach Updates ist fehlgeschlagen:",
+            "Impossible de rechercher des mises à jour :",
+            "No se pudo buscar actualizaciones:",
+        ],
+    ),
+    (
+        "Problems were found in {}:",
+        [
+            "In {} wurden Probleme gefunden:",
+            "Des problèmes ont été trouvés dans {} :",
+            "Se encontraron problemas en {}:",
+        ],
+    ),
+    (
+        "Invalid values were replaced with defaults.",
+        [
+            "Ungültige Werte wurden durch Standardwerte ersetzt.",
+            "Les valeurs non valides ont été remplacées par les valeurs par défaut.",
+            "Los valores no válidos se reemplazaron por los predeterminados.",
+        ],
+    ),
+    // Update check
+    (
+        "Version {} is available.",
+        ["Version {} ist verfügbar.", "La version {} est disponible.", "La versión {} está disponible."],
+    ),
+    (
+        "You are running version {}.",
+        ["Installiert ist Version {}.", "Vous utilisez la version {}.", "Estás usando la versión {}."],
+    ),
+    (
+        "You are running the latest version ({}).",
+        [
+            "Die neueste Version ist installiert ({}).",
+            "Vous utilisez la dernière version ({}).",
+            "Estás usando la última versión ({}).",
+        ],
+    ),
+    (
+        "Open the release page?",
+        ["Release-Seite öffnen?", "Ouvrir la page de la version ?", "¿Abrir la página de la versión?"],
+    ),
+    // Settings dialog
+    (
+        "G27 LED Bridge Settings",
+        ["G27 LED Bridge Einstellungen", "Paramètres de G27 LED Bridge", "Configuración de G27 LED Bridge"],
+    ),
+    ("Profile:", ["Profil:", "Profil :", "Perfil:"]),
+    ("Delete", ["Löschen", "Supprimer", "Eliminar"]),
+    ("Game:", ["Spiel:", "Jeu :", "Juego:"]),
+    ("UDP port:", ["UDP-Port:", "Port UDP :", "Puerto UDP:"]),
+    ("Bind address:", ["Lokale Adresse:", "Adresse d'écoute :", "Dirección de escucha:"]),
+    ("Cancel", ["Abbrechen", "Annuler", "Cancelar"]),
+    ("Invalid setting", ["Ungültige Einstellung", "Paramètre non valide", "Valor no válido"]),
+    ("Select a game", ["Bitte ein Spiel auswählen", "Choisissez un jeu", "Seleccione un juego"]),
+    (
+        "The last profile cannot be deleted",
+        [
+            "Das letzte Profil kann nicht gelöscht werden",
+            "Le dernier profil ne peut pas être supprimé",
+            "El último perfil no se puede eliminar",
+        ],
+    ),
+    (
+        "Port must be a number between 1 and 65535",
+        [
+            "Der Port muss eine Zahl zwischen 1 und 65535 sein",
+            "Le port doit être un nombre entre 1 et 65535",
+            "El puerto debe ser un número entre 1 y 65535",
+        ],
+    ),
+    (
+        "'{}' is not a valid IPv4 or IPv6 address (e.g. 127.0.0.1, 0.0.0.0 or ::)",
+        [
+            "'{}' ist keine gültige IPv4- oder IPv6-Adresse (z. B. 127.0.0.1, 0.0.0.0 oder ::)",
+            "« {} » n'est pas une adresse IPv4 ou IPv6 valide (par ex. 127.0.0.1, 0.0.0.0 ou ::)",
+            "'{}' no es una dirección IPv4 o IPv6 válida (p. ej. 127.0.0.1, 0.0.0.0 o ::)",
+        ],
+    ),
+    // Dashboard
+    (
+        "G27 LED Bridge Dashboard",
+        ["G27 LED Bridge Dashboard", "Tableau de bord de G27 LED Bridge", "Panel de G27 LED Bridge"],
+    ),
+    ("Settings", ["Einstellungen", "Paramètres", "Configuración"]),
+    (
+        "The bridge is not running.",
+        ["Die Bridge läuft nicht.", "Le pont n'est pas en cours d'exécution.", "El puente no se está ejecutando."],
+    ),
+    ("State:", ["Zustand:", "État :", "Estado:"]),
+    ("Listening on:", ["Empfang auf:", "Écoute sur :", "Escuchando en:"]),
+    ("Packet rate:", ["Paketrate:", "Débit de paquets :", "Paquetes por segundo:"]),
+    ("Rejected packets:", ["Abgewiesene Pakete:", "Paquets rejetés :", "Paquetes rechazados:"]),
+    ("Last error:", ["Letzter Fehler:", "Dernière erreur :", "Último error:"]),
+    ("Gear", ["Gang", "Rapport", "Marcha"]),
+    ("LED profile:", ["LED-Profil:", "Profil des LED :", "Perfil de LED:"]),
+    ("LED pattern:", ["LED-Muster:", "Motif des LED :", "Patrón de LED:"]),
+    (
+        "Mirror the LEDs in the tray icon",
+        [
+            "LEDs im Infobereich-Symbol anzeigen",
+            "Afficher les LED dans l'icône de la zone de notification",
+            "Mostrar los LED en el icono de la bandeja",
+        ],
+    ),
+    ("Notifications", ["Benachrichtigungen", "Notifications", "Notificaciones"]),
+    (
+        "Check for updates at startup",
+        [
+            "Beim Start nach Updates suchen",
+            "Rechercher des mises à jour au démarrage",
+            "Buscar actualizaciones al iniciar",
+        ],
+    ),
+    ("Language:", ["Sprache:", "Langue :", "Idioma:"]),
+    ("Windows language", ["Windows-Sprache", "Langue de Windows", "Idioma de Windows"]),
+    ("Save", ["Speichern", "Enregistrer", "Guardar"]),
+    ("Revert", ["Zurücksetzen", "Rétablir", "Revertir"]),
+    (
+        "Saved. The running bridge applies the changes automatically.",
+        [
+            "Gespeichert. Die laufende Bridge übernimmt die Änderungen automatisch.",
+            "Enregistré. Le pont en cours d'exécution applique les modifications automatiquement.",
+            "Guardado. El puente en ejecución aplica los cambios automáticamente.",
+        ],
+    ),
+    // Log viewer
+    ("G27 LED Bridge Log", ["G27 LED Bridge Protokoll", "Journal de G27 LED Bridge", "Registro de G27 LED Bridge"]),
+    ("Copy to Clipboard", ["In Zwischenablage kopieren", "Copier dans le presse-papiers", "Copiar al portapapeles"]),
+    ("Refresh", ["Aktualisieren", "Actualiser", "Actualizar"]),
+    ("Close", ["Schließen", "Fermer", "Cerrar"]),
+    (
+        "Nothing has been logged yet.",
+        ["Bisher wurde nichts protokolliert.", "Rien n'a encore été journalisé.", "Todavía no hay nada registrado."],
+    ),
+    (
+        "The log could not be copied to the clipboard.",
+        [
+            "Das Protokoll konnte nicht in die Zwischenablage kopiert werden.",
+            "Le journal n'a pas pu être copié dans le presse-papiers.",
+            "No se pudo copiar el registro al portapapeles.",
+        ],
+    ),
+    // Notifications
+    ("G27 connected", ["G27 verbunden", "G27 connecté", "G27 conectado"]),
+    (
+        "The wheel LEDs now follow your RPM",
+        [
+            "Die Lenkrad-LEDs folgen jetzt der Drehzahl",
+            "Les LED du volant suivent maintenant votre régime moteur",
+            "Los LED del volante ya siguen tus RPM",
+        ],
+    ),
+    ("G27 disconnected", ["G27 getrennt", "G27 déconnecté", "G27 desconectado"]),
+    (
+        "G27 found but not available",
+        ["G27 gefunden, aber nicht verfügbar", "G27 détecté mais indisponible", "G27 encontrado pero no disponible"],
+    ),
+    (
+        "access denied, another app has the wheel",
+        [
+            "Zugriff verweigert, eine andere App nutzt das Lenkrad",
+            "Accès refusé, une autre application utilise le volant",
+            "Acceso denegado, otra aplicación usa el volante",
+        ],
+    ),
+    (
+        "the wheel is in use by another app",
+        [
+            "das Lenkrad wird von einer anderen App verwendet",
+            "le volant est utilisé par une autre application",
+            "otra aplicación está usando el volante",
+        ],
+    ),
+    ("the wheel disconnected", ["das Lenkrad wurde getrennt", "le volant s'est déconnecté", "el volante se desconectó"]),
+    ("the wheel did not respond", ["das Lenkrad antwortet nicht", "le volant ne répond pas", "el volante no respondió"]),
+    (
+        "Quit Logitech G HUB or Logitech Gaming Software from its tray icon",
+        [
+            "Logitech G HUB oder Logitech Gaming Software über das Tray-Symbol beenden",
+            "Quittez Logitech G HUB ou Logitech Gaming Software depuis son icône de notification",
+            "Cierra Logitech G HUB o Logitech Gaming Software desde su icono de la bandeja",
+        ],
+    ),
+    (
+        "Close other apps that drive the wheel LEDs, such as SimHub",
+        [
+            "Andere Apps schließen, die die Lenkrad-LEDs steuern, etwa SimHub",
+            "Fermez les autres applications qui pilotent les LED du volant, comme SimHub",
+            "Cierra otras aplicaciones que controlan los LED del volante, como SimHub",
+        ],
+    ),
+    (
+        "Unplug the wheel and plug it back in",
+        [
+            "Lenkrad abziehen und wieder anschließen",
+            "Débranchez le volant puis rebranchez-le",
+            "Desconecta el volante y vuelve a conectarlo",
+        ],
+    ),
+    (
+        "Make sure the bridge is not running twice, e.g. as the service and in the tray",
+        [
+            "Sicherstellen, dass die Bridge nicht doppelt läuft, z. B. als Dienst und im Tray",
+            "Vérifiez que le pont ne tourne pas deux fois, p. ex. en service et dans la zone de notification",
+            "Comprueba que el puente no se ejecute dos veces, p. ej. como servicio y en la bandeja",
+        ],
+    ),
+    (
+        "Check the USB cable, or try another USB port",
+        [
+            "USB-Kabel prüfen oder einen anderen USB-Anschluss versuchen",
+            "Vérifiez le câble USB ou essayez un autre port USB",
+            "Revisa el cable USB o prueba otro puerto USB",
+        ],
+    ),
+    (
+        "Connect the wheel directly instead of through an unpowered hub",
+        [
+            "Lenkrad direkt statt über einen Hub ohne Netzteil anschließen",
+            "Branchez le volant directement plutôt que sur un hub non alimenté",
+            "Conecta el volante directamente en lugar de a un hub sin alimentación",
+        ],
+    ),
+    (
+        "Restart Windows if the wheel stays unavailable",
+        [
+            "Windows neu starten, wenn das Lenkrad nicht verfügbar bleibt",
+            "Redémarrez Windows si le volant reste indisponible",
+            "Reinicia Windows si el volante sigue sin estar disponible",
+        ],
+    ),
+    (
+        "Reconnect the wheel to continue",
+        [
+            "Lenkrad wieder anschließen, um fortzufahren",
+            "Reconnectez le volant pour continuer",
+            "Vuelve a conectar el volante para continuar",
+        ],
+    ),
+    (
+        "Telemetry from the game is arriving",
+        ["Telemetrie vom Spiel kommt an", "La télémétrie du jeu arrive", "Está llegando telemetría del juego"],
+    ),
+    (
+        "G27 LED Bridge problem",
+        ["Problem mit G27 LED Bridge", "Problème de G27 LED Bridge", "Problema en G27 LED Bridge"],
+    ),
+    (
+        "G27 LED Bridge crashed",
+        ["G27 LED Bridge ist abgestürzt", "G27 LED Bridge a planté", "G27 LED Bridge se cerró inesperadamente"],
+    ),
+    (
+        "Please attach the crash report to your bug report: {}",
+        [
+            "Bitte den Absturzbericht an die Fehlermeldung anhängen: {}",
+            "Joignez le rapport de plantage à votre signalement : {}",
+            "Adjunta el informe de error a tu reporte: {}",
+        ],
+    ),
+];
@@ -0,0 +1,56 @@
+// Single-instance detection for the tray app
+//
+// Two tray apps would fight over the wheel and the UDP port, so the first one holds a
+// named mutex for as long as it runs. A second launch finds the mutex taken and hands
+// its arguments to the first one over the pipe in `ipc` instead of starting.
+
+#[cfg_attr(not(windows), allow(dead_code))]
+const MUTEX_NAME: &str = r"Local\G27LedBridge";
+
+/// Held by the running tray app; released when dropped or when the process ends
+pub struct InstanceLock {
+    #[cfg(windows)]
+    handle: winapi::um::winnt::HANDLE,
+}
+
+/// Take the instance lock, or `None` if another tray app of this user holds it
+pub fn acquire() -> Option<InstanceLock> {
+    #[cfg(windows)]
+    {
+        use std::ffi::OsStr;
+        use std::os::windows::ffi::OsStrExt;
+        use std::ptr::null_mut;
+
+        use winapi::shared::winerror::ERROR_ALREADY_EXISTS;
+        use winapi::um::errhandlingapi::GetLastError;
+        use winapi::um::handleapi::CloseHandle;
+        use winapi::um::synchapi::CreateMutexW;
+
+        let name: Vec<u16> = OsStr::new(MUTEX_NAME).encode_wide().chain(std::iter::once(0)).collect();
+        let handle = unsafe { CreateMutexW(null_mut(), 0, name.as_ptr()) };
+        if handle.is_null() {
+            // Without the mutex we cannot tell; running twice beats not running
+            log::warn!("Cannot check for another running instance: {}", std::io::Error::last_os_error());
+            return Some(InstanceLock { handle });
+        }
+        if unsafe { GetLastError() } == ERROR_ALREADY_EXISTS {
+            unsafe { CloseHandle(handle) };
+            return None;
+        }
+        Some(InstanceLock { handle })
+    }
+
+    #[cfg(not(windows))]
+    {
+        Some(InstanceLock {})
+    }
+}
+
+#[cfg(windows)]
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        if !self.handle.is_null() {
+            unsafe { winapi::um::handleapi::CloseHandle(self.handle) };
+        }
+    }
+}
@@ -0,0 +1,212 @@
+// Connection settings for external integrations
+//
+// The `[integrations]` settings area groups everything needed to reach services
+// outside the wheel, each with its own enable flag:
+// - MQTT broker (host, credentials, topic), published to by `mqtt`
+// - Philips Hue bridge (host and application key)
+// - WLED controller (host and realtime UDP port)
+// - OpenRGB SDK server (address)
+// - OSC receiver, e.g. a lighting console or TouchOSC (host, port and address prefix),
+//   sent to by `osc`
+//
+// Settings are loaded once with the rest of the file; each output reads its own section
+// from the current settings when the bridge starts it, and restarts when it changes.
+
+use serde::{Deserialize, Serialize};
+use toml::{Table, Value};
+
+/// Setting keys holding secrets, masked when settings are listed or put in a diagnostic report
+pub const SECRET_KEYS: &[&str] = &["password", "app_key"];
+
+/// Replace non-empty values of `SECRET_KEYS`, in any section or profile, so they do not leave the PC
+pub fn mask_secrets(table: &mut Table) {
+    for (key, value) in table.iter_mut() {
+        match value {
+            Value::Table(section) => mask_secrets(section),
+            Value::String(secret) if SECRET_KEYS.contains(&key.as_str()) && !secret.is_empty() => {
+                *secret = "********".to_string();
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct MqttSettings {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    /// Topic prefix that telemetry and status are published under
+    pub topic: String,
+}
+
+impl Default for MqttSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: String::new(),
+            port: 1883,
+            username: String::new(),
+            password: String::new(),
+            topic: "g27-led-bridge".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct HueSettings {
+    pub enabled: bool,
+    /// Bridge IP address or host name
+    pub bridge: String,
+    /// Application key created by pressing the bridge link button
+    pub app_key: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct WledSettings {
+    pub enabled: bool,
+    pub host: String,
+    /// Realtime UDP port configured in WLED's sync settings
+    pub port: u16,
+}
+
+impl Default for WledSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: String::new(),
+            port: 21324,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct OpenRgbSettings {
+    pub enabled: bool,
+    /// SDK server address as host:port
+    pub address: String,
+}
+
+impl Default for OpenRgbSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            address: "127.0.0.1:6742".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct OscSettings {
+    pub enabled: bool,
+    pub host: String,
+    /// UDP port the receiving application listens for OSC on
+    pub port: u16,
+    /// Prepended to every OSC address, e.g. "/g27" sends "/g27/rpm"
+    pub prefix: String,
+}
+
+impl Default for OscSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            host: String::new(),
+            port: 9000,
+            prefix: "/g27".to_string(),
+        }
+    }
+}
+
+/// All integration sections, as stored under `[integrations]`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct IntegrationSettings {
+    pub mqtt: MqttSettings,
+    pub hue: HueSettings,
+    pub wled: WledSettings,
+    pub openrgb: OpenRgbSettings,
+    pub osc: OscSettings,
+}
+
+impl IntegrationSettings {
+    /// Section names and their keys, for unknown-key detection
+    pub const KNOWN_KEYS: &'static [(&'static str, &'static [&'static str])] = &[
+        ("mqtt", &["enabled", "host", "port", "username", "password", "topic"]),
+        ("hue", &["enabled", "bridge", "app_key"]),
+        ("wled", &["enabled", "host", "port"]),
+        ("openrgb", &["enabled", "address"]),
+        ("osc", &["enabled", "host", "port", "prefix"]),
+    ];
+
+    /// Integration names, in menu order
+    pub const NAMES: [&'static str; 5] = ["mqtt", "hue", "wled", "openrgb", "osc"];
+
+    /// Mutable access to an integration's enable flag by name
+    pub fn enabled_mut(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "mqtt" => Some(&mut self.mqtt.enabled),
+            "hue" => Some(&mut self.hue.enabled),
+            "wled" => Some(&mut self.wled.enabled),
+            "openrgb" => Some(&mut self.openrgb.enabled),
+            "osc" => Some(&mut self.osc.enabled),
+            _ => None,
+        }
+    }
+
+    /// Whether an integration is switched on
+    pub fn is_enabled(&self, name: &str) -> bool {
+        match name {
+            "mqtt" => self.mqtt.enabled,
+            "hue" => self.hue.enabled,
+            "wled" => self.wled.enabled,
+            "openrgb" => self.openrgb.enabled,
+            "osc" => self.osc.enabled,
+            _ => false,
+        }
+    }
+
+    /// First required setting that is still empty for an integration, if any
+    pub fn missing_setting(&self, name: &str) -> Option<&'static str> {
+        let blank = |value: &str| value.trim().is_empty();
+        match name {
+            "mqtt" => blank(&self.mqtt.host).then_some("host"),
+            "hue" if blank(&self.hue.bridge) => Some("bridge"),
+            "hue" => blank(&self.hue.app_key).then_some("app_key"),
+            "wled" => blank(&self.wled.host).then_some("host"),
+            "openrgb" => blank(&self.openrgb.address).then_some("address"),
+            "osc" => blank(&self.osc.host).then_some("host"),
+            _ => None,
+        }
+    }
+
+    /// Names of the integrations that are switched on
+    pub fn enabled_names(&self) -> Vec<&'static str> {
+        Self::NAMES
+            .into_iter()
+            .filter(|name| self.is_enabled(name))
+            .collect()
+    }
+
+    /// Disable integrations that are switched on without the settings they need,
+    /// returning a message for each one
+    pub fn disable_incomplete(&mut self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for name in Self::NAMES {
+            let Some(key) = self.missing_setting(name) else {
+                continue;
+            };
+            if let Some(enabled) = self.enabled_mut(name).filter(|enabled| **enabled) {
+                *enabled = false;
+                problems.push(format!("[integrations.{}] requires '{}' (integration disabled)", name, key));
+            }
+        }
+        problems
+    }
+}
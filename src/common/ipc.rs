@@ -0,0 +1,257 @@
+// Named pipe for querying and controlling a running bridge
+//
+// The tray app and the Windows service listen on `\\.\pipe\g27-led-bridge`. Every
+// connection is sent one line of JSON with the current `InstanceStatus`, which is what
+// `g27-led-bridge status` shows, e.g. to check a headless sim PC over remote shell.
+// Clients only need read access, which is all a normal user gets on the service's pipe.
+//
+// A client with write access may then send one `Request` line, answered by a `Reply`
+// line; a second launch of the tray app uses this to hand its arguments over.
+
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+use crate::common::telemetry::GameType;
+
+#[cfg_attr(not(windows), allow(dead_code))]
+pub const PIPE_NAME: &str = r"\\.\pipe\g27-led-bridge";
+
+/// Longest wait for a running instance to answer
+#[cfg_attr(not(windows), allow(dead_code))]
+const QUERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Longest wait for a client's request after sending it the status
+#[cfg_attr(not(windows), allow(dead_code))]
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// What a running bridge reports about itself
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceStatus {
+    /// "tray" or "service"
+    pub mode: String,
+    pub game: GameType,
+    pub listen_address: SocketAddr,
+    /// Lifecycle state, e.g. "Receiving telemetry"
+    pub state: String,
+    pub wheel_connected: bool,
+    /// Paused from the tray, hotkey or HTTP API
+    #[serde(default)]
+    pub paused: bool,
+    pub packets_per_second: f32,
+    /// Average deviation of the time between packets from its mean
+    #[serde(default)]
+    pub jitter_ms: f32,
+    /// Times packets stopped for several intervals without the game pausing
+    #[serde(default)]
+    pub packet_gaps: u64,
+    #[serde(default)]
+    pub longest_gap_ms: f32,
+    /// Packets that could not be parsed, e.g. too short for the game
+    #[serde(default)]
+    pub malformed_packets: u64,
+    /// Packets dropped because their sender is not in `allowed_senders`
+    #[serde(default)]
+    pub rejected_packets: u64,
+    /// Most recent wheel or listener failure, even if the bridge recovered since
+    pub last_error: Option<String>,
+    /// Sizes of the last packets received, oldest first, for diagnostic reports
+    #[serde(default)]
+    pub recent_packet_sizes: Vec<usize>,
+    /// Latest telemetry and the LED bar it maps to, while packets arrive; shown by the dashboard
+    #[serde(default)]
+    pub live: Option<LiveReadout>,
+}
+
+/// The latest telemetry packet as the bridge shows it on the wheel
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LiveReadout {
+    pub rpm: f32,
+    pub max_rpm: f32,
+    /// Current gear (-1 reverse, 0 neutral), if the game sends it
+    pub gear: Option<i8>,
+    /// LEDs lit, lowest bit for the leftmost (green) LED; 0 while paused
+    pub leds: u8,
+}
+
+/// Sent to the running instance after reading its status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "request", rename_all = "snake_case")]
+pub enum Request {
+    /// Apply a second launch's arguments, in the order the command line applies them;
+    /// `None` keeps the current value
+    Apply {
+        profile: Option<String>,
+        game: Option<GameType>,
+        /// `--game auto`
+        #[serde(default)]
+        auto_detect_game: bool,
+        port: Option<u16>,
+        bind: Option<IpAddr>,
+    },
+}
+
+/// Answer to a `Request`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reply {
+    pub error: Option<String>,
+}
+
+/// Answer connections with `status()`, and requests with `handle`, until shutdown.
+/// Fails if another instance already owns the pipe.
+pub async fn serve(
+    status: impl Fn() -> InstanceStatus,
+    handle: impl Fn(Request) -> Result<(), String>,
+    mut shutdown: watch::Receiver<bool>,
+) -> io::Result<()> {
+    #[cfg(windows)]
+    {
+        use log::debug;
+        use tokio::net::windows::named_pipe::ServerOptions;
+
+        let mut server = ServerOptions::new().first_pipe_instance(true).create(PIPE_NAME)?;
+        loop {
+            tokio::select! {
+                connected = server.connect() => connected?,
+                _ = shutdown.wait_for(|exit| *exit) => return Ok(()),
+            }
+            // Have the next instance ready before answering, so clients are not turned away
+            let client = std::mem::replace(&mut server, ServerOptions::new().create(PIPE_NAME)?);
+            if let Err(e) = answer(client, &status, &handle).await {
+                debug!("Pipe client went away: {}", e);
+            }
+        }
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = (status, handle);
+        let _ = shutdown.wait_for(|exit| *exit).await;
+        Ok(())
+    }
+}
+
+/// Send the status, then handle the request if the client sends one
+#[cfg(windows)]
+async fn answer(
+    client: tokio::net::windows::named_pipe::NamedPipeServer,
+    status: &impl Fn() -> InstanceStatus,
+    handle: &impl Fn(Request) -> Result<(), String>,
+) -> io::Result<()> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let mut client = BufReader::new(client);
+    write_line(client.get_mut(), &status()).await?;
+
+    // Status queries just close the pipe
+    let mut line = String::new();
+    match tokio::time::timeout(REQUEST_TIMEOUT, client.read_line(&mut line)).await {
+        Ok(Ok(0)) | Err(_) => return Ok(()),
+        Ok(Ok(_)) => {}
+        Ok(Err(e)) => return Err(e),
+    }
+    let reply = match serde_json::from_str::<Request>(&line) {
+        Ok(request) => Reply {
+            error: handle(request).err(),
+        },
+        Err(e) => Reply {
+            error: Some(format!("invalid request: {}", e)),
+        },
+    };
+    write_line(client.get_mut(), &reply).await?;
+    client.get_mut().flush().await
+}
+
+#[cfg(windows)]
+async fn write_line(pipe: &mut (impl tokio::io::AsyncWrite + Unpin), value: &impl Serialize) -> io::Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut line = serde_json::to_string(value)?;
+    line.push('\n');
+    pipe.write_all(line.as_bytes()).await
+}
+
+/// Status of the running tray app or service, or `None` if neither is running
+pub async fn query_status() -> io::Result<Option<InstanceStatus>> {
+    #[cfg(windows)]
+    {
+        use tokio::io::BufReader;
+
+        let deadline = tokio::time::Instant::now() + QUERY_TIMEOUT;
+        let Some(pipe) = connect(false, deadline).await? else {
+            return Ok(None);
+        };
+        let status = read_line(&mut BufReader::new(pipe), deadline).await?;
+        Ok(Some(status))
+    }
+
+    #[cfg(not(windows))]
+    {
+        Ok(None)
+    }
+}
+
+/// Send `request` to the running instance and return its answer, or `None` if no
+/// instance is running
+pub async fn send_request(request: &Request) -> io::Result<Option<Result<(), String>>> {
+    #[cfg(windows)]
+    {
+        use tokio::io::BufReader;
+
+        let deadline = tokio::time::Instant::now() + QUERY_TIMEOUT;
+        let Some(pipe) = connect(true, deadline).await? else {
+            return Ok(None);
+        };
+        let mut pipe = BufReader::new(pipe);
+        let _: InstanceStatus = read_line(&mut pipe, deadline).await?;
+        write_line(pipe.get_mut(), request).await?;
+        let reply: Reply = read_line(&mut pipe, deadline).await?;
+        Ok(Some(reply.error.map_or(Ok(()), Err)))
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = request;
+        Ok(None)
+    }
+}
+
+/// Open the pipe, or `None` if no instance is listening
+#[cfg(windows)]
+async fn connect(
+    write: bool,
+    deadline: tokio::time::Instant,
+) -> io::Result<Option<tokio::net::windows::named_pipe::NamedPipeClient>> {
+    use tokio::net::windows::named_pipe::ClientOptions;
+    use winapi::shared::winerror::ERROR_PIPE_BUSY;
+
+    loop {
+        match ClientOptions::new().write(write).open(PIPE_NAME) {
+            Ok(pipe) => return Ok(Some(pipe)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            // Another client is being answered; the next pipe instance is moments away
+            Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY as i32) => {
+                if tokio::time::Instant::now() >= deadline {
+                    return Err(e);
+                }
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(windows)]
+async fn read_line<T: serde::de::DeserializeOwned>(
+    pipe: &mut (impl tokio::io::AsyncBufRead + Unpin),
+    deadline: tokio::time::Instant,
+) -> io::Result<T> {
+    use tokio::io::AsyncBufReadExt;
+
+    let mut line = String::new();
+    tokio::time::timeout_at(deadline, pipe.read_line(&mut line)).await??;
+    Ok(serde_json::from_str(&line)?)
+}
@@ -0,0 +1,68 @@
+// Low-latency mode
+//
+// Windows wakes sleeping threads on its timer tick, every 15.6 ms unless a program asks
+// for less, and schedules the bridge like any other background program next to the game.
+// With `low_latency = true` in settings.toml the bridge instead:
+// - Requests 1 ms timer resolution (timeBeginPeriod) while it runs
+// - Raises the priority of the bridge thread and of the wheel's LED writer thread
+//
+// so an LED update lands within a frame of its telemetry packet, e.g. for drivers who shift
+// on the red LED. It costs some power, so it is off by default, and applies after a restart.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use log::info;
+
+/// Set while a `LowLatency` is held
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Timer resolution requested while low-latency mode is on, in milliseconds
+#[cfg(windows)]
+const TIMER_RESOLUTION_MS: u32 = 1;
+
+/// Low-latency mode, on until this is dropped
+pub struct LowLatency(());
+
+impl LowLatency {
+    /// Turn low-latency mode on and raise the calling thread's priority
+    pub fn enter() -> Self {
+        ACTIVE.store(true, Ordering::Relaxed);
+        #[cfg(windows)]
+        {
+            use winapi::um::timeapi::timeBeginPeriod;
+
+            if unsafe { timeBeginPeriod(TIMER_RESOLUTION_MS) } != 0 {
+                log::warn!("Low-latency mode: 1 ms timer resolution is not available");
+            }
+        }
+        raise_thread_priority();
+        info!("Low-latency mode on: 1 ms timer resolution, raised thread priority");
+        Self(())
+    }
+}
+
+impl Drop for LowLatency {
+    fn drop(&mut self) {
+        ACTIVE.store(false, Ordering::Relaxed);
+        #[cfg(windows)]
+        {
+            use winapi::um::timeapi::timeEndPeriod;
+
+            unsafe { timeEndPeriod(TIMER_RESOLUTION_MS) };
+        }
+    }
+}
+
+/// Raise the calling thread's priority while low-latency mode is on; threads started later,
+/// such as the LED writer after the wheel reconnects, call this themselves
+pub fn raise_thread_priority() {
+    #[cfg(windows)]
+    if ACTIVE.load(Ordering::Relaxed) {
+        use winapi::um::processthreadsapi::{GetCurrentThread, SetThreadPriority};
+        use winapi::um::winbase::THREAD_PRIORITY_HIGHEST;
+
+        if unsafe { SetThreadPriority(GetCurrentThread(), THREAD_PRIORITY_HIGHEST as i32) } == 0 {
+            log::warn!("Cannot raise the thread priority: {}", std::io::Error::last_os_error());
+        }
+    }
+}
@@ -0,0 +1,231 @@
+// Bridge lifecycle state machine
+//
+// The bridge as a whole moves through these states:
+//
+//   SearchingWheel -> WheelConnected -> Listening -> Receiving <-> Stale
+//
+// and into Error whenever the wheel or the listener fails, or WheelUnavailable while a
+// connected wheel cannot be opened (e.g. G HUB holds it). Components report what
+// happened as events; the state machine decides the resulting state, which drives the
+// tray status line, the tooltip and the state log messages. While telemetry arrives,
+// the tooltip shows a `LiveTelemetry` summary instead.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use log::{info, warn};
+
+use g27_led_bridge_core::PacketStatsSummary;
+
+use crate::common::telemetry::GameType;
+use crate::common::util::WheelOpenFailure;
+use crate::common::waker::WakingSender;
+
+/// Where the bridge is in getting telemetry to the wheel
+#[derive(Debug, Clone, PartialEq)]
+pub enum BridgeState {
+    SearchingWheel,
+    /// Wheel is open but the telemetry listener is not bound yet
+    WheelConnected,
+    /// Wheel and listener are ready; no telemetry has arrived yet
+    Listening,
+    Receiving,
+    /// Telemetry stopped arriving, e.g. the game is paused or closed
+    Stale,
+    /// The G27 is connected but cannot be opened; the search keeps retrying
+    WheelUnavailable(WheelOpenFailure),
+    Error(String),
+}
+
+impl fmt::Display for BridgeState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BridgeState::SearchingWheel => write!(f, "Searching for wheel"),
+            BridgeState::WheelConnected => write!(f, "Wheel connected, starting listener"),
+            BridgeState::Listening => write!(f, "Waiting for telemetry"),
+            BridgeState::Receiving => write!(f, "Receiving telemetry"),
+            BridgeState::Stale => write!(f, "Telemetry stopped"),
+            BridgeState::WheelUnavailable(failure) => write!(f, "G27 found but not available - {}", failure),
+            BridgeState::Error(error) => write!(f, "Error - {}", error),
+        }
+    }
+}
+
+/// Recent telemetry in one line, e.g. "FH5 • 6 450 rpm • gear 3 • 62 pkt/s ±1.2 ms", with
+/// gaps and malformed packets once there are any
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LiveTelemetry {
+    pub game: GameType,
+    pub rpm: f32,
+    pub gear: Option<i8>,
+    pub stats: PacketStatsSummary,
+}
+
+impl fmt::Display for LiveTelemetry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} • {} rpm", self.game.short_name(), group_thousands(self.rpm.max(0.0).round() as u32))?;
+        match self.gear {
+            Some(-1) => write!(f, " • gear R")?,
+            Some(0) => write!(f, " • gear N")?,
+            Some(gear) => write!(f, " • gear {}", gear)?,
+            None => {}
+        }
+        write!(f, " • {:.0} pkt/s ±{:.1} ms", self.stats.packets_per_second, self.stats.jitter_ms)?;
+        if self.stats.gaps > 0 {
+            write!(f, " • {} gaps", self.stats.gaps)?;
+        }
+        if self.stats.malformed > 0 {
+            write!(f, " • {} malformed", self.stats.malformed)?;
+        }
+        Ok(())
+    }
+}
+
+/// `6450` as "6 450"
+fn group_thousands(value: u32) -> String {
+    let digits = value.to_string();
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(' ');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Something that happened to a bridge component
+#[derive(Debug, Clone, PartialEq)]
+pub enum LifecycleEvent {
+    WheelSearching,
+    WheelConnected,
+    /// The wheel was found but opening it failed
+    WheelOpenFailed(WheelOpenFailure),
+    WheelFailed(String),
+    ListenerBound,
+    ListenerFailed(String),
+    FrameReceived,
+    /// No frame arrived for a while
+    TelemetryTimeout,
+}
+
+/// The state machine itself
+#[derive(Debug)]
+pub struct Lifecycle {
+    state: BridgeState,
+    wheel_connected: bool,
+    listener_bound: bool,
+    /// Most recent failure, kept after the bridge recovers
+    last_error: Option<String>,
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        Self {
+            state: BridgeState::SearchingWheel,
+            wheel_connected: false,
+            listener_bound: false,
+            last_error: None,
+        }
+    }
+}
+
+impl Lifecycle {
+    pub fn state(&self) -> &BridgeState {
+        &self.state
+    }
+
+    pub fn wheel_connected(&self) -> bool {
+        self.wheel_connected
+    }
+
+    pub fn last_error(&self) -> Option<&str> {
+        self.last_error.as_deref()
+    }
+
+    /// Apply `event`, returning the new state if it changed
+    pub fn apply(&mut self, event: LifecycleEvent) -> Option<&BridgeState> {
+        match event {
+            LifecycleEvent::WheelSearching | LifecycleEvent::WheelOpenFailed(_) | LifecycleEvent::WheelFailed(_) => {
+                self.wheel_connected = false
+            }
+            LifecycleEvent::WheelConnected => self.wheel_connected = true,
+            LifecycleEvent::ListenerBound => self.listener_bound = true,
+            LifecycleEvent::ListenerFailed(_) => self.listener_bound = false,
+            LifecycleEvent::FrameReceived | LifecycleEvent::TelemetryTimeout => {}
+        }
+        if let LifecycleEvent::WheelFailed(error) | LifecycleEvent::ListenerFailed(error) = &event {
+            self.last_error = Some(error.clone());
+        }
+        if let LifecycleEvent::WheelOpenFailed(failure) = &event {
+            self.last_error = Some(format!("cannot open the G27: {}", failure));
+        }
+        let ready = self.wheel_connected && self.listener_bound;
+
+        let next = match (&self.state, event) {
+            (_, LifecycleEvent::WheelFailed(error) | LifecycleEvent::ListenerFailed(error)) => {
+                BridgeState::Error(error)
+            }
+            (_, LifecycleEvent::WheelOpenFailed(failure)) => BridgeState::WheelUnavailable(failure),
+            (_, LifecycleEvent::WheelSearching) => BridgeState::SearchingWheel,
+            (_, LifecycleEvent::WheelConnected | LifecycleEvent::ListenerBound) if ready => BridgeState::Listening,
+            (_, LifecycleEvent::WheelConnected) => BridgeState::WheelConnected,
+            // Listener is up but the wheel is not: still waiting on the wheel
+            (_, LifecycleEvent::ListenerBound) => BridgeState::SearchingWheel,
+            // Telemetry flow only matters once wheel and listener are both up
+            (BridgeState::Listening | BridgeState::Stale, LifecycleEvent::FrameReceived) if ready => {
+                BridgeState::Receiving
+            }
+            (BridgeState::Receiving, LifecycleEvent::TelemetryTimeout) => BridgeState::Stale,
+            (state, LifecycleEvent::FrameReceived | LifecycleEvent::TelemetryTimeout) => state.clone(),
+        };
+
+        if next == self.state {
+            return None;
+        }
+        self.state = next;
+        Some(&self.state)
+    }
+}
+
+/// Shared handle components report events through; state changes are logged
+/// and forwarded to the tray
+#[derive(Clone)]
+pub struct LifecycleReporter {
+    lifecycle: Arc<Mutex<Lifecycle>>,
+    sender: WakingSender<BridgeState>,
+}
+
+impl LifecycleReporter {
+    pub fn new(sender: WakingSender<BridgeState>) -> Self {
+        Self {
+            lifecycle: Arc::new(Mutex::new(Lifecycle::default())),
+            sender,
+        }
+    }
+
+    pub fn report(&self, event: LifecycleEvent) {
+        let Ok(mut lifecycle) = self.lifecycle.lock() else {
+            return;
+        };
+        if let Some(state) = lifecycle.apply(event) {
+            match state {
+                BridgeState::Error(_) => warn!("State: {}", state),
+                _ => info!("State: {}", state),
+            }
+            let _ = self.sender.send(state.clone());
+        }
+    }
+
+    /// Current state, whether the wheel is open and the most recent failure
+    pub fn snapshot(&self) -> (BridgeState, bool, Option<String>) {
+        match self.lifecycle.lock() {
+            Ok(lifecycle) => (
+                lifecycle.state().clone(),
+                lifecycle.wheel_connected(),
+                lifecycle.last_error().map(str::to_string),
+            ),
+            Err(_) => (BridgeState::Error("state unavailable".to_string()), false, None),
+        }
+    }
+}
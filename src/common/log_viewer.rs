@@ -0,0 +1,247 @@
+// Log viewer window for G27 LED Bridge
+//
+// In tray mode the console is hidden, so the tray's "Show Log" entry opens this window
+// instead:
+// - The recent log lines kept by `logging`, scrolled to the newest
+// - Refresh to pick up lines logged since the window opened
+// - Copy to Clipboard, for pasting into bug reports
+//
+// Runs its own message loop, so call it from a dedicated thread rather than the
+// tray event loop. Opening it again brings the existing window to the front.
+// Buttons and messages are shown in the configured language; the log itself stays English.
+
+use crate::common::i18n::tr;
+use crate::common::logging;
+
+/// The recent log as shown in the window, with Windows line endings
+#[cfg_attr(not(windows), allow(dead_code))]
+fn log_text() -> String {
+    let lines = logging::recent_lines();
+    if lines.is_empty() {
+        return tr("Nothing has been logged yet.").to_string();
+    }
+    lines.join("\r\n")
+}
+
+/// Show the log window until the user closes it
+pub fn show() {
+    #[cfg(windows)]
+    {
+        win32::show();
+    }
+
+    #[cfg(not(windows))]
+    {
+        log::warn!("The log viewer is only available on Windows; see the log file instead");
+    }
+}
+
+#[cfg(windows)]
+mod win32 {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr::null_mut;
+
+    use winapi::shared::minwindef::{LOWORD, LPARAM, LRESULT, UINT, WPARAM};
+    use winapi::shared::windef::{HBRUSH, HMENU, HWND};
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    use winapi::um::winbase::{GlobalAlloc, GlobalFree, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
+    use winapi::um::wingdi::{GetStockObject, ANSI_FIXED_FONT, DEFAULT_GUI_FONT};
+    use winapi::um::winuser::*;
+
+    use super::log_text;
+    use crate::common::i18n::tr;
+
+    const ID_LOG: i32 = 201;
+    const ID_COPY: i32 = 202;
+    const ID_REFRESH: i32 = 203;
+
+    const CLASS_NAME: &str = "G27LedBridgeLogViewer";
+    const WIDTH: i32 = 720;
+    const HEIGHT: i32 = 460;
+
+    fn wide(s: &str) -> Vec<u16> {
+        OsStr::new(s).encode_wide().chain(std::iter::once(0)).collect()
+    }
+
+    unsafe fn create_control(
+        parent: HWND,
+        class: &str,
+        style: u32,
+        ex_style: u32,
+        (x, y, w, h): (i32, i32, i32, i32),
+        id: i32,
+        text: &str,
+    ) -> HWND {
+        CreateWindowExW(
+            ex_style,
+            wide(class).as_ptr(),
+            wide(text).as_ptr(),
+            WS_CHILD | WS_VISIBLE | style,
+            x,
+            y,
+            w,
+            h,
+            parent,
+            id as usize as HMENU,
+            GetModuleHandleW(null_mut()),
+            null_mut(),
+        )
+    }
+
+    unsafe fn create_controls(hwnd: HWND) {
+        let log = create_control(
+            hwnd,
+            "EDIT",
+            ES_MULTILINE | ES_READONLY | ES_AUTOVSCROLL | ES_AUTOHSCROLL | WS_VSCROLL | WS_HSCROLL | WS_TABSTOP,
+            WS_EX_CLIENTEDGE,
+            (8, 8, WIDTH - 32, HEIGHT - 96),
+            ID_LOG,
+            "",
+        );
+        SendMessageW(log, WM_SETFONT, GetStockObject(ANSI_FIXED_FONT as i32) as WPARAM, 1);
+        // Lift the default 32K character limit; the ring buffer can hold more
+        SendMessageW(log, EM_SETLIMITTEXT, 0, 0);
+
+        let buttons = [
+            (tr("Copy to Clipboard"), ID_COPY, BS_PUSHBUTTON, WIDTH - 400, 130),
+            (tr("Refresh"), ID_REFRESH, BS_PUSHBUTTON, WIDTH - 260, 110),
+            (tr("Close"), IDCANCEL, BS_DEFPUSHBUTTON, WIDTH - 140, 110),
+        ];
+        for (text, id, style, x, w) in buttons {
+            let button = create_control(hwnd, "BUTTON", style | WS_TABSTOP, 0, (x, HEIGHT - 80, w, 26), id, text);
+            SendMessageW(button, WM_SETFONT, GetStockObject(DEFAULT_GUI_FONT as i32) as WPARAM, 1);
+        }
+
+        refresh(hwnd);
+        SetFocus(GetDlgItem(hwnd, IDCANCEL));
+    }
+
+    /// Reload the log text and scroll to its end
+    unsafe fn refresh(hwnd: HWND) {
+        let log = GetDlgItem(hwnd, ID_LOG);
+        SetWindowTextW(log, wide(&log_text()).as_ptr());
+        let end = GetWindowTextLengthW(log) as WPARAM;
+        SendMessageW(log, EM_SETSEL, end, end as LPARAM);
+        SendMessageW(log, EM_SCROLLCARET, 0, 0);
+    }
+
+    /// Put `text` on the clipboard as Unicode text; returns whether it worked
+    unsafe fn copy_to_clipboard(hwnd: HWND, text: &str) -> bool {
+        if OpenClipboard(hwnd) == 0 {
+            return false;
+        }
+        EmptyClipboard();
+
+        let text = wide(text);
+        let memory = GlobalAlloc(GMEM_MOVEABLE, text.len() * std::mem::size_of::<u16>());
+        let mut copied = false;
+        if !memory.is_null() {
+            let target = GlobalLock(memory) as *mut u16;
+            if !target.is_null() {
+                std::ptr::copy_nonoverlapping(text.as_ptr(), target, text.len());
+                GlobalUnlock(memory);
+                // The clipboard owns the memory once this succeeds
+                copied = !SetClipboardData(CF_UNICODETEXT, memory).is_null();
+            }
+            if !copied {
+                GlobalFree(memory);
+            }
+        }
+
+        CloseClipboard();
+        copied
+    }
+
+    unsafe extern "system" fn window_proc(hwnd: HWND, msg: UINT, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        match msg {
+            WM_COMMAND => {
+                match LOWORD(wparam as u32) as i32 {
+                    ID_COPY => {
+                        if !copy_to_clipboard(hwnd, &log_text()) {
+                            MessageBoxW(
+                                hwnd,
+                                wide(tr("The log could not be copied to the clipboard.")).as_ptr(),
+                                wide(tr("G27 LED Bridge Log")).as_ptr(),
+                                MB_OK | MB_ICONWARNING,
+                            );
+                        }
+                    }
+                    ID_REFRESH => refresh(hwnd),
+                    IDCANCEL => {
+                        DestroyWindow(hwnd);
+                    }
+                    _ => {}
+                }
+                0
+            }
+            WM_CLOSE => {
+                DestroyWindow(hwnd);
+                0
+            }
+            WM_DESTROY => {
+                PostQuitMessage(0);
+                0
+            }
+            _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+        }
+    }
+
+    pub fn show() {
+        unsafe {
+            let class_name = wide(CLASS_NAME);
+
+            // One viewer is enough; bring an open one forward and show it the latest lines
+            let existing = FindWindowW(class_name.as_ptr(), null_mut());
+            if !existing.is_null() {
+                refresh(existing);
+                SetForegroundWindow(existing);
+                return;
+            }
+
+            let instance = GetModuleHandleW(null_mut());
+            let mut class: WNDCLASSEXW = std::mem::zeroed();
+            class.cbSize = std::mem::size_of::<WNDCLASSEXW>() as u32;
+            class.lpfnWndProc = Some(window_proc);
+            class.hInstance = instance;
+            class.hCursor = LoadCursorW(null_mut(), IDC_ARROW);
+            class.hbrBackground = (COLOR_BTNFACE + 1) as usize as HBRUSH;
+            class.lpszClassName = class_name.as_ptr();
+            // Fails harmlessly if the class is already registered from an earlier viewer
+            RegisterClassExW(&class);
+
+            let x = (GetSystemMetrics(SM_CXSCREEN) - WIDTH) / 2;
+            let y = (GetSystemMetrics(SM_CYSCREEN) - HEIGHT) / 2;
+            let hwnd = CreateWindowExW(
+                0,
+                class_name.as_ptr(),
+                wide(tr("G27 LED Bridge Log")).as_ptr(),
+                WS_CAPTION | WS_SYSMENU | WS_MINIMIZEBOX | WS_VISIBLE,
+                x,
+                y,
+                WIDTH,
+                HEIGHT,
+                null_mut(),
+                null_mut(),
+                instance,
+                null_mut(),
+            );
+            if hwnd.is_null() {
+                log::error!("Failed to create log viewer window");
+                return;
+            }
+
+            create_controls(hwnd);
+            SetForegroundWindow(hwnd);
+
+            let mut msg: MSG = std::mem::zeroed();
+            while GetMessageW(&mut msg, null_mut(), 0, 0) > 0 {
+                // Handles Tab, Enter (Close) and Escape (IDCANCEL)
+                if IsDialogMessageW(hwnd, &mut msg) == 0 {
+                    TranslateMessage(&msg);
+                    DispatchMessageW(&msg);
+                }
+            }
+        }
+    }
+}
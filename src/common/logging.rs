@@ -0,0 +1,272 @@
+// Logging for G27 LED Bridge
+//
+// A small `log` backend used by both console and tray modes:
+// - Console output keeps the familiar "# message" style (warnings and errors on stderr,
+//   everything on stderr while a command prints JSON to stdout)
+// - Log file with timestamps, levels and modules, appended across runs and rotated by
+//   size; by default logs\g27-led-bridge.log in the data directory, so diagnostics from
+//   tray mode (no console) are kept
+// - Level and file can be changed at runtime when settings are reloaded; `-v` flags
+//   raise the configured level
+// - Panics are logged too, so a crash in tray mode still ends up in the file
+// - The most recent lines are kept in memory for the tray's log viewer
+
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use serde::{Deserialize, Serialize};
+
+use crate::common::paths;
+use crate::common::util::panic_message;
+
+/// Log file used when the settings do not name one, inside `paths::logs_dir()`
+const DEFAULT_LOG_FILE: &str = "g27-led-bridge.log";
+
+/// Size at which the log file is rotated
+const MAX_LOG_SIZE: u64 = 1024 * 1024;
+
+/// Rotated files kept next to the current one: g27-led-bridge.log.1 (newest) to .4
+const ROTATED_LOGS_KEPT: usize = 4;
+
+/// Lines kept in memory for `recent_lines`
+const RECENT_LINES_KEPT: usize = 500;
+
+/// Levels added to the configured one by `-v` flags
+static VERBOSITY: AtomicU8 = AtomicU8::new(0);
+
+/// Console output goes to stderr only, leaving stdout to machine-readable output
+static STDERR_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Verbosity setting, from least to most output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl LogLevel {
+    fn filter(self) -> LevelFilter {
+        match self {
+            LogLevel::Error => LevelFilter::Error,
+            LogLevel::Warn => LevelFilter::Warn,
+            LogLevel::Info => LevelFilter::Info,
+            LogLevel::Debug => LevelFilter::Debug,
+            LogLevel::Trace => LevelFilter::Trace,
+        }
+    }
+
+    /// This level made `steps` levels more verbose, up to `Trace`
+    fn raised_by(self, steps: u8) -> LevelFilter {
+        const LEVELS: [LogLevel; 5] = [LogLevel::Error, LogLevel::Warn, LogLevel::Info, LogLevel::Debug, LogLevel::Trace];
+        let index = (self as usize + steps as usize).min(LEVELS.len() - 1);
+        LEVELS[index].filter()
+    }
+}
+
+/// The open log file and how much has been written to it
+struct LogFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+}
+
+impl LogFile {
+    fn open(path: PathBuf) -> io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        let size = file.metadata()?.len();
+        Ok(Self { path, file, size })
+    }
+
+    fn write_line(&mut self, line: &str) {
+        if self.size >= MAX_LOG_SIZE {
+            if let Err(e) = self.rotate() {
+                eprintln!("# Cannot rotate log file {:?}: {}", self.path, e);
+                // Keep appending rather than retrying on every line
+                self.size = 0;
+            }
+        }
+        if writeln!(self.file, "{}", line).is_ok() {
+            self.size += line.len() as u64 + 1;
+        }
+    }
+
+    /// Shift the current file to .1, .1 to .2 and so on, dropping the oldest, and start a new one
+    fn rotate(&mut self) -> io::Result<()> {
+        for index in (1..ROTATED_LOGS_KEPT).rev() {
+            let older = rotated_path(&self.path, index);
+            if older.exists() {
+                fs::rename(&older, rotated_path(&self.path, index + 1))?;
+            }
+        }
+        fs::rename(&self.path, rotated_path(&self.path, 1))?;
+        self.file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// `path` with `.index` appended, e.g. g27-led-bridge.log.1
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+struct BridgeLogger {
+    file: Mutex<Option<LogFile>>,
+    recent: Mutex<VecDeque<String>>,
+}
+
+static LOGGER: BridgeLogger = BridgeLogger {
+    file: Mutex::new(None),
+    recent: Mutex::new(VecDeque::new()),
+};
+
+impl Log for BridgeLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= log::max_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        if record.level() <= Level::Warn || STDERR_ONLY.load(Ordering::Relaxed) {
+            eprintln!("# {}", record.args());
+        } else {
+            println!("# {}", record.args());
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs_f64())
+            .unwrap_or(0.0);
+        let line = format!("{:.3} {:<5} [{}] {}", timestamp, record.level(), record.target(), record.args());
+
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(file) = file.as_mut() {
+                file.write_line(&line);
+            }
+        }
+
+        if let Ok(mut recent) = self.recent.lock() {
+            if recent.len() == RECENT_LINES_KEPT {
+                recent.pop_front();
+            }
+            recent.push_back(line);
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            if let Some(file) = file.as_mut() {
+                let _ = file.file.flush();
+            }
+        }
+    }
+}
+
+/// Install the logger with default settings and route panic messages through it;
+/// call once at startup before anything logs
+pub fn init() {
+    if log::set_logger(&LOGGER).is_ok() {
+        log::set_max_level(LogLevel::default().raised_by(VERBOSITY.load(Ordering::Relaxed)));
+    }
+
+    panic::set_hook(Box::new(|info| {
+        let thread = std::thread::current();
+        let location = info.location().map(|location| format!(" at {}", location)).unwrap_or_default();
+        log::error!(
+            "Panic in thread '{}'{}: {}",
+            thread.name().unwrap_or("unnamed"),
+            location,
+            panic_message(info.payload())
+        );
+    }));
+}
+
+/// Make the output `steps` levels more verbose than configured (one per `-v` flag);
+/// call after `init`, before the settings are loaded
+pub fn set_verbosity(steps: u8) {
+    VERBOSITY.store(steps, Ordering::Relaxed);
+    log::set_max_level(LogLevel::default().raised_by(steps));
+}
+
+/// Send all console log output to stderr, e.g. while a command prints JSON to stdout
+pub fn use_stderr_only() {
+    STDERR_ONLY.store(true, Ordering::Relaxed);
+}
+
+/// Apply the configured level and log file. Relative paths are resolved against the data
+/// directory; without a file the log goes to the default file in `paths::logs_dir()`.
+pub fn configure(level: LogLevel, log_file: Option<&Path>) {
+    log::set_max_level(level.raised_by(VERBOSITY.load(Ordering::Relaxed)));
+
+    let path = match log_file {
+        Some(path) if path.is_relative() => match paths::data_dir() {
+            Ok(dir) => Some(dir.join(path)),
+            Err(e) => {
+                log::error!("Cannot resolve log file {:?}: {}", path, e);
+                None
+            }
+        },
+        Some(path) => Some(path.to_path_buf()),
+        None => match paths::logs_dir() {
+            Ok(dir) => Some(dir.join(DEFAULT_LOG_FILE)),
+            Err(e) => {
+                log::error!("Cannot create the logs directory: {}", e);
+                None
+            }
+        },
+    };
+
+    let Ok(mut current) = LOGGER.file.lock() else {
+        return;
+    };
+
+    // Keep the open handle if the destination did not change
+    if current.as_ref().map(|open| &open.path) == path.as_ref() {
+        return;
+    }
+
+    *current = None;
+    if let Some(path) = path {
+        match LogFile::open(path.clone()) {
+            Ok(file) => *current = Some(file),
+            Err(e) => {
+                drop(current);
+                log::error!("Cannot open log file {:?}: {}", path, e);
+            }
+        }
+    }
+}
+
+/// File the log is written to, if one is open
+pub fn current_file() -> Option<PathBuf> {
+    LOGGER.file.lock().ok()?.as_ref().map(|open| open.path.clone())
+}
+
+/// The last lines logged in this run, oldest first, in the log file's format
+pub fn recent_lines() -> Vec<String> {
+    LOGGER
+        .recent
+        .lock()
+        .map(|recent| recent.iter().cloned().collect())
+        .unwrap_or_default()
+}
@@ -0,0 +1,384 @@
+// mDNS advertisement of the bridge's WebSocket stream
+//
+// Off by default. While `[mdns] enabled` is set and the WebSocket server accepts
+// connections from the network (`[websocket] bind_address` other than localhost), the
+// bridge announces itself on the LAN as a `_g27bridge._tcp` DNS-SD service, so companion
+// apps on phones and tablets find it without typing an IP address:
+// - SRV    <name>._g27bridge._tcp.local  ->  <computer>.local:<websocket port>
+// - TXT    txtvers=1, protocol=websocket, path=/, version=<bridge version>
+// - A      <computer>.local  ->  the address other devices reach the bridge on
+//
+// The HTTP and gRPC APIs only listen on localhost, so they are not advertised.
+// Only the part of RFC 6762 a single service needs is implemented: queries are
+// answered over multicast, the service is announced on start and a goodbye is sent
+// on stop so browsers drop it straight away.
+
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::time::Duration;
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::net::UdpSocket;
+
+use crate::common::websocket::WebSocketSettings;
+
+const MDNS_GROUP: Ipv4Addr = Ipv4Addr::new(224, 0, 0, 251);
+pub const MDNS_PORT: u16 = 5353;
+
+/// Service type browsed for by companion apps
+pub const SERVICE_TYPE: &str = "_g27bridge._tcp.local";
+
+/// Browsed for by tools listing every service type on the network (RFC 6763, section 9)
+const SERVICE_TYPES: &str = "_services._dns-sd._udp.local";
+
+const TYPE_A: u16 = 1;
+const TYPE_PTR: u16 = 12;
+const TYPE_TXT: u16 = 16;
+const TYPE_SRV: u16 = 33;
+const TYPE_ANY: u16 = 255;
+const CLASS_IN: u16 = 1;
+/// Set on records only this host answers for (RFC 6762, section 10.2)
+const CACHE_FLUSH: u16 = 0x8000;
+
+/// Record lifetimes recommended by RFC 6762, section 10
+const HOST_TTL: u32 = 120;
+const SERVICE_TTL: u32 = 4500;
+
+/// Gap between the two announcements sent on start (RFC 6762, section 8.3)
+const ANNOUNCE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Largest mDNS packet accepted (RFC 6762, section 17)
+const MAX_PACKET: usize = 9000;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct MdnsSettings {
+    pub enabled: bool,
+    /// Name apps list the bridge under; the computer name when empty
+    pub name: String,
+}
+
+impl MdnsSettings {
+    pub const KNOWN_KEYS: &'static [&'static str] = &["enabled", "name"];
+}
+
+/// Records describing one bridge on the LAN
+#[derive(Debug, Clone, PartialEq)]
+pub struct Advertisement {
+    /// Instance label, e.g. "RACING-PC"
+    instance: String,
+    /// Host label, e.g. "RACING-PC" for RACING-PC.local
+    host: String,
+    address: Ipv4Addr,
+    port: u16,
+    txt: Vec<String>,
+}
+
+/// Record kinds a response can carry
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Record {
+    /// _services._dns-sd._udp.local -> _g27bridge._tcp.local
+    ServiceType,
+    /// _g27bridge._tcp.local -> <instance>._g27bridge._tcp.local
+    Instance,
+    Srv,
+    Txt,
+    A,
+}
+
+impl Advertisement {
+    /// Advertisement of the WebSocket server in `websocket`, `None` when it is off or only
+    /// reachable from this PC
+    pub fn for_websocket(settings: &MdnsSettings, websocket: &WebSocketSettings) -> Option<Self> {
+        if !websocket.enabled || websocket.bind_address.is_loopback() {
+            return None;
+        }
+        let address = lan_address(websocket.bind_address)?;
+        let computer = std::env::var("COMPUTERNAME").unwrap_or_default();
+        let host = host_label(&computer);
+        let instance = match settings.name.trim() {
+            "" if computer.trim().is_empty() => "G27 LED Bridge".to_string(),
+            "" => computer.trim().to_string(),
+            name => name.to_string(),
+        };
+        Some(Self {
+            // Labels are at most 63 bytes
+            instance: truncate(&instance, 63).to_string(),
+            host,
+            address,
+            port: websocket.port,
+            txt: vec![
+                "txtvers=1".to_string(),
+                "protocol=websocket".to_string(),
+                "path=/".to_string(),
+                format!("version={}", env!("CARGO_PKG_VERSION")),
+            ],
+        })
+    }
+
+    /// Full service instance name, as companion apps list it
+    pub fn instance_name(&self) -> String {
+        format!("{}.{}", self.instance, SERVICE_TYPE)
+    }
+
+    pub fn address(&self) -> SocketAddr {
+        SocketAddr::new(self.address.into(), self.port)
+    }
+
+    /// Response to `query`, `None` when it asks for nothing advertised here
+    pub fn answer(&self, query: &[u8]) -> Option<Vec<u8>> {
+        let questions = parse_questions(query)?;
+        let mut answers = Vec::new();
+        for (name, qtype) in &questions {
+            let wants = |rtype: u16| *qtype == rtype || *qtype == TYPE_ANY;
+            if name_is(name, &[], SERVICE_TYPE) && wants(TYPE_PTR) {
+                answers.push(Record::Instance);
+            } else if name_is(name, &[], SERVICE_TYPES) && wants(TYPE_PTR) {
+                answers.push(Record::ServiceType);
+            } else if name_is(name, &[&self.instance], SERVICE_TYPE) {
+                answers.extend([(TYPE_SRV, Record::Srv), (TYPE_TXT, Record::Txt)].iter().filter_map(
+                    |&(rtype, record)| wants(rtype).then_some(record),
+                ));
+            } else if name_is(name, &[&self.host], "local") && wants(TYPE_A) {
+                answers.push(Record::A);
+            }
+        }
+        // Questions repeated in one query get one answer
+        let mut seen = Vec::new();
+        answers.retain(|record| {
+            let first = !seen.contains(record);
+            seen.push(*record);
+            first
+        });
+        if answers.is_empty() {
+            return None;
+        }
+
+        // What a browser resolves next, so it does not have to ask again
+        let mut additional = Vec::new();
+        if answers.contains(&Record::Instance) {
+            additional.extend([Record::Srv, Record::Txt]);
+        }
+        if answers.contains(&Record::Instance) || answers.contains(&Record::Srv) {
+            additional.push(Record::A);
+        }
+        additional.retain(|record| !answers.contains(record));
+        Some(self.response(&answers, &additional, false))
+    }
+
+    /// Unsolicited response announcing every record, or withdrawing them with `goodbye`
+    pub fn announcement(&self, goodbye: bool) -> Vec<u8> {
+        self.response(&[Record::Instance, Record::Srv, Record::Txt, Record::A], &[], goodbye)
+    }
+
+    fn response(&self, answers: &[Record], additional: &[Record], goodbye: bool) -> Vec<u8> {
+        let mut packet = Vec::with_capacity(512);
+        // Query ID 0, flags: response, authoritative answer
+        packet.extend_from_slice(&[0, 0, 0x84, 0]);
+        for count in [0, answers.len(), 0, additional.len()] {
+            packet.extend_from_slice(&(count as u16).to_be_bytes());
+        }
+        for &record in answers.iter().chain(additional) {
+            self.push_record(&mut packet, record, goodbye);
+        }
+        packet
+    }
+
+    fn push_record(&self, packet: &mut Vec<u8>, record: Record, goodbye: bool) {
+        let service: Vec<&str> = SERVICE_TYPE.split('.').collect();
+        let instance: Vec<&str> = [self.instance.as_str()].into_iter().chain(service.iter().copied()).collect();
+        let host = [self.host.as_str(), "local"];
+
+        let mut data = Vec::new();
+        let (name, rtype, ttl, unique): (Vec<&str>, _, _, _) = match record {
+            Record::ServiceType => {
+                push_name(&mut data, &service);
+                (SERVICE_TYPES.split('.').collect(), TYPE_PTR, SERVICE_TTL, false)
+            }
+            Record::Instance => {
+                push_name(&mut data, &instance);
+                (service.clone(), TYPE_PTR, SERVICE_TTL, false)
+            }
+            Record::Srv => {
+                // Priority and weight 0, then the port and host to connect to
+                data.extend_from_slice(&[0, 0, 0, 0]);
+                data.extend_from_slice(&self.port.to_be_bytes());
+                push_name(&mut data, &host);
+                (instance.clone(), TYPE_SRV, HOST_TTL, true)
+            }
+            Record::Txt => {
+                for entry in &self.txt {
+                    let entry = truncate(entry, 255);
+                    data.push(entry.len() as u8);
+                    data.extend_from_slice(entry.as_bytes());
+                }
+                (instance.clone(), TYPE_TXT, SERVICE_TTL, true)
+            }
+            Record::A => {
+                data.extend_from_slice(&self.address.octets());
+                (host.to_vec(), TYPE_A, HOST_TTL, true)
+            }
+        };
+
+        push_name(packet, &name);
+        packet.extend_from_slice(&rtype.to_be_bytes());
+        let class = if unique { CLASS_IN | CACHE_FLUSH } else { CLASS_IN };
+        packet.extend_from_slice(&class.to_be_bytes());
+        packet.extend_from_slice(&(if goodbye { 0 } else { ttl }).to_be_bytes());
+        packet.extend_from_slice(&(data.len() as u16).to_be_bytes());
+        packet.extend_from_slice(&data);
+    }
+}
+
+/// Announce `advertisement` and answer queries for it until `stop` completes, then withdraw it
+pub async fn advertise(advertisement: &Advertisement, stop: impl Future) -> io::Result<()> {
+    let socket = bind(advertisement.address)?;
+    let group = SocketAddr::from((MDNS_GROUP, MDNS_PORT));
+
+    let serve = async {
+        for announcement in 0..2 {
+            if announcement > 0 {
+                tokio::time::sleep(ANNOUNCE_INTERVAL).await;
+            }
+            socket.send_to(&advertisement.announcement(false), group).await?;
+        }
+
+        let mut buffer = vec![0u8; MAX_PACKET];
+        loop {
+            let (read, from) = match socket.recv_from(&mut buffer).await {
+                Ok(received) => received,
+                // Windows reports ICMP errors for earlier sends on the next receive
+                Err(e) => {
+                    debug!("mDNS receive failed: {}", e);
+                    continue;
+                }
+            };
+            if let Some(response) = advertisement.answer(&buffer[..read]) {
+                debug!("mDNS: answering {}", from);
+                socket.send_to(&response, group).await?;
+            }
+        }
+    };
+
+    tokio::select! {
+        result = serve => result,
+        _ = stop => {
+            socket.send_to(&advertisement.announcement(true), group).await?;
+            Ok(())
+        }
+    }
+}
+
+/// Socket on the mDNS port, shared with the system's own responder, joined to the group on
+/// the adapter with `interface`
+fn bind(interface: Ipv4Addr) -> io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    socket.set_nonblocking(true)?;
+    socket.bind(&SocketAddr::from((Ipv4Addr::UNSPECIFIED, MDNS_PORT)).into())?;
+    socket.join_multicast_v4(&MDNS_GROUP, &interface)?;
+    socket.set_multicast_if_v4(&interface)?;
+    socket.set_multicast_ttl_v4(255)?;
+    UdpSocket::from_std(socket.into())
+}
+
+/// Address other devices reach the bridge on: `bind` if it is a specific address, otherwise
+/// the one the system would send multicast from
+fn lan_address(bind: IpAddr) -> Option<Ipv4Addr> {
+    match bind {
+        IpAddr::V4(address) if !address.is_unspecified() => Some(address),
+        IpAddr::V4(_) => {
+            let probe = std::net::UdpSocket::bind(SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0)).ok()?;
+            probe.connect((MDNS_GROUP, MDNS_PORT)).ok()?;
+            match probe.local_addr().ok()?.ip() {
+                IpAddr::V4(address) if !address.is_unspecified() && !address.is_loopback() => Some(address),
+                _ => None,
+            }
+        }
+        // mDNS over IPv6 is not implemented
+        IpAddr::V6(_) => None,
+    }
+}
+
+/// Host name label from a computer name: letters, digits and hyphens only
+fn host_label(computer: &str) -> String {
+    let label: String = computer
+        .trim()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .take(63)
+        .collect();
+    let label = label.trim_matches('-');
+    if label.is_empty() { "g27-led-bridge".to_string() } else { label.to_string() }
+}
+
+/// `text` cut to at most `max` bytes on a character boundary
+fn truncate(text: &str, max: usize) -> &str {
+    let mut end = text.len().min(max);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// Whether the question `name` is `labels` followed by `domain`, ignoring case
+fn name_is(name: &[String], labels: &[&str], domain: &str) -> bool {
+    let expected = labels.iter().copied().chain(domain.split('.'));
+    name.len() == labels.len() + domain.split('.').count()
+        && name.iter().zip(expected).all(|(label, expected)| label.eq_ignore_ascii_case(expected))
+}
+
+fn push_name(packet: &mut Vec<u8>, labels: &[&str]) {
+    for label in labels {
+        packet.push(label.len() as u8);
+        packet.extend_from_slice(label.as_bytes());
+    }
+    packet.push(0);
+}
+
+/// Names and types asked for by a query, `None` for responses and malformed packets
+fn parse_questions(packet: &[u8]) -> Option<Vec<(Vec<String>, u16)>> {
+    let header = packet.get(..12)?;
+    // Responses from other hosts are not questions
+    if header[2] & 0x80 != 0 {
+        return None;
+    }
+    let count = u16::from_be_bytes([header[4], header[5]]);
+    let mut questions = Vec::with_capacity(count.into());
+    let mut pos = 12;
+    for _ in 0..count {
+        let (name, end) = read_name(packet, pos)?;
+        let qtype = u16::from_be_bytes([*packet.get(end)?, *packet.get(end + 1)?]);
+        // Class, with the unicast-response bit, follows; replies go to the group either way
+        packet.get(end + 3)?;
+        questions.push((name, qtype));
+        pos = end + 4;
+    }
+    Some(questions)
+}
+
+/// Labels of the name at `pos`, following compression pointers, and where the name ends
+fn read_name(packet: &[u8], mut pos: usize) -> Option<(Vec<String>, usize)> {
+    let mut labels = Vec::new();
+    let mut end = None;
+    // Pointers may only loop so often before the packet counts as malformed
+    for _ in 0..128 {
+        let length = usize::from(*packet.get(pos)?);
+        match length {
+            0 => return Some((labels, end.unwrap_or(pos + 1))),
+            length if length & 0xC0 == 0xC0 => {
+                end.get_or_insert(pos + 2);
+                pos = ((length & 0x3F) << 8) | usize::from(*packet.get(pos + 1)?);
+            }
+            length => {
+                let label = packet.get(pos + 1..pos + 1 + length)?;
+                labels.push(String::from_utf8_lossy(label).into_owned());
+                pos += 1 + length;
+            }
+        }
+    }
+    None
+}
@@ -0,0 +1,71 @@
+// Settings schema migrations for G27 LED Bridge
+//
+// Upgrades settings.toml files written by older versions in place:
+// - Each step transforms the raw TOML table from version N to N + 1
+// - Steps run in order until the file reaches SETTINGS_VERSION
+// - Files without a `version` key are treated as version 0 (pre-versioning)
+
+use log::info;
+use toml::{Table, Value};
+
+use crate::common::settings::DEFAULT_PROFILE;
+
+/// Current settings schema version; bump together with a new migration step
+pub const SETTINGS_VERSION: u32 = 2;
+
+type Migration = fn(&mut Table);
+
+/// Migration steps, where `MIGRATIONS[n]` upgrades a version `n` table to `n + 1`
+const MIGRATIONS: &[Migration] = &[migrate_v0_to_v1, migrate_v1_to_v2];
+
+/// Read the schema version of a raw settings table
+pub fn schema_version(table: &Table) -> u32 {
+    table
+        .get("version")
+        .and_then(Value::as_integer)
+        .map(|version| version.max(0) as u32)
+        .unwrap_or(0)
+}
+
+/// Run all pending migrations on `table`, returning true if anything changed
+pub fn migrate(table: &mut Table) -> Result<bool, String> {
+    let from = schema_version(table);
+
+    if from > SETTINGS_VERSION {
+        return Err(format!(
+            "settings file version {} is newer than supported version {}",
+            from, SETTINGS_VERSION
+        ));
+    }
+
+    for version in from..SETTINGS_VERSION {
+        info!("Migrating settings from version {} to {}", version, version + 1);
+        MIGRATIONS[version as usize](table);
+        table.insert("version".into(), Value::Integer(i64::from(version + 1)));
+    }
+
+    Ok(from != SETTINGS_VERSION)
+}
+
+/// v0 -> v1: introduce the `version` key; field layout is unchanged
+fn migrate_v0_to_v1(_table: &mut Table) {}
+
+/// v1 -> v2: wrap the existing game choice, port, outputs and LED style into a "Default"
+/// profile and make it active
+fn migrate_v1_to_v2(table: &mut Table) {
+    if table.contains_key("profiles") {
+        return;
+    }
+
+    let mut profile = Table::new();
+    for key in ["game_type", "auto_detect_game", "port", "outputs", "leds"] {
+        if let Some(value) = table.get(key) {
+            profile.insert(key.into(), value.clone());
+        }
+    }
+
+    let mut profiles = Table::new();
+    profiles.insert(DEFAULT_PROFILE.into(), Value::Table(profile));
+    table.insert("profiles".into(), Value::Table(profiles));
+    table.insert("active_profile".into(), Value::String(DEFAULT_PROFILE.into()));
+}
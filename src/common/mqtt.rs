@@ -0,0 +1,285 @@
+// MQTT output for home automation, dashboards and smart lights
+//
+// While `[integrations.mqtt]` is enabled, the bridge connects to the broker (MQTT 3.1.1,
+// logging in with `username` and `password` if set) and publishes under `topic`:
+// - <topic>/status  "online" while connected, retained; the broker publishes "offline"
+//                   for the bridge when the connection is lost
+// - <topic>/rpm     RPM as a share of max RPM ("0.000" - "1.000"), at most every `RPM_INTERVAL`
+// - <topic>/gear    -1 reverse, 0 neutral; retained, when it changes and the game sends it
+// - <topic>/shift   "1" on reaching the top of the LED bar's range (where the shift flash
+//                   starts), "0" on dropping out of it; retained
+// Pausing publishes rpm 0 and shift 0 once, like OSC. Everything goes out at QoS 0; a lost
+// connection ends the output with an error, and the supervisor connects again.
+
+use std::io::{self, ErrorKind};
+use std::time::{Duration, Instant};
+
+use g27_led_bridge_core::{LoopControl, Subscription, TelemetryFrame};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::watch;
+
+use crate::common::integrations::MqttSettings;
+use crate::common::leds::LedStyle;
+use crate::common::util::{DR2G27Error, DR2G27Result};
+
+/// Longest silence the broker allows before dropping the client; pings fill quiet stretches
+const KEEP_ALIVE: Duration = Duration::from_secs(60);
+/// How long the broker has to accept the connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Shortest time between two RPM messages; brokers and home automation do not need 60 a second
+const RPM_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Append `text` as an MQTT string: its length as a big-endian u16, then the UTF-8 bytes
+fn push_string(buffer: &mut Vec<u8>, text: &str) {
+    buffer.extend_from_slice(&(text.len() as u16).to_be_bytes());
+    buffer.extend_from_slice(text.as_bytes());
+}
+
+/// A packet of type `header` around `body`, with the remaining length as a variable-length integer
+fn packet(header: u8, body: &[u8]) -> Vec<u8> {
+    let mut packet = vec![header];
+    let mut remaining = body.len();
+    loop {
+        let byte = (remaining % 128) as u8;
+        remaining /= 128;
+        if remaining == 0 {
+            packet.push(byte);
+            break;
+        }
+        packet.push(byte | 0x80);
+    }
+    packet.extend_from_slice(body);
+    packet
+}
+
+/// Encode a CONNECT packet for a clean session with a retained last will, and the
+/// credentials where they are set
+pub fn encode_connect(client_id: &str, will_topic: &str, will: &str, username: &str, password: &str) -> Vec<u8> {
+    // Clean session, will flag and will retain
+    let mut flags = 0x02 | 0x04 | 0x20;
+    if !username.is_empty() {
+        flags |= 0x80;
+    }
+    if !password.is_empty() {
+        flags |= 0x40;
+    }
+
+    let mut body = Vec::new();
+    push_string(&mut body, "MQTT");
+    body.push(4);
+    body.push(flags);
+    body.extend_from_slice(&(KEEP_ALIVE.as_secs() as u16).to_be_bytes());
+    push_string(&mut body, client_id);
+    push_string(&mut body, will_topic);
+    push_string(&mut body, will);
+    if !username.is_empty() {
+        push_string(&mut body, username);
+    }
+    if !password.is_empty() {
+        push_string(&mut body, password);
+    }
+    packet(0x10, &body)
+}
+
+/// Encode a QoS 0 PUBLISH packet
+pub fn encode_publish(topic: &str, payload: &str, retain: bool) -> Vec<u8> {
+    let mut body = Vec::with_capacity(topic.len() + payload.len() + 2);
+    push_string(&mut body, topic);
+    body.extend_from_slice(payload.as_bytes());
+    packet(0x30 | u8::from(retain), &body)
+}
+
+/// Why the broker refused a connection, from the CONNACK return code
+fn refusal(code: u8) -> &'static str {
+    match code {
+        1 => "the broker does not speak MQTT 3.1.1",
+        2 => "the client ID was rejected",
+        3 => "the broker is unavailable",
+        4 => "wrong username or password",
+        5 => "not authorized",
+        _ => "unknown reason",
+    }
+}
+
+/// A connection to the broker that publishes under the configured topic
+pub struct MqttClient {
+    stream: TcpStream,
+    broker: String,
+    topic: String,
+    last_sent: Instant,
+}
+
+impl MqttClient {
+    /// Connect and log in to the broker in `settings`, and announce the bridge online
+    pub async fn connect(settings: &MqttSettings) -> Result<Self, DR2G27Error> {
+        let host = settings.host.trim();
+        let broker = format!("{}:{}", host, settings.port);
+        let failed = |source: io::Error| DR2G27Error::Connection {
+            service: "MQTT broker",
+            addr: broker.clone(),
+            source,
+        };
+
+        let mut stream = TcpStream::connect((host, settings.port)).await.map_err(failed)?;
+        // Small messages should leave right away instead of waiting to fill a segment
+        stream.set_nodelay(true).map_err(failed)?;
+        let topic = settings.topic.trim_end_matches('/').to_string();
+        let client_id = format!("g27-led-bridge-{}", std::process::id());
+        let connect = encode_connect(
+            &client_id,
+            &format!("{}/status", topic),
+            "offline",
+            &settings.username,
+            &settings.password,
+        );
+        stream.write_all(&connect).await.map_err(failed)?;
+
+        let mut connack = [0u8; 4];
+        match tokio::time::timeout(CONNECT_TIMEOUT, stream.read_exact(&mut connack)).await {
+            Ok(Ok(_)) => {}
+            Ok(Err(e)) => return Err(failed(e)),
+            Err(_) => return Err(failed(io::Error::new(ErrorKind::TimedOut, "no answer to CONNECT"))),
+        }
+        if connack[..2] != [0x20, 0x02] {
+            return Err(failed(io::Error::new(ErrorKind::InvalidData, "not an MQTT broker")));
+        }
+        if connack[3] != 0 {
+            return Err(failed(io::Error::new(ErrorKind::ConnectionRefused, refusal(connack[3]))));
+        }
+
+        let mut client = Self {
+            stream,
+            broker,
+            topic,
+            last_sent: Instant::now(),
+        };
+        client.publish("status", "online", true).await?;
+        Ok(client)
+    }
+
+    /// Broker as host:port, for status lines and logs
+    pub fn broker(&self) -> &str {
+        &self.broker
+    }
+
+    /// Publish `payload` to `<topic>/<name>`
+    async fn publish(&mut self, name: &str, payload: &str, retain: bool) -> DR2G27Result {
+        let message = encode_publish(&format!("{}/{}", self.topic, name), payload, retain);
+        self.send(&message).await
+    }
+
+    async fn send(&mut self, packet: &[u8]) -> DR2G27Result {
+        self.stream.write_all(packet).await.map_err(|source| DR2G27Error::Connection {
+            service: "MQTT broker",
+            addr: self.broker.clone(),
+            source,
+        })?;
+        self.last_sent = Instant::now();
+        Ok(())
+    }
+
+    /// Announce the bridge offline and close the connection
+    async fn disconnect(&mut self) -> DR2G27Result {
+        self.publish("status", "offline", true).await?;
+        self.send(&[0xe0, 0x00]).await
+    }
+}
+
+/// Publish the RPM, gear and shift range of each frame from `frames` with `style`'s shift
+/// range, going dark while `control` is `Mute`, until `control` holds `LoopControl::Stop`,
+/// its sender is dropped or the bus closes. Fails when the connection is lost.
+pub async fn publish_frames(
+    client: &mut MqttClient,
+    style: LedStyle,
+    mut frames: Subscription,
+    mut control: watch::Receiver<LoopControl>,
+) -> DR2G27Result {
+    let mut state = PublishedState::default();
+    let mut dark = true;
+    let mut keep_alive = tokio::time::interval(KEEP_ALIVE / 2);
+    // The broker only answers pings; read them so a closed connection is noticed
+    let mut incoming = [0u8; 64];
+    loop {
+        let control_state = *control.borrow_and_update();
+        match control_state {
+            LoopControl::Stop => return client.disconnect().await,
+            LoopControl::Mute if !dark => {
+                client.publish("rpm", "0.000", false).await?;
+                client.publish("shift", "0", true).await?;
+                state.shifting = false;
+                dark = true;
+            }
+            _ => {}
+        }
+
+        tokio::select! {
+            changed = control.changed() => {
+                if changed.is_err() {
+                    return client.disconnect().await;
+                }
+            }
+            read = client.stream.read(&mut incoming) => match read {
+                Ok(0) => {
+                    let closed = io::Error::new(ErrorKind::ConnectionReset, "the broker closed the connection");
+                    return Err(DR2G27Error::Connection {
+                        service: "MQTT broker",
+                        addr: client.broker.clone(),
+                        source: closed,
+                    });
+                }
+                Ok(_) => {}
+                Err(source) => {
+                    return Err(DR2G27Error::Connection { service: "MQTT broker", addr: client.broker.clone(), source });
+                }
+            },
+            _ = keep_alive.tick() => {
+                if client.last_sent.elapsed() >= KEEP_ALIVE / 2 {
+                    client.send(&[0xc0, 0x00]).await?;
+                }
+            }
+            // Only the current state matters; skip what queued up behind it
+            frame = frames.recv_latest() => match frame {
+                Some(frame) if control_state == LoopControl::Run => {
+                    state.publish(client, style, &frame).await?;
+                    dark = false;
+                }
+                Some(_) => {}
+                None => return client.disconnect().await,
+            },
+        }
+    }
+}
+
+/// What was last published, so gear and shift go out only on changes
+#[derive(Default)]
+struct PublishedState {
+    gear: Option<i8>,
+    shifting: bool,
+    last_rpm: Option<Instant>,
+}
+
+impl PublishedState {
+    async fn publish(&mut self, client: &mut MqttClient, style: LedStyle, frame: &TelemetryFrame) -> DR2G27Result {
+        let racing = frame.race_active && frame.max_rpm > 0.0;
+        if self.last_rpm.is_none_or(|last| last.elapsed() >= RPM_INTERVAL) {
+            let rpm = if racing { (frame.rpm / frame.max_rpm).clamp(0.0, 1.0) } else { 0.0 };
+            client.publish("rpm", &format!("{:.3}", rpm), false).await?;
+            self.last_rpm = Some(Instant::now());
+        }
+
+        if frame.gear != self.gear {
+            self.gear = frame.gear;
+            if let Some(gear) = frame.gear {
+                client.publish("gear", &gear.to_string(), true).await?;
+            }
+        }
+
+        let in_shift_range = racing && style.in_shift_range(frame.rpm, frame.max_rpm, frame.idle_rpm);
+        if in_shift_range != self.shifting {
+            self.shifting = in_shift_range;
+            client.publish("shift", if in_shift_range { "1" } else { "0" }, true).await?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+// Windows toast notifications for G27 LED Bridge
+//
+// The bridge state is otherwise only visible in the tray menu and a console that is
+// usually hidden. `Notifier` follows the state changes and picks the few worth a toast:
+// - The wheel connecting and disconnecting
+// - A connected wheel that cannot be opened, with what to try (usually quitting G HUB)
+// - The first telemetry of the run
+// - Errors, once each until the bridge recovers (e.g. the port is in use)
+//
+// Toasts are switched off with `notifications = false` in settings.toml, and shown in the
+// configured language.
+
+use log::debug;
+
+use crate::common::i18n::tr;
+use crate::common::lifecycle::BridgeState;
+use crate::common::util::WheelOpenFailure;
+
+/// A toast to show
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub title: &'static str,
+    pub message: String,
+}
+
+impl Notification {
+    /// Toast with `title` and `message` translated; an error message without a translation stays as is
+    fn new(title: &'static str, message: impl Into<String>) -> Self {
+        let message = message.into();
+        Self {
+            title: tr(title),
+            message: tr(&message).to_string(),
+        }
+    }
+
+    /// Toast naming why the wheel cannot be opened, followed by a line per hint
+    fn wheel_unavailable(failure: WheelOpenFailure) -> Self {
+        let mut message = tr(&failure.to_string()).to_string();
+        for hint in failure.hints() {
+            message.push_str("\n• ");
+            message.push_str(tr(hint));
+        }
+        Self {
+            title: tr("G27 found but not available"),
+            message,
+        }
+    }
+
+    /// Show the toast without waiting for it
+    pub fn show(self) {
+        std::thread::spawn(move || self.show_now());
+    }
+
+    /// Show the toast on this thread, e.g. while the process is about to end
+    pub fn show_now(&self) {
+        #[cfg(windows)]
+        {
+            use tauri_winrt_notification::{Duration, Toast};
+
+            // Unpackaged apps cannot register their own sender, so toasts appear under PowerShell
+            let result = Toast::new(Toast::POWERSHELL_APP_ID)
+                .title(self.title)
+                .text1(&self.message)
+                .duration(Duration::Short)
+                .show();
+            if let Err(e) = result {
+                debug!("Failed to show notification '{}': {}", self.title, e);
+            }
+        }
+
+        #[cfg(not(windows))]
+        {
+            debug!("Notification: {} - {}", self.title, self.message);
+        }
+    }
+}
+
+/// Decides which bridge state changes deserve a toast
+#[derive(Debug, Default)]
+pub struct Notifier {
+    wheel_connected: bool,
+    received: bool,
+    /// Why the wheel could not be opened, as last shown, until it connects
+    unavailable: Option<WheelOpenFailure>,
+    /// Error last shown, until the bridge recovers
+    error: Option<String>,
+}
+
+impl Notifier {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Toast for the bridge entering `state`, if any
+    pub fn on_state(&mut self, state: &BridgeState) -> Option<Notification> {
+        match state {
+            // Listening also follows the wheel connecting while the listener was already up
+            BridgeState::WheelConnected | BridgeState::Listening if !self.wheel_connected => {
+                self.wheel_connected = true;
+                self.error = None;
+                self.unavailable = None;
+                Some(Notification::new("G27 connected", "The wheel LEDs now follow your RPM"))
+            }
+            BridgeState::SearchingWheel if self.wheel_connected => {
+                self.wheel_connected = false;
+                Some(Notification::new("G27 disconnected", "Reconnect the wheel to continue"))
+            }
+            BridgeState::WheelUnavailable(failure) if self.unavailable != Some(*failure) => {
+                self.unavailable = Some(*failure);
+                Some(Notification::wheel_unavailable(*failure))
+            }
+            BridgeState::Receiving => {
+                self.error = None;
+                (!std::mem::replace(&mut self.received, true))
+                    .then(|| Notification::new("Receiving telemetry", "Telemetry from the game is arriving"))
+            }
+            BridgeState::Error(error) if self.error.as_ref() != Some(error) => {
+                self.error = Some(error.clone());
+                Some(Notification::new("G27 LED Bridge problem", error.clone()))
+            }
+            BridgeState::Listening => {
+                self.error = None;
+                None
+            }
+            _ => None,
+        }
+    }
+}
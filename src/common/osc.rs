@@ -0,0 +1,170 @@
+// OSC output for lighting consoles, TouchOSC layouts and VJ software
+//
+// While `[integrations.osc]` is enabled, each telemetry frame is sent to `host:port`
+// over UDP as Open Sound Control messages under `prefix`:
+// - <prefix>/rpm    float, RPM as a share of max RPM (0.0 - 1.0), every frame
+// - <prefix>/gear   int, -1 reverse, 0 neutral; when it changes and the game sends it
+// - <prefix>/shift  int, 1 on reaching the top of the LED bar's range (where the shift
+//                   flash starts), 0 on dropping out of it
+// Pausing sends rpm 0 and shift 0 once, so rigs go dark like the wheel.
+
+use std::net::SocketAddr;
+
+use g27_led_bridge_core::{LoopControl, Subscription, TelemetryFrame};
+use log::{debug, info, warn};
+use tokio::net::UdpSocket;
+use tokio::sync::watch;
+
+use crate::common::integrations::OscSettings;
+use crate::common::leds::LedStyle;
+use crate::common::util::{DR2G27Error, DR2G27Result};
+
+/// Single argument of an OSC message
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OscArg {
+    Float(f32),
+    Int(i32),
+}
+
+/// Encode an OSC message with one argument (OSC 1.0: padded strings, big-endian values)
+pub fn encode_message(address: &str, arg: OscArg) -> Vec<u8> {
+    fn push_padded(buffer: &mut Vec<u8>, text: &str) {
+        buffer.extend_from_slice(text.as_bytes());
+        // At least one NUL, then up to the next multiple of four
+        buffer.extend(std::iter::repeat_n(0, 4 - text.len() % 4));
+    }
+
+    let mut message = Vec::with_capacity(address.len() + 12);
+    push_padded(&mut message, address);
+    match arg {
+        OscArg::Float(value) => {
+            push_padded(&mut message, ",f");
+            message.extend_from_slice(&value.to_be_bytes());
+        }
+        OscArg::Int(value) => {
+            push_padded(&mut message, ",i");
+            message.extend_from_slice(&value.to_be_bytes());
+        }
+    }
+    message
+}
+
+/// Sends OSC messages to one receiver, logging failed sends instead of failing on them
+pub struct OscSender {
+    socket: UdpSocket,
+    target: SocketAddr,
+    prefix: String,
+    failing: bool,
+}
+
+impl OscSender {
+    /// Resolve the receiver in `settings` and open a socket to send to it from
+    pub async fn connect(settings: &OscSettings) -> Result<Self, DR2G27Error> {
+        let host = settings.host.trim();
+        let target = tokio::net::lookup_host((host, settings.port))
+            .await
+            .ok()
+            .and_then(|mut addrs| addrs.next())
+            .ok_or_else(|| DR2G27Error::ConfigError(format!("cannot resolve OSC host '{}'", host)))?;
+        let local: SocketAddr = if target.is_ipv4() { ([0, 0, 0, 0], 0).into() } else { ([0u16; 8], 0).into() };
+        let socket = UdpSocket::bind(local)
+            .await
+            .map_err(|source| DR2G27Error::BindFailed { addr: local, source })?;
+        Ok(Self {
+            socket,
+            target,
+            prefix: settings.prefix.trim_end_matches('/').to_string(),
+            failing: false,
+        })
+    }
+
+    pub fn target(&self) -> SocketAddr {
+        self.target
+    }
+
+    /// Send `arg` to `<prefix>/<name>`
+    async fn send(&mut self, name: &str, arg: OscArg) {
+        let message = encode_message(&format!("{}/{}", self.prefix, name), arg);
+        match self.socket.send_to(&message, self.target).await {
+            Ok(_) if self.failing => {
+                info!("Sending OSC to {} works again", self.target);
+                self.failing = false;
+            }
+            Ok(_) => {}
+            Err(e) if !self.failing => {
+                warn!("Cannot send OSC to {}: {}", self.target, e);
+                self.failing = true;
+            }
+            Err(e) => debug!("Sending OSC to {} failed: {}", self.target, e),
+        }
+    }
+}
+
+/// Send the RPM, gear and shift range of each frame from `frames` with `style`'s shift range,
+/// going dark while `control` is `Mute`, until `control` holds `LoopControl::Stop`, its
+/// sender is dropped or the bus closes
+pub async fn send_frames(
+    sender: &mut OscSender,
+    style: LedStyle,
+    mut frames: Subscription,
+    mut control: watch::Receiver<LoopControl>,
+) -> DR2G27Result {
+    let mut gear = None;
+    let mut shifting = false;
+    let mut dark = true;
+    loop {
+        let state = *control.borrow_and_update();
+        match state {
+            LoopControl::Stop => return Ok(()),
+            LoopControl::Mute if !dark => {
+                sender.send("rpm", OscArg::Float(0.0)).await;
+                sender.send("shift", OscArg::Int(0)).await;
+                shifting = false;
+                dark = true;
+            }
+            _ => {}
+        }
+
+        tokio::select! {
+            changed = control.changed() => {
+                if changed.is_err() {
+                    return Ok(());
+                }
+            }
+            // Only the current state matters to a display; skip what queued up behind it
+            frame = frames.recv_latest() => match frame {
+                Some(frame) if state == LoopControl::Run => {
+                    send_frame(sender, style, &frame, &mut gear, &mut shifting).await;
+                    dark = false;
+                }
+                Some(_) => {}
+                None => return Ok(()),
+            },
+        }
+    }
+}
+
+async fn send_frame(
+    sender: &mut OscSender,
+    style: LedStyle,
+    frame: &TelemetryFrame,
+    gear: &mut Option<i8>,
+    shifting: &mut bool,
+) {
+    let racing = frame.race_active && frame.max_rpm > 0.0;
+    let rpm = if racing { (frame.rpm / frame.max_rpm).clamp(0.0, 1.0) } else { 0.0 };
+    sender.send("rpm", OscArg::Float(rpm)).await;
+
+    if frame.gear != *gear {
+        *gear = frame.gear;
+        if let Some(gear) = frame.gear {
+            sender.send("gear", OscArg::Int(gear.into())).await;
+        }
+    }
+
+    let in_shift_range = racing && style.in_shift_range(frame.rpm, frame.max_rpm, frame.idle_rpm);
+    if in_shift_range != *shifting {
+        *shifting = in_shift_range;
+        sender.send("shift", OscArg::Int(in_shift_range.into())).await;
+    }
+}
@@ -0,0 +1,70 @@
+// LED outputs for G27 LED Bridge
+//
+// Every destination the bridge can drive has its own persisted enable switch:
+// - The G27 wheel LEDs, under `[outputs]`
+// - Each external integration, through its `enabled` flag under `[integrations]`
+//
+// Muting one output leaves the others running.
+
+use serde::{Deserialize, Serialize};
+
+use crate::common::integrations::IntegrationSettings;
+
+/// Enable switches for outputs that are not external integrations
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct OutputSettings {
+    /// Drive the RPM LEDs on the G27 wheel
+    pub wheel_leds: bool,
+}
+
+impl Default for OutputSettings {
+    fn default() -> Self {
+        Self { wheel_leds: true }
+    }
+}
+
+impl OutputSettings {
+    pub const KNOWN_KEYS: &'static [&'static str] = &["wheel_leds"];
+}
+
+/// A destination for LED state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Output {
+    WheelLeds,
+    /// External integration, by its `[integrations]` section name
+    Integration(&'static str),
+}
+
+impl Output {
+    /// Outputs that something in the bridge drives, in menu order
+    pub fn all() -> impl Iterator<Item = Output> {
+        std::iter::once(Output::WheelLeds)
+            .chain(IntegrationSettings::NAMES.into_iter().map(Output::Integration))
+            .filter(|output| output.component().is_some())
+    }
+
+    /// Human-readable name for menus and logs
+    pub fn label(&self) -> &'static str {
+        match self {
+            Output::WheelLeds => "G27 wheel LEDs",
+            Output::Integration("mqtt") => "MQTT",
+            Output::Integration("hue") => "Philips Hue",
+            Output::Integration("wled") => "WLED",
+            Output::Integration("openrgb") => "OpenRGB",
+            Output::Integration("osc") => "OSC",
+            Output::Integration(name) => name,
+        }
+    }
+
+    /// Supervised bridge component that drives this output and reports its status, if any
+    pub fn component(&self) -> Option<&'static str> {
+        match self {
+            Output::WheelLeds => Some("Wheel"),
+            Output::Integration("mqtt") => Some("MQTT"),
+            Output::Integration("osc") => Some("OSC"),
+            // The other integration backends do not report a status yet
+            Output::Integration(_) => None,
+        }
+    }
+}
@@ -0,0 +1,166 @@
+// Packet explorer for `capture-offsets`
+//
+// Helps find where an unsupported game keeps its RPM. The latest packet is shown as
+// 4-byte words with their offset, hex bytes and little-endian f32/u32 values. Bytes that
+// changed since the previous screen are highlighted, and each word counts the packets
+// it changed in, so the engine RPM shows up as a float that follows the throttle.
+
+use std::fmt::Write;
+
+const WORD: usize = 4;
+
+const HIGHLIGHT: &str = "\x1b[7m";
+const RESET: &str = "\x1b[0m";
+/// Move to the top left and clear the screen, to redraw in place
+const CLEAR: &str = "\x1b[H\x1b[J";
+
+/// Which words `render` shows
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExplorerFilter {
+    /// Hide words that never changed
+    pub changing: bool,
+    /// Hide words whose f32 value is below this
+    pub min: Option<f32>,
+    /// Hide words whose f32 value is above this
+    pub max: Option<f32>,
+}
+
+impl ExplorerFilter {
+    fn shows(&self, value: f32, changes: u64) -> bool {
+        (!self.changing || changes > 0)
+            && self.min.is_none_or(|min| value >= min)
+            && self.max.is_none_or(|max| value <= max)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct PacketExplorer {
+    latest: Vec<u8>,
+    /// Packet as of the previous screen
+    shown: Vec<u8>,
+    /// Per word, the number of packets it changed in
+    changes: Vec<u64>,
+    packets: u64,
+}
+
+impl PacketExplorer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Packets received so far
+    pub fn packets(&self) -> u64 {
+        self.packets
+    }
+
+    /// Take the next packet
+    pub fn observe(&mut self, packet: &[u8]) {
+        let words = packet.len().div_ceil(WORD);
+        if self.changes.len() < words {
+            self.changes.resize(words, 0);
+        }
+        if self.packets > 0 {
+            for (word, changes) in self.changes.iter_mut().enumerate().take(words) {
+                let range = word * WORD..((word + 1) * WORD).min(packet.len());
+                if self.latest.get(range.clone()) != Some(&packet[range]) {
+                    *changes += 1;
+                }
+            }
+        }
+        self.latest.clear();
+        self.latest.extend_from_slice(packet);
+        self.packets += 1;
+    }
+
+    /// Screen showing the latest packet, highlighting bytes that changed since the previous call
+    pub fn render(&mut self, filter: &ExplorerFilter, packets_per_second: f32) -> String {
+        let mut screen = String::from(CLEAR);
+        if self.packets == 0 {
+            screen.push_str("Waiting for packets...\n");
+            return screen;
+        }
+        let _ = writeln!(
+            screen,
+            "{} byte packets, {:.0}/s - bytes that changed since the last refresh are highlighted",
+            self.latest.len(),
+            packets_per_second
+        );
+        let _ = writeln!(screen, "Offset  {:<12}{:>18} {:>12} {:>9}", "Bytes", "f32 (LE)", "u32 (LE)", "Changes");
+
+        let mut hidden = 0;
+        for (word, bytes) in self.latest.chunks(WORD).enumerate() {
+            let offset = word * WORD;
+            let value = le_f32(bytes);
+            let changes = self.changes[word];
+            if !filter.shows(value, changes) {
+                hidden += 1;
+                continue;
+            }
+
+            let _ = write!(screen, "{:>6}  ", offset);
+            for (i, byte) in bytes.iter().enumerate() {
+                match self.shown.get(offset + i) {
+                    Some(old) if old == byte => {
+                        let _ = write!(screen, "{:02X} ", byte);
+                    }
+                    _ => {
+                        let _ = write!(screen, "{}{:02X}{} ", HIGHLIGHT, byte, RESET);
+                    }
+                }
+            }
+            screen.push_str(&"   ".repeat(WORD - bytes.len()));
+            let _ = writeln!(screen, "{:>18} {:>12} {:>9}", format_f32(value), le_u32(bytes), changes);
+        }
+        if hidden > 0 {
+            let _ = writeln!(screen, "({} offsets hidden by the filter)", hidden);
+        }
+
+        self.shown.clone_from(&self.latest);
+        screen
+    }
+}
+
+/// Little-endian f32 of a word; NaN for a partial word at the end of the packet
+fn le_f32(bytes: &[u8]) -> f32 {
+    bytes.try_into().map(f32::from_le_bytes).unwrap_or(f32::NAN)
+}
+
+fn le_u32(bytes: &[u8]) -> String {
+    match bytes.try_into() {
+        Ok(word) => u32::from_le_bytes(word).to_string(),
+        Err(_) => "-".to_string(),
+    }
+}
+
+/// Plain notation for values a game might send; other bit patterns are rarely floats
+fn format_f32(value: f32) -> String {
+    if value == 0.0 || (value.is_finite() && (1e-3..1e7).contains(&value.abs())) {
+        format!("{:.3}", value)
+    } else {
+        "-".to_string()
+    }
+}
+
+/// Let the console interpret the highlighting and redraw escape codes.
+/// Returns false if it cannot, e.g. on Windows before Windows 10.
+pub fn enable_ansi_escapes() -> bool {
+    #[cfg(windows)]
+    {
+        use winapi::um::consoleapi::{GetConsoleMode, SetConsoleMode};
+        use winapi::um::processenv::GetStdHandle;
+        use winapi::um::winbase::STD_OUTPUT_HANDLE;
+        use winapi::um::wincon::ENABLE_VIRTUAL_TERMINAL_PROCESSING;
+
+        unsafe {
+            let console = GetStdHandle(STD_OUTPUT_HANDLE);
+            let mut mode = 0;
+            GetConsoleMode(console, &mut mode) != 0
+                && SetConsoleMode(console, mode | ENABLE_VIRTUAL_TERMINAL_PROCESSING) != 0
+        }
+    }
+
+    #[cfg(not(windows))]
+    {
+        true
+    }
+}
@@ -0,0 +1,95 @@
+// Data directory resolution for G27 LED Bridge
+//
+// Decides where settings, logs and captures are stored:
+// - Installed mode: %APPDATA%\G27-LED-Bridge
+// - Portable mode: the directory containing the executable, enabled by a
+//   `portable.txt` marker next to the exe or the `--portable` flag
+// - Windows service: the directory of the user who installed it, passed on its command line
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+/// Marker file that switches the bridge into portable mode
+pub const PORTABLE_MARKER: &str = "portable.txt";
+
+const APP_DIR_NAME: &str = "G27-LED-Bridge";
+
+static PORTABLE_FLAG: AtomicBool = AtomicBool::new(false);
+
+static DATA_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Force portable mode for this process (the `--portable` flag)
+pub fn enable_portable_mode() {
+    PORTABLE_FLAG.store(true, Ordering::Relaxed);
+}
+
+/// Store data in `dir` for this process. The Windows service runs without the
+/// installing user's profile, so it is told where their settings are.
+pub fn use_data_dir(dir: PathBuf) {
+    let _ = DATA_DIR_OVERRIDE.set(dir);
+}
+
+/// Directory containing the running executable
+pub fn exe_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let exe = std::env::current_exe()?;
+    Ok(exe
+        .parent()
+        .ok_or("Executable has no parent directory")?
+        .to_path_buf())
+}
+
+/// Whether data is stored next to the executable instead of the user profile
+pub fn is_portable() -> bool {
+    PORTABLE_FLAG.load(Ordering::Relaxed)
+        || exe_dir()
+            .map(|dir| dir.join(PORTABLE_MARKER).exists())
+            .unwrap_or(false)
+}
+
+/// Directory for settings, logs and captures, created if missing
+pub fn data_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = if let Some(dir) = DATA_DIR_OVERRIDE.get() {
+        dir.clone()
+    } else if is_portable() {
+        exe_dir()?
+    } else {
+        let mut path = dirs::config_dir().ok_or("Could not find config directory")?;
+        path.push(APP_DIR_NAME);
+        path
+    };
+
+    if !path.exists() {
+        fs::create_dir_all(&path)?;
+    }
+
+    Ok(path)
+}
+
+/// Directory for the rotating log file, created if missing
+pub fn logs_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = data_dir()?.join("logs");
+    if !path.exists() {
+        fs::create_dir_all(&path)?;
+    }
+    Ok(path)
+}
+
+/// Directory for crash reports and minidumps, created if missing
+pub fn crashes_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = data_dir()?.join("crashes");
+    if !path.exists() {
+        fs::create_dir_all(&path)?;
+    }
+    Ok(path)
+}
+
+/// Directory for telemetry recordings started from the tray, created if missing
+pub fn recordings_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let path = data_dir()?.join("recordings");
+    if !path.exists() {
+        fs::create_dir_all(&path)?;
+    }
+    Ok(path)
+}
@@ -0,0 +1,164 @@
+// Sleep and resume for G27 LED Bridge
+//
+// Windows powers USB devices down when the PC goes to sleep, so a wheel handle opened
+// before is dead once it wakes up. The bridge follows the power state instead of failing
+// on the first write after resume:
+// - `watch` listens for WM_POWERBROADCAST on a hidden window of its own in tray and console
+//   mode; the service receives the same events from the service control manager and
+//   passes them to `report`
+// - While asleep the wheel component closes the G27 and waits; on resume it looks for the
+//   wheel again and reopens it
+//
+// A wheel that Windows puts into USB selective suspend while the PC stays awake fails
+// its writes instead, which `RetryingWheel` recovers from by reopening it.
+
+use std::sync::OnceLock;
+
+use log::info;
+use tokio::sync::watch;
+
+/// A change of the system power state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerEvent {
+    /// The PC is about to sleep or hibernate
+    Suspending,
+    /// The PC woke up again
+    Resumed,
+}
+
+/// Whether the PC is asleep, as last reported
+fn state() -> &'static watch::Sender<bool> {
+    static ASLEEP: OnceLock<watch::Sender<bool>> = OnceLock::new();
+    ASLEEP.get_or_init(|| watch::Sender::new(false))
+}
+
+/// Follow a power state change; resuming twice (after a wake by the user and automatic) is harmless
+pub fn report(event: PowerEvent) {
+    let asleep = event == PowerEvent::Suspending;
+    if state().send_replace(asleep) != asleep {
+        match event {
+            PowerEvent::Suspending => info!("Windows is going to sleep"),
+            PowerEvent::Resumed => info!("Windows resumed from sleep"),
+        }
+    }
+}
+
+/// Receiver that is `true` while the PC is asleep
+pub fn asleep() -> watch::Receiver<bool> {
+    state().subscribe()
+}
+
+/// Listening for power events; stops once dropped
+#[cfg_attr(not(windows), allow(dead_code))]
+pub struct PowerWatcher {
+    #[cfg(windows)]
+    thread_id: u32,
+}
+
+/// Report sleep and resume to `report` from a hidden window's message loop.
+/// Returns `None` if the window cannot be created.
+pub fn watch() -> Option<PowerWatcher> {
+    #[cfg(windows)]
+    {
+        win32::watch()
+    }
+
+    #[cfg(not(windows))]
+    {
+        None
+    }
+}
+
+#[cfg(windows)]
+impl Drop for PowerWatcher {
+    fn drop(&mut self) {
+        use winapi::um::winuser::{PostThreadMessageW, WM_QUIT};
+
+        unsafe { PostThreadMessageW(self.thread_id, WM_QUIT, 0, 0) };
+    }
+}
+
+#[cfg(windows)]
+mod win32 {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr::null_mut;
+    use std::sync::mpsc;
+    use std::thread;
+
+    use winapi::shared::minwindef::{LPARAM, LRESULT, TRUE, UINT, WPARAM};
+    use winapi::shared::windef::HWND;
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    use winapi::um::processthreadsapi::GetCurrentThreadId;
+    use winapi::um::winuser::*;
+
+    use super::{report, PowerEvent, PowerWatcher};
+
+    const CLASS_NAME: &str = "G27LedBridgePower";
+
+    fn wide(s: &str) -> Vec<u16> {
+        OsStr::new(s).encode_wide().chain(std::iter::once(0)).collect()
+    }
+
+    unsafe extern "system" fn window_proc(hwnd: HWND, msg: UINT, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        if msg != WM_POWERBROADCAST {
+            return DefWindowProcW(hwnd, msg, wparam, lparam);
+        }
+        match wparam {
+            PBT_APMSUSPEND => report(PowerEvent::Suspending),
+            PBT_APMRESUMEAUTOMATIC | PBT_APMRESUMESUSPEND => report(PowerEvent::Resumed),
+            _ => {}
+        }
+        TRUE as LRESULT
+    }
+
+    pub fn watch() -> Option<PowerWatcher> {
+        let (created_tx, created_rx) = mpsc::channel();
+        thread::spawn(move || unsafe {
+            let class_name = wide(CLASS_NAME);
+            let instance = GetModuleHandleW(null_mut());
+            let mut class: WNDCLASSEXW = std::mem::zeroed();
+            class.cbSize = std::mem::size_of::<WNDCLASSEXW>() as u32;
+            class.lpfnWndProc = Some(window_proc);
+            class.hInstance = instance;
+            class.lpszClassName = class_name.as_ptr();
+            RegisterClassExW(&class);
+
+            // A hidden top-level window: message-only windows do not receive broadcasts
+            let hwnd = CreateWindowExW(
+                0,
+                class_name.as_ptr(),
+                class_name.as_ptr(),
+                0,
+                0,
+                0,
+                0,
+                0,
+                null_mut(),
+                null_mut(),
+                instance,
+                null_mut(),
+            );
+            if hwnd.is_null() {
+                let _ = created_tx.send(Err(std::io::Error::last_os_error()));
+                return;
+            }
+            let _ = created_tx.send(Ok(GetCurrentThreadId()));
+
+            let mut msg: MSG = std::mem::zeroed();
+            while GetMessageW(&mut msg, null_mut(), 0, 0) > 0 {
+                DispatchMessageW(&msg);
+            }
+            DestroyWindow(hwnd);
+        });
+
+        match created_rx.recv() {
+            Ok(Ok(thread_id)) => Some(PowerWatcher { thread_id }),
+            Ok(Err(e)) => {
+                log::warn!("Cannot watch for sleep and resume - restart the bridge after the PC wakes up: {}", e);
+                None
+            }
+            Err(_) => None,
+        }
+    }
+}
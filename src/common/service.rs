@@ -0,0 +1,264 @@
+// Windows service mode for G27 LED Bridge
+//
+// `service install` registers the executable with the service control manager so the
+// bridge runs from boot, without anyone logging in (needs an administrator prompt).
+// The service runs `service run` with the installing user's data directory, so it
+// shares their settings file and reloads it when the tray or `config set` changes it.
+// A tray started while the service runs leaves the wheel and the port to the service.
+
+use std::sync::OnceLock;
+
+use tokio::sync::watch;
+
+use crate::common::paths;
+
+/// Name in the service control manager, e.g. for `sc query G27LedBridge`
+pub const SERVICE_NAME: &str = "G27LedBridge";
+#[cfg_attr(not(windows), allow(dead_code))]
+const DISPLAY_NAME: &str = "G27 LED Bridge";
+#[cfg_attr(not(windows), allow(dead_code))]
+const DESCRIPTION: &str = "Shows racing game telemetry on the Logitech G27 RPM LEDs";
+
+/// Runs the bridge until the receiver turns true; set by `run` for the service thread
+static BRIDGE: OnceLock<fn(watch::Receiver<bool>)> = OnceLock::new();
+
+/// Arguments the service control manager starts the executable with
+fn service_arguments() -> Result<Vec<std::ffi::OsString>, Box<dyn std::error::Error>> {
+    Ok(vec![
+        "service".into(),
+        "run".into(),
+        "--data-dir".into(),
+        paths::data_dir()?.into_os_string(),
+    ])
+}
+
+/// Register the service to start automatically with Windows
+pub fn install() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(windows)]
+    {
+        scm::install(service_arguments()?)?;
+        log::info!("Installed the {} service; start it with `service start`", SERVICE_NAME);
+        Ok(())
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = service_arguments()?;
+        Err("Windows services are only supported on Windows".into())
+    }
+}
+
+/// Stop the service if it runs and remove it
+pub fn uninstall() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(windows)]
+    {
+        scm::uninstall()?;
+        log::info!("Removed the {} service", SERVICE_NAME);
+        Ok(())
+    }
+
+    #[cfg(not(windows))]
+    {
+        Err("Windows services are only supported on Windows".into())
+    }
+}
+
+/// Start the installed service
+pub fn start() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(windows)]
+    {
+        scm::start()?;
+        log::info!("Started the {} service", SERVICE_NAME);
+        Ok(())
+    }
+
+    #[cfg(not(windows))]
+    {
+        Err("Windows services are only supported on Windows".into())
+    }
+}
+
+/// Stop the running service, which turns the LEDs off
+pub fn stop() -> Result<(), Box<dyn std::error::Error>> {
+    #[cfg(windows)]
+    {
+        scm::stop()?;
+        log::info!("Stopped the {} service", SERVICE_NAME);
+        Ok(())
+    }
+
+    #[cfg(not(windows))]
+    {
+        Err("Windows services are only supported on Windows".into())
+    }
+}
+
+/// Whether the service is installed and running
+pub fn is_running() -> bool {
+    #[cfg(windows)]
+    {
+        scm::is_running()
+    }
+
+    #[cfg(not(windows))]
+    {
+        false
+    }
+}
+
+/// Whether this process is the service, i.e. `run` was called
+pub fn is_service_process() -> bool {
+    BRIDGE.get().is_some()
+}
+
+/// Hand this process to the service control manager and run `bridge` until the
+/// service is stopped. Only works when started by the service control manager.
+pub fn run(bridge: fn(watch::Receiver<bool>)) -> Result<(), Box<dyn std::error::Error>> {
+    let _ = BRIDGE.set(bridge);
+
+    #[cfg(windows)]
+    {
+        scm::dispatch()?;
+        Ok(())
+    }
+
+    #[cfg(not(windows))]
+    {
+        Err("Windows services are only supported on Windows".into())
+    }
+}
+
+#[cfg(windows)]
+mod scm {
+    use std::ffi::{OsStr, OsString};
+    use std::time::Duration;
+
+    use log::{error, info};
+    use tokio::sync::watch;
+    use windows_service::service::{
+        PowerEventParam, ServiceAccess, ServiceControl, ServiceControlAccept, ServiceErrorControl, ServiceExitCode,
+        ServiceInfo, ServiceStartType, ServiceState, ServiceStatus, ServiceType,
+    };
+    use windows_service::service_control_handler::{self, ServiceControlHandlerResult};
+    use windows_service::service_manager::{ServiceManager, ServiceManagerAccess};
+    use windows_service::{define_windows_service, service_dispatcher};
+
+    use super::{BRIDGE, DESCRIPTION, DISPLAY_NAME, SERVICE_NAME};
+    use crate::common::power::{self, PowerEvent};
+
+    fn manager(access: ServiceManagerAccess) -> windows_service::Result<ServiceManager> {
+        ServiceManager::local_computer(None::<&str>, access)
+    }
+
+    pub fn install(arguments: Vec<OsString>) -> windows_service::Result<()> {
+        let info = ServiceInfo {
+            name: OsString::from(SERVICE_NAME),
+            display_name: OsString::from(DISPLAY_NAME),
+            service_type: ServiceType::OWN_PROCESS,
+            start_type: ServiceStartType::AutoStart,
+            error_control: ServiceErrorControl::Normal,
+            executable_path: std::env::current_exe().map_err(windows_service::Error::Winapi)?,
+            launch_arguments: arguments,
+            dependencies: Vec::new(),
+            // LocalSystem, which can open HID devices without a logged-in user
+            account_name: None,
+            account_password: None,
+        };
+        let manager = manager(ServiceManagerAccess::CONNECT | ServiceManagerAccess::CREATE_SERVICE)?;
+        let service = manager.create_service(&info, ServiceAccess::CHANGE_CONFIG)?;
+        service.set_description(DESCRIPTION)
+    }
+
+    pub fn uninstall() -> windows_service::Result<()> {
+        let manager = manager(ServiceManagerAccess::CONNECT)?;
+        let service = manager.open_service(
+            SERVICE_NAME,
+            ServiceAccess::QUERY_STATUS | ServiceAccess::STOP | ServiceAccess::DELETE,
+        )?;
+        if service.query_status()?.current_state != ServiceState::Stopped {
+            service.stop()?;
+        }
+        service.delete()
+    }
+
+    pub fn start() -> windows_service::Result<()> {
+        let manager = manager(ServiceManagerAccess::CONNECT)?;
+        let service = manager.open_service(SERVICE_NAME, ServiceAccess::START)?;
+        service.start::<&OsStr>(&[])
+    }
+
+    pub fn stop() -> windows_service::Result<()> {
+        let manager = manager(ServiceManagerAccess::CONNECT)?;
+        let service = manager.open_service(SERVICE_NAME, ServiceAccess::STOP)?;
+        service.stop().map(|_| ())
+    }
+
+    pub fn is_running() -> bool {
+        manager(ServiceManagerAccess::CONNECT)
+            .and_then(|manager| manager.open_service(SERVICE_NAME, ServiceAccess::QUERY_STATUS))
+            .and_then(|service| service.query_status())
+            .map(|status| status.current_state == ServiceState::Running)
+            .unwrap_or(false)
+    }
+
+    define_windows_service!(ffi_service_main, service_main);
+
+    pub fn dispatch() -> windows_service::Result<()> {
+        service_dispatcher::start(SERVICE_NAME, ffi_service_main)
+    }
+
+    fn status(state: ServiceState) -> ServiceStatus {
+        let controls_accepted = match state {
+            ServiceState::Running => {
+                ServiceControlAccept::STOP | ServiceControlAccept::SHUTDOWN | ServiceControlAccept::POWER_EVENT
+            }
+            _ => ServiceControlAccept::empty(),
+        };
+        ServiceStatus {
+            service_type: ServiceType::OWN_PROCESS,
+            current_state: state,
+            controls_accepted,
+            exit_code: ServiceExitCode::Win32(0),
+            checkpoint: 0,
+            wait_hint: Duration::default(),
+            process_id: None,
+        }
+    }
+
+    fn service_main(_arguments: Vec<OsString>) {
+        if let Err(e) = run_service() {
+            error!("Service failed: {}", e);
+        }
+    }
+
+    fn run_service() -> windows_service::Result<()> {
+        let (shutdown_tx, shutdown_rx) = watch::channel(false);
+        let handler = move |control| match control {
+            ServiceControl::Stop | ServiceControl::Shutdown => {
+                info!("Service stop requested");
+                shutdown_tx.send_replace(true);
+                ServiceControlHandlerResult::NoError
+            }
+            // Services get sleep and resume here rather than as window messages
+            ServiceControl::PowerEvent(event) => {
+                match event {
+                    PowerEventParam::Suspend => power::report(PowerEvent::Suspending),
+                    PowerEventParam::ResumeAutomatic | PowerEventParam::ResumeSuspend => {
+                        power::report(PowerEvent::Resumed)
+                    }
+                    _ => {}
+                }
+                ServiceControlHandlerResult::NoError
+            }
+            ServiceControl::Interrogate => ServiceControlHandlerResult::NoError,
+            _ => ServiceControlHandlerResult::NotImplemented,
+        };
+        let status_handle = service_control_handler::register(SERVICE_NAME, handler)?;
+
+        status_handle.set_service_status(status(ServiceState::Running))?;
+        if let Some(bridge) = BRIDGE.get() {
+            bridge(shutdown_rx);
+        }
+        status_handle.set_service_status(status(ServiceState::Stopped))
+    }
+}
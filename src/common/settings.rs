@@ -2,76 +2,496 @@
 // 
 // Handles automatic loading/saving of user preferences including:
 // - Game type selection (DiRT Rally 2.0, Forza Horizon 5)
-// - UDP port configuration
-// - Persistent storage to %APPDATA%\G27-LED-Bridge\settings.toml
+// - UDP port and bind address configuration, or a multicast group to join, and port sharing
+// - Telemetry over TCP, listening or connecting out, for tools that relay it as a stream
+// - Wheel search and reconnect/retry intervals
+// - Start with Windows
+// - Toast notifications for wheel, telemetry and error events
+// - Language of the tray menu, dialogs and notifications
+// - Opt-in update check at startup
+// - Log verbosity and optional log file
+// - Connection settings for external integrations
+// - The optional WebSocket server streaming telemetry to overlays, and its mDNS advertisement
+// - The optional local HTTP API for status and control, and the gRPC API
+// - Per-output enable switches
+// - LED profile and fill pattern
+// - Pause behaviour and the optional global pause hotkey
+// - Tray icon behaviour, such as mirroring the LED bar and the icon theme
+// - Exit confirmation and what closing the console window does
+// - Named profiles bundling game and port, with last-used tracking
+// - Persistent storage to %APPDATA%\G27-LED-Bridge\settings.toml (or next to the exe in portable mode)
+// - Schema versioning with in-place migration of older files
+// - Import/export of settings files for backup and sharing
+// - Validation with an actionable report instead of silent fallback to defaults
+// - Backup of corrupt files as settings.toml.bak-<timestamp> before restoring defaults
 // - CLI argument override support
 
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::PathBuf;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use log::{error, info};
 use serde::{Deserialize, Serialize};
+use g27_led_bridge_core::LengthPrefix;
+use crate::common::autostart;
+use crate::common::crash;
+use crate::common::grpc::GrpcSettings;
+use crate::common::hotkey::Hotkey;
+use crate::common::http_api::HttpApiSettings;
+use crate::common::i18n::Language;
+use crate::common::integrations::IntegrationSettings;
+use crate::common::leds::LedStyle;
+use crate::common::logging::{self, LogLevel};
+use crate::common::mdns::MdnsSettings;
+use crate::common::migrations::{self, SETTINGS_VERSION};
+use crate::common::outputs::{Output, OutputSettings};
+use crate::common::paths;
+use crate::common::settings_template;
 use crate::common::telemetry::GameType;
+use crate::common::util::DR2G27Error;
+use crate::common::validation::{self, SettingsReport};
+use crate::common::websocket::WebSocketSettings;
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Name of the profile created for new installs and migrated settings files
+pub const DEFAULT_PROFILE: &str = "Default";
+
+/// A named bundle of bridge settings that is switched as a unit
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Profile {
+    pub game_type: GameType,
+    /// Follow whichever game's packets arrive instead of `game_type`
+    #[serde(default)]
+    pub auto_detect_game: bool,
+    pub port: u16,
+    #[serde(default)]
+    pub outputs: OutputSettings,
+    #[serde(default)]
+    pub leds: LedStyle,
+}
+
+/// How long the bridge waits before retrying after a failure, in milliseconds
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct RetrySettings {
+    /// Interval between scans for the wheel while it is not connected
+    pub wheel_search_ms: u64,
+    /// Delay before reconnecting after the wheel connection was lost
+    pub reconnect_delay_ms: u64,
+    /// Delay before rebinding after a UDP socket error
+    pub socket_retry_delay_ms: u64,
+}
+
+impl RetrySettings {
+    /// Shortest accepted interval; anything lower would busy-loop
+    pub const MIN_MS: u64 = 100;
+    /// Longest accepted interval
+    pub const MAX_MS: u64 = 600_000;
+
+    pub fn wheel_search_interval(&self) -> Duration {
+        Duration::from_millis(self.wheel_search_ms)
+    }
+
+    pub fn reconnect_delay(&self) -> Duration {
+        Duration::from_millis(self.reconnect_delay_ms)
+    }
+
+    pub fn socket_retry_delay(&self) -> Duration {
+        Duration::from_millis(self.socket_retry_delay_ms)
+    }
+}
+
+impl Default for RetrySettings {
+    fn default() -> Self {
+        Self {
+            wheel_search_ms: 5000,
+            reconnect_delay_ms: 2000,
+            socket_retry_delay_ms: 5000,
+        }
+    }
+}
+
+/// Multicast group to receive telemetry from, for setups that send it to a group so several
+/// tools can listen; replaces the plain `bind_address` listener while `group` is set
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct MulticastSettings {
+    /// Group address, e.g. 239.255.20.77
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<IpAddr>,
+    /// Local IPv4 address of the network adapter to join on; the system picks one without it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interface: Option<IpAddr>,
+}
+
+/// Telemetry over TCP instead of UDP, for tools that relay it as a stream of packets each
+/// preceded by its length
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct TcpSettings {
+    pub mode: TcpMode,
+    /// `host:port` to connect to in `connect` mode
+    pub connect_to: String,
+    pub length_prefix: LengthPrefix,
+}
+
+impl TcpSettings {
+    pub const KNOWN_KEYS: &'static [&'static str] = &["mode", "connect_to", "length_prefix"];
+}
+
+/// How the bridge gets telemetry over TCP
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TcpMode {
+    /// Receive UDP datagrams, as the games send them
+    #[default]
+    Off,
+    /// Accept a connection on `bind_address:port`
+    Listen,
+    /// Connect to `connect_to`, again whenever the connection drops
+    Connect,
+}
+
+/// What pausing the bridge from the tray or hotkey does besides turning the LEDs off
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct PauseSettings {
+    /// Close the UDP port while paused so another program can use it
+    pub release_port: bool,
+    /// Global hotkey that pauses and resumes, e.g. "Ctrl+Alt+P"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hotkey: Option<Hotkey>,
+}
+
+/// What clicking the tray icon does; the menu stays on the right button
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ClickAction {
+    Nothing,
+    TogglePause,
+    /// Open the settings dialog
+    Settings,
+    ShowLog,
+}
+
+impl ClickAction {
+    pub fn name(self) -> &'static str {
+        match self {
+            ClickAction::Nothing => "nothing",
+            ClickAction::TogglePause => "toggle_pause",
+            ClickAction::Settings => "settings",
+            ClickAction::ShowLog => "show_log",
+        }
+    }
+}
+
+/// Colors of the tray icon around its LED bar, for the taskbar behind it
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum IconTheme {
+    /// Follow the Windows taskbar theme and high-contrast mode
+    #[default]
+    Auto,
+    /// Light frame for a dark taskbar
+    Dark,
+    /// Dark frame for a light taskbar
+    Light,
+    /// White frame around a taller bar
+    HighContrast,
+}
+
+impl IconTheme {
+    pub const ALL: [IconTheme; 4] = [IconTheme::Auto, IconTheme::Dark, IconTheme::Light, IconTheme::HighContrast];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            IconTheme::Auto => "auto",
+            IconTheme::Dark => "dark",
+            IconTheme::Light => "light",
+            IconTheme::HighContrast => "high_contrast",
+        }
+    }
+}
+
+/// How the tray icon behaves
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct TraySettings {
+    /// Draw the wheel's current LED bar into the tray icon while telemetry arrives
+    pub led_mirror: bool,
+    pub left_click: ClickAction,
+    pub double_click: ClickAction,
+    pub theme: IconTheme,
+}
+
+impl Default for TraySettings {
+    fn default() -> Self {
+        Self {
+            led_mirror: false,
+            left_click: ClickAction::TogglePause,
+            double_click: ClickAction::Settings,
+            theme: IconTheme::Auto,
+        }
+    }
+}
+
+/// What closing the console window does in `--console` mode
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ConsoleClose {
+    /// Exit like the tray's Exit entry
+    #[default]
+    Quit,
+    /// Keep running: the close button is disabled and minimizing hides the window to the tray
+    MinimizeToTray,
+}
+
+impl ConsoleClose {
+    pub fn name(self) -> &'static str {
+        match self {
+            ConsoleClose::Quit => "quit",
+            ConsoleClose::MinimizeToTray => "minimize_to_tray",
+        }
+    }
+}
+
+/// How the bridge is exited, so an accidental close mid-race does not turn the LEDs off
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct ExitSettings {
+    /// Ask before Exit in the tray menu stops the bridge
+    pub confirm: bool,
+    pub console_close: ConsoleClose,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
 pub struct AppSettings {
+    /// Settings schema version, used to migrate files written by older releases
+    #[serde(default)]
+    pub version: u32,
     pub game_type: GameType,
+    /// Switch `game_type` to whichever game's packets arrive
+    pub auto_detect_game: bool,
     pub port: u16,
+    /// Local address the UDP listener binds to; use 0.0.0.0 or :: to accept LAN telemetry
+    pub bind_address: IpAddr,
+    /// Senders accepted while listening on the LAN, e.g. the Xbox; empty accepts any sender
+    pub allowed_senders: Vec<IpAddr>,
+    /// Set SO_REUSEADDR so the port can be shared with other telemetry tools where the OS allows it
+    pub reuse_address: bool,
+    pub multicast: MulticastSettings,
+    pub tcp: TcpSettings,
+    pub retry: RetrySettings,
+    /// Start the bridge when the user logs in to Windows
+    pub autostart: bool,
+    /// Show toast notifications when the wheel connects or disconnects, telemetry starts or an error occurs
+    pub notifications: bool,
+    /// Language of the tray menu, dialogs and notifications
+    pub language: Language,
+    /// Look for a newer release on GitHub at startup
+    pub check_for_updates: bool,
+    /// 1 ms timer resolution and raised thread priority, for the fastest LED response
+    pub low_latency: bool,
+    /// Verbosity for console and log file output
+    pub log_level: LogLevel,
+    /// File that receives a copy of the log instead of logs\g27-led-bridge.log; relative paths are
+    /// resolved against the data directory
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_file: Option<PathBuf>,
+    /// Save a minidump with each crash report
+    pub crash_dumps: bool,
+    /// Add a summary of each race session to sessions.csv in the data directory
+    pub session_history: bool,
+    /// How RPM is shown on the wheel LEDs
+    pub leds: LedStyle,
+    pub pause: PauseSettings,
+    pub tray: TraySettings,
+    pub exit: ExitSettings,
+    pub outputs: OutputSettings,
+    pub integrations: IntegrationSettings,
+    pub websocket: WebSocketSettings,
+    pub mdns: MdnsSettings,
+    pub http_api: HttpApiSettings,
+    pub grpc: GrpcSettings,
+    /// Last-used profile; changes to the live settings are written back into it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
 }
 
 impl Default for AppSettings {
     fn default() -> Self {
+        let game_type = GameType::DirtRally2;
+        let port = game_type.default_port();
+
         Self {
-            game_type: GameType::DirtRally2,
-            port: GameType::DirtRally2.default_port(),
+            version: SETTINGS_VERSION,
+            game_type,
+            auto_detect_game: false,
+            port,
+            bind_address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            allowed_senders: Vec::new(),
+            reuse_address: false,
+            multicast: MulticastSettings::default(),
+            tcp: TcpSettings::default(),
+            retry: RetrySettings::default(),
+            autostart: false,
+            notifications: true,
+            language: Language::default(),
+            check_for_updates: false,
+            low_latency: false,
+            log_level: LogLevel::default(),
+            log_file: None,
+            crash_dumps: false,
+            session_history: false,
+            leds: LedStyle::default(),
+            pause: PauseSettings::default(),
+            tray: TraySettings::default(),
+            exit: ExitSettings::default(),
+            outputs: OutputSettings::default(),
+            integrations: IntegrationSettings::default(),
+            websocket: WebSocketSettings::default(),
+            mdns: MdnsSettings::default(),
+            http_api: HttpApiSettings::default(),
+            grpc: GrpcSettings::default(),
+            active_profile: Some(DEFAULT_PROFILE.to_string()),
+            profiles: BTreeMap::from([(
+                DEFAULT_PROFILE.to_string(),
+                Profile {
+                    game_type,
+                    auto_detect_game: false,
+                    port,
+                    outputs: OutputSettings::default(),
+                    leds: LedStyle::default(),
+                },
+            )]),
         }
     }
 }
 
 impl AppSettings {
-    /// Get the config file path in the user's AppData directory
+    /// Get the config file path in the data directory (AppData, or next to the exe when portable)
     pub fn config_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
-        let mut path = dirs::config_dir()
-            .ok_or("Could not find config directory")?;
-        path.push("G27-LED-Bridge");
-        
-        // Create directory if it doesn't exist
-        if !path.exists() {
-            fs::create_dir_all(&path)?;
-        }
-        
+        let mut path = paths::data_dir()?;
         path.push("settings.toml");
         Ok(path)
     }
     
-    /// Load settings from config file, or return defaults if file doesn't exist
+    /// Load settings from config file, or return defaults if file doesn't exist.
+    /// Problems found in the file are printed to the console.
     pub fn load() -> Self {
+        let (settings, report) = Self::load_with_report();
+        report.print();
+        settings
+    }
+    
+    /// Load settings and return a report of invalid or unknown entries.
+    /// Invalid values are replaced individually so the rest of the file still applies.
+    /// The loaded log level, log file and crash dump setting take effect immediately.
+    pub fn load_with_report() -> (Self, SettingsReport) {
+        let (settings, report) = Self::read_with_report();
+        logging::configure(settings.log_level, settings.log_file.as_deref());
+        crash::set_minidumps(settings.crash_dumps);
+        (settings, report)
+    }
+    
+    fn read_with_report() -> (Self, SettingsReport) {
+        let mut report = SettingsReport::default();
+        
         match Self::config_path() {
             Ok(path) => {
                 if path.exists() {
                     match fs::read_to_string(&path) {
                         Ok(contents) => {
-                            match toml::from_str(&contents) {
-                                Ok(settings) => {
-                                    println!("# Loaded settings from {:?}", path);
-                                    return settings;
+                            match Self::parse(&contents, &mut report) {
+                                Ok((settings, migrated)) => {
+                                    info!("Loaded settings from {:?}", path);
+                                    if migrated {
+                                        if let Err(e) = settings.save() {
+                                            error!("Failed to save migrated settings: {}", e);
+                                        }
+                                    }
+                                    return (settings, report);
                                 }
                                 Err(e) => {
-                                    eprintln!("# Error parsing settings file: {}", e);
+                                    report.errors.push(format!(
+                                        "{:?} is not valid TOML: {}",
+                                        path,
+                                        e.to_string().trim_end()
+                                    ));
+                                    Self::recover_corrupt_file(&path, &mut report);
                                 }
                             }
                         }
+                        Err(e) if e.kind() == std::io::ErrorKind::InvalidData => {
+                            report.errors.push(format!("{:?} is not a text file: {}", path, e));
+                            Self::recover_corrupt_file(&path, &mut report);
+                        }
                         Err(e) => {
-                            eprintln!("# Error reading settings file: {}", e);
+                            report.errors.push(format!("could not read {:?}: {}", path, e));
                         }
                     }
                 }
             }
             Err(e) => {
-                eprintln!("# Error accessing config directory: {}", e);
+                error!("Error accessing config directory: {}", e);
             }
         }
         
-        println!("# Using default settings");
-        Self::default()
+        info!("Using default settings");
+        (Self::default(), report)
+    }
+    
+    /// Move an unreadable settings file aside and write fresh defaults in its place,
+    /// so the next save cannot silently overwrite the user's original
+    fn recover_corrupt_file(path: &Path, report: &mut SettingsReport) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(format!(".bak-{}", timestamp));
+        let backup = PathBuf::from(backup);
+
+        if let Err(e) = fs::rename(path, &backup) {
+            report.errors.push(format!("could not back up {:?}: {}", path, e));
+            return;
+        }
+
+        match fs::write(path, settings_template::commented_defaults()) {
+            Ok(()) => report.errors.push(format!(
+                "the broken file was backed up to {:?} and replaced with default settings",
+                backup
+            )),
+            Err(e) => report.errors.push(format!(
+                "the broken file was backed up to {:?}, but writing default settings failed: {}",
+                backup, e
+            )),
+        }
+    }
+    
+    /// Parse settings file contents, migrating older schema versions and
+    /// repairing invalid values. Returns the settings and whether a migration was applied.
+    /// Only TOML syntax errors are fatal; everything else is recorded in `report`.
+    fn parse(contents: &str, report: &mut SettingsReport) -> Result<(Self, bool), Box<dyn std::error::Error>> {
+        let mut table: toml::Table = toml::from_str(contents)?;
+
+        let migrated = match migrations::migrate(&mut table) {
+            Ok(migrated) => migrated,
+            Err(e) => {
+                // Best effort: newer files may still contain everything we understand
+                report.warnings.push(e);
+                false
+            }
+        };
+
+        validation::check_unknown_keys(&table, report);
+        let mut settings = validation::deserialize_lenient(table, report);
+        validation::repair(&mut settings, report);
+        
+        Ok((settings, migrated))
     }
     
     /// Save settings to config file
@@ -79,27 +499,227 @@ impl AppSettings {
         let path = Self::config_path()?;
         let contents = toml::to_string_pretty(self)?;
         fs::write(&path, contents)?;
-        println!("# Settings saved to {:?}", path);
+        info!("Settings saved to {:?}", path);
+        Ok(())
+    }
+    
+    /// Write a fully commented default settings file, refusing to replace an existing one unless `force`
+    pub fn init_file(force: bool) -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let path = Self::config_path()?;
+        if path.exists() && !force {
+            return Err(format!("{:?} already exists (use --force to overwrite it)", path).into());
+        }
+        fs::write(&path, settings_template::commented_defaults())?;
+        info!("Wrote commented default settings to {:?}", path);
+        Ok(path)
+    }
+    
+    /// Write the current settings to an arbitrary file for backup or sharing
+    pub fn export_to(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        let contents = toml::to_string_pretty(self)?;
+        fs::write(path, contents)?;
+        info!("Settings exported to {:?}", path);
         Ok(())
     }
     
-    /// Update game type and save
+    /// Read, migrate and validate a settings file, then save it as the active configuration.
+    /// Unlike loading, any invalid value rejects the whole file.
+    pub fn import_from(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        let contents = fs::read_to_string(path)?;
+        let mut report = SettingsReport::default();
+        let (settings, _) = Self::parse(&contents, &mut report)?;
+        if report.has_errors() {
+            return Err(DR2G27Error::ConfigError(format!("{:?} has invalid settings:\n{}", path, report)).into());
+        }
+        report.print();
+        settings.save()?;
+        info!("Settings imported from {:?}", path);
+        Ok(settings)
+    }
+    
+    /// Update game type, turning auto-detection off, and save
     pub fn set_game_type(&mut self, game_type: GameType) {
         self.game_type = game_type;
+        self.auto_detect_game = false;
         // Update port to default for the new game if current port matches old game's default
         if self.port == self.game_type.default_port() {
             self.port = game_type.default_port();
         }
+        self.sync_active_profile();
         if let Err(e) = self.save() {
-            eprintln!("# Failed to save settings: {}", e);
+            error!("Failed to save settings: {}", e);
         }
     }
     
     /// Update port and save
     pub fn set_port(&mut self, port: u16) {
         self.port = port;
+        self.sync_active_profile();
+        if let Err(e) = self.save() {
+            error!("Failed to save settings: {}", e);
+        }
+    }
+    
+    /// Update game, port and bind address together and save once
+    pub fn set_listener(&mut self, game_type: GameType, port: u16, bind_address: IpAddr) {
+        self.game_type = game_type;
+        self.port = port;
+        self.bind_address = bind_address;
+        self.sync_active_profile();
+        if let Err(e) = self.save() {
+            error!("Failed to save settings: {}", e);
+        }
+    }
+    
+    /// Enable or disable detecting the game from its packets and save
+    pub fn set_auto_detect_game(&mut self, enabled: bool) {
+        self.auto_detect_game = enabled;
+        self.sync_active_profile();
+        if let Err(e) = self.save() {
+            error!("Failed to save settings: {}", e);
+        }
+    }
+    
+    /// Change how RPM is shown on the wheel LEDs and save
+    pub fn set_led_style(&mut self, style: LedStyle) {
+        self.leds = style;
+        self.sync_active_profile();
+        if let Err(e) = self.save() {
+            error!("Failed to save settings: {}", e);
+        }
+    }
+    
+    /// Change the tray icon theme and save
+    pub fn set_icon_theme(&mut self, theme: IconTheme) {
+        self.tray.theme = theme;
+        if let Err(e) = self.save() {
+            error!("Failed to save settings: {}", e);
+        }
+    }
+    
+    /// Enable or disable starting with Windows, update the startup entry and save
+    pub fn set_autostart(&mut self, enabled: bool) {
+        self.autostart = enabled;
+        autostart::sync(enabled);
+        if let Err(e) = self.save() {
+            error!("Failed to save settings: {}", e);
+        }
+    }
+    
+    /// Whether an output is switched on
+    pub fn output_enabled(&self, output: Output) -> bool {
+        match output {
+            Output::WheelLeds => self.outputs.wheel_leds,
+            Output::Integration(name) => self.integrations.is_enabled(name),
+        }
+    }
+    
+    /// Whether an output has everything it needs to be switched on
+    pub fn output_available(&self, output: Output) -> bool {
+        match output {
+            Output::WheelLeds => true,
+            Output::Integration(name) => self.integrations.missing_setting(name).is_none(),
+        }
+    }
+    
+    /// Switch an output on or off and save
+    pub fn set_output_enabled(&mut self, output: Output, enabled: bool) -> Result<(), String> {
+        if enabled && !self.output_available(output) {
+            return Err(format!(
+                "{} is not configured; fill in its [integrations] section first",
+                output.label()
+            ));
+        }
+        
+        match output {
+            Output::WheelLeds => self.outputs.wheel_leds = enabled,
+            Output::Integration(name) => {
+                if let Some(flag) = self.integrations.enabled_mut(name) {
+                    *flag = enabled;
+                }
+            }
+        }
+        info!("{} {}", output.label(), if enabled { "enabled" } else { "disabled" });
+        self.sync_active_profile();
+        
+        if let Err(e) = self.save() {
+            error!("Failed to save settings: {}", e);
+        }
+        Ok(())
+    }
+    
+    /// Names of all configured profiles, in sorted order
+    pub fn profile_names(&self) -> Vec<String> {
+        self.profiles.keys().cloned().collect()
+    }
+    
+    /// Switch to a named profile, remember it as last-used and save
+    pub fn apply_profile(&mut self, name: &str) -> Result<(), String> {
+        let profile = self.profiles.get(name).cloned().ok_or_else(|| {
+            format!(
+                "Unknown profile '{}'. Available profiles: {}",
+                name,
+                self.profile_names().join(", ")
+            )
+        })?;
+
+        self.game_type = profile.game_type;
+        self.auto_detect_game = profile.auto_detect_game;
+        self.port = profile.port;
+        self.outputs = profile.outputs;
+        self.leds = profile.leds;
+        self.active_profile = Some(name.to_string());
+        info!("Switched to profile '{}'", name);
+
         if let Err(e) = self.save() {
-            eprintln!("# Failed to save settings: {}", e);
+            error!("Failed to save settings: {}", e);
+        }
+        Ok(())
+    }
+    
+    /// Store the live settings as profile `name`, creating it if needed, and make it active; does not save
+    pub fn save_as_profile(&mut self, name: &str) {
+        self.profiles.insert(
+            name.to_string(),
+            Profile {
+                game_type: self.game_type,
+                auto_detect_game: self.auto_detect_game,
+                port: self.port,
+                outputs: self.outputs.clone(),
+                leds: self.leds,
+            },
+        );
+        self.active_profile = Some(name.to_string());
+    }
+    
+    /// Remove a profile, keeping at least one; does not save
+    pub fn remove_profile(&mut self, name: &str) -> Result<(), String> {
+        if !self.profiles.contains_key(name) {
+            return Err(format!("Unknown profile '{}'", name));
+        }
+        if self.profiles.len() == 1 {
+            return Err(format!("'{}' is the only profile and cannot be removed", name));
+        }
+        self.profiles.remove(name);
+        if self.active_profile.as_deref() == Some(name) {
+            self.active_profile = None;
+        }
+        info!("Removed profile '{}'", name);
+        Ok(())
+    }
+    
+    /// Copy the live settings into the active profile so it stays in sync
+    pub(crate) fn sync_active_profile(&mut self) {
+        if let Some(profile) = self
+            .active_profile
+            .as_ref()
+            .and_then(|name| self.profiles.get_mut(name))
+        {
+            profile.game_type = self.game_type;
+            profile.auto_detect_game = self.auto_detect_game;
+            profile.port = self.port;
+            profile.outputs = self.outputs.clone();
+            profile.leds = self.leds;
         }
     }
     
@@ -107,4 +727,25 @@ impl AppSettings {
     pub fn get_effective_port(&self, cli_port: Option<u16>) -> u16 {
         cli_port.unwrap_or(self.port)
     }
+    
+    /// Get the effective bind address (command line override or saved setting)
+    pub fn get_effective_bind_address(&self, cli_bind: Option<IpAddr>) -> IpAddr {
+        cli_bind.unwrap_or(self.bind_address)
+    }
+    
+    /// Socket address the telemetry listener binds to
+    pub fn listen_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.bind_address, self.port)
+    }
+    
+    /// Where telemetry is received, for display: the multicast group when one is joined, or
+    /// the TCP endpoint
+    pub fn listen_description(&self) -> String {
+        match (self.tcp.mode, self.multicast.group) {
+            (TcpMode::Listen, _) => format!("{} (TCP)", self.listen_addr()),
+            (TcpMode::Connect, _) => format!("{} (TCP client)", self.tcp.connect_to.trim()),
+            (TcpMode::Off, Some(group)) => format!("{} (multicast)", SocketAddr::new(group, self.port)),
+            (TcpMode::Off, None) => self.listen_addr().to_string(),
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,452 @@
+// Native settings dialog for G27 LED Bridge
+//
+// A small Win32 window for editing the everyday settings without touching the file:
+// - Profile picker: choosing a profile loads its game, port and LED style into the fields,
+//   typing a new name saves the fields as a new profile, and Delete removes the shown one
+// - Game selection drop-down
+// - UDP port and bind address fields, validated before closing
+// - LED profile and pattern drop-downs and the shift flash switch
+// - Opening with the port field focused, for the tray's "Change Port..." entry
+// - Labels and messages in the configured language
+//
+// Runs its own message loop, so call it from a dedicated thread rather than the
+// tray event loop. Advanced settings are still edited in settings.toml.
+
+use std::net::IpAddr;
+
+use crate::common::i18n::{tr, tr_with};
+use crate::common::leds::LedStyle;
+use crate::common::settings::AppSettings;
+use crate::common::telemetry::GameType;
+
+/// Values confirmed by the user
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DialogValues {
+    pub game_type: GameType,
+    pub port: u16,
+    pub bind_address: IpAddr,
+}
+
+impl DialogValues {
+    /// Check the values typed into the dialog or the dashboard, with a message for the first problem
+    pub fn parse(game_index: usize, port: &str, bind_address: &str) -> Result<Self, String> {
+        let game_type = *GameType::ALL.get(game_index).ok_or(tr("Select a game"))?;
+        let port = match port.trim().parse::<u16>() {
+            Ok(port) if port != 0 => port,
+            _ => return Err(tr("Port must be a number between 1 and 65535").to_string()),
+        };
+        let bind_address = bind_address.trim().parse::<IpAddr>().map_err(|_| {
+            tr_with(
+                "'{}' is not a valid IPv4 or IPv6 address (e.g. 127.0.0.1, 0.0.0.0 or ::)",
+                bind_address.trim(),
+            )
+        })?;
+
+        Ok(Self {
+            game_type,
+            port,
+            bind_address,
+        })
+    }
+}
+
+/// Everything confirmed in the dialog
+#[derive(Debug, Clone, PartialEq)]
+pub struct DialogResult {
+    pub values: DialogValues,
+    pub leds: LedStyle,
+    /// Profile the values are saved as and switched to, created if new; `None` leaves profiles alone
+    pub profile: Option<String>,
+    /// Profiles deleted with the Delete button
+    pub deleted_profiles: Vec<String>,
+}
+
+impl DialogResult {
+    /// Apply the result to `settings` and save once
+    pub fn apply(&self, settings: &mut AppSettings) {
+        for name in &self.deleted_profiles {
+            if let Err(e) = settings.remove_profile(name) {
+                log::warn!("{}", e);
+            }
+        }
+        settings.leds = self.leds;
+        if let Some(name) = &self.profile {
+            // The dialog does not show outputs; keep those of the chosen profile
+            if let Some(profile) = settings.profiles.get(name) {
+                settings.outputs = profile.outputs.clone();
+            }
+            settings.save_as_profile(name);
+        }
+        // Syncs the active profile and saves
+        settings.set_listener(self.values.game_type, self.values.port, self.values.bind_address);
+    }
+}
+
+/// Field that has the keyboard focus when the dialog opens
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DialogField {
+    #[default]
+    Game,
+    /// The port, with its text selected so typing replaces it
+    Port,
+}
+
+/// Show the dialog pre-filled from `current`; returns `None` if it was cancelled
+pub fn show(current: &AppSettings, focus: DialogField) -> Option<DialogResult> {
+    #[cfg(windows)]
+    {
+        win32::show(current, focus)
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = (current, focus);
+        log::warn!("The settings dialog is only available on Windows; use 'config set' instead");
+        None
+    }
+}
+
+#[cfg(windows)]
+mod win32 {
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use std::ptr::null_mut;
+
+    use winapi::shared::minwindef::{HIWORD, LOWORD, LPARAM, LRESULT, UINT, WPARAM};
+    use winapi::shared::windef::{HBRUSH, HMENU, HWND};
+    use winapi::um::libloaderapi::GetModuleHandleW;
+    use winapi::um::wingdi::{GetStockObject, DEFAULT_GUI_FONT};
+    use winapi::um::winuser::*;
+
+    use super::{DialogField, DialogResult, DialogValues};
+    use crate::common::i18n::tr;
+    use crate::common::leds::{FillPattern, LedProfile, LedStyle};
+    use crate::common::settings::{AppSettings, Profile};
+    use crate::common::systray::pattern_label;
+    use crate::common::telemetry::GameType;
+
+    const ID_GAME: i32 = 101;
+    const ID_PORT: i32 = 102;
+    const ID_BIND: i32 = 103;
+    const ID_PROFILE: i32 = 104;
+    const ID_DELETE: i32 = 105;
+    const ID_LED_PROFILE: i32 = 106;
+    const ID_PATTERN: i32 = 107;
+    const ID_SHIFT_FLASH: i32 = 108;
+
+    const CLASS_NAME: &str = "G27LedBridgeSettingsDialog";
+    const WIDTH: i32 = 340;
+    const HEIGHT: i32 = 350;
+
+    thread_local! {
+        static RESULT: RefCell<Option<DialogResult>> = const { RefCell::new(None) };
+        /// Profiles as listed in the picker, in the same order
+        static PROFILES: RefCell<BTreeMap<String, Profile>> = const { RefCell::new(BTreeMap::new()) };
+        static DELETED: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn wide(s: &str) -> Vec<u16> {
+        OsStr::new(s).encode_wide().chain(std::iter::once(0)).collect()
+    }
+
+    fn window_text(hwnd: HWND) -> String {
+        unsafe {
+            let len = GetWindowTextLengthW(hwnd);
+            let mut buffer = vec![0u16; len as usize + 1];
+            let copied = GetWindowTextW(hwnd, buffer.as_mut_ptr(), buffer.len() as i32);
+            String::from_utf16_lossy(&buffer[..copied.max(0) as usize])
+        }
+    }
+
+    unsafe fn create_control(
+        parent: HWND,
+        class: &str,
+        text: &str,
+        style: u32,
+        ex_style: u32,
+        (x, y, w, h): (i32, i32, i32, i32),
+        id: i32,
+    ) -> HWND {
+        let control = CreateWindowExW(
+            ex_style,
+            wide(class).as_ptr(),
+            wide(text).as_ptr(),
+            WS_CHILD | WS_VISIBLE | style,
+            x,
+            y,
+            w,
+            h,
+            parent,
+            id as usize as HMENU,
+            GetModuleHandleW(null_mut()),
+            null_mut(),
+        );
+        SendMessageW(control, WM_SETFONT, GetStockObject(DEFAULT_GUI_FONT as i32) as WPARAM, 1);
+        control
+    }
+
+    /// A drop-down list at row `y` holding `items`, with `selected` chosen
+    unsafe fn create_list(hwnd: HWND, y: i32, items: &[&str], selected: usize, id: i32) -> HWND {
+        let list = create_control(
+            hwnd,
+            "COMBOBOX",
+            "",
+            CBS_DROPDOWNLIST | WS_TABSTOP | WS_VSCROLL,
+            0,
+            (110, y - 4, 200, 120),
+            id,
+        );
+        for item in items {
+            SendMessageW(list, CB_ADDSTRING, 0, wide(item).as_ptr() as LPARAM);
+        }
+        SendMessageW(list, CB_SETCURSEL, selected, 0);
+        list
+    }
+
+    unsafe fn create_controls(hwnd: HWND, current: &AppSettings, focus: DialogField) {
+        create_control(hwnd, "STATIC", tr("Profile:"), 0, 0, (16, 20, 90, 20), -1);
+        let profile = create_control(
+            hwnd,
+            "COMBOBOX",
+            "",
+            CBS_DROPDOWN | CBS_AUTOHSCROLL | WS_TABSTOP | WS_VSCROLL,
+            0,
+            (110, 16, 120, 120),
+            ID_PROFILE,
+        );
+        for name in current.profiles.keys() {
+            SendMessageW(profile, CB_ADDSTRING, 0, wide(name).as_ptr() as LPARAM);
+        }
+        if let Some(active) = current.active_profile.as_ref() {
+            if let Some(index) = current.profiles.keys().position(|name| name == active) {
+                SendMessageW(profile, CB_SETCURSEL, index, 0);
+            }
+        }
+        create_control(hwnd, "BUTTON", tr("Delete"), BS_PUSHBUTTON | WS_TABSTOP, 0, (235, 15, 75, 24), ID_DELETE);
+
+        create_control(hwnd, "STATIC", tr("Game:"), 0, 0, (16, 56, 90, 20), -1);
+        let games: Vec<_> = GameType::ALL.iter().map(|game| game.parser().game_name()).collect();
+        let selected = GameType::ALL.iter().position(|g| *g == current.game_type).unwrap_or(0);
+        let game = create_list(hwnd, 56, &games, selected, ID_GAME);
+
+        create_control(hwnd, "STATIC", tr("UDP port:"), 0, 0, (16, 92, 90, 20), -1);
+        let port = create_control(
+            hwnd,
+            "EDIT",
+            &current.port.to_string(),
+            ES_NUMBER | ES_AUTOHSCROLL | WS_TABSTOP,
+            WS_EX_CLIENTEDGE,
+            (110, 88, 200, 22),
+            ID_PORT,
+        );
+
+        create_control(hwnd, "STATIC", tr("Bind address:"), 0, 0, (16, 128, 90, 20), -1);
+        create_control(
+            hwnd,
+            "EDIT",
+            &current.bind_address.to_string(),
+            ES_AUTOHSCROLL | WS_TABSTOP,
+            WS_EX_CLIENTEDGE,
+            (110, 124, 200, 22),
+            ID_BIND,
+        );
+
+        create_control(hwnd, "STATIC", tr("LED profile:"), 0, 0, (16, 164, 90, 20), -1);
+        let led_profiles: Vec<_> = LedProfile::ALL.iter().map(LedProfile::name).collect();
+        let selected = LedProfile::ALL.iter().position(|p| *p == current.leds.profile).unwrap_or(0);
+        create_list(hwnd, 164, &led_profiles, selected, ID_LED_PROFILE);
+
+        create_control(hwnd, "STATIC", tr("LED pattern:"), 0, 0, (16, 200, 90, 20), -1);
+        let patterns: Vec<_> = FillPattern::ALL.into_iter().map(|pattern| tr(pattern_label(pattern))).collect();
+        let selected = FillPattern::ALL.iter().position(|p| *p == current.leds.pattern).unwrap_or(0);
+        create_list(hwnd, 200, &patterns, selected, ID_PATTERN);
+
+        let shift_flash = create_control(
+            hwnd,
+            "BUTTON",
+            tr("Shift Flash"),
+            BS_AUTOCHECKBOX | WS_TABSTOP,
+            0,
+            (110, 234, 200, 20),
+            ID_SHIFT_FLASH,
+        );
+        SendMessageW(shift_flash, BM_SETCHECK, current.leds.shift_flash as WPARAM, 0);
+
+        create_control(hwnd, "BUTTON", "OK", BS_DEFPUSHBUTTON | WS_TABSTOP, 0, (130, 272, 85, 26), IDOK);
+        create_control(hwnd, "BUTTON", tr("Cancel"), BS_PUSHBUTTON | WS_TABSTOP, 0, (225, 272, 85, 26), IDCANCEL);
+
+        match focus {
+            DialogField::Game => {
+                SetFocus(game);
+            }
+            DialogField::Port => {
+                SetFocus(port);
+                SendMessageW(port, EM_SETSEL, 0, -1);
+            }
+        }
+    }
+
+    unsafe fn show_warning(hwnd: HWND, message: &str) {
+        MessageBoxW(
+            hwnd,
+            wide(message).as_ptr(),
+            wide(tr("Invalid setting")).as_ptr(),
+            MB_OK | MB_ICONWARNING,
+        );
+    }
+
+    /// Index selected in the drop-down list `id`, 0 if none
+    unsafe fn selected_index(hwnd: HWND, id: i32) -> usize {
+        SendMessageW(GetDlgItem(hwnd, id), CB_GETCURSEL, 0, 0).max(0) as usize
+    }
+
+    /// Load the profile at `index` of the picker into the fields
+    unsafe fn show_profile(hwnd: HWND, index: usize) {
+        let Some(profile) = PROFILES.with(|profiles| profiles.borrow().values().nth(index).cloned()) else {
+            return;
+        };
+        let game = GameType::ALL.iter().position(|g| *g == profile.game_type).unwrap_or(0);
+        SendMessageW(GetDlgItem(hwnd, ID_GAME), CB_SETCURSEL, game, 0);
+        SetWindowTextW(GetDlgItem(hwnd, ID_PORT), wide(&profile.port.to_string()).as_ptr());
+        let led_profile = LedProfile::ALL.iter().position(|p| *p == profile.leds.profile).unwrap_or(0);
+        SendMessageW(GetDlgItem(hwnd, ID_LED_PROFILE), CB_SETCURSEL, led_profile, 0);
+        let pattern = FillPattern::ALL.iter().position(|p| *p == profile.leds.pattern).unwrap_or(0);
+        SendMessageW(GetDlgItem(hwnd, ID_PATTERN), CB_SETCURSEL, pattern, 0);
+        SendMessageW(GetDlgItem(hwnd, ID_SHIFT_FLASH), BM_SETCHECK, profile.leds.shift_flash as WPARAM, 0);
+    }
+
+    /// Delete the profile named in the picker, keeping at least one
+    unsafe fn delete_profile(hwnd: HWND) {
+        let picker = GetDlgItem(hwnd, ID_PROFILE);
+        let name = window_text(picker).trim().to_string();
+        let (index, remaining) = PROFILES.with(|profiles| {
+            let profiles = profiles.borrow();
+            (profiles.keys().position(|profile| *profile == name), profiles.len())
+        });
+        let Some(index) = index else {
+            return;
+        };
+        if remaining == 1 {
+            show_warning(hwnd, tr("The last profile cannot be deleted"));
+            return;
+        }
+
+        PROFILES.with(|profiles| profiles.borrow_mut().remove(&name));
+        DELETED.with(|deleted| deleted.borrow_mut().push(name));
+        SendMessageW(picker, CB_DELETESTRING, index, 0);
+        SendMessageW(picker, CB_SETCURSEL, 0, 0);
+        show_profile(hwnd, 0);
+    }
+
+    unsafe fn confirm(hwnd: HWND) {
+        let game_index = selected_index(hwnd, ID_GAME);
+        let port = window_text(GetDlgItem(hwnd, ID_PORT));
+        let bind_address = window_text(GetDlgItem(hwnd, ID_BIND));
+        let leds = LedStyle {
+            profile: LedProfile::ALL[selected_index(hwnd, ID_LED_PROFILE).min(LedProfile::ALL.len() - 1)],
+            pattern: FillPattern::ALL[selected_index(hwnd, ID_PATTERN).min(FillPattern::ALL.len() - 1)],
+            shift_flash: SendMessageW(GetDlgItem(hwnd, ID_SHIFT_FLASH), BM_GETCHECK, 0, 0) == BST_CHECKED as LRESULT,
+        };
+        let profile = window_text(GetDlgItem(hwnd, ID_PROFILE)).trim().to_string();
+
+        match DialogValues::parse(game_index, &port, &bind_address) {
+            Ok(values) => {
+                let result = DialogResult {
+                    values,
+                    leds,
+                    profile: (!profile.is_empty()).then_some(profile),
+                    deleted_profiles: DELETED.with(|deleted| deleted.take()),
+                };
+                RESULT.with(|slot| *slot.borrow_mut() = Some(result));
+                DestroyWindow(hwnd);
+            }
+            Err(message) => show_warning(hwnd, &message),
+        }
+    }
+
+    unsafe extern "system" fn window_proc(hwnd: HWND, msg: UINT, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+        match msg {
+            WM_COMMAND => {
+                match LOWORD(wparam as u32) as i32 {
+                    ID_PROFILE if HIWORD(wparam as u32) == CBN_SELCHANGE => {
+                        show_profile(hwnd, selected_index(hwnd, ID_PROFILE));
+                    }
+                    ID_DELETE => delete_profile(hwnd),
+                    IDOK => confirm(hwnd),
+                    IDCANCEL => {
+                        DestroyWindow(hwnd);
+                    }
+                    _ => {}
+                }
+                0
+            }
+            WM_CLOSE => {
+                DestroyWindow(hwnd);
+                0
+            }
+            WM_DESTROY => {
+                PostQuitMessage(0);
+                0
+            }
+            _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+        }
+    }
+
+    pub fn show(current: &AppSettings, focus: DialogField) -> Option<DialogResult> {
+        RESULT.with(|result| *result.borrow_mut() = None);
+        PROFILES.with(|profiles| *profiles.borrow_mut() = current.profiles.clone());
+        DELETED.with(|deleted| deleted.borrow_mut().clear());
+
+        unsafe {
+            let instance = GetModuleHandleW(null_mut());
+            let class_name = wide(CLASS_NAME);
+
+            let mut class: WNDCLASSEXW = std::mem::zeroed();
+            class.cbSize = std::mem::size_of::<WNDCLASSEXW>() as u32;
+            class.lpfnWndProc = Some(window_proc);
+            class.hInstance = instance;
+            class.hCursor = LoadCursorW(null_mut(), IDC_ARROW);
+            class.hbrBackground = (COLOR_BTNFACE + 1) as usize as HBRUSH;
+            class.lpszClassName = class_name.as_ptr();
+            // Fails harmlessly if the class is already registered from an earlier dialog
+            RegisterClassExW(&class);
+
+            let x = (GetSystemMetrics(SM_CXSCREEN) - WIDTH) / 2;
+            let y = (GetSystemMetrics(SM_CYSCREEN) - HEIGHT) / 2;
+            let hwnd = CreateWindowExW(
+                WS_EX_DLGMODALFRAME | WS_EX_TOPMOST,
+                class_name.as_ptr(),
+                wide(tr("G27 LED Bridge Settings")).as_ptr(),
+                WS_CAPTION | WS_SYSMENU | WS_VISIBLE,
+                x,
+                y,
+                WIDTH,
+                HEIGHT,
+                null_mut(),
+                null_mut(),
+                instance,
+                null_mut(),
+            );
+            if hwnd.is_null() {
+                log::error!("Failed to create settings dialog");
+                return None;
+            }
+
+            create_controls(hwnd, current, focus);
+            SetForegroundWindow(hwnd);
+
+            let mut msg: MSG = std::mem::zeroed();
+            while GetMessageW(&mut msg, null_mut(), 0, 0) > 0 {
+                // Handles Tab, Enter (default button) and Escape (IDCANCEL)
+                if IsDialogMessageW(hwnd, &mut msg) == 0 {
+                    TranslateMessage(&msg);
+                    DispatchMessageW(&msg);
+                }
+            }
+        }
+
+        RESULT.with(|result| result.borrow_mut().take())
+    }
+}
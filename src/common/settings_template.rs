@@ -0,0 +1,295 @@
+// Commented default settings file for `config init`
+//
+// Documents every option with its default value so users can discover features
+// from the file itself. Values are filled in from `AppSettings::default()` so the
+// template cannot drift from the real defaults.
+
+use crate::common::settings::{AppSettings, RetrySettings, DEFAULT_PROFILE};
+use crate::common::telemetry::GameType;
+
+/// Render a fully commented settings.toml containing the default settings
+pub fn commented_defaults() -> String {
+    let defaults = AppSettings::default();
+    let retry = &defaults.retry;
+    let integrations = &defaults.integrations;
+    let log_level = format!("{:?}", defaults.log_level).to_lowercase();
+
+    format!(
+        r#"# G27 LED Bridge settings
+#
+# Changes are picked up automatically while the bridge is running.
+# Values set with --flags or G27LB_* environment variables override this file.
+# Note: saving from the tray or `config set` rewrites this file without comments.
+
+# Settings schema version, used to migrate files from older releases. Do not edit.
+version = {version}
+
+# Game to read telemetry from: "DirtRally2" or "ForzaHorizon5"
+game_type = "{game_type:?}"
+
+# Switch game_type to whichever game's telemetry arrives
+auto_detect_game = {auto_detect_game}
+
+# UDP port the game sends telemetry to.
+# Defaults: DiRT Rally 2.0 = {dr2_port}, Forza Horizon 5 = {fh5_port}
+port = {port}
+
+# Local address to listen on. 127.0.0.1 only accepts telemetry from this PC;
+# use "0.0.0.0" (IPv4) or "::" (IPv6) for a game running on an Xbox or another PC.
+bind_address = "{bind_address}"
+
+# With a LAN bind address, only accept telemetry from these senders, e.g. ["192.168.1.50"]
+# for the Xbox; packets from anyone else are dropped and counted. Empty accepts any sender.
+allowed_senders = []
+
+# Share the port with other telemetry tools that also allow it (SO_REUSEADDR), instead of
+# failing with "port in use". On Windows each unicast packet then reaches only one of the
+# programs, so this mainly helps with [multicast] or broadcast telemetry.
+reuse_address = {reuse_address}
+
+# Start the bridge when you log in to Windows
+autostart = {autostart}
+
+# Show Windows notifications when the wheel connects or disconnects, telemetry starts
+# arriving, or a problem such as the port being in use occurs
+notifications = {notifications}
+
+# Language of the tray menu, dialogs and notifications: "auto" (the Windows display
+# language), "en", "de", "fr" or "es". Applies fully after a restart.
+language = "{language}"
+
+# Look for a newer release on GitHub at startup and mark "About" in the tray menu when
+# there is one. Off by default; "Check for Updates..." in the tray always works.
+check_for_updates = {check_for_updates}
+
+# Low-latency mode: request 1 ms Windows timer resolution and raise the bridge's thread
+# priority, so the LEDs follow each telemetry packet within a frame (e.g. when you shift on
+# the red LED). Uses a little more power. Applies after a restart.
+low_latency = {low_latency}
+
+# Log verbosity: "error", "warn", "info", "debug" or "trace"
+log_level = "{log_level}"
+
+# The log is also written to logs\g27-led-bridge.log next to settings.toml, rotated at 1 MB.
+# Write it to another file instead (relative paths are stored next to settings.toml)
+# log_file = "g27-led-bridge.log"
+
+# A crash writes a report to the crashes folder next to settings.toml. Also save a minidump
+# with it, which helps find crashes inside drivers (a few MB each)
+crash_dumps = {crash_dumps}
+
+# A summary of each race (length, top RPM, time in the shift range, packets lost) is logged
+# when it ends. Also add it to sessions.csv next to settings.toml, e.g. for a spreadsheet
+session_history = {session_history}
+
+# Profile applied at startup; changes made while it is active are saved into it
+active_profile = "{profile}"
+
+# Join a multicast group instead of listening on bind_address, for setups that send telemetry
+# to a group so several tools can receive it. Uses `port` above. An IPv4 group can be joined
+# on a chosen network adapter by its local address; otherwise Windows picks one.
+[multicast]
+# group = "239.255.20.77"
+# interface = "192.168.1.20"
+
+# Receive telemetry over TCP instead of UDP, from tools that relay it as a stream with each
+# packet preceded by its length as a little-endian integer ("u16" or "u32").
+# mode: "off" (UDP), "listen" (accept a connection on bind_address:port) or "connect"
+# (connect to connect_to, as host:port, and reconnect when the connection drops)
+[tcp]
+mode = "off"
+connect_to = ""
+length_prefix = "u32"
+
+# How long to wait before retrying, in milliseconds ({min_ms} - {max_ms})
+[retry]
+# Interval between scans for the wheel while it is not connected
+wheel_search_ms = {wheel_search_ms}
+# Wait after the wheel connection is lost
+reconnect_delay_ms = {reconnect_delay_ms}
+# Wait after a UDP socket error before binding again
+socket_retry_delay_ms = {socket_retry_delay_ms}
+
+# How RPM is shown on the wheel LEDs; try others for one run with --led-profile and --pattern
+[leds]
+# Where the bar lights up: "relaxed" (from 30% of the way from idle to max RPM),
+# "standard" (from halfway) or "aggressive" (from 70%)
+profile = "{led_profile}"
+# Order the LEDs light up in: "progressive" (green to red), "center-out" (red to green)
+# or "dot" (a single pair moving in)
+pattern = "{led_pattern}"
+# Flash the whole bar close to max RPM, as a shift light
+shift_flash = {shift_flash}
+
+# Pausing from the tray (or the hotkey) turns the LEDs off until resumed. It is not saved.
+[pause]
+# Also close the UDP port while paused, so another telemetry app can use it
+release_port = {release_port}
+# Global hotkey that pauses and resumes, e.g. "Ctrl+Alt+P" or "Ctrl+Shift+F9"
+# (modifiers Ctrl, Alt, Shift, Win; keys A-Z, 0-9, F1-F24, Pause, ScrollLock, Insert, Home, End)
+# hotkey = "Ctrl+Alt+P"
+
+[tray]
+# Show the wheel's LED bar in the tray icon while telemetry arrives, e.g. for streaming
+# or trying out LED profiles; otherwise the icon color shows the bridge state
+led_mirror = {led_mirror}
+# What clicking the icon does: "toggle_pause", "settings" (the settings dialog),
+# "show_log" or "nothing"; the menu is always on the right button
+left_click = "{left_click}"
+double_click = "{double_click}"
+# Icon colors: "auto" follows the taskbar (light or dark) and high-contrast mode; or pick
+# "dark" (light frame for a dark taskbar), "light" (dark frame) or "high_contrast"
+theme = "{icon_theme}"
+
+[exit]
+# Ask before Exit in the tray menu stops the bridge and turns the LEDs off
+confirm = {exit_confirm}
+# What closing the console window does with --console: "quit", or "minimize_to_tray" to
+# disable its close button and hide the window to the tray when it is minimized
+# (Show Console in the tray menu brings it back)
+console_close = "{console_close}"
+
+# Outputs that receive the LED state; integrations are switched in their own sections
+[outputs]
+# Drive the RPM LEDs on the G27 wheel
+wheel_leds = {wheel_leds}
+
+# Connections to external services. Each integration is off until `enabled = true`
+# and its address is filled in. Credentials are stored in plain text in this file.
+[integrations.mqtt]
+enabled = {mqtt_enabled}
+host = ""
+port = {mqtt_port}
+username = ""
+password = ""
+# Topic prefix: publishes <topic>/rpm (0.000 - 1.000 of max RPM), <topic>/gear,
+# <topic>/shift (1 in the shift range, 0 out of it) and <topic>/status (online/offline)
+topic = "{mqtt_topic}"
+
+[integrations.hue]
+enabled = {hue_enabled}
+# Bridge IP address or host name
+bridge = ""
+# Application key created by pressing the bridge link button
+app_key = ""
+
+[integrations.wled]
+enabled = {wled_enabled}
+host = ""
+# Realtime UDP port from WLED's sync settings
+port = {wled_port}
+
+[integrations.openrgb]
+enabled = {openrgb_enabled}
+# OpenRGB SDK server as host:port
+address = "{openrgb_address}"
+
+# OSC for lighting consoles, TouchOSC layouts and VJ software: sends <prefix>/rpm
+# (0.0 - 1.0 of max RPM), <prefix>/gear and <prefix>/shift (1 entering the shift range, 0 leaving)
+[integrations.osc]
+enabled = {osc_enabled}
+host = ""
+port = {osc_port}
+prefix = "{osc_prefix}"
+
+# WebSocket server streaming each telemetry frame and the LED bar as JSON, for browser
+# overlays, OBS browser sources and phone dashboards; connect to ws://<bind_address>:<port>.
+# Use bind_address = "0.0.0.0" to reach it from other devices (there is no authentication).
+[websocket]
+enabled = {websocket_enabled}
+bind_address = "{websocket_bind_address}"
+port = {websocket_port}
+
+# Announce the WebSocket server on the LAN as a _g27bridge._tcp service, so companion apps
+# find it without an IP address. Needs [websocket] bind_address = "0.0.0.0" (or this PC's
+# address); name is what apps list, the computer name when empty.
+[mdns]
+enabled = {mdns_enabled}
+name = ""
+
+# Local HTTP API for stream decks and automation tools, on http://127.0.0.1:<port> only:
+# GET /status, POST /pause, POST /resume and POST /profile/<name>. GET / is a dashboard
+# with the LED bar, RPM and gear for a phone on the rig; lan_dashboard = true lets phones
+# reach it at http://<this PC's address>:<port>/ (commands still only from this PC).
+[http_api]
+enabled = {http_api_enabled}
+port = {http_api_port}
+lan_dashboard = {http_api_lan_dashboard}
+
+# gRPC API for other applications on this PC, on 127.0.0.1:<port>; see proto/g27_bridge.proto.
+# Only in builds with the `grpc` feature.
+[grpc]
+enabled = {grpc_enabled}
+port = {grpc_port}
+
+# Named profiles bundle a game (or auto-detection), port, outputs and LED style (keys as
+# in [outputs] and [leds]); switch with --profile <name> or the tray.
+# Quote names containing spaces: [profiles."Forza couch"]
+[profiles.{profile}]
+game_type = "{game_type:?}"
+auto_detect_game = {auto_detect_game}
+port = {port}
+
+[profiles.{profile}.outputs]
+wheel_leds = {wheel_leds}
+
+[profiles.{profile}.leds]
+profile = "{led_profile}"
+pattern = "{led_pattern}"
+shift_flash = {shift_flash}
+"#,
+        version = defaults.version,
+        game_type = defaults.game_type,
+        auto_detect_game = defaults.auto_detect_game,
+        dr2_port = GameType::DirtRally2.default_port(),
+        fh5_port = GameType::ForzaHorizon5.default_port(),
+        port = defaults.port,
+        bind_address = defaults.bind_address,
+        reuse_address = defaults.reuse_address,
+        autostart = defaults.autostart,
+        notifications = defaults.notifications,
+        language = defaults.language.code(),
+        check_for_updates = defaults.check_for_updates,
+        low_latency = defaults.low_latency,
+        log_level = log_level,
+        crash_dumps = defaults.crash_dumps,
+        session_history = defaults.session_history,
+        led_profile = defaults.leds.profile,
+        led_pattern = defaults.leds.pattern,
+        shift_flash = defaults.leds.shift_flash,
+        release_port = defaults.pause.release_port,
+        led_mirror = defaults.tray.led_mirror,
+        left_click = defaults.tray.left_click.name(),
+        double_click = defaults.tray.double_click.name(),
+        icon_theme = defaults.tray.theme.name(),
+        exit_confirm = defaults.exit.confirm,
+        console_close = defaults.exit.console_close.name(),
+        wheel_leds = defaults.outputs.wheel_leds,
+        mqtt_enabled = integrations.mqtt.enabled,
+        mqtt_port = integrations.mqtt.port,
+        mqtt_topic = integrations.mqtt.topic,
+        hue_enabled = integrations.hue.enabled,
+        wled_enabled = integrations.wled.enabled,
+        wled_port = integrations.wled.port,
+        openrgb_enabled = integrations.openrgb.enabled,
+        openrgb_address = integrations.openrgb.address,
+        osc_enabled = integrations.osc.enabled,
+        osc_port = integrations.osc.port,
+        osc_prefix = integrations.osc.prefix,
+        websocket_enabled = defaults.websocket.enabled,
+        websocket_bind_address = defaults.websocket.bind_address,
+        websocket_port = defaults.websocket.port,
+        mdns_enabled = defaults.mdns.enabled,
+        http_api_enabled = defaults.http_api.enabled,
+        http_api_port = defaults.http_api.port,
+        http_api_lan_dashboard = defaults.http_api.lan_dashboard,
+        grpc_enabled = defaults.grpc.enabled,
+        grpc_port = defaults.grpc.port,
+        profile = DEFAULT_PROFILE,
+        min_ms = RetrySettings::MIN_MS,
+        max_ms = RetrySettings::MAX_MS,
+        wheel_search_ms = retry.wheel_search_ms,
+        reconnect_delay_ms = retry.reconnect_delay_ms,
+        socket_retry_delay_ms = retry.socket_retry_delay_ms,
+    )
+}
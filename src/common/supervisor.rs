@@ -0,0 +1,180 @@
+// Supervisor for bridge components
+//
+// Each part of the bridge (telemetry listener, wheel LEDs, and later servers and
+// integrations) runs as its own component:
+// - A failed component is restarted on its own with exponential backoff while the
+//   others keep running
+// - A component that returns cleanly (e.g. for a settings change) restarts right away
+// - A component that panics is treated as failed, so a bug in one part is reported in
+//   the tray instead of taking the whole bridge thread down
+// - Status changes are logged and sent to the tray for its per-component status lines
+
+use std::cell::RefCell;
+use std::fmt;
+use std::future::{self, Future};
+use std::task::Poll;
+use std::time::{Duration, Instant};
+
+use log::{debug, info, warn};
+use tokio::sync::watch;
+
+use crate::common::util::{catch_unwind, panic_message, DR2G27Error, DR2G27Result};
+use crate::common::waker::WakingSender;
+
+/// Upper bound for restart delays, unless a component's initial delay is longer
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// A component that ran at least this long before failing starts its backoff over
+const HEALTHY_RUN: Duration = Duration::from_secs(30);
+
+/// What a component is doing, as shown in the tray
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComponentStatus {
+    Starting,
+    /// Working normally, e.g. "Connected"
+    Running(String),
+    /// Up but waiting on something outside the bridge, e.g. the wheel being plugged in
+    Waiting(String),
+    /// Failed; the next attempt starts after `delay`
+    Restarting { error: String, delay: Duration },
+    Stopped,
+}
+
+impl fmt::Display for ComponentStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComponentStatus::Starting => write!(f, "Starting..."),
+            ComponentStatus::Running(detail) => write!(f, "{} ✓", detail),
+            ComponentStatus::Waiting(detail) => write!(f, "{}", detail),
+            ComponentStatus::Restarting { error, delay } => {
+                write!(f, "Error - {} (retrying in {:.1}s)", error, delay.as_secs_f32())
+            }
+            ComponentStatus::Stopped => write!(f, "Stopped"),
+        }
+    }
+}
+
+/// Status update sent to the tray: component name and its new status
+pub type StatusUpdate = (&'static str, ComponentStatus);
+
+/// Reports one component's status changes
+pub struct StatusReporter {
+    name: &'static str,
+    sender: WakingSender<StatusUpdate>,
+    last: RefCell<Option<ComponentStatus>>,
+}
+
+impl StatusReporter {
+    /// Log and forward `status`; repeats of the current status are ignored
+    pub fn set(&self, status: ComponentStatus) {
+        if self.last.borrow().as_ref() == Some(&status) {
+            return;
+        }
+        *self.last.borrow_mut() = Some(status.clone());
+
+        match &status {
+            ComponentStatus::Running(detail) | ComponentStatus::Waiting(detail) => {
+                info!("{}: {}", self.name, detail)
+            }
+            ComponentStatus::Restarting { error, delay } => warn!(
+                "{} failed: {} - retrying in {:.1} seconds...",
+                self.name,
+                error,
+                delay.as_secs_f32()
+            ),
+            ComponentStatus::Starting | ComponentStatus::Stopped => debug!("{}: {}", self.name, status),
+        }
+        let _ = self.sender.send((self.name, status));
+    }
+}
+
+/// Exponential backoff between restarts of a failed component
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RestartPolicy {
+    pub initial: Duration,
+    pub max: Duration,
+}
+
+impl RestartPolicy {
+    /// Start at `initial` and double on every consecutive failure
+    pub fn backoff(initial: Duration) -> Self {
+        Self {
+            initial,
+            max: MAX_BACKOFF.max(initial),
+        }
+    }
+
+    /// Delay before the next attempt after `failures` earlier consecutive failures
+    pub fn delay(&self, failures: u32) -> Duration {
+        self.initial
+            .saturating_mul(2u32.saturating_pow(failures))
+            .min(self.max)
+    }
+}
+
+/// A long-running part of the bridge managed by `supervise`
+pub trait Component {
+    /// Name shown in the tray and logs
+    fn name(&self) -> &'static str;
+
+    /// Backoff after a failure; asked on every failure so changed retry settings apply
+    fn restart_policy(&self) -> RestartPolicy;
+
+    /// Run until stopped (`Ok`, restarted right away unless shutting down) or failed
+    /// (`Err`, restarted after the backoff). Must return promptly once shutdown is requested.
+    fn run(&mut self, status: &StatusReporter) -> impl Future<Output = DR2G27Result>;
+}
+
+/// Keep `component` running until `shutdown` becomes true
+pub async fn supervise<C: Component>(
+    mut component: C,
+    mut shutdown: watch::Receiver<bool>,
+    status: WakingSender<StatusUpdate>,
+) {
+    let reporter = StatusReporter {
+        name: component.name(),
+        sender: status,
+        last: RefCell::new(None),
+    };
+    let mut failures = 0;
+
+    while !*shutdown.borrow() {
+        reporter.set(ComponentStatus::Starting);
+        let started = Instant::now();
+
+        match catch_panic(reporter.name, component.run(&reporter)).await {
+            Ok(()) => failures = 0,
+            Err(error) => {
+                if started.elapsed() >= HEALTHY_RUN {
+                    failures = 0;
+                }
+                let delay = component.restart_policy().delay(failures);
+                failures = failures.saturating_add(1);
+                reporter.set(ComponentStatus::Restarting {
+                    error: error.to_string(),
+                    delay,
+                });
+
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = shutdown.wait_for(|exit| *exit) => break,
+                }
+            }
+        }
+    }
+
+    reporter.set(ComponentStatus::Stopped);
+}
+
+/// Await `run`, turning a panic while it is polled into a failure of component `name`
+async fn catch_panic(name: &'static str, run: impl Future<Output = DR2G27Result>) -> DR2G27Result {
+    let mut run = Box::pin(run);
+    future::poll_fn(|cx| match catch_unwind(|| run.as_mut().poll(cx)) {
+        Ok(poll) => poll,
+        Err(payload) => Poll::Ready(Err(DR2G27Error::Panicked {
+            component: name,
+            message: panic_message(payload.as_ref()),
+        })),
+    })
+    .await
+}
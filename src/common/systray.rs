@@ -1,115 +1,667 @@
 // System tray implementation for G27 LED Bridge
 // 
 // Provides a comprehensive background interface with:
-// - Game selection menu (DiRT Rally 2.0, Forza Horizon 5)
-// - Settings editor integration (Notepad)
-// - Manual settings reload functionality
-// - Status display and about dialog
-// - Clean exit handling
-// 
-// Simplified from complex file watching to reliable manual reload approach
+// - Game selection menu with every supported game, checking the active one
+// - Profile selection menu for named settings profiles, checking the active one, with a shortcut to edit them
+// - Native settings dialog, with Notepad for editing the full settings file
+// - Change Port shortcut into the settings dialog; the listener rebinds once it is confirmed
+// - Start with Windows toggle
+// - Outputs submenu with a checkable enable switch per output
+// - LED Style submenu with the fill pattern and shift flash, saved right away
+// - Telemetry recording start/stop, saved to the recordings folder
+// - 30 second packet capture for bug reports, saved next to the recordings
+// - Pause/resume toggle, also on an optional global hotkey from the settings
+// - Configurable left-click and double-click actions on the icon (pause and settings by default)
+// - Automatic settings hot-reload via file watcher
+// - Manual settings reload as a fallback
+// - Settings import/export via file dialogs
+// - Settings validation reports shown as a warning popup
+// - Log window with the recent log lines and copy to clipboard, as the console is hidden
+// - Toast notifications for wheel, telemetry and error events, unless switched off
+// - Status display with the bridge state, a line per supervised component, and about dialog
+// - Status line per output (wheel LEDs and each integration), next to its toggle in the Outputs submenu
+// - Tooltip with live RPM, gear and packet rate while telemetry arrives
+// - Icon colored by the bridge state: grey without wheel, blue waiting, green receiving, red on error
+// - Optional mirror of the wheel's LED bar in the icon
+// - Icon colors for a dark or light taskbar and for high contrast, following Windows by default
+// - Controller mode while the Windows service drives the wheel (settings only)
+// - Icon re-added when Explorer restarts (TaskbarCreated, handled by tray-icon), and a wait
+//   for a taskbar that is still starting instead of running on without an icon
+// - Menu, dialogs and notifications in the configured language
+// - Update check against GitHub releases, on request or at startup when enabled
+// - Clean exit handling, with an optional confirmation and Show Console for a console
+//   hidden to the tray
 
+use std::cell::{Cell, RefCell};
 use std::sync::{Arc, Mutex, OnceLock};
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+use log::{debug, error, info, warn};
+use tokio::sync::watch;
 use tray_icon::{
-    menu::{Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu},
-    TrayIcon, TrayIconBuilder,
+    menu::{CheckMenuItem, Menu, MenuEvent, MenuItem, PredefinedMenuItem, Submenu},
+    MouseButton, MouseButtonState, TrayIcon, TrayIconBuilder, TrayIconEvent,
 };
 use winit::{
     event_loop::{EventLoop, EventLoopBuilder},
     platform::windows::EventLoopBuilderExtWindows,
 };
-use crate::common::{settings::AppSettings, telemetry::GameType};
+use crate::common::{
+    autostart, capture::CAPTURE_EXTENSION, console, diagnostics, hotkey::{self, Hotkey, HotkeyListener},
+    i18n::{self, tr, tr_with}, leds::{FillPattern, LedStyle}, lifecycle::{BridgeState, LiveTelemetry}, log_viewer,
+    notifications::Notifier, outputs::Output, paths, recording::RECORDING_EXTENSION,
+    settings::{AppSettings, ClickAction, IconTheme}, settings_dialog::{self, DialogField}, supervisor::ComponentStatus,
+    telemetry::GameType, validation::SettingsReport, updates::{self, Release},
+    watcher::{SettingsChanged, SettingsWatcher},
+};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 enum MenuAction {
     Quit,
     About,
-    SelectDirtRally,
-    SelectForzaHorizon,
+    SelectGame(GameType),
+    ToggleAutoDetect,
+    SelectProfile(String),
     OpenSettings,
+    ChangePort,
+    OpenSettingsFile,
     ReloadSettings,
+    ExportSettings,
+    ImportSettings,
+    ToggleAutostart,
+    ToggleOutput(Output),
+    SelectPattern(FillPattern),
+    ToggleShiftFlash,
+    SelectIconTheme(IconTheme),
+    ToggleRecording,
+    CapturePackets,
+    TogglePause,
+    ShowLog,
+    GenerateDiagnosticReport,
+    CheckForUpdates,
+    OpenDashboard,
+    ShowConsole,
+    OpenDataFolder,
+}
+
+const TOOLTIP: &str = "G27 LED Bridge - Racing Game Telemetry";
+
+/// How long to keep trying to add the icon while the taskbar is not there yet, e.g. at login
+/// with autostart or while Explorer restarts
+const TASKBAR_WAIT: Duration = Duration::from_secs(60);
+/// Wait between attempts to add the icon
+const TASKBAR_RETRY: Duration = Duration::from_secs(2);
+/// Start of the tooltip while it shows live telemetry
+const LIVE_TOOLTIP: &str = "G27 LED Bridge";
+/// How often the automatic icon theme looks at the Windows theme again
+const THEME_POLL: Duration = Duration::from_secs(2);
+
+/// What the tray icon shows, by the color of its LED bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IconState {
+    /// The G27 is not connected
+    NoWheel,
+    /// Wheel and listener are getting ready, or no telemetry arrives
+    Waiting,
+    Receiving,
+    Error,
+    /// The Windows service drives the wheel; the classic green/orange/red bar
+    Service,
+}
+
+impl IconState {
+    fn from_state(state: &BridgeState) -> Self {
+        match state {
+            BridgeState::SearchingWheel => IconState::NoWheel,
+            BridgeState::WheelConnected | BridgeState::Listening | BridgeState::Stale => IconState::Waiting,
+            BridgeState::Receiving => IconState::Receiving,
+            BridgeState::WheelUnavailable(_) | BridgeState::Error(_) => IconState::Error,
+        }
+    }
+
+    /// Color of the LED at column `x`
+    fn led_color(&self, x: u32, palette: IconPalette) -> (u8, u8, u8) {
+        match self {
+            IconState::NoWheel => palette.no_wheel,
+            IconState::Waiting => (30, 144, 255),
+            IconState::Receiving => (0, 255, 0),
+            IconState::Error => (255, 0, 0),
+            IconState::Service => match x {
+                2..=7 => (0, 255, 0),
+                8..=10 => (255, 165, 0),
+                _ => (255, 0, 0),
+            },
+        }
+    }
+}
+
+/// Color of the LED at column `x` of the icon while it mirrors the LED bar `leds`: the five
+/// G27 LEDs from green to red, dimmed while off
+fn mirrored_led_color(leds: u8, x: u32, palette: IconPalette) -> (u8, u8, u8) {
+    let led = (x.saturating_sub(2) * 5 / 12).min(4);
+    let lit = leds & (1 << led) != 0;
+    match (led, lit) {
+        (_, false) => palette.unlit,
+        (0 | 1, true) => (0, 255, 0),
+        (2 | 3, true) => (255, 165, 0),
+        (_, true) => (255, 0, 0),
+    }
+}
+
+/// Colors around the LED bar, picked for the taskbar behind the icon
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct IconPalette {
+    border: (u8, u8, u8),
+    /// LED color while the wheel is not connected
+    no_wheel: (u8, u8, u8),
+    /// Mirrored LEDs that are off
+    unlit: (u8, u8, u8),
+    /// Taller bar in a two pixel frame
+    bold: bool,
+}
+
+impl IconPalette {
+    fn for_theme(theme: IconTheme) -> Self {
+        match theme {
+            IconTheme::Auto => Self::for_theme(system_icon_theme()),
+            IconTheme::Dark => IconPalette {
+                border: (200, 200, 200),
+                no_wheel: (128, 128, 128),
+                unlit: (64, 64, 64),
+                bold: false,
+            },
+            IconTheme::Light => IconPalette {
+                border: (32, 32, 32),
+                no_wheel: (112, 112, 112),
+                unlit: (176, 176, 176),
+                bold: false,
+            },
+            IconTheme::HighContrast => IconPalette {
+                border: (255, 255, 255),
+                no_wheel: (0, 0, 0),
+                unlit: (0, 0, 0),
+                bold: true,
+            },
+        }
+    }
+}
+
+/// Theme of the Windows taskbar: high contrast when that is on, otherwise light or dark as set
+/// in Personalization > Colors ("Choose your default Windows mode")
+fn system_icon_theme() -> IconTheme {
+    #[cfg(windows)]
+    {
+        use std::ffi::OsStr;
+        use std::os::windows::ffi::OsStrExt;
+        use winapi::shared::winerror::ERROR_SUCCESS;
+        use winapi::um::winreg::{RegGetValueW, HKEY_CURRENT_USER, RRF_RT_REG_DWORD};
+        use winapi::um::winuser::{SystemParametersInfoW, HCF_HIGHCONTRASTON, HIGHCONTRASTW, SPI_GETHIGHCONTRAST};
+
+        let mut contrast: HIGHCONTRASTW = unsafe { std::mem::zeroed() };
+        contrast.cbSize = std::mem::size_of::<HIGHCONTRASTW>() as u32;
+        let found = unsafe {
+            SystemParametersInfoW(SPI_GETHIGHCONTRAST, contrast.cbSize, &mut contrast as *mut _ as *mut _, 0)
+        };
+        if found != 0 && contrast.dwFlags & HCF_HIGHCONTRASTON != 0 {
+            return IconTheme::HighContrast;
+        }
+
+        let wide = |text: &str| -> Vec<u16> { OsStr::new(text).encode_wide().chain(std::iter::once(0)).collect() };
+        let mut light: u32 = 0;
+        let mut size = std::mem::size_of::<u32>() as u32;
+        let status = unsafe {
+            RegGetValueW(
+                HKEY_CURRENT_USER,
+                wide(r"Software\Microsoft\Windows\CurrentVersion\Themes\Personalize").as_ptr(),
+                wide("SystemUsesLightTheme").as_ptr(),
+                RRF_RT_REG_DWORD,
+                std::ptr::null_mut(),
+                &mut light as *mut _ as *mut _,
+                &mut size,
+            )
+        };
+        // Windows before 10 1903 has no light taskbar and no value
+        if status == ERROR_SUCCESS as i32 && light == 1 {
+            IconTheme::Light
+        } else {
+            IconTheme::Dark
+        }
+    }
+
+    #[cfg(not(windows))]
+    {
+        IconTheme::Dark
+    }
+}
+
+/// Menu label of an icon theme
+fn icon_theme_label(theme: IconTheme) -> &'static str {
+    match theme {
+        IconTheme::Auto => "Automatic",
+        IconTheme::Dark => "Dark Taskbar",
+        IconTheme::Light => "Light Taskbar",
+        IconTheme::HighContrast => "High Contrast",
+    }
+}
+
+/// Menu label of a fill pattern
+pub fn pattern_label(pattern: FillPattern) -> &'static str {
+    match pattern {
+        FillPattern::Progressive => "Progressive",
+        FillPattern::CenterOut => "Center-out",
+        FillPattern::Dot => "Dot",
+    }
 }
 
+// Supervised components with a status line in the menu, in display order; components that drive
+// an output report on that output's line instead
+const STATUS_COMPONENTS: [&str; 1] = ["Telemetry"];
+
 // Global menu ID registry
 static MENU_ACTIONS: OnceLock<Mutex<HashMap<String, MenuAction>>> = OnceLock::new();
 
+/// Runs the `[tray]` click actions for left-clicks on the icon
+#[derive(Clone)]
+struct ClickHandler {
+    settings: Arc<Mutex<AppSettings>>,
+    settings_changed: Arc<SettingsChanged>,
+    paused: Arc<watch::Sender<bool>>,
+    /// Time of a left-click that may still become a double-click
+    pending: Arc<Mutex<Option<Instant>>>,
+}
+
+impl ClickHandler {
+    fn on_left_click(&self) {
+        let Ok((left_click, double_click)) =
+            self.settings.lock().map(|settings| (settings.tray.left_click, settings.tray.double_click))
+        else {
+            return;
+        };
+        if double_click == ClickAction::Nothing {
+            self.run(left_click);
+            return;
+        }
+
+        let now = Instant::now();
+        let window = double_click_time();
+        let Ok(mut pending) = self.pending.lock() else {
+            return;
+        };
+        if pending.is_some_and(|first| now.duration_since(first) <= window) {
+            *pending = None;
+            drop(pending);
+            self.run(double_click);
+            return;
+        }
+        *pending = Some(now);
+        drop(pending);
+
+        // A single click only counts once no second click followed in time
+        if left_click != ClickAction::Nothing {
+            let handler = self.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(window);
+                let single = handler.pending.lock().is_ok_and(|mut pending| {
+                    let single = *pending == Some(now);
+                    if single {
+                        *pending = None;
+                    }
+                    single
+                });
+                if single {
+                    handler.run(left_click);
+                }
+            });
+        }
+    }
+
+    fn run(&self, action: ClickAction) {
+        match action {
+            ClickAction::Nothing => {}
+            ClickAction::TogglePause => toggle_pause(&self.paused),
+            ClickAction::Settings => {
+                SystemTray::open_settings_dialog(&self.settings, &self.settings_changed, DialogField::Game);
+            }
+            ClickAction::ShowLog => {
+                std::thread::spawn(log_viewer::show);
+            }
+        }
+    }
+}
+
+/// Longest gap between the clicks of a double-click, as set in Windows
+fn double_click_time() -> Duration {
+    Duration::from_millis(u64::from(unsafe { winapi::um::winuser::GetDoubleClickTime() }))
+}
+
 pub struct SystemTray {
-    _tray: TrayIcon,
+    tray: TrayIcon,
     pub should_exit: Arc<Mutex<bool>>,
-    pub settings_changed: Arc<Mutex<bool>>,
+    pub settings_changed: Arc<SettingsChanged>,
     pub settings: Arc<Mutex<AppSettings>>,
+    /// Validation problems waiting to be shown to the user
+    pub settings_report: Arc<Mutex<Option<SettingsReport>>>,
+    /// File telemetry is being recorded to; the bridge records while this is set
+    pub recording: Arc<watch::Sender<Option<PathBuf>>>,
+    /// File packets are captured to; the bridge sets it back to `None` when the capture ends
+    pub capture: Arc<watch::Sender<Option<PathBuf>>>,
+    /// The bridge keeps the LEDs off while this is set; never saved
+    pub paused: Arc<watch::Sender<bool>>,
     status_item: MenuItem,
     port_item: MenuItem,
-    wheel_status_item: MenuItem,
+    state_item: MenuItem,
+    component_items: Vec<(&'static str, MenuItem)>,
+    output_status_items: Vec<(MenuItem, Output)>,
+    /// Last status of the components that drive outputs, by component name
+    output_status: RefCell<HashMap<&'static str, ComponentStatus>>,
+    /// The Windows service drives the outputs, so their status is not known here
+    service_mode: Cell<bool>,
+    autostart_item: CheckMenuItem,
+    game_items: Vec<(CheckMenuItem, GameType)>,
+    auto_detect_item: CheckMenuItem,
+    profile_items: Vec<(CheckMenuItem, String)>,
+    output_items: Vec<(CheckMenuItem, Output)>,
+    pattern_items: Vec<(CheckMenuItem, FillPattern)>,
+    shift_flash_item: CheckMenuItem,
+    recording_item: MenuItem,
+    capture_item: MenuItem,
+    pause_item: CheckMenuItem,
+    /// Registered pause hotkey, replaced when the setting changes
+    pause_hotkey: RefCell<Option<(Hotkey, HotkeyListener)>>,
+    notifier: RefCell<Notifier>,
+    /// Bridge state shown in the tooltip while no telemetry arrives
+    lifecycle_text: RefCell<String>,
+    live_telemetry: RefCell<Option<LiveTelemetry>>,
+    icon_state: RefCell<IconState>,
+    /// LED bar drawn instead of the state color while `[tray] led_mirror` is on
+    led_mirror: RefCell<Option<u8>>,
+    theme_items: Vec<(CheckMenuItem, IconTheme)>,
+    /// Colors the icon is drawn in, from `[tray] theme`
+    palette: Cell<IconPalette>,
+    /// Last look at the Windows theme for the automatic icon theme
+    theme_checked: Cell<Instant>,
+    about_item: MenuItem,
+    /// Newer release found by an update check
+    available_update: Arc<Mutex<Option<Release>>>,
+    /// The About entry already shows that an update is available
+    update_marked: Cell<bool>,
+    _settings_watcher: Option<SettingsWatcher>,
 }
 
 impl SystemTray {
-    pub fn new() -> Result<Self, Box<dyn std::error::Error>> {
+    /// Create the tray icon and menu; `console` adds Show Console for a `--console` window
+    pub fn new(console: bool) -> Result<Self, Box<dyn std::error::Error>> {
         let should_exit = Arc::new(Mutex::new(false));
         let should_exit_clone = should_exit.clone();
-        let settings_changed = Arc::new(Mutex::new(false));
+        let settings_changed = Arc::new(SettingsChanged::new());
         let settings_changed_clone = settings_changed.clone();
         
-        // Load settings
-        let settings = Arc::new(Mutex::new(AppSettings::load()));
+        // Load settings, keeping any validation problems to show once the tray is up
+        let (loaded_settings, report) = AppSettings::load_with_report();
+        report.print();
+        i18n::set_language(loaded_settings.language);
+        let settings = Arc::new(Mutex::new(loaded_settings));
         let settings_clone = settings.clone();
-        let current_game = settings.lock().unwrap().game_type;
+        let settings_report = Arc::new(Mutex::new((!report.is_empty()).then_some(report)));
+        let settings_report_clone = settings_report.clone();
+        let recording = Arc::new(watch::Sender::new(None));
+        let recording_clone = recording.clone();
+        let capture = Arc::new(watch::Sender::new(None));
+        let capture_clone = capture.clone();
+        let paused = Arc::new(watch::Sender::new(false));
+        let paused_clone = paused.clone();
+        let available_update = Arc::new(Mutex::new(None));
+        let available_update_clone = available_update.clone();
 
-        // Create game selection menu items
-        let dirt_rally_item = MenuItem::new("DiRT Rally 2.0", true, None);
-        let forza_horizon_item = MenuItem::new("Forza Horizon 5", true, None);
+        // Create game selection menu items, one per supported game
+        let game_items: Vec<(CheckMenuItem, GameType)> = {
+            let active = settings.lock().unwrap().game_type;
+            GameType::ALL
+                .into_iter()
+                .map(|game| (CheckMenuItem::new(game.parser().game_name(), true, game == active, None), game))
+                .collect()
+        };
+        let auto_detect_item =
+            CheckMenuItem::new(tr("Auto-detect"), true, settings.lock().unwrap().auto_detect_game, None);
+        
+        let games_submenu = Submenu::new(tr("Select Game"), true);
+        for (item, _) in &game_items {
+            games_submenu.append(item)?;
+        }
+        games_submenu.append(&PredefinedMenuItem::separator())?;
+        games_submenu.append(&auto_detect_item)?;
         
-        let games_submenu = Submenu::new("Select Game", true);
-        games_submenu.append(&dirt_rally_item)?;
-        games_submenu.append(&forza_horizon_item)?;
+        // Create profile selection menu items
+        let profiles_submenu = Submenu::new(tr("Profiles"), true);
+        let profile_items: Vec<(CheckMenuItem, String)> = {
+            let settings = settings.lock().unwrap();
+            settings
+                .profile_names()
+                .into_iter()
+                .map(|name| {
+                    let active = settings.active_profile.as_ref() == Some(&name);
+                    (CheckMenuItem::new(&name, true, active, None), name)
+                })
+                .collect()
+        };
+        for (item, _) in &profile_items {
+            profiles_submenu.append(item)?;
+        }
+        // Profiles live in the [profiles] table of the settings file
+        let edit_profiles_item = MenuItem::new(tr("Edit Profiles..."), true, None);
+        profiles_submenu.append(&PredefinedMenuItem::separator())?;
+        profiles_submenu.append(&edit_profiles_item)?;
         
-        // Create settings menu items
-        let open_settings_item = MenuItem::new("Edit Settings...", true, None);
-        let reload_settings_item = MenuItem::new("Reload Settings", true, None);
+        // Create output toggle items
+        let outputs_submenu = Submenu::new(tr("Outputs"), true);
+        let output_items: Vec<(CheckMenuItem, Output)> = {
+            let settings = settings.lock().unwrap();
+            Output::all()
+                .map(|output| {
+                    let item = CheckMenuItem::new(
+                        tr(output.label()),
+                        settings.output_available(output),
+                        settings.output_enabled(output),
+                        None,
+                    );
+                    (item, output)
+                })
+                .collect()
+        };
+        for (item, _) in &output_items {
+            outputs_submenu.append(item)?;
+        }
+        
+        // Create LED style quick settings
+        let led_style_submenu = Submenu::new(tr("LED Style"), true);
+        let led_style = settings.lock().unwrap().leds;
+        let pattern_items: Vec<(CheckMenuItem, FillPattern)> = FillPattern::ALL
+            .into_iter()
+            .map(|pattern| {
+                let item = CheckMenuItem::new(tr(pattern_label(pattern)), true, pattern == led_style.pattern, None);
+                (item, pattern)
+            })
+            .collect();
+        for (item, _) in &pattern_items {
+            led_style_submenu.append(item)?;
+        }
+        let shift_flash_item = CheckMenuItem::new(tr("Shift Flash"), true, led_style.shift_flash, None);
+        led_style_submenu.append(&PredefinedMenuItem::separator())?;
+        led_style_submenu.append(&shift_flash_item)?;
+        
+        // Create the icon theme choice
+        let icon_theme_submenu = Submenu::new(tr("Icon Theme"), true);
+        let icon_theme = settings.lock().unwrap().tray.theme;
+        let theme_items: Vec<(CheckMenuItem, IconTheme)> = IconTheme::ALL
+            .into_iter()
+            .map(|theme| (CheckMenuItem::new(tr(icon_theme_label(theme)), true, theme == icon_theme, None), theme))
+            .collect();
+        for (item, _) in &theme_items {
+            icon_theme_submenu.append(item)?;
+        }
+        
+        let recording_item = MenuItem::new(tr("Start Recording"), true, None);
+        let capture_item = MenuItem::new(tr("Capture Packets (30 s)"), true, None);
+        let pause_item = CheckMenuItem::new(tr("Pause LED Bridge"), true, false, None);
+        
+        // Create settings menu items; the dashboard is left out of builds without egui
+        let dashboard_item = MenuItem::new(tr("Dashboard..."), cfg!(feature = "dashboard"), None);
+        let open_settings_item = MenuItem::new(tr("Settings..."), true, None);
+        let change_port_item = MenuItem::new(tr("Change Port..."), true, None);
+        let open_settings_file_item = MenuItem::new(tr("Edit Settings File..."), true, None);
+        let open_data_folder_item = MenuItem::new(tr("Open Data Folder"), true, None);
+        let reload_settings_item = MenuItem::new(tr("Reload Settings"), true, None);
+        let export_settings_item = MenuItem::new(tr("Export Settings..."), true, None);
+        let import_settings_item = MenuItem::new(tr("Import Settings..."), true, None);
+        let autostart = settings.lock().unwrap().autostart;
+        let autostart_item = CheckMenuItem::new(tr("Start with Windows"), true, autostart, None);
         
         // Create other menu items  
-        let status_item = MenuItem::new(format!("Active: {}", current_game.parser().game_name()), false, None);
-        let port_item = MenuItem::new(format!("Port: {}", settings.lock().unwrap().port), false, None);
-        let wheel_status_item = MenuItem::new("Wheel: Checking...", false, None);
+        let status_item = MenuItem::new(Self::status_text(&settings.lock().unwrap()), false, None);
+        let port_item = MenuItem::new(Self::listening_text(&settings.lock().unwrap()), false, None);
+        let initial_state = bridge_state_text(&BridgeState::SearchingWheel);
+        let state_item = MenuItem::new(Self::status_line(&initial_state), false, None);
+        let component_items: Vec<(&'static str, MenuItem)> = STATUS_COMPONENTS
+            .into_iter()
+            .map(|name| (name, MenuItem::new(format!("{}: {}", tr(name), tr("Checking...")), false, None)))
+            .collect();
+        let output_status_items: Vec<(MenuItem, Output)> = {
+            let settings = settings.lock().unwrap();
+            Output::all()
+                .filter(|output| output.component().is_some())
+                .map(|output| (MenuItem::new(Self::output_status_text(&settings, output, None), false, None), output))
+                .collect()
+        };
         let separator1 = PredefinedMenuItem::separator();
         let separator2 = PredefinedMenuItem::separator();
-        let about_item = MenuItem::new("About G27 LED Bridge", true, None);
-        let quit_item = MenuItem::new("Exit G27 LED Bridge", true, None);
+        let show_log_item = MenuItem::new(tr("Show Log"), true, None);
+        let show_console_item = MenuItem::new(tr("Show Console"), true, None);
+        let diagnostic_report_item = MenuItem::new(tr("Generate Diagnostic Report"), true, None);
+        let check_updates_item = MenuItem::new(tr("Check for Updates..."), true, None);
+        let about_item = MenuItem::new(tr("About G27 LED Bridge"), true, None);
+        let quit_item = MenuItem::new(tr("Exit G27 LED Bridge"), true, None);
 
         let menu = Menu::new();
         menu.append(&status_item)?;
         menu.append(&port_item)?;
-        menu.append(&wheel_status_item)?;
+        menu.append(&state_item)?;
+        for (_, item) in &component_items {
+            menu.append(item)?;
+        }
+        for (item, _) in &output_status_items {
+            menu.append(item)?;
+        }
         menu.append(&separator1)?;
+        menu.append(&pause_item)?;
         menu.append(&games_submenu)?;
+        menu.append(&profiles_submenu)?;
+        menu.append(&outputs_submenu)?;
+        menu.append(&led_style_submenu)?;
+        menu.append(&icon_theme_submenu)?;
+        menu.append(&recording_item)?;
+        menu.append(&capture_item)?;
+        menu.append(&dashboard_item)?;
         menu.append(&open_settings_item)?;
+        menu.append(&change_port_item)?;
+        menu.append(&open_settings_file_item)?;
+        menu.append(&open_data_folder_item)?;
         menu.append(&reload_settings_item)?;
+        menu.append(&export_settings_item)?;
+        menu.append(&import_settings_item)?;
+        menu.append(&autostart_item)?;
         menu.append(&separator2)?;
+        menu.append(&show_log_item)?;
+        if console {
+            menu.append(&show_console_item)?;
+        }
+        menu.append(&diagnostic_report_item)?;
+        menu.append(&check_updates_item)?;
         menu.append(&about_item)?;
         menu.append(&quit_item)?;
 
-        // Create tray icon using embedded icon data
-        let icon = Self::create_tray_icon()?;
+        // Create tray icon using embedded icon data; the bridge starts out searching for the wheel
+        let palette = IconPalette::for_theme(icon_theme);
+        let icon = Self::create_tray_icon(IconState::NoWheel, palette)?;
         
-        let tray = TrayIconBuilder::new()
-            .with_menu(Box::new(menu))
-            .with_tooltip("G27 LED Bridge - Racing Game Telemetry")
-            .with_icon(icon)
-            .build()?;
+        // Once added, tray-icon adds the icon again whenever Explorer broadcasts TaskbarCreated
+        let started = Instant::now();
+        let tray = loop {
+            let built = TrayIconBuilder::new()
+                .with_menu(Box::new(menu.clone()))
+                .with_tooltip(tr(TOOLTIP))
+                .with_icon(icon.clone())
+                .build();
+            match built {
+                Ok(tray) => break tray,
+                Err(e) if started.elapsed() < TASKBAR_WAIT => {
+                    debug!("Cannot add the tray icon yet, waiting for the taskbar: {}", e);
+                    std::thread::sleep(TASKBAR_RETRY);
+                }
+                Err(e) => return Err(e.into()),
+            }
+        };
 
         // Store menu IDs for event matching
         let menu_actions = MENU_ACTIONS.get_or_init(|| Mutex::new(HashMap::new()));
         if let Ok(mut actions) = menu_actions.lock() {
             actions.insert(format!("{:?}", quit_item.id()), MenuAction::Quit);
             actions.insert(format!("{:?}", about_item.id()), MenuAction::About);
-            actions.insert(format!("{:?}", dirt_rally_item.id()), MenuAction::SelectDirtRally);
-            actions.insert(format!("{:?}", forza_horizon_item.id()), MenuAction::SelectForzaHorizon);
+            actions.insert(format!("{:?}", show_log_item.id()), MenuAction::ShowLog);
+            actions.insert(format!("{:?}", show_console_item.id()), MenuAction::ShowConsole);
+            actions.insert(format!("{:?}", diagnostic_report_item.id()), MenuAction::GenerateDiagnosticReport);
+            actions.insert(format!("{:?}", check_updates_item.id()), MenuAction::CheckForUpdates);
+            for (item, game) in &game_items {
+                actions.insert(format!("{:?}", item.id()), MenuAction::SelectGame(*game));
+            }
+            actions.insert(format!("{:?}", auto_detect_item.id()), MenuAction::ToggleAutoDetect);
+            for (item, name) in &profile_items {
+                actions.insert(format!("{:?}", item.id()), MenuAction::SelectProfile(name.clone()));
+            }
+            actions.insert(format!("{:?}", edit_profiles_item.id()), MenuAction::OpenSettings);
+            actions.insert(format!("{:?}", dashboard_item.id()), MenuAction::OpenDashboard);
             actions.insert(format!("{:?}", open_settings_item.id()), MenuAction::OpenSettings);
+            actions.insert(format!("{:?}", change_port_item.id()), MenuAction::ChangePort);
+            actions.insert(format!("{:?}", open_settings_file_item.id()), MenuAction::OpenSettingsFile);
+            actions.insert(format!("{:?}", open_data_folder_item.id()), MenuAction::OpenDataFolder);
             actions.insert(format!("{:?}", reload_settings_item.id()), MenuAction::ReloadSettings);
+            actions.insert(format!("{:?}", export_settings_item.id()), MenuAction::ExportSettings);
+            actions.insert(format!("{:?}", import_settings_item.id()), MenuAction::ImportSettings);
+            actions.insert(format!("{:?}", autostart_item.id()), MenuAction::ToggleAutostart);
+            for (item, output) in &output_items {
+                actions.insert(format!("{:?}", item.id()), MenuAction::ToggleOutput(*output));
+            }
+            for (item, pattern) in &pattern_items {
+                actions.insert(format!("{:?}", item.id()), MenuAction::SelectPattern(*pattern));
+            }
+            actions.insert(format!("{:?}", shift_flash_item.id()), MenuAction::ToggleShiftFlash);
+            for (item, theme) in &theme_items {
+                actions.insert(format!("{:?}", item.id()), MenuAction::SelectIconTheme(*theme));
+            }
+            actions.insert(format!("{:?}", recording_item.id()), MenuAction::ToggleRecording);
+            actions.insert(format!("{:?}", capture_item.id()), MenuAction::CapturePackets);
+            actions.insert(format!("{:?}", pause_item.id()), MenuAction::TogglePause);
         }
 
+        // Handle clicks on the icon itself; the menu opens on the right button
+        let click_handler = ClickHandler {
+            settings: settings.clone(),
+            settings_changed: settings_changed.clone(),
+            paused: paused.clone(),
+            pending: Arc::new(Mutex::new(None)),
+        };
+        TrayIconEvent::set_event_handler(Some(move |event: TrayIconEvent| {
+            if let TrayIconEvent::Click {
+                button: MouseButton::Left,
+                button_state: MouseButtonState::Up,
+                ..
+            } = event
+            {
+                click_handler.on_left_click();
+            }
+        }));
+
         // Handle menu events
         MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
             let event_id = format!("{:?}", event.id);
@@ -119,41 +671,186 @@ impl SystemTray {
                     if let Some(action) = actions.get(&event_id) {
                         match action {
                             MenuAction::Quit => {
-                                if let Ok(mut should_exit) = should_exit_clone.lock() {
-                                    *should_exit = true;
+                                let should_exit = should_exit_clone.clone();
+                                let quit = move || {
+                                    if let Ok(mut should_exit) = should_exit.lock() {
+                                        *should_exit = true;
+                                    }
+                                };
+                                if settings_clone.lock().is_ok_and(|settings| settings.exit.confirm) {
+                                    // Ask on its own thread so the tray keeps responding meanwhile
+                                    std::thread::spawn(move || {
+                                        let question = tr("Stop the bridge and turn the wheel LEDs off?");
+                                        if ask(tr("Exit G27 LED Bridge"), question) {
+                                            quit();
+                                        }
+                                    });
+                                } else {
+                                    quit();
                                 }
                             }
                             MenuAction::About => {
-                                Self::show_about_dialog();
+                                let update = available_update_clone.lock().ok().and_then(|update| update.clone());
+                                Self::show_about_dialog(update);
                             }
-                            MenuAction::SelectDirtRally => {
+                            MenuAction::SelectGame(game) => {
                                 if let Ok(mut settings) = settings_clone.lock() {
-                                    settings.set_game_type(GameType::DirtRally2);
-                                }
-                                if let Ok(mut changed) = settings_changed_clone.lock() {
-                                    *changed = true;
+                                    settings.set_game_type(*game);
                                 }
-                                // Note: Menu update will happen in main loop
+                                settings_changed_clone.raise();
+                                // Note: Menu update (including check marks) will happen in main loop
                             }
-                            MenuAction::SelectForzaHorizon => {
+                            MenuAction::ToggleAutoDetect => {
                                 if let Ok(mut settings) = settings_clone.lock() {
-                                    settings.set_game_type(GameType::ForzaHorizon5);
+                                    let enabled = !settings.auto_detect_game;
+                                    settings.set_auto_detect_game(enabled);
                                 }
-                                if let Ok(mut changed) = settings_changed_clone.lock() {
-                                    *changed = true;
+                                settings_changed_clone.raise();
+                            }
+                            MenuAction::SelectProfile(name) => {
+                                if let Ok(mut settings) = settings_clone.lock() {
+                                    if let Err(e) = settings.apply_profile(name) {
+                                        error!("{}", e);
+                                    }
                                 }
-                                // Note: Menu update will happen in main loop
+                                // Check marks follow in the main loop, also when the active profile was clicked again
+                                settings_changed_clone.raise();
+                            }
+                            MenuAction::OpenDataFolder => {
+                                Self::open_data_folder();
+                            }
+                            MenuAction::ShowConsole => {
+                                console::show();
+                            }
+                            MenuAction::OpenDashboard => {
+                                Self::open_dashboard();
                             }
                             MenuAction::OpenSettings => {
+                                Self::open_settings_dialog(&settings_clone, &settings_changed_clone, DialogField::Game);
+                            }
+                            MenuAction::ChangePort => {
+                                Self::open_settings_dialog(&settings_clone, &settings_changed_clone, DialogField::Port);
+                            }
+                            MenuAction::OpenSettingsFile => {
                                 Self::open_settings_file();
                             }
                             MenuAction::ReloadSettings => {
+                                let (reloaded, report) = AppSettings::load_with_report();
+                                report.print();
+                                if !report.is_empty() {
+                                    if let Ok(mut pending) = settings_report_clone.lock() {
+                                        *pending = Some(report);
+                                    }
+                                }
                                 if let Ok(mut settings) = settings_clone.lock() {
-                                    *settings = AppSettings::load();
-                                    println!("# Settings reloaded from file");
+                                    *settings = reloaded;
+                                    info!("Settings reloaded from file");
                                 }
-                                if let Ok(mut changed) = settings_changed_clone.lock() {
-                                    *changed = true;
+                                settings_changed_clone.raise();
+                            }
+                            MenuAction::ExportSettings => {
+                                if let Some(path) = Self::pick_settings_file(true) {
+                                    let result = settings_clone.lock().map(|settings| settings.export_to(&path));
+                                    if let Ok(Err(e)) = result {
+                                        Self::show_error_dialog("Failed to export settings:", e);
+                                    }
+                                }
+                            }
+                            MenuAction::ToggleAutostart => {
+                                if let Ok(mut settings) = settings_clone.lock() {
+                                    let enabled = !settings.autostart;
+                                    settings.set_autostart(enabled);
+                                }
+                                // Menu check state is refreshed from settings in the main loop
+                                settings_changed_clone.raise();
+                            }
+                            MenuAction::ToggleOutput(output) => {
+                                if let Ok(mut settings) = settings_clone.lock() {
+                                    let enabled = !settings.output_enabled(*output);
+                                    if let Err(e) = settings.set_output_enabled(*output, enabled) {
+                                        error!("{}", e);
+                                    }
+                                }
+                                settings_changed_clone.raise();
+                            }
+                            MenuAction::SelectPattern(pattern) => {
+                                if let Ok(mut settings) = settings_clone.lock() {
+                                    let style = LedStyle { pattern: *pattern, ..settings.leds };
+                                    settings.set_led_style(style);
+                                }
+                                // Check marks follow in the main loop; the wheel picks up the new style
+                                settings_changed_clone.raise();
+                            }
+                            MenuAction::ToggleShiftFlash => {
+                                if let Ok(mut settings) = settings_clone.lock() {
+                                    let style = LedStyle { shift_flash: !settings.leds.shift_flash, ..settings.leds };
+                                    settings.set_led_style(style);
+                                }
+                                settings_changed_clone.raise();
+                            }
+                            MenuAction::SelectIconTheme(theme) => {
+                                if let Ok(mut settings) = settings_clone.lock() {
+                                    settings.set_icon_theme(*theme);
+                                }
+                                // The icon is redrawn with the menu in the main loop
+                                settings_changed_clone.raise();
+                            }
+                            MenuAction::ToggleRecording => {
+                                // Menu text is refreshed in the main loop
+                                if recording_clone.borrow().is_some() {
+                                    recording_clone.send_replace(None);
+                                } else {
+                                    match Self::new_recording_path() {
+                                        Ok(path) => {
+                                            recording_clone.send_replace(Some(path));
+                                        }
+                                        Err(e) => {
+                                            Self::show_error_dialog("Failed to start recording:", e);
+                                        }
+                                    }
+                                }
+                            }
+                            MenuAction::CapturePackets => {
+                                // The entry is disabled while a capture runs; it ends by itself
+                                if capture_clone.borrow().is_none() {
+                                    match Self::new_capture_path() {
+                                        Ok(path) => {
+                                            capture_clone.send_replace(Some(path));
+                                        }
+                                        Err(e) => {
+                                            Self::show_error_dialog("Failed to start capture:", e);
+                                        }
+                                    }
+                                }
+                            }
+                            MenuAction::TogglePause => {
+                                // Menu check state is refreshed in the main loop
+                                toggle_pause(&paused_clone);
+                            }
+                            MenuAction::ShowLog => {
+                                // The viewer runs its own message loop
+                                std::thread::spawn(log_viewer::show);
+                            }
+                            MenuAction::GenerateDiagnosticReport => {
+                                Self::generate_diagnostic_report();
+                            }
+                            MenuAction::CheckForUpdates => {
+                                Self::check_for_updates(&available_update_clone, true);
+                            }
+                            MenuAction::ImportSettings => {
+                                if let Some(path) = Self::pick_settings_file(false) {
+                                    match AppSettings::import_from(&path) {
+                                        Ok(imported) => {
+                                            autostart::sync(imported.autostart);
+                                            if let Ok(mut settings) = settings_clone.lock() {
+                                                *settings = imported;
+                                            }
+                                            settings_changed_clone.raise();
+                                        }
+                                        Err(e) => {
+                                            Self::show_error_dialog("Failed to import settings:", e);
+                                        }
+                                    }
                                 }
                             }
                         }
@@ -162,40 +859,119 @@ impl SystemTray {
             }
         }));
 
+        // Opt-in check at startup; the About entry is marked once a newer release is found
+        if settings.lock().is_ok_and(|settings| settings.check_for_updates) {
+            Self::check_for_updates(&available_update, false);
+        }
+
+        // Watch settings.toml for external edits; manual reload remains if this fails
+        let settings_watcher = match SettingsWatcher::start(
+            settings.clone(),
+            settings_changed.clone(),
+            settings_report.clone(),
+        ) {
+            Ok(watcher) => Some(watcher),
+            Err(e) => {
+                error!("Failed to start settings watcher: {}", e);
+                info!("Use 'Reload Settings' menu to apply changes");
+                None
+            }
+        };
+
         Ok(SystemTray {
-            _tray: tray,
+            tray,
             should_exit,
             settings_changed,
             settings,
+            settings_report,
+            recording,
+            capture,
+            paused,
             status_item,
             port_item,
-            wheel_status_item,
+            state_item,
+            component_items,
+            output_status_items,
+            output_status: RefCell::new(HashMap::new()),
+            service_mode: Cell::new(false),
+            autostart_item,
+            game_items,
+            auto_detect_item,
+            profile_items,
+            output_items,
+            pattern_items,
+            shift_flash_item,
+            recording_item,
+            capture_item,
+            pause_item,
+            pause_hotkey: RefCell::new(None),
+            notifier: RefCell::new(Notifier::new()),
+            lifecycle_text: RefCell::new(initial_state),
+            live_telemetry: RefCell::new(None),
+            icon_state: RefCell::new(IconState::NoWheel),
+            led_mirror: RefCell::new(None),
+            theme_items,
+            palette: Cell::new(palette),
+            theme_checked: Cell::new(Instant::now()),
+            about_item,
+            available_update,
+            update_marked: Cell::new(false),
+            _settings_watcher: settings_watcher,
         })
     }
 
-    fn create_tray_icon() -> Result<tray_icon::Icon, Box<dyn std::error::Error>> {
-        // Create a simple 16x16 icon with G27 colors (green/orange/red)
-        let icon_data = Self::create_icon_data();
+    fn status_text(settings: &AppSettings) -> String {
+        let mut game_name = settings.game_type.parser().game_name().to_string();
+        if settings.auto_detect_game {
+            game_name.push(' ');
+            game_name.push_str(tr("(auto-detected)"));
+        }
+        match settings.active_profile {
+            Some(ref profile) => format!("{}: {} ({})", tr("Active"), game_name, profile),
+            None => format!("{}: {}", tr("Active"), game_name),
+        }
+    }
+
+    /// New file in the recordings folder, named after the current time
+    fn new_recording_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let started = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        Ok(paths::recordings_dir()?.join(format!("session-{}.{}", started, RECORDING_EXTENSION)))
+    }
+
+    /// New packet capture next to the recordings, named after the current time
+    fn new_capture_path() -> Result<PathBuf, Box<dyn std::error::Error>> {
+        let started = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        Ok(paths::recordings_dir()?.join(format!("capture-{}.{}", started, CAPTURE_EXTENSION)))
+    }
+
+    fn create_tray_icon(state: IconState, palette: IconPalette) -> Result<tray_icon::Icon, Box<dyn std::error::Error>> {
+        // Create a simple 16x16 icon of the G27 LED bar, colored by `state`
+        let icon_data = Self::create_icon_data(|x| state.led_color(x, palette), palette);
         let icon = tray_icon::Icon::from_rgba(icon_data, 16, 16)?;
         Ok(icon)
     }
 
-    fn create_icon_data() -> Vec<u8> {
+    /// Icon showing the LED bar `leds` as the wheel does
+    fn create_mirror_icon(leds: u8, palette: IconPalette) -> Result<tray_icon::Icon, Box<dyn std::error::Error>> {
+        let icon_data = Self::create_icon_data(|x| mirrored_led_color(leds, x, palette), palette);
+        Ok(tray_icon::Icon::from_rgba(icon_data, 16, 16)?)
+    }
+
+    fn create_icon_data(led_color: impl Fn(u32) -> (u8, u8, u8), palette: IconPalette) -> Vec<u8> {
         let mut data = Vec::with_capacity(16 * 16 * 4); // RGBA
+        // High contrast gets a taller bar and a thicker frame
+        let (bar_rows, border_columns, border_rows) =
+            if palette.bold { (5..=10, 0..=15, 3..=12) } else { (6..=9, 1..=14, 5..=10) };
         
         for y in 0..16 {
             for x in 0..16 {
-                let (r, g, b, a) = if (2..=13).contains(&x) && (6..=9).contains(&y) {
+                let (r, g, b, a) = if (2..=13).contains(&x) && bar_rows.contains(&y) {
                     // LED bar area
-                    match x {
-                        2..=4 => (0, 255, 0, 255),   // Green LEDs
-                        5..=7 => (0, 255, 0, 255),   // Green LEDs
-                        8..=10 => (255, 165, 0, 255), // Orange LEDs
-                        11..=13 => (255, 0, 0, 255),  // Red LED
-                        _ => (64, 64, 64, 255),       // Background
-                    }
-                } else if (1..=14).contains(&x) && (5..=10).contains(&y) {
-                    (32, 32, 32, 255) // Border
+                    let (r, g, b) = led_color(x);
+                    (r, g, b, 255)
+                } else if border_columns.contains(&x) && border_rows.contains(&y) {
+                    let (r, g, b) = palette.border;
+                    (r, g, b, 255)
                 } else {
                     (0, 0, 0, 0) // Transparent
                 };
@@ -206,81 +982,275 @@ impl SystemTray {
         data
     }
 
-    fn show_about_dialog() {
+    fn show_about_dialog(update: Option<Release>) {
+        let games: String = GameType::ALL
+            .iter()
+            .map(|game| format!("- {}\n", game.parser().game_name()))
+            .collect();
+        let mut message = format!(
+            "G27 LED Bridge v{}\n\n{}\n\n{}\n{}\n{}\n{}\n\n{}",
+            updates::CURRENT_VERSION,
+            tr("Multi-game telemetry bridge for Logitech G27 Racing Wheel"),
+            tr("Supported Games:"),
+            games,
+            tr("Based on DR2G27 by Aely0"),
+            tr("Extended by Rajitha Perera"),
+            tr("MIT License"),
+        );
+        if let Some(release) = update {
+            message.push_str("\n\n");
+            message.push_str(&Self::update_text(&release));
+            message.push('\n');
+            message.push_str(tr("Open the release page?"));
+            if ask(tr("About G27 LED Bridge"), &message) {
+                open_url(&release.url);
+            }
+            return;
+        }
+        #[cfg(windows)]
+        message_box(tr("About G27 LED Bridge"), &message, winapi::um::winuser::MB_ICONINFORMATION);
+        #[cfg(not(windows))]
+        let _ = message;
+    }
+    
+    /// Look for a newer release on a background thread and remember it for the About entry;
+    /// `interactive` checks from the menu report the outcome and offer to open the release page
+    fn check_for_updates(available_update: &Arc<Mutex<Option<Release>>>, interactive: bool) {
+        let available_update = available_update.clone();
+        std::thread::spawn(move || match updates::check() {
+            Ok(Some(release)) => {
+                info!("G27 LED Bridge {} is available: {}", release.version, release.url);
+                if let Ok(mut available) = available_update.lock() {
+                    *available = Some(release.clone());
+                }
+                if interactive {
+                    let message = format!("{}\n\n{}", Self::update_text(&release), tr("Open the release page?"));
+                    if ask("G27 LED Bridge", &message) {
+                        open_url(&release.url);
+                    }
+                }
+            }
+            Ok(None) => {
+                info!("G27 LED Bridge {} is the latest release", updates::CURRENT_VERSION);
+                if interactive {
+                    show_info_dialog(&tr_with("You are running the latest version ({}).", updates::CURRENT_VERSION));
+                }
+            }
+            Err(e) if interactive => Self::show_error_dialog("Could not check for updates:", e),
+            Err(e) => warn!("Could not check for updates: {}", e),
+        });
+    }
+    
+    /// Which release is available and which one is running
+    fn update_text(release: &Release) -> String {
+        format!(
+            "{}\n{}",
+            tr_with("Version {} is available.", &release.version),
+            tr_with("You are running version {}.", updates::CURRENT_VERSION)
+        )
+    }
+    
+    /// Log an error in English and show it translated, e.g. "Failed to import settings:" and the cause
+    fn show_error_dialog(context: &str, error: impl std::fmt::Display) {
+        error!("{}\n{}", context, error);
+
+        #[cfg(windows)]
+        message_box("G27 LED Bridge", &format!("{}\n{}", tr(context), error), winapi::um::winuser::MB_ICONERROR);
+    }
+    
+    fn show_warning_dialog(message: String) {
+        #[cfg(windows)]
+        {
+            use winapi::um::winuser::{MB_ICONWARNING, MB_SETFOREGROUND};
+
+            // Run on its own thread so the tray keeps responding while the popup is open
+            std::thread::spawn(move || {
+                message_box("G27 LED Bridge - Settings", &message, MB_ICONWARNING | MB_SETFOREGROUND);
+            });
+        }
+        
+        #[cfg(not(windows))]
+        {
+            let _ = message;
+        }
+    }
+    
+    /// Show a native open/save dialog for settings files
+    fn pick_settings_file(save: bool) -> Option<PathBuf> {
         #[cfg(windows)]
         {
-            use winapi::um::winuser::{MessageBoxA, MB_ICONINFORMATION, MB_OK};
-            use std::ffi::CString;
+            use winapi::um::commdlg::{
+                GetOpenFileNameW, GetSaveFileNameW, OPENFILENAMEW,
+                OFN_FILEMUSTEXIST, OFN_OVERWRITEPROMPT, OFN_PATHMUSTEXIST,
+            };
+            use std::ffi::{OsStr, OsString};
+            use std::os::windows::ffi::{OsStrExt, OsStringExt};
             
-            let title = CString::new("About G27 LED Bridge").unwrap();
-            let message = CString::new(
-                "G27 LED Bridge v2.0.0\n\n\
-                Multi-game telemetry bridge for Logitech G27 Racing Wheel\n\n\
-                Supported Games:\n\
-                - DiRT Rally 2.0\n\
-                - Forza Horizon 5\n\n\
-                Based on DR2G27 by Aely0\n\
-                Extended by Rajitha Perera\n\n\
-                MIT License"
-            ).unwrap();
+            let filter: Vec<u16> = OsStr::new("Settings (*.toml)\0*.toml\0All files (*.*)\0*.*\0\0")
+                .encode_wide()
+                .collect();
+                
+            let default_ext: Vec<u16> = OsStr::new("toml")
+                .encode_wide()
+                .chain(std::iter::once(0))
+                .collect();
             
-            unsafe {
-                MessageBoxA(
-                    std::ptr::null_mut(),
-                    message.as_ptr(),
-                    title.as_ptr(),
-                    MB_OK | MB_ICONINFORMATION,
-                );
+            // Pre-fill a file name when exporting
+            let mut file_buffer = [0u16; 260];
+            if save {
+                for (slot, c) in file_buffer.iter_mut().zip(OsStr::new("g27-led-bridge-settings.toml").encode_wide()) {
+                    *slot = c;
+                }
+            }
+            
+            let mut dialog: OPENFILENAMEW = unsafe { std::mem::zeroed() };
+            dialog.lStructSize = std::mem::size_of::<OPENFILENAMEW>() as u32;
+            dialog.lpstrFilter = filter.as_ptr();
+            dialog.lpstrFile = file_buffer.as_mut_ptr();
+            dialog.nMaxFile = file_buffer.len() as u32;
+            dialog.lpstrDefExt = default_ext.as_ptr();
+            dialog.Flags = OFN_PATHMUSTEXIST | if save { OFN_OVERWRITEPROMPT } else { OFN_FILEMUSTEXIST };
+            
+            let accepted = unsafe {
+                if save {
+                    GetSaveFileNameW(&mut dialog)
+                } else {
+                    GetOpenFileNameW(&mut dialog)
+                }
+            };
+            
+            if accepted == 0 {
+                return None;
             }
+            
+            let len = file_buffer.iter().position(|&c| c == 0).unwrap_or(file_buffer.len());
+            Some(PathBuf::from(OsString::from_wide(&file_buffer[..len])))
+        }
+        
+        #[cfg(not(windows))]
+        {
+            let _ = save;
+            warn!("File dialogs are only available on Windows; use 'config export' / 'config import'");
+            None
         }
     }
     
-    fn open_settings_file() {
+    /// Show the settings dialog on its own thread so the tray stays responsive
+    fn open_settings_dialog(
+        settings: &Arc<Mutex<AppSettings>>,
+        settings_changed: &Arc<SettingsChanged>,
+        focus: DialogField,
+    ) {
+        let settings = settings.clone();
+        let settings_changed = settings_changed.clone();
+        
+        std::thread::spawn(move || {
+            let current = match settings.lock() {
+                Ok(settings) => settings.clone(),
+                Err(_) => return,
+            };
+            
+            if let Some(result) = settings_dialog::show(&current, focus) {
+                if let Ok(mut settings) = settings.lock() {
+                    result.apply(&mut settings);
+                }
+                settings_changed.raise();
+            }
+        });
+    }
+    
+    /// Start the dashboard as a process of its own; winit allows only one event loop per process
+    fn open_dashboard() {
+        let exe = match std::env::current_exe() {
+            Ok(exe) => exe,
+            Err(e) => {
+                Self::show_error_dialog("Failed to open the dashboard:", e);
+                return;
+            }
+        };
+        let mut command = std::process::Command::new(exe);
+        // A --portable launch has no marker file to tell the dashboard where the settings are
+        if paths::is_portable() {
+            command.arg("--portable");
+        }
+        command.arg("dashboard");
         #[cfg(windows)]
         {
-            if let Ok(settings_path) = AppSettings::config_path() {
-                // Use Windows ShellExecute API which works reliably in Windows subsystem mode
-                use winapi::um::shellapi::ShellExecuteW;
-                use winapi::um::winuser::SW_SHOW;
-                use std::ffi::OsStr;
-                use std::os::windows::ffi::OsStrExt;
-                
-                let file_path_wide: Vec<u16> = OsStr::new(&settings_path)
-                    .encode_wide()
-                    .chain(std::iter::once(0))
-                    .collect();
-                    
-                let operation_wide: Vec<u16> = OsStr::new("open")
-                    .encode_wide()
-                    .chain(std::iter::once(0))
-                    .collect();
-                    
-                let application_wide: Vec<u16> = OsStr::new("notepad.exe")
-                    .encode_wide()
-                    .chain(std::iter::once(0))
-                    .collect();
-                
-                unsafe {
-                    ShellExecuteW(
-                        std::ptr::null_mut(),
-                        operation_wide.as_ptr(),
-                        application_wide.as_ptr(),
-                        file_path_wide.as_ptr(),
-                        std::ptr::null(),
-                        SW_SHOW,
-                    );
+            use std::os::windows::process::CommandExt;
+            use winapi::um::winbase::CREATE_NO_WINDOW;
+
+            command.creation_flags(CREATE_NO_WINDOW);
+        }
+        if let Err(e) = command.spawn() {
+            Self::show_error_dialog("Failed to open the dashboard:", e);
+        }
+    }
+    
+    /// Write a diagnostic report and offer to show it in Explorer
+    fn generate_diagnostic_report() {
+        // Asking the bridge for its status takes a moment; keep the tray responding meanwhile
+        std::thread::spawn(|| match diagnostics::write_report(None) {
+            Ok(path) => {
+                info!("Diagnostic report written to {}", path.display());
+                let message = format!(
+                    "{}\n{}\n\n{}",
+                    tr("Diagnostic report saved. Attach it to your bug report:"),
+                    path.display(),
+                    tr("Show it in Explorer?")
+                );
+                if ask("G27 LED Bridge", &message) {
+                    #[cfg(windows)]
+                    {
+                        let select = format!("/select,\"{}\"", path.display());
+                        if let Err(e) = shell_open("explorer.exe".as_ref(), Some(select.as_ref())) {
+                            error!("Failed to show the diagnostic report: {}", e);
+                        }
+                    }
                 }
-                println!("# Opened settings file in Notepad");
             }
+            Err(e) => Self::show_error_dialog("Failed to write the diagnostic report:", e),
+        });
+    }
+
+    fn open_settings_file() {
+        let settings_path = match AppSettings::config_path() {
+            Ok(path) => path,
+            Err(e) => {
+                error!("Failed to find the settings file: {}", e);
+                return;
+            }
+        };
+        
+        #[cfg(windows)]
+        match shell_open("notepad.exe".as_ref(), Some(settings_path.as_os_str())) {
+            Ok(()) => info!("Opened settings file in Notepad"),
+            Err(e) => error!("Failed to open the settings file in Notepad: {}", e),
         }
         
         #[cfg(not(windows))]
         {
-            if let Ok(settings_path) = AppSettings::config_path() {
-                println!("# Settings file location: {}", settings_path.display());
-                println!("# Edit the file and use 'Reload Settings' menu to apply changes");
+            info!("Settings file location: {}", settings_path.display());
+            info!("Changes are applied automatically when the file is saved");
+        }
+    }
+    
+    /// Show the folder with the settings, logs and recordings in Explorer
+    fn open_data_folder() {
+        let data_dir = match paths::data_dir() {
+            Ok(dir) => dir,
+            Err(e) => {
+                Self::show_error_dialog("Failed to open the data folder:", e);
+                return;
             }
+        };
+        
+        #[cfg(windows)]
+        if let Err(e) = shell_open(data_dir.as_os_str(), None) {
+            Self::show_error_dialog("Failed to open the data folder:", e);
         }
+        
+        #[cfg(not(windows))]
+        info!("Data folder: {}", data_dir.display());
     }
 
     pub fn should_exit(&self) -> bool {
@@ -288,56 +1258,393 @@ impl SystemTray {
     }
     
     pub fn settings_changed(&self) -> bool {
-        if let Ok(mut changed) = self.settings_changed.lock() {
-            let result = *changed;
-            *changed = false; // Reset the flag
-            result
-        } else {
-            false
+        self.settings_changed.take()
+    }
+    
+    /// Show any pending settings validation report to the user (already printed to the console)
+    pub fn show_settings_report(&self) {
+        let report = match self.settings_report.lock() {
+            Ok(mut pending) => pending.take(),
+            Err(_) => None,
+        };
+        
+        if let Some(report) = report {
+            let settings_path = AppSettings::config_path()
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|_| "settings.toml".to_string());
+            Self::show_warning_dialog(format!(
+                "{}\n\n{}\n{}",
+                tr_with("Problems were found in {}:", &settings_path),
+                report,
+                tr("Invalid values were replaced with defaults.")
+            ));
+        }
+    }
+    
+    /// Mark the About entry once an update check found a newer release
+    pub fn show_available_update(&self) {
+        if self.update_marked.get() || !self.available_update.lock().is_ok_and(|update| update.is_some()) {
+            return;
         }
+        self.about_item.set_text(format!("{} {}", tr("About G27 LED Bridge"), tr("(update available)")));
+        self.update_marked.set(true);
     }
     
     pub fn get_current_settings(&self) -> AppSettings {
         self.settings.lock().unwrap().clone()
     }
 
-    pub fn update_status(&self, status: &str) {
-        println!("# Status: {}", status);
+    /// Show the bridge lifecycle state in the menu, tooltip and icon color (already logged), with
+    /// a toast for the changes worth one
+    pub fn update_lifecycle(&self, state: &BridgeState) {
+        self.update_lifecycle_text(&bridge_state_text(state));
+        self.update_icon(IconState::from_state(state));
+        if let Some(notification) = self.notifier.borrow_mut().on_state(state) {
+            if self.settings.lock().is_ok_and(|settings| settings.notifications) {
+                notification.show();
+            }
+        }
+    }
+    
+    /// Redraw the icon if its state changed
+    fn update_icon(&self, state: IconState) {
+        if self.icon_state.replace(state) != state {
+            self.redraw_icon();
+        }
+    }
+    
+    /// Draw the LED bar into the icon, or the state color again with `None`
+    pub fn update_led_mirror(&self, leds: Option<u8>) {
+        if self.led_mirror.replace(leds) != leds {
+            self.redraw_icon();
+        }
+    }
+    
+    /// Follow changes of the Windows theme while the icon theme is automatic, looking at most
+    /// every `THEME_POLL`
+    pub fn refresh_icon_theme(&self) {
+        if self.theme_checked.get().elapsed() >= THEME_POLL {
+            self.apply_icon_theme();
+        }
+    }
+    
+    /// Redraw the icon if `[tray] theme`, or the Windows theme it follows, changed its colors
+    fn apply_icon_theme(&self) {
+        self.theme_checked.set(Instant::now());
+        let theme = self.settings.lock().map_or(IconTheme::Auto, |settings| settings.tray.theme);
+        let palette = IconPalette::for_theme(theme);
+        if self.palette.replace(palette) != palette {
+            self.redraw_icon();
+        }
+    }
+    
+    fn redraw_icon(&self) {
+        let palette = self.palette.get();
+        let icon = match *self.led_mirror.borrow() {
+            Some(leds) => Self::create_mirror_icon(leds, palette),
+            None => Self::create_tray_icon(*self.icon_state.borrow(), palette),
+        };
+        match icon {
+            Ok(icon) => {
+                if let Err(e) = self.tray.set_icon(Some(icon)) {
+                    debug!("Failed to update tray icon: {}", e);
+                }
+            }
+            Err(e) => debug!("Failed to create tray icon: {}", e),
+        }
+    }
+    
+    /// Menu line with the address the bridge listens on
+    fn listening_text(settings: &AppSettings) -> String {
+        format!("{}: {}", tr("Listening"), settings.listen_description())
+    }
+    
+    /// Menu line with the bridge state
+    fn status_line(state: &str) -> String {
+        format!("{}: {}", tr("Status"), state)
+    }
+    
+    fn update_lifecycle_text(&self, state: &str) {
+        self.state_item.set_text(Self::status_line(state));
+        *self.lifecycle_text.borrow_mut() = state.to_string();
+        self.update_tooltip();
+    }
+    
+    /// Show the latest telemetry summary in the tooltip, or the bridge state again with `None`
+    pub fn update_live_telemetry(&self, live: Option<LiveTelemetry>) {
+        if self.live_telemetry.replace(live) != live {
+            self.update_tooltip();
+        }
+    }
+    
+    fn update_tooltip(&self) {
+        let tooltip = match *self.live_telemetry.borrow() {
+            Some(live) => format!("{} - {}", LIVE_TOOLTIP, live),
+            None => format!("{} - {}", tr(TOOLTIP), self.lifecycle_text.borrow()),
+        };
+        if let Err(e) = self.tray.set_tooltip(Some(tooltip)) {
+            debug!("Failed to update tray tooltip: {}", e);
+        }
     }
     
     pub fn update_menu_display(&self) {
         if let Ok(settings) = self.settings.lock() {
+            i18n::set_language(settings.language);
             let game_name = settings.game_type.parser().game_name();
-            let port = settings.port;
+            let listen_addr = settings.listen_addr();
             
             // Update menu item text
-            self.status_item.set_text(format!("Active: {}", game_name));
-            self.port_item.set_text(format!("Port: {}", port));
+            self.status_item.set_text(Self::status_text(&settings));
+            self.port_item.set_text(Self::listening_text(&settings));
+            self.autostart_item.set_checked(settings.autostart);
+            for (item, game) in &self.game_items {
+                item.set_checked(*game == settings.game_type);
+            }
+            self.auto_detect_item.set_checked(settings.auto_detect_game);
+            for (item, name) in &self.profile_items {
+                item.set_checked(settings.active_profile.as_ref() == Some(name));
+            }
+            for (item, output) in &self.output_items {
+                item.set_enabled(settings.output_available(*output));
+                item.set_checked(settings.output_enabled(*output));
+            }
+            for (item, pattern) in &self.pattern_items {
+                item.set_checked(*pattern == settings.leds.pattern);
+            }
+            self.shift_flash_item.set_checked(settings.leds.shift_flash);
+            for (item, theme) in &self.theme_items {
+                item.set_checked(*theme == settings.tray.theme);
+            }
+            self.update_output_status(&settings);
+            self.update_pause_hotkey(settings.pause.hotkey);
             
-            println!("# Menu updated: {} on port {}", game_name, port);
+            debug!("Menu updated: {} on {}", game_name, listen_addr);
         }
+        self.apply_icon_theme();
     }
     
-    pub fn update_wheel_status(&self, connected: bool, error_msg: Option<&str>) {
-        let status_text = if connected {
-            "Wheel: Connected ✓"
-        } else if let Some(msg) = error_msg {
-            &format!("Wheel: Error - {}", msg)
+    /// Register the pause hotkey, or a new one in place of the old
+    fn update_pause_hotkey(&self, hotkey: Option<Hotkey>) {
+        let mut registered = self.pause_hotkey.borrow_mut();
+        if registered.as_ref().map(|(current, _)| *current) == hotkey {
+            return;
+        }
+        // Unregister the old hotkey first, in case the new one is the same key with other modifiers
+        *registered = None;
+        if let Some(hotkey) = hotkey {
+            let paused = self.paused.clone();
+            *registered = hotkey::listen(hotkey, move || toggle_pause(&paused)).map(|listener| (hotkey, listener));
+        }
+    }
+    
+    /// Check the pause entry while paused
+    pub fn update_pause_display(&self) {
+        self.pause_item.set_checked(*self.paused.borrow());
+    }
+    
+    /// Offer to stop a running recording, or to start one
+    pub fn update_recording_display(&self) {
+        let text = match self.recording.borrow().as_ref() {
+            Some(_) => "Stop Recording",
+            None => "Start Recording",
+        };
+        self.recording_item.set_text(tr(text));
+    }
+    
+    /// Show a running packet capture, which cannot be stopped early
+    pub fn update_capture_display(&self) {
+        if self.service_mode.get() {
+            return;
+        }
+        let capturing = self.capture.borrow().is_some();
+        let text = if capturing { "Capturing Packets..." } else { "Capture Packets (30 s)" };
+        self.capture_item.set_text(tr(text));
+        self.capture_item.set_enabled(!capturing);
+    }
+    
+    /// Show that the Windows service drives the wheel; this tray only edits the settings it reloads
+    pub fn show_service_mode(&self) {
+        self.update_lifecycle_text(tr("Running as Windows service"));
+        self.update_icon(IconState::Service);
+        for (name, item) in &self.component_items {
+            item.set_text(format!("{}: {}", tr(name), tr("Handled by the service")));
+        }
+        self.service_mode.set(true);
+        if let Ok(settings) = self.settings.lock() {
+            self.update_output_status(&settings);
+        }
+        // Recordings and captures are made and pauses applied by the bridge, and this tray has none
+        self.recording_item.set_enabled(false);
+        self.capture_item.set_enabled(false);
+        self.pause_item.set_enabled(false);
+    }
+    
+    /// Show a supervised component's status on its menu line, or on the line of the output it drives
+    pub fn update_component_status(&self, component: &'static str, status: &ComponentStatus) {
+        if Output::all().any(|output| output.component() == Some(component)) {
+            self.output_status.borrow_mut().insert(component, status.clone());
+            if let Ok(settings) = self.settings.lock() {
+                self.update_output_status(&settings);
+            }
+            return;
+        }
+        match self.component_items.iter().find(|(name, _)| *name == component) {
+            Some((_, item)) => item.set_text(format!("{}: {}", tr(component), component_status_text(status))),
+            None => debug!("No status line for component '{}'", component),
+        }
+    }
+    
+    /// Refresh the status line of every output
+    fn update_output_status(&self, settings: &AppSettings) {
+        let output_status = self.output_status.borrow();
+        for (item, output) in &self.output_status_items {
+            let status = if self.service_mode.get() {
+                Some(ComponentStatus::Waiting("Handled by the service".to_string()))
+            } else {
+                output.component().and_then(|component| output_status.get(component).cloned())
+            };
+            item.set_text(Self::output_status_text(settings, *output, status.as_ref()));
+        }
+    }
+    
+    /// Menu line for `output`, with the status of the component driving it. Outputs without
+    /// a component get no line: there is no connection to report on.
+    fn output_status_text(settings: &AppSettings, output: Output, status: Option<&ComponentStatus>) -> String {
+        let detail = if !settings.output_enabled(output) {
+            tr(if settings.output_available(output) { "Off" } else { "Not configured" }).to_string()
         } else {
-            "Wheel: Not Found ✗"
+            component_status_text(status.unwrap_or(&ComponentStatus::Starting))
         };
-        
-        self.wheel_status_item.set_text(status_text);
-        
-        if !connected {
-            println!("# Wheel Status: {}", status_text);
+        format!("{}: {}", tr(output.label()), detail)
+    }
+
+}
+
+/// The bridge state in the configured language
+fn bridge_state_text(state: &BridgeState) -> String {
+    match state {
+        BridgeState::WheelUnavailable(failure) => {
+            format!("{} - {}", tr("G27 found but not available"), tr(&failure.to_string()))
         }
+        BridgeState::Error(error) => format!("{} - {}", tr("Error"), error),
+        state => tr(&state.to_string()).to_string(),
+    }
+}
+
+/// A component status in the configured language; details without a translation stay as they are
+fn component_status_text(status: &ComponentStatus) -> String {
+    match status {
+        ComponentStatus::Starting => tr("Starting...").to_string(),
+        ComponentStatus::Running(detail) => format!("{} ✓", tr(detail)),
+        ComponentStatus::Waiting(detail) => tr(detail).to_string(),
+        ComponentStatus::Restarting { error, delay } => {
+            format!("{} - {} ({} {:.1}s)", tr("Error"), error, tr("retrying in"), delay.as_secs_f32())
+        }
+        ComponentStatus::Stopped => tr("Stopped").to_string(),
+    }
+}
+
+/// Pause the bridge, or resume it
+fn toggle_pause(paused: &watch::Sender<bool>) {
+    // Nothing follows the pause while the Windows service drives the wheel
+    if paused.is_closed() {
+        debug!("No bridge in this process to pause");
+        return;
+    }
+    let pause = !*paused.borrow();
+    paused.send_replace(pause);
+    if pause {
+        info!("LED bridge paused - LEDs stay off until resumed");
+    } else {
+        info!("LED bridge resumed");
+    }
+}
+
+/// Show a message box without a tray, e.g. before exiting
+pub fn show_info_dialog(message: &str) {
+    #[cfg(windows)]
+    {
+        use winapi::um::winuser::{MB_ICONINFORMATION, MB_SETFOREGROUND};
+
+        message_box("G27 LED Bridge", message, MB_ICONINFORMATION | MB_SETFOREGROUND);
     }
     
-    pub fn update_wheel_connecting(&self) {
-        self.wheel_status_item.set_text("Wheel: Connecting...");
+    #[cfg(not(windows))]
+    {
+        let _ = message;
+    }
+}
+
+/// Show a message box with an OK button, or the buttons in `flags` alongside its icon, and return
+/// the button pressed; the Unicode API keeps translated text intact
+#[cfg(windows)]
+fn message_box(title: &str, message: &str, flags: u32) -> i32 {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::um::winuser::{MessageBoxW, MB_OK};
+
+    let wide = |text: &str| -> Vec<u16> {
+        OsStr::new(&text.replace('\0', "")).encode_wide().chain(std::iter::once(0)).collect()
+    };
+    unsafe { MessageBoxW(std::ptr::null_mut(), wide(message).as_ptr(), wide(title).as_ptr(), MB_OK | flags) }
+}
+
+/// Ask a yes/no question, answered with no where there are no dialogs
+fn ask(title: &str, message: &str) -> bool {
+    #[cfg(windows)]
+    {
+        use winapi::um::winuser::{IDYES, MB_ICONQUESTION, MB_SETFOREGROUND, MB_YESNO};
+
+        message_box(title, message, MB_YESNO | MB_ICONQUESTION | MB_SETFOREGROUND) == IDYES
     }
 
+    #[cfg(not(windows))]
+    {
+        let _ = (title, message);
+        false
+    }
+}
+
+/// Open a web page in the default browser
+fn open_url(url: &str) {
+    #[cfg(windows)]
+    if let Err(e) = shell_open(url.as_ref(), None) {
+        warn!("Failed to open {}: {}", url, e);
+    }
+
+    #[cfg(not(windows))]
+    {
+        info!("Release page: {}", url);
+    }
+}
+
+/// Open `target` through the Windows shell: a folder in Explorer, a page in the browser, or a
+/// program started with `parameters`. Unlike `cmd /c start`, this needs no console, so it
+/// works from the windowless tray app.
+#[cfg(windows)]
+fn shell_open(target: &std::ffi::OsStr, parameters: Option<&std::ffi::OsStr>) -> Result<(), String> {
+    use std::ffi::OsStr;
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::um::shellapi::ShellExecuteW;
+    use winapi::um::winuser::SW_SHOWNORMAL;
+
+    let wide = |text: &OsStr| -> Vec<u16> { text.encode_wide().chain(std::iter::once(0)).collect() };
+    let parameters = parameters.map(wide);
+    let result = unsafe {
+        ShellExecuteW(
+            std::ptr::null_mut(),
+            wide("open".as_ref()).as_ptr(),
+            wide(target).as_ptr(),
+            parameters.as_ref().map_or(std::ptr::null(), |parameters| parameters.as_ptr()),
+            std::ptr::null(),
+            SW_SHOWNORMAL,
+        )
+    };
+    // Values above 32 mean success; lower ones are error codes
+    match result as isize {
+        code if code > 32 => Ok(()),
+        code => Err(format!("ShellExecute error {}", code)),
+    }
 }
 
 pub fn hide_console_window() {
@@ -0,0 +1,65 @@
+// Update check for G27 LED Bridge
+//
+// Asks the GitHub releases API for the latest release and compares its tag with the
+// version of this build. Nothing is downloaded or installed; the tray only offers to
+// open the release page.
+//
+// Checks only run when the user asks for one from the tray, or at startup when
+// `check_for_updates = true` is set. Pre-releases and drafts are ignored by the API.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+/// Latest published release of the repository
+const RELEASES_URL: &str = "https://api.github.com/repos/rajiteh/FH5G27/releases/latest";
+/// Give up on a slow or unreachable API instead of keeping the check thread around
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Version of this build
+pub const CURRENT_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// A release newer than this build
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Release {
+    /// Version without the tag's leading "v", e.g. "2.1.0"
+    pub version: String,
+    /// Release page on GitHub
+    pub url: String,
+}
+
+#[derive(Deserialize)]
+struct GithubRelease {
+    tag_name: String,
+    html_url: String,
+}
+
+/// Fetch the latest release, returning it only when it is newer than this build
+pub fn check() -> Result<Option<Release>, String> {
+    let response = ureq::get(RELEASES_URL)
+        .set("User-Agent", concat!("g27-led-bridge/", env!("CARGO_PKG_VERSION")))
+        .set("Accept", "application/vnd.github+json")
+        .timeout(REQUEST_TIMEOUT)
+        .call()
+        .map_err(|e| e.to_string())?;
+    let body = response.into_string().map_err(|e| e.to_string())?;
+    let latest: GithubRelease =
+        serde_json::from_str(&body).map_err(|e| format!("Unexpected response from GitHub: {}", e))?;
+
+    let version = latest.tag_name.trim_start_matches('v').to_string();
+    Ok(is_newer(&version, CURRENT_VERSION).then_some(Release { version, url: latest.html_url }))
+}
+
+/// Whether `latest` is a higher version than `current`, comparing the numeric parts
+/// ("2.10.0" is newer than "2.9.1"; a suffix such as "-beta" is ignored)
+fn is_newer(latest: &str, current: &str) -> bool {
+    fn parts(version: &str) -> Vec<u64> {
+        let release = version.split(['-', '+']).next().unwrap_or_default();
+        let mut parts: Vec<u64> = release.split('.').map(|part| part.parse().unwrap_or(0)).collect();
+        if parts.len() < 3 {
+            parts.resize(3, 0);
+        }
+        parts
+    }
+    parts(latest) > parts(current)
+}
@@ -0,0 +1,323 @@
+// Settings validation for G27 LED Bridge
+//
+// Produces an actionable report instead of silently reverting to defaults:
+// - Unknown keys (typos) are reported as warnings and ignored
+// - Values that fail to deserialize are dropped individually, keeping the rest
+// - Out-of-range values are replaced with sensible defaults and reported
+
+use std::fmt;
+
+use log::{error, warn};
+use serde::Deserialize;
+use toml::{Table, Value};
+
+use crate::common::grpc::GrpcSettings;
+use crate::common::http_api::HttpApiSettings;
+use crate::common::integrations::IntegrationSettings;
+use crate::common::mdns::MdnsSettings;
+use crate::common::outputs::OutputSettings;
+use crate::common::settings::{AppSettings, RetrySettings, TcpMode, TcpSettings};
+use crate::common::websocket::WebSocketSettings;
+
+/// Top-level keys understood by the current settings schema
+const KNOWN_KEYS: &[&str] = &[
+    "version",
+    "game_type",
+    "auto_detect_game",
+    "port",
+    "bind_address",
+    "allowed_senders",
+    "reuse_address",
+    "multicast",
+    "tcp",
+    "retry",
+    "autostart",
+    "notifications",
+    "language",
+    "check_for_updates",
+    "low_latency",
+    "log_level",
+    "log_file",
+    "crash_dumps",
+    "session_history",
+    "leds",
+    "pause",
+    "tray",
+    "exit",
+    "outputs",
+    "integrations",
+    "websocket",
+    "mdns",
+    "http_api",
+    "grpc",
+    "active_profile",
+    "profiles",
+];
+
+/// Keys understood inside each `[profiles.<name>]` table
+const KNOWN_PROFILE_KEYS: &[&str] = &["game_type", "auto_detect_game", "port", "outputs", "leds"];
+
+/// Keys understood inside fixed sub-tables, by dotted table path
+const KNOWN_SECTION_KEYS: &[(&str, &[&str])] = &[
+    ("multicast", &["group", "interface"]),
+    ("tcp", TcpSettings::KNOWN_KEYS),
+    (
+        "retry",
+        &["wheel_search_ms", "reconnect_delay_ms", "socket_retry_delay_ms"],
+    ),
+    ("leds", &["profile", "pattern", "shift_flash"]),
+    ("pause", &["release_port", "hotkey"]),
+    ("tray", &["led_mirror", "left_click", "double_click", "theme"]),
+    ("exit", &["confirm", "console_close"]),
+    ("outputs", OutputSettings::KNOWN_KEYS),
+    ("integrations", &IntegrationSettings::NAMES),
+    ("websocket", WebSocketSettings::KNOWN_KEYS),
+    ("mdns", MdnsSettings::KNOWN_KEYS),
+    ("http_api", HttpApiSettings::KNOWN_KEYS),
+    ("grpc", GrpcSettings::KNOWN_KEYS),
+];
+
+/// Problems found while loading or validating settings
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SettingsReport {
+    /// Invalid values that were replaced with defaults
+    pub errors: Vec<String>,
+    /// Suspicious content that was ignored
+    pub warnings: Vec<String>,
+}
+
+impl SettingsReport {
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty() && self.warnings.is_empty()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        !self.errors.is_empty()
+    }
+
+    /// Write the report to the log
+    pub fn print(&self) {
+        for error in &self.errors {
+            error!("Settings error: {}", error);
+        }
+        for warning in &self.warnings {
+            warn!("Settings warning: {}", warning);
+        }
+    }
+}
+
+impl fmt::Display for SettingsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for error in &self.errors {
+            writeln!(f, "Error: {}", error)?;
+        }
+        for warning in &self.warnings {
+            writeln!(f, "Warning: {}", warning)?;
+        }
+        Ok(())
+    }
+}
+
+/// Report keys that the current schema does not recognize
+pub fn check_unknown_keys(table: &Table, report: &mut SettingsReport) {
+    for key in table.keys().filter(|key| !KNOWN_KEYS.contains(&key.as_str())) {
+        report.warnings.push(format!(
+            "unknown key '{}' is ignored (known keys: {})",
+            key,
+            KNOWN_KEYS.join(", ")
+        ));
+    }
+
+    let integration_sections = IntegrationSettings::KNOWN_KEYS
+        .iter()
+        .map(|(name, known)| (format!("integrations.{}", name), *known));
+    let sections = KNOWN_SECTION_KEYS
+        .iter()
+        .map(|(path, known)| (path.to_string(), *known))
+        .chain(integration_sections);
+
+    for (path, known) in sections {
+        let Some(section_table) = path.split('.').try_fold(table, |current, segment| {
+            match current.get(segment) {
+                Some(Value::Table(next)) => Some(next),
+                _ => None,
+            }
+        }) else {
+            continue;
+        };
+        for key in section_table.keys().filter(|key| !known.contains(&key.as_str())) {
+            report.warnings.push(format!(
+                "[{}]: unknown key '{}' is ignored (known keys: {})",
+                path,
+                key,
+                known.join(", ")
+            ));
+        }
+    }
+
+    if let Some(Value::Table(profiles)) = table.get("profiles") {
+        for (name, profile) in profiles {
+            let Value::Table(profile) = profile else {
+                continue;
+            };
+            for key in profile.keys().filter(|key| !KNOWN_PROFILE_KEYS.contains(&key.as_str())) {
+                report.warnings.push(format!(
+                    "profile '{}': unknown key '{}' is ignored (known keys: {})",
+                    name,
+                    key,
+                    KNOWN_PROFILE_KEYS.join(", ")
+                ));
+            }
+        }
+    }
+}
+
+/// Deserialize settings, dropping individual keys with invalid values so the
+/// rest of the user's configuration survives
+pub fn deserialize_lenient(mut table: Table, report: &mut SettingsReport) -> AppSettings {
+    // Check each key on its own; missing keys fall back to their defaults
+    let mut invalid = Vec::new();
+    for (key, value) in &table {
+        let single = Table::from_iter([(key.clone(), value.clone())]);
+        if let Err(e) = AppSettings::deserialize(Value::Table(single)) {
+            report.errors.push(format!("{} (using default)", error_message(&e)));
+            invalid.push(key.clone());
+        }
+    }
+
+    for key in &invalid {
+        table.remove(key);
+    }
+
+    let mut settings = match AppSettings::deserialize(Value::Table(table)) {
+        Ok(settings) => settings,
+        Err(e) => {
+            report.errors.push(format!("{} (using default settings)", error_message(&e)));
+            return AppSettings::default();
+        }
+    };
+
+    // The generic default port belongs to DiRT Rally 2.0; follow the configured game instead
+    if invalid.iter().any(|key| key == "port") {
+        settings.port = settings.game_type.default_port();
+    }
+
+    settings
+}
+
+fn error_message(error: &toml::de::Error) -> String {
+    error.to_string().split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Replace out-of-range values with defaults, recording what was changed
+pub fn repair(settings: &mut AppSettings, report: &mut SettingsReport) {
+    if settings.port == 0 {
+        settings.port = settings.game_type.default_port();
+        report.errors.push(format!(
+            "port must be between 1 and 65535 (using {})",
+            settings.port
+        ));
+    }
+
+    for (name, profile) in settings.profiles.iter_mut() {
+        if profile.port == 0 {
+            profile.port = profile.game_type.default_port();
+            report.errors.push(format!(
+                "profile '{}': port must be between 1 and 65535 (using {})",
+                name, profile.port
+            ));
+        }
+    }
+
+    if settings.websocket.port == 0 {
+        settings.websocket.port = WebSocketSettings::default().port;
+        report.errors.push(format!(
+            "[websocket] port must be between 1 and 65535 (using {})",
+            settings.websocket.port
+        ));
+    }
+    if settings.http_api.port == 0 {
+        settings.http_api.port = HttpApiSettings::default().port;
+        report.errors.push(format!(
+            "[http_api] port must be between 1 and 65535 (using {})",
+            settings.http_api.port
+        ));
+    }
+    if settings.grpc.port == 0 {
+        settings.grpc.port = GrpcSettings::default().port;
+        report.errors.push(format!(
+            "[grpc] port must be between 1 and 65535 (using {})",
+            settings.grpc.port
+        ));
+    }
+
+    let multicast = &mut settings.multicast;
+    if let Some(group) = multicast.group.filter(|group| !group.is_multicast()) {
+        multicast.group = None;
+        report.errors.push(format!(
+            "[multicast] group {} is not a multicast address (224.0.0.0/4 or ff00::/8) and was cleared",
+            group
+        ));
+    }
+    if let (Some(group), Some(interface)) = (multicast.group, multicast.interface) {
+        if !(group.is_ipv4() && interface.is_ipv4()) {
+            multicast.interface = None;
+            report.warnings.push(format!(
+                "[multicast] interface {} is ignored: only IPv4 groups are joined on a chosen interface",
+                interface
+            ));
+        }
+    }
+
+    let tcp = &mut settings.tcp;
+    if tcp.mode == TcpMode::Connect {
+        let has_port = tcp
+            .connect_to
+            .trim()
+            .rsplit_once(':')
+            .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok_and(|port| port != 0));
+        if !has_port {
+            report.errors.push(format!(
+                "[tcp] connect_to '{}' must be host:port to connect to (receiving UDP instead)",
+                tcp.connect_to
+            ));
+            tcp.mode = TcpMode::Off;
+        }
+    }
+    if tcp.mode != TcpMode::Off && settings.multicast.group.is_some() {
+        report
+            .warnings
+            .push("[multicast] group is ignored while [tcp] mode receives telemetry over TCP".to_string());
+    }
+
+    let retry_defaults = RetrySettings::default();
+    let retry = &mut settings.retry;
+    for (name, value, default) in [
+        ("wheel_search_ms", &mut retry.wheel_search_ms, retry_defaults.wheel_search_ms),
+        ("reconnect_delay_ms", &mut retry.reconnect_delay_ms, retry_defaults.reconnect_delay_ms),
+        ("socket_retry_delay_ms", &mut retry.socket_retry_delay_ms, retry_defaults.socket_retry_delay_ms),
+    ] {
+        if !(RetrySettings::MIN_MS..=RetrySettings::MAX_MS).contains(value) {
+            *value = default;
+            report.errors.push(format!(
+                "[retry] {} must be between {} and {} (using {})",
+                name,
+                RetrySettings::MIN_MS,
+                RetrySettings::MAX_MS,
+                default
+            ));
+        }
+    }
+
+    report.errors.extend(settings.integrations.disable_incomplete());
+
+    if let Some(name) = settings.active_profile.clone() {
+        if !settings.profiles.contains_key(&name) {
+            settings.active_profile = None;
+            report.warnings.push(format!(
+                "active_profile '{}' does not exist in [profiles] and was cleared",
+                name
+            ));
+        }
+    }
+}
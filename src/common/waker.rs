@@ -0,0 +1,57 @@
+// Channels that wake the tray's event loop
+//
+// The tray sleeps until something happens instead of polling on a timer. The bridge
+// thread reports through these senders, which wake the event loop after every message
+// so status, state and LED updates show up as soon as they are sent. Without a waker
+// (e.g. in the service, where nothing reads the channels) they are plain senders.
+
+use std::sync::{mpsc, Arc};
+
+/// Wakes the thread that reads a channel
+#[derive(Clone, Default)]
+pub struct Waker(Option<Arc<dyn Fn() + Send + Sync>>);
+
+impl Waker {
+    pub fn new(wake: impl Fn() + Send + Sync + 'static) -> Self {
+        Self(Some(Arc::new(wake)))
+    }
+
+    pub fn wake(&self) {
+        if let Some(wake) = &self.0 {
+            wake();
+        }
+    }
+}
+
+/// Sender that calls its waker after each message
+pub struct WakingSender<T> {
+    sender: mpsc::Sender<T>,
+    waker: Waker,
+}
+
+impl<T> WakingSender<T> {
+    pub fn send(&self, value: T) -> Result<(), mpsc::SendError<T>> {
+        self.sender.send(value)?;
+        self.waker.wake();
+        Ok(())
+    }
+}
+
+impl<T> Clone for WakingSender<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            waker: self.waker.clone(),
+        }
+    }
+}
+
+/// Channel whose sender wakes `waker` after each message
+pub fn channel<T>(waker: &Waker) -> (WakingSender<T>, mpsc::Receiver<T>) {
+    let (sender, receiver) = mpsc::channel();
+    let sender = WakingSender {
+        sender,
+        waker: waker.clone(),
+    };
+    (sender, receiver)
+}
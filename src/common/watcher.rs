@@ -0,0 +1,140 @@
+// Settings file watcher for G27 LED Bridge
+//
+// Watches settings.toml for external edits and applies them live:
+// - Debounces bursts of filesystem events (editors often write several times)
+// - Watches the parent directory so atomic "write temp + rename" saves are seen
+// - Reloads settings into the shared tray state and raises the changed signal
+// - Hands any validation problems to the tray so they can be shown to the user
+//
+// The manual "Reload Settings" menu entry remains available as a fallback
+
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use log::{debug, error, info};
+use notify_debouncer_mini::{
+    new_debouncer, notify::RecommendedWatcher, notify::RecursiveMode, DebounceEventResult,
+    Debouncer,
+};
+use tokio::sync::watch;
+
+use crate::common::{settings::AppSettings, validation::SettingsReport};
+
+/// Raised whenever the shared settings are modified.
+/// The tray polls it to refresh its menu; the bridge awaits it through `subscribe`.
+pub struct SettingsChanged {
+    pending: Mutex<bool>,
+    notify: watch::Sender<()>,
+}
+
+impl SettingsChanged {
+    pub fn new() -> Self {
+        Self {
+            pending: Mutex::new(false),
+            notify: watch::Sender::new(()),
+        }
+    }
+
+    /// Mark settings as changed and wake any subscribers
+    pub fn raise(&self) {
+        if let Ok(mut pending) = self.pending.lock() {
+            *pending = true;
+        }
+        self.notify.send_replace(());
+    }
+
+    /// Whether settings changed since the last call
+    pub fn take(&self) -> bool {
+        self.pending
+            .lock()
+            .map(|mut pending| std::mem::take(&mut *pending))
+            .unwrap_or(false)
+    }
+
+    /// Receiver that is notified on every change
+    pub fn subscribe(&self) -> watch::Receiver<()> {
+        self.notify.subscribe()
+    }
+}
+
+impl Default for SettingsChanged {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct SettingsWatcher {
+    _debouncer: Debouncer<RecommendedWatcher>,
+}
+
+impl SettingsWatcher {
+    const DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(500);
+
+    /// Start watching the settings file, reloading into `settings` and raising `changed` on edits.
+    /// Problems found in the edited file are stored in `report` for the tray to display.
+    pub fn start(
+        settings: Arc<Mutex<AppSettings>>,
+        changed: Arc<SettingsChanged>,
+        report: Arc<Mutex<Option<SettingsReport>>>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let settings_path = AppSettings::config_path()?;
+        let watch_dir = settings_path
+            .parent()
+            .ok_or("Settings file has no parent directory")?
+            .to_path_buf();
+
+        let mut debouncer = new_debouncer(Self::DEBOUNCE_TIMEOUT, move |result: DebounceEventResult| {
+            match result {
+                Ok(events) => {
+                    if events.iter().any(|event| Self::is_settings_file(&event.path, &settings_path)) {
+                        Self::reload(&settings, &changed, &report);
+                    }
+                }
+                Err(e) => {
+                    error!("Settings watcher error: {}", e);
+                }
+            }
+        })?;
+
+        debouncer
+            .watcher()
+            .watch(&watch_dir, RecursiveMode::NonRecursive)?;
+
+        debug!("Watching {:?} for settings changes", watch_dir);
+
+        Ok(SettingsWatcher {
+            _debouncer: debouncer,
+        })
+    }
+
+    fn is_settings_file(path: &Path, settings_path: &Path) -> bool {
+        path.file_name() == settings_path.file_name()
+    }
+
+    fn reload(
+        settings: &Arc<Mutex<AppSettings>>,
+        changed: &Arc<SettingsChanged>,
+        report: &Arc<Mutex<Option<SettingsReport>>>,
+    ) {
+        let (reloaded, new_report) = AppSettings::load_with_report();
+        new_report.print();
+
+        if !new_report.is_empty() {
+            if let Ok(mut report) = report.lock() {
+                *report = Some(new_report);
+            }
+        }
+
+        if let Ok(mut settings) = settings.lock() {
+            // Our own saves also trigger the watcher; skip those to avoid redundant updates
+            if *settings == reloaded {
+                return;
+            }
+            *settings = reloaded;
+            info!("Settings file changed - applying new settings");
+        }
+
+        changed.raise();
+    }
+}
@@ -0,0 +1,264 @@
+// Embedded WebSocket server streaming telemetry as JSON
+//
+// Off by default. While `[websocket] enabled` is set, the bridge accepts WebSocket
+// connections on `bind_address:port` and sends every client one text message per
+// telemetry frame, for browser overlays, OBS browser sources and phone dashboards:
+//
+//   {"type":"telemetry","game":"ForzaHorizon5","rpm":5230.5,"max_rpm":8000.0,
+//    "idle_rpm":800.0,"race_active":true,"speed":31.2,"gear":3,"leds":7}
+//
+// `leds` is the LED bar as the wheel shows it, one bit per LED from the outside in.
+// Only what a browser needs of RFC 6455 is implemented: the handshake, unfragmented
+// text frames out, and close/ping handling for frames coming in. Clients that fall
+// behind skip messages rather than slowing the bridge down.
+
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+
+use base64::Engine;
+use log::debug;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{broadcast, mpsc};
+
+use g27_led_bridge_core::TelemetryFrame;
+
+/// Appended to the client's key to prove the server speaks WebSocket (RFC 6455, section 1.3)
+const ACCEPT_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Longest handshake request accepted
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// Largest frame accepted from a client; they only ever send pings and close frames
+const MAX_CLIENT_FRAME: u64 = 4 * 1024;
+
+/// Messages a client may fall behind by before it skips ahead
+const CLIENT_BACKLOG: usize = 64;
+
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xA;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct WebSocketSettings {
+    pub enabled: bool,
+    /// Local address to accept connections on; use 0.0.0.0 for phones and other PCs
+    pub bind_address: IpAddr,
+    pub port: u16,
+}
+
+impl WebSocketSettings {
+    pub const KNOWN_KEYS: &'static [&'static str] = &["enabled", "bind_address", "port"];
+
+    pub fn listen_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.bind_address, self.port)
+    }
+}
+
+impl Default for WebSocketSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            bind_address: IpAddr::V4(Ipv4Addr::LOCALHOST),
+            port: 8765,
+        }
+    }
+}
+
+/// JSON message sent to clients for `frame`, with `leds` the LED bar shown for it
+pub fn telemetry_message(frame: &TelemetryFrame, leds: u8) -> String {
+    json!({
+        "type": "telemetry",
+        "game": frame.game_type,
+        "rpm": frame.rpm,
+        "max_rpm": frame.max_rpm,
+        "idle_rpm": frame.idle_rpm,
+        "race_active": frame.race_active,
+        "speed": frame.speed,
+        "gear": frame.gear,
+        "leds": leds,
+    })
+    .to_string()
+}
+
+/// Listening socket whose clients each receive every broadcast message
+pub struct WebSocketServer {
+    listener: TcpListener,
+    messages: broadcast::Sender<Arc<str>>,
+}
+
+impl WebSocketServer {
+    pub async fn bind(addr: SocketAddr) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr).await?,
+            messages: broadcast::channel(CLIENT_BACKLOG).0,
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Number of clients connected
+    pub fn clients(&self) -> usize {
+        self.messages.receiver_count()
+    }
+
+    /// Send `message` to every connected client
+    pub fn broadcast(&self, message: &str) {
+        // Fails only without clients
+        let _ = self.messages.send(Arc::from(message));
+    }
+
+    /// Accept the next connection and serve it on a task of its own until it closes.
+    /// Cancel-safe, so it can be raced against telemetry in `select!`.
+    pub async fn accept(&self) -> io::Result<()> {
+        let (stream, peer) = self.listener.accept().await?;
+        let messages = self.messages.subscribe();
+        tokio::spawn(async move {
+            debug!("WebSocket client {} connected", peer);
+            match serve_client(stream, messages).await {
+                Ok(()) => debug!("WebSocket client {} disconnected", peer),
+                Err(e) => debug!("WebSocket client {} dropped: {}", peer, e),
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Complete the handshake, then forward messages until the client or the server goes away
+async fn serve_client(mut stream: TcpStream, mut messages: broadcast::Receiver<Arc<str>>) -> io::Result<()> {
+    handshake(&mut stream).await?;
+    let (reader, mut writer) = stream.into_split();
+    let (pong_tx, mut pongs) = mpsc::channel(4);
+    let incoming = read_frames(reader, pong_tx);
+    tokio::pin!(incoming);
+
+    loop {
+        tokio::select! {
+            message = messages.recv() => match message {
+                Ok(message) => writer.write_all(&frame(OPCODE_TEXT, message.as_bytes())).await?,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    debug!("WebSocket client fell behind, skipped {} messages", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => {
+                    return writer.write_all(&frame(OPCODE_CLOSE, &[])).await;
+                }
+            },
+            Some(pong) = pongs.recv() => writer.write_all(&pong).await?,
+            result = &mut incoming => {
+                // Answer the client's close; it may already be gone
+                let _ = writer.write_all(&frame(OPCODE_CLOSE, &[])).await;
+                return result;
+            }
+        }
+    }
+}
+
+/// Read the HTTP upgrade request and answer it with 101 Switching Protocols
+async fn handshake(stream: &mut TcpStream) -> io::Result<()> {
+    let mut request = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+        if request.len() > MAX_REQUEST_BYTES {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "handshake request too long"));
+        }
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        request.extend_from_slice(&buffer[..read]);
+    }
+
+    let request = String::from_utf8_lossy(&request);
+    let key = request
+        .starts_with("GET ")
+        .then(|| {
+            request.lines().skip(1).find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.trim().eq_ignore_ascii_case("sec-websocket-key").then(|| value.trim())
+            })
+        })
+        .flatten();
+    let Some(key) = key else {
+        stream.write_all(b"HTTP/1.1 400 Bad Request\r\nConnection: close\r\n\r\n").await?;
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not a WebSocket upgrade request"));
+    };
+
+    let response = format!(
+        "HTTP/1.1 101 Switching Protocols\r\n\
+         Upgrade: websocket\r\n\
+         Connection: Upgrade\r\n\
+         Sec-WebSocket-Accept: {}\r\n\r\n",
+        accept_key(key)
+    );
+    stream.write_all(response.as_bytes()).await
+}
+
+/// `Sec-WebSocket-Accept` value for a client's `Sec-WebSocket-Key`
+fn accept_key(key: &str) -> String {
+    let input = format!("{}{}", key, ACCEPT_GUID);
+    let digest = ring::digest::digest(&ring::digest::SHA1_FOR_LEGACY_USE_ONLY, input.as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(digest.as_ref())
+}
+
+/// Unmasked, unfragmented server frame
+fn frame(opcode: u8, payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x80 | opcode);
+    match payload.len() {
+        length @ 0..=125 => frame.push(length as u8),
+        length @ 126..=0xFFFF => {
+            frame.push(126);
+            frame.extend_from_slice(&(length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(127);
+            frame.extend_from_slice(&(length as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(payload);
+    frame
+}
+
+/// Read client frames until it closes the connection, queueing a pong for every ping
+async fn read_frames(mut reader: impl AsyncRead + Unpin, pongs: mpsc::Sender<Vec<u8>>) -> io::Result<()> {
+    loop {
+        let mut header = [0u8; 2];
+        match reader.read_exact(&mut header).await {
+            Ok(_) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(e),
+        }
+        let opcode = header[0] & 0x0F;
+        let length = match header[1] & 0x7F {
+            126 => u64::from(reader.read_u16().await?),
+            127 => reader.read_u64().await?,
+            length => u64::from(length),
+        };
+        if length > MAX_CLIENT_FRAME {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "client frame too large"));
+        }
+        let mut mask = [0u8; 4];
+        if header[1] & 0x80 != 0 {
+            reader.read_exact(&mut mask).await?;
+        }
+        let mut payload = vec![0u8; length as usize];
+        reader.read_exact(&mut payload).await?;
+        for (i, byte) in payload.iter_mut().enumerate() {
+            *byte ^= mask[i % 4];
+        }
+
+        match opcode {
+            OPCODE_CLOSE => return Ok(()),
+            OPCODE_PING if pongs.send(frame(OPCODE_PONG, &payload)).await.is_err() => return Ok(()),
+            // Clients have nothing to tell the bridge
+            _ => {}
+        }
+    }
+}
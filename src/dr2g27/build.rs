@@ -1,4 +1,8 @@
 fn main() {
+    #[cfg(feature = "grpc")]
+    tonic_build::compile_protos("proto/g27_bridge.proto")
+        .expect("cannot compile proto/g27_bridge.proto; is protoc installed?");
+
     #[cfg(not(debug_assertions))]
     {
         use std::path::Path;
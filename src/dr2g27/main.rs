@@ -5,376 +5,3167 @@
 //
 // MIT License - see LICENSE file for details
 
-use clap::{Parser, Subcommand};
+use clap::builder::{PossibleValue, PossibleValuesParser};
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use g27_led_bridge_core::{
+    capture_frames, drive_leds, forward_frames, open_g27, publish_frames, publish_frames_detecting,
+    publish_frames_switching, record_frames, replay_frames, wait_for_bounds, AsyncTcpSource,
+    AsyncTelemetrySource, AsyncUdpSource, BoundOutcome, Capture, CaptureReader, ForwardFormat, LedOutput, LoopControl,
+    PacketStats, PacketStatsSummary, Recorder, RecordingReader, ReplaySource, RetryingWheel, RunBounds, SenderFilter,
+    SessionSummary, SessionTracker, TelemetryBus, TelemetryFrame, WatchedOutput,
+};
+use g27_led_bridge_core::capture::{CAPTURE_EXTENSION, PCAP_EXTENSION};
+use g27_led_bridge_core::output::write_g27_leds;
+use g27_led_bridge_core::simulator::{Pattern, Simulator};
+use g27_led_bridge_core::WheelDevice;
 use g27_led_bridge::common::{
-    leds::LEDS,
-    settings::AppSettings,
-    systray::{SystemTray, hide_console_window, create_event_loop},
+    autostart,
+    config_keys,
+    console,
+    crash,
+    dashboard,
+    diagnostics,
+    exit_code::ExitCode,
+    grpc,
+    http_api::{self, Command, Response},
+    instance,
+    ipc::{self, InstanceStatus, LiveReadout, Request},
+    latency::{self, LowLatency},
+    service,
+    logging,
+    mdns::{self, Advertisement},
+    mqtt::{self, MqttClient},
+    osc::{self, OscSender},
+    power,
+    paths,
+    packet_explorer::{self, ExplorerFilter, PacketExplorer},
+    leds::{FillPattern, LedProfile, LedStyle, LEDS},
+    lifecycle::{BridgeState, LifecycleEvent, LifecycleReporter, LiveTelemetry},
+    settings::{AppSettings, ConsoleClose, MulticastSettings, RetrySettings, TcpMode, TcpSettings},
+    supervisor::{supervise, Component, ComponentStatus, RestartPolicy, StatusReporter, StatusUpdate},
+    systray::{SystemTray, hide_console_window, create_event_loop, show_info_dialog},
+    waker::{self, Waker, WakingSender},
+    watcher::{SettingsChanged, SettingsWatcher},
+    websocket::{self, WebSocketServer},
     telemetry::GameType,
-    util::{DR2G27Error, DR2G27Result, G27_PID, G27_VID},
+    util::{DR2G27Error, DR2G27Result, WheelOpenFailure, G27_PID, G27_VID},
 };
 use hidapi::{HidApi, HidDevice};
-use std::{net::UdpSocket, thread::{self, sleep}, time::Duration, sync::Arc};
+use log::{debug, error, info, warn};
+use std::{cell::Cell, future::Future, io::ErrorKind, net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs, UdpSocket}, path::{Path, PathBuf}, thread::{self, sleep}, time::{Duration, Instant}, sync::{Arc, Mutex}};
+use tokio::sync::watch;
 use winit::event::WindowEvent;
 
 // Telemetry config "hardware_settings_config.xml"
 // <udp enabled="true" extradata="3" ip="127.0.0.1" port="20777" delay="1" />
 
+// Every option can also be set through a G27LB_* environment variable, e.g. for
+// launch scripts or Steam launch options. Precedence: flag > environment > settings file.
 #[derive(Parser)]
 #[command(name = "g27-led-bridge")]
 #[command(about = "Racing game telemetry to Logitech G27 LED bridge")]
 struct Cli {
-    /// Game to bridge telemetry from (overrides saved setting)
-    #[arg(short, long)]
+    /// Game to bridge telemetry from, or auto to detect it from its packets (overrides saved setting)
+    #[arg(short, long, global = true, env = "G27LB_GAME", value_parser = game_names(), ignore_case = true)]
     game: Option<String>,
     
     /// UDP port to listen on (overrides saved setting)
-    #[arg(short, long)]
+    #[arg(short, long, global = true, env = "G27LB_PORT")]
     port: Option<u16>,
     
+    /// Local address to listen on, e.g. 0.0.0.0 or :: for telemetry from another machine (overrides saved setting)
+    #[arg(long, global = true, env = "G27LB_BIND")]
+    bind: Option<IpAddr>,
+    
+    /// Named settings profile to switch to (remembered as last-used)
+    #[arg(long, env = "G27LB_PROFILE")]
+    profile: Option<String>,
+    
+    /// Store settings next to the executable instead of %APPDATA% (same as a portable.txt marker)
+    #[arg(long, env = "G27LB_PORTABLE")]
+    portable: bool,
+    
     /// Run in console mode instead of system tray
-    #[arg(long)]
+    #[arg(long, env = "G27LB_CONSOLE")]
     console: bool,
     
+    /// Run without tray icon or console window changes, e.g. under a process manager; logs go to
+    /// the console and the log file, and Ctrl+C, closing the console or SIGTERM exit cleanly
+    #[arg(long, env = "G27LB_HEADLESS", conflicts_with = "console")]
+    headless: bool,
+    
+    /// More detailed output: -v for debug, -vv for trace (on top of the configured log level)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+    
+    /// LED profile for this run only: relaxed, standard or aggressive (not saved)
+    #[arg(long, env = "G27LB_LED_PROFILE", value_name = "PROFILE")]
+    led_profile: Option<LedProfile>,
+    
+    /// LED fill pattern for this run only: progressive, center-out or dot (not saved)
+    #[arg(long, env = "G27LB_PATTERN")]
+    pattern: Option<FillPattern>,
+    
     /// Exit immediately if G27 wheel is not found during startup
-    #[arg(long)]
+    #[arg(long, env = "G27LB_REQUIRE_WHEEL")]
     require_wheel: bool,
     
+    /// Stop the bridge, monitor or LED test after this many seconds
+    #[arg(long, global = true, value_name = "SECONDS", value_parser = parse_seconds)]
+    duration: Option<Duration>,
+    
+    /// Stop the bridge or monitor after this many telemetry packets; exits with code 6 if
+    /// --duration runs out first
+    #[arg(long, global = true, value_name = "COUNT", value_parser = clap::value_parser!(u64).range(1..))]
+    packets: Option<u64>,
+    
+    /// Capture the telemetry packets of the first 30 seconds to this .pcapng file, e.g. for a bug report
+    #[arg(long, value_name = "FILE")]
+    capture: Option<PathBuf>,
+    
+    /// Log a hex dump of packets the selected game does not send (at most one every 5 seconds),
+    /// with the values that could be floats, to diagnose a wrong game or an unknown format
+    #[arg(long, global = true)]
+    dump_unknown: bool,
+    
     #[command(subcommand)]
     command: Option<Commands>,
 }
 
+/// `--duration` value: seconds, fractions allowed
+fn parse_seconds(value: &str) -> Result<Duration, String> {
+    value
+        .parse::<f64>()
+        .ok()
+        .filter(|seconds| *seconds > 0.0)
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| format!("'{}' is not a positive number of seconds", value))
+}
+
+/// `--led-profile` and `--pattern` for this run, kept out of the settings so they are never saved
+#[derive(Clone, Copy, Default)]
+struct LedOverrides {
+    profile: Option<LedProfile>,
+    pattern: Option<FillPattern>,
+}
+
+impl LedOverrides {
+    /// The configured `style` with the overrides in place
+    fn apply(self, style: LedStyle) -> LedStyle {
+        LedStyle {
+            profile: self.profile.unwrap_or(style.profile),
+            pattern: self.pattern.unwrap_or(style.pattern),
+            ..style
+        }
+    }
+}
+
+/// Command-line options for the wheel output of the bridge
+#[derive(Clone, Copy, Default)]
+struct WheelOptions {
+    /// `--require-wheel`: exit if the first search does not find the wheel
+    require_wheel: bool,
+    led_overrides: LedOverrides,
+}
+
+/// `--game` value that turns on game auto-detection
+const AUTO_DETECT_GAME: &str = "auto";
+
+/// `--game` values for validation and shell completion: each game's full name, with its
+/// short names as aliases, and `auto`
+fn game_names() -> PossibleValuesParser {
+    let games = GameType::ALL.map(|game| {
+        let (name, aliases) = game.aliases().split_first().expect("every game has a name");
+        PossibleValue::new(*name)
+            .aliases(aliases.iter().copied())
+            .help(game.parser().game_name())
+    });
+    let auto = PossibleValue::new(AUTO_DETECT_GAME).help("Detect the game from its telemetry packets");
+    PossibleValuesParser::new(games.into_iter().chain([auto]))
+}
+
+/// How informational commands print their results
+#[derive(Clone, Copy, PartialEq, Default, ValueEnum)]
+enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// JSON for scripts and Stream Deck plugins
+    Json,
+}
+
 #[derive(Subcommand)]
 enum Commands {
+    /// List supported games with their --game names, packet sizes and default ports
+    ListGames {
+        #[arg(long, value_enum, default_value_t)]
+        output: OutputFormat,
+    },
+    /// Print the telemetry fields each game's parser reads: offsets, types and units (only --game's if given)
+    Schema {
+        #[arg(long, value_enum, default_value_t)]
+        output: OutputFormat,
+    },
+    /// List connected Logitech HID devices and which one would be used as the G27
+    ListDevices {
+        #[arg(long, value_enum, default_value_t)]
+        output: OutputFormat,
+    },
+    /// Show the effective settings, whether the G27 is connected and whether the telemetry port is taken
+    Status {
+        #[arg(long, value_enum, default_value_t)]
+        output: OutputFormat,
+    },
+    /// Show what a bug report needs: versions, Logitech software and devices, and the running bridge's state
+    Doctor {
+        /// Bundle it with the settings (credentials removed) and the log into a zip for a GitHub issue,
+        /// written to FILE or to the data folder
+        #[arg(long, value_name = "FILE")]
+        report: Option<Option<PathBuf>>,
+    },
+    /// Print a shell completion script, e.g. `completions powershell >> $PROFILE`
+    Completions {
+        /// Shell to generate the script for
+        #[arg(value_enum)]
+        shell: Shell,
+    },
     /// Test LED functionality without game running
     Test {
         /// Run a continuous LED test pattern
         #[arg(short, long)]
         continuous: bool,
     },
+    /// Show live parsed telemetry until Ctrl+C, without driving the wheel, to check the game's telemetry settings
+    Monitor {
+        /// `json` prints one JSON object per line instead of a refreshing status line
+        #[arg(long, value_enum, default_value_t)]
+        output: OutputFormat,
+    },
+    /// Show incoming packets as offsets with hex and float values until Ctrl+C, to find the RPM of an unsupported game
+    CaptureOffsets {
+        /// Only show offsets whose value has changed
+        #[arg(long)]
+        changing: bool,
+        /// Only show offsets whose float value is at least this, e.g. 500 for an idling engine's RPM
+        #[arg(long, allow_negative_numbers = true)]
+        min: Option<f32>,
+        /// Only show offsets whose float value is at most this
+        #[arg(long, allow_negative_numbers = true)]
+        max: Option<f32>,
+    },
+    /// Record telemetry to a session file until Ctrl+C, without driving the wheel
+    Record {
+        /// Session file to write, e.g. session.g27rec
+        #[arg(short, long)]
+        output: PathBuf,
+        /// Also write parsed frames to a .csv or .jsonl file
+        #[arg(long)]
+        frames: Option<PathBuf>,
+    },
+    /// Forward the game's telemetry to a bridge on another machine until Ctrl+C, without driving the wheel
+    Send {
+        /// Machine running the bridge, as HOST or HOST:PORT (defaults to the port listened on); repeat for
+        /// more destinations. Append =json for JSON frames or =fh5/=dr2 for packets in that game's format
+        #[arg(long, value_name = "HOST[:PORT][=FORMAT]", required = true)]
+        to: Vec<String>,
+    },
+    /// Play a recorded session or a packet capture back on the wheel LEDs
+    Replay {
+        /// Session file written by `record` or the tray, or a .pcap/.pcapng capture of the packets to --port
+        file: PathBuf,
+        /// Playback speed, e.g. 0.5 for half or 2 for double speed
+        #[arg(long, default_value_t = 1.0)]
+        speed: f32,
+    },
+    /// Send synthetic telemetry to the bridge's port until Ctrl+C, for demos and tests without a game
+    Simulate {
+        /// RPM pattern: rev-sweep, shifts, redline, idle or menu
+        #[arg(long, default_value = "rev-sweep")]
+        pattern: Pattern,
+        /// Packets per second
+        #[arg(long, default_value_t = 60)]
+        rate: u32,
+    },
+    /// Open the dashboard: live status, virtual LEDs and the everyday settings in one window
+    Dashboard,
+    /// Manage the settings file
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommands,
+    },
+    /// Run the bridge as a Windows service, without a tray or a logged-in user
+    Service {
+        #[command(subcommand)]
+        action: ServiceCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigCommands {
+    /// Write a commented settings file documenting every option
+    Init {
+        /// Replace an existing settings file
+        #[arg(long)]
+        force: bool,
+    },
+    /// List all settings as dotted keys with their current values
+    List,
+    /// Print a setting, e.g. `port` or `retry.wheel_search_ms`
+    Get {
+        /// Dotted setting key
+        key: String,
+    },
+    /// Change a setting and save it (a running bridge picks it up automatically)
+    Set {
+        /// Dotted setting key, e.g. `profiles."Rally rig".port` (quote it for the shell)
+        key: String,
+        /// New value, e.g. `fh5`, `5685` or `0.0.0.0`
+        value: String,
+    },
+    /// Export current settings to a file for backup or sharing
+    Export {
+        /// Destination file
+        file: PathBuf,
+    },
+    /// Import settings from a file (validated before saving)
+    Import {
+        /// Settings file to import
+        file: PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+enum ServiceCommands {
+    /// Register the service to start with Windows, using your settings (run as administrator)
+    Install,
+    /// Stop and remove the service (run as administrator)
+    Uninstall,
+    /// Start the installed service
+    Start,
+    /// Stop the service, turning the LEDs off
+    Stop,
+    /// Entry point used by the service control manager
+    #[command(hide = true)]
+    Run {
+        /// Directory with the settings of the user who installed the service
+        #[arg(long)]
+        data_dir: PathBuf,
+    },
+}
+
+// Longest wait for the bridge thread to clean up after Exit
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(1);
+
+// How often the tray loop wakes without an event, for changes nothing reports (e.g. the
+// Windows theme or a minimized console)
+const TRAY_IDLE_POLL: Duration = Duration::from_secs(1);
+
+// Telemetry counts as stopped after this long without a packet
+const STALE_AFTER: Duration = Duration::from_secs(2);
+
+// Race sessions are added to this file in the data directory while `session_history` is on
+const SESSION_HISTORY_FILE: &str = "sessions.csv";
+
+// Packet sizes kept for status queries, which diagnostic reports include
+const RECENT_PACKET_SIZES: usize = 10;
+
+// How often the tray tooltip shows new telemetry
+const TOOLTIP_REFRESH: Duration = Duration::from_secs(1);
+
+// How often the LED bar mirrored in the tray icon is redrawn at most
+const MIRROR_REFRESH: Duration = Duration::from_millis(100);
+
+// How long a packet capture from the tray or --capture runs
+const CAPTURE_DURATION: Duration = Duration::from_secs(30);
+
+fn retry_settings(settings: &Arc<Mutex<AppSettings>>) -> RetrySettings {
+    settings.lock().map(|settings| settings.retry.clone()).unwrap_or_default()
+}
+
+/// Signals the bridge components react to without waiting for telemetry
+#[derive(Clone)]
+struct BridgeSignals {
+    settings: Arc<Mutex<AppSettings>>,
+    settings_changed: watch::Receiver<()>,
+    /// Raised when the bridge changes `settings` itself, so the tray shows it too
+    notify_settings_changed: Arc<SettingsChanged>,
+    /// Set while the user has paused the bridge from the tray
+    paused: watch::Receiver<bool>,
+    /// Sender of `paused`, for pausing through the HTTP API
+    pause_control: Arc<watch::Sender<bool>>,
+    shutdown: watch::Receiver<bool>,
+}
+
+impl BridgeSignals {
+    fn shutdown_requested(&self) -> bool {
+        *self.shutdown.borrow()
+    }
+
+    /// Paused with `[pause] release_port` set, so the listener must not hold the port
+    fn port_released(&self) -> bool {
+        *self.paused.borrow() && self.settings.lock().is_ok_and(|settings| settings.pause.release_port)
+    }
+
+    /// Wait until the bridge is resumed, returning false if shutdown was requested first
+    async fn resumed(&mut self) -> bool {
+        tokio::select! {
+            resumed = self.paused.wait_for(|paused| !*paused) => resumed.is_ok(),
+            _ = self.shutdown.wait_for(|exit| *exit) => false,
+        }
+    }
+
+    /// Wait for `duration`, returning false if shutdown was requested first
+    async fn sleep(&mut self, duration: Duration) -> bool {
+        tokio::select! {
+            _ = tokio::time::sleep(duration) => true,
+            _ = self.shutdown.wait_for(|exit| *exit) => false,
+        }
+    }
+
+    /// Wait until `read` gives something other than `current` after a settings change, returning
+    /// false if shutdown was requested first
+    async fn settings_differ<T: PartialEq>(&mut self, current: &T, read: impl Fn(&AppSettings) -> T) -> bool {
+        loop {
+            tokio::select! {
+                Ok(()) = self.settings_changed.changed() => {
+                    if self.settings.lock().is_ok_and(|settings| read(&settings) != *current) {
+                        return true;
+                    }
+                }
+                _ = self.shutdown.wait_for(|exit| *exit) => return false,
+            }
+        }
+    }
+
+    /// Run `task` with a control channel that follows `decide` on every settings
+    /// change or pause/resume and switches to `Stop` on shutdown. `decide` is given
+    /// the settings and whether the bridge is paused.
+    async fn controlled<Fut>(
+        &mut self,
+        decide: impl Fn(&AppSettings, bool) -> LoopControl,
+        task: impl FnOnce(watch::Receiver<LoopControl>) -> Fut,
+    ) -> DR2G27Result
+    where
+        Fut: Future<Output = DR2G27Result>,
+    {
+        let current = |settings: &Arc<Mutex<AppSettings>>, paused: bool| {
+            settings.lock().map(|settings| decide(&settings, paused)).unwrap_or(LoopControl::Run)
+        };
+        let (control_tx, control_rx) = watch::channel(current(&self.settings, *self.paused.borrow()));
+        
+        let run = task(control_rx);
+        tokio::pin!(run);
+        
+        loop {
+            tokio::select! {
+                result = &mut run => return result,
+                Ok(()) = self.settings_changed.changed() => {
+                    control_tx.send_replace(current(&self.settings, *self.paused.borrow()));
+                }
+                Ok(()) = self.paused.changed() => {
+                    control_tx.send_replace(current(&self.settings, *self.paused.borrow_and_update()));
+                }
+                _ = self.shutdown.wait_for(|exit| *exit) => {
+                    control_tx.send_replace(LoopControl::Stop);
+                    return (&mut run).await;
+                }
+            }
+        }
+    }
+}
+
+/// What the telemetry listener was started with; changing any of it needs a new listener
+#[derive(Clone, PartialEq)]
+struct ListenerBinding {
+    listen_addr: SocketAddr,
+    multicast: MulticastSettings,
+    reuse_address: bool,
+    tcp: TcpSettings,
+    auto_detect: bool,
+}
+
+impl ListenerBinding {
+    fn of(settings: &AppSettings) -> Self {
+        Self {
+            listen_addr: settings.listen_addr(),
+            multicast: settings.multicast.clone(),
+            reuse_address: settings.reuse_address,
+            tcp: settings.tcp.clone(),
+            auto_detect: settings.auto_detect_game,
+        }
+    }
+}
+
+/// Binds the telemetry listener and publishes received frames on the bus
+struct TelemetryListener {
+    signals: BridgeSignals,
+    bus: TelemetryBus,
+    lifecycle: LifecycleReporter,
+    /// `allowed_senders` as the listener applies it, counting rejected packets across rebinds
+    sender_filter: Arc<SenderFilter>,
+    /// `--dump-unknown`: log packets the game does not send
+    dump_unknown: bool,
+}
+
+impl TelemetryListener {
+    fn listening(game_type: GameType, auto_detect: bool, listen_label: &str) -> ComponentStatus {
+        let detected = if auto_detect { " (auto-detected)" } else { "" };
+        ComponentStatus::Running(format!("{}{} on {}", game_type.parser().game_name(), detected, listen_label))
+    }
+
+    async fn listen(&mut self, status: &StatusReporter) -> DR2G27Result {
+        // Hand the port back until resumed; the supervisor then starts the listener again
+        if self.signals.port_released() {
+            status.set(ComponentStatus::Waiting("Paused - port released".to_string()));
+            self.signals.resumed().await;
+            return Ok(());
+        }
+        
+        let (game_type, binding, listen_label, allowed_senders, retry_delay) = self
+            .signals
+            .settings
+            .lock()
+            .map(|settings| {
+                (
+                    settings.game_type,
+                    ListenerBinding::of(&settings),
+                    settings.listen_description(),
+                    settings.allowed_senders.clone(),
+                    settings.retry.socket_retry_delay(),
+                )
+            })
+            .map_err(|_| DR2G27Error::ConfigError("settings are unavailable".to_string()))?;
+        if !allowed_senders.is_empty() {
+            info!("Accepting telemetry only from {:?}", allowed_senders);
+        }
+        self.sender_filter.set_allowed(allowed_senders);
+        
+        let (listen_addr, multicast, reuse_address, tcp) =
+            (binding.listen_addr, &binding.multicast, binding.reuse_address, &binding.tcp);
+        match tcp.mode {
+            TcpMode::Off => {}
+            TcpMode::Listen => {
+                debug!("Attempting to listen for TCP telemetry on {}", listen_addr);
+                let source = AsyncTcpSource::listen(listen_addr, tcp.length_prefix).await.map_err(|source| {
+                    warn!("Port may be in use. Try another port with --port <PORT>");
+                    DR2G27Error::BindFailed { addr: listen_addr, source }
+                })?;
+                let source = source.with_sender_filter(self.sender_filter.clone());
+                return self.publish(status, &source, game_type, &binding, &listen_label).await;
+            }
+            TcpMode::Connect => {
+                let source = AsyncTcpSource::connect(tcp.connect_to.trim().to_string(), tcp.length_prefix, retry_delay)
+                    .with_sender_filter(self.sender_filter.clone());
+                return self.publish(status, &source, game_type, &binding, &listen_label).await;
+            }
+        }
+        
+        let bound = match multicast.group {
+            Some(group) => {
+                debug!("Joining multicast group {} on port {}", group, listen_addr.port());
+                AsyncUdpSource::bind_multicast(group, listen_addr.port(), multicast.interface, reuse_address).await
+            }
+            None if reuse_address => {
+                debug!("Attempting to bind UDP listener to {}, sharing the port", listen_addr);
+                AsyncUdpSource::bind_shared(listen_addr).await
+            }
+            None => {
+                debug!("Attempting to bind UDP listener to {}", listen_addr);
+                AsyncUdpSource::bind(listen_addr).await
+            }
+        };
+        let source = match bound {
+            Ok(source) => source.with_sender_filter(self.sender_filter.clone()),
+            Err(e) => {
+                if multicast.group.is_some() && e.kind() != ErrorKind::AddrInUse {
+                    warn!("Cannot join the multicast group. Check group and interface under [multicast]");
+                } else if e.kind() == ErrorKind::AddrNotAvailable {
+                    warn!("Address is not assigned to this machine. Try --bind 0.0.0.0 to listen on all interfaces");
+                } else if reuse_address {
+                    warn!("Another program may hold the port exclusively. Try a different port with --port <PORT>");
+                } else {
+                    warn!("Port may be in use. Try another port with --port <PORT>, or set reuse_address = true");
+                }
+                let addr = multicast.group.map_or(listen_addr, |group| SocketAddr::new(group, listen_addr.port()));
+                return Err(DR2G27Error::BindFailed { addr, source: e });
+            }
+        };
+        self.publish(status, &source, game_type, &binding, &listen_label).await
+    }
+    
+    /// Publish frames from `source` until the settings no longer match `binding`
+    async fn publish(
+        &mut self,
+        status: &StatusReporter,
+        source: &impl AsyncTelemetrySource,
+        game_type: GameType,
+        binding: &ListenerBinding,
+        listen_label: &str,
+    ) -> DR2G27Result {
+        let auto_detect = binding.auto_detect;
+        status.set(Self::listening(game_type, auto_detect, listen_label));
+        self.lifecycle.report(LifecycleEvent::ListenerBound);
+        
+        // A new game or sender list applies in place; a new address, transport or detection mode needs the
+        // supervisor to rebind
+        let (game_tx, game_rx) = watch::channel(game_type);
+        let sender_filter = &self.sender_filter;
+        let follow_settings = |settings: &AppSettings, paused: bool| {
+            sender_filter.set_allowed(settings.allowed_senders.clone());
+            if ListenerBinding::of(settings) != *binding {
+                info!("Settings changed - restarting telemetry listener");
+                return LoopControl::Stop;
+            }
+            if paused && settings.pause.release_port {
+                info!("Paused - releasing {}", listen_label);
+                return LoopControl::Stop;
+            }
+            if settings.game_type != *game_tx.borrow() {
+                game_tx.send_replace(settings.game_type);
+                status.set(Self::listening(settings.game_type, auto_detect, listen_label));
+            }
+            LoopControl::Run
+        };
+        let (bus, dump_unknown) = (&self.bus, self.dump_unknown);
+        if !auto_detect {
+            return self
+                .signals
+                .controlled(follow_settings, |control| {
+                    publish_frames_switching(source, game_rx, dump_unknown, bus, control)
+                })
+                .await;
+        }
+        
+        // Show the detected game in the tray and `status` like a selected one
+        let settings = self.signals.settings.clone();
+        let notify_settings_changed = self.signals.notify_settings_changed.clone();
+        let mut detected = game_tx.subscribe();
+        let follow_detected = async move {
+            while detected.changed().await.is_ok() {
+                let game_type = *detected.borrow_and_update();
+                status.set(Self::listening(game_type, true, listen_label));
+                if let Ok(mut settings) = settings.lock() {
+                    settings.game_type = game_type;
+                }
+                notify_settings_changed.raise();
+            }
+        };
+        let game_tx = &game_tx;
+        self.signals
+            .controlled(follow_settings, |control| async move {
+                tokio::select! {
+                    result = publish_frames_detecting(source, game_tx, dump_unknown, bus, control) => result,
+                    () = follow_detected => Ok(()),
+                }
+            })
+            .await
+    }
+}
+
+impl Component for TelemetryListener {
+    fn name(&self) -> &'static str {
+        "Telemetry"
+    }
+
+    fn restart_policy(&self) -> RestartPolicy {
+        RestartPolicy::backoff(retry_settings(&self.signals.settings).socket_retry_delay())
+    }
+
+    async fn run(&mut self, status: &StatusReporter) -> DR2G27Result {
+        let result = self.listen(status).await;
+        if let Err(error) = &result {
+            self.lifecycle.report(LifecycleEvent::ListenerFailed(error.to_string()));
+        }
+        result
+    }
+}
+
+/// Finds the G27 and drives its LEDs from the bus
+struct WheelLeds {
+    signals: BridgeSignals,
+    bus: TelemetryBus,
+    lifecycle: LifecycleReporter,
+    require_wheel: bool,
+    led_overrides: LedOverrides,
+}
+
+impl WheelLeds {
+    /// Configured LED style with the command-line overrides in place
+    fn led_style(&self) -> LedStyle {
+        let configured = self.signals.settings.lock().map(|settings| settings.leds).unwrap_or_default();
+        self.led_overrides.apply(configured)
+    }
+
+    /// Wait for the G27 and open it; `None` if shutdown was requested first
+    async fn find_wheel(&mut self, status: &StatusReporter) -> Result<Option<HidDevice>, DR2G27Error> {
+        info!("Looking for G27");
+        status.set(ComponentStatus::Waiting("Searching...".to_string()));
+        self.lifecycle.report(LifecycleEvent::WheelSearching);
+        
+        let mut hid = HidApi::new().map_err(DR2G27Error::HidInit)?;
+        loop {
+            if device_connected(&hid) {
+                match hid.open(G27_VID, G27_PID) {
+                    Ok(device) => return Ok(Some(device)),
+                    Err(e) => {
+                        let failure = WheelOpenFailure::from_error(&e);
+                        warn!("Found G27 but failed to open connection: {}", DR2G27Error::hid_open(e));
+                        status.set(ComponentStatus::Waiting("Connection failed".to_string()));
+                        // Shown once per failure; the search keeps retrying
+                        self.lifecycle.report(LifecycleEvent::WheelOpenFailed(failure));
+                    }
+                }
+            } else {
+                if self.require_wheel {
+                    error!("Exiting: G27 wheel required but not found");
+                    ExitCode::WheelNotFound.exit();
+                }
+                status.set(ComponentStatus::Waiting("Not found".to_string()));
+                // Unplugged after failing to open: back to searching
+                self.lifecycle.report(LifecycleEvent::WheelSearching);
+            }
+            
+            // Only the first search at startup may exit
+            self.require_wheel = false;
+            
+            if !self.signals.sleep(retry_settings(&self.signals.settings).wheel_search_interval()).await {
+                return Ok(None);
+            }
+            hid.refresh_devices().map_err(DR2G27Error::HidInit)?;
+        }
+    }
+
+    async fn drive(&mut self, status: &StatusReporter) -> DR2G27Result {
+        let Some(device) = self.find_wheel(status).await? else {
+            return Ok(());
+        };
+        status.set(ComponentStatus::Running("Connected".to_string()));
+        self.lifecycle.report(LifecycleEvent::WheelConnected);
+        
+        // A failed write is retried and the wheel reopened before the search starts over. Writes run
+        // on a thread of their own, so a hung driver fails the wheel instead of stalling the listener.
+        let wheel = RetryingWheel::new(device).reopening(open_g27);
+        let mut output =
+            WatchedOutput::spawn_with(wheel, WatchedOutput::DEFAULT_TIMEOUT, latency::raise_thread_priority)?;
+        // After a hung write the LEDs may still show its state
+        output.set_state(0)?;
+        let mut leds = LEDS::new(output);
+        let mut style = self.led_style();
+        let result = loop {
+            info!("LED style: {}", style);
+            leds.set_style(style);
+            // A wheel found after telemetry started shows the current RPM without waiting for a packet
+            let frames = self.bus.subscribe_from_latest("wheel LEDs", STALE_AFTER);
+            
+            // A muted wheel output or a paused bridge goes dark but keeps the listener running for
+            // other outputs; a new LED style restarts the loop below with it
+            let overrides = self.led_overrides;
+            let decide = move |settings: &AppSettings, paused: bool| {
+                if overrides.apply(settings.leds) != style {
+                    return LoopControl::Stop;
+                }
+                if paused {
+                    status.set(ComponentStatus::Running("Paused".to_string()));
+                    return LoopControl::Mute;
+                }
+                status.set(ComponentStatus::Running("Connected".to_string()));
+                if settings.outputs.wheel_leds {
+                    LoopControl::Run
+                } else {
+                    LoopControl::Mute
+                }
+            };
+            let result = self.signals.controlled(decide, |control| drive_leds(&mut leds, frames, control)).await;
+            
+            let next = self.led_style();
+            if result.is_err() || self.signals.shutdown_requested() || next == style {
+                break result;
+            }
+            style = next;
+        };
+        
+        // Leave the wheel dark after Exit, and after a failure rather than stuck on the last state
+        if result.is_err() || self.signals.shutdown_requested() {
+            let _ = leds.clear();
+        }
+        result
+    }
+}
+
+impl Component for WheelLeds {
+    fn name(&self) -> &'static str {
+        "Wheel"
+    }
+
+    fn restart_policy(&self) -> RestartPolicy {
+        RestartPolicy::backoff(retry_settings(&self.signals.settings).reconnect_delay())
+    }
+
+    async fn run(&mut self, status: &StatusReporter) -> DR2G27Result {
+        // The wheel's handle does not survive sleep: close it before and look for the wheel again after
+        let mut asleep = power::asleep();
+        if *asleep.borrow_and_update() {
+            status.set(ComponentStatus::Waiting("Asleep".to_string()));
+            tokio::select! {
+                _ = asleep.wait_for(|asleep| !*asleep) => {}
+                _ = self.signals.shutdown.wait_for(|exit| *exit) => return Ok(()),
+            }
+        }
+        let result = tokio::select! {
+            result = self.drive(status) => result,
+            _ = asleep.wait_for(|asleep| *asleep) => {
+                info!("Closing the G27 while Windows sleeps");
+                return Ok(());
+            }
+        };
+        if let Err(error) = &result {
+            self.lifecycle.report(LifecycleEvent::WheelFailed(error.to_string()));
+        }
+        result
+    }
+}
+
+/// Sends RPM, gear and shift range as OSC while `[integrations.osc]` is enabled
+struct OscOutput {
+    signals: BridgeSignals,
+    bus: TelemetryBus,
+    led_overrides: LedOverrides,
+}
+
+impl Component for OscOutput {
+    fn name(&self) -> &'static str {
+        "OSC"
+    }
+
+    fn restart_policy(&self) -> RestartPolicy {
+        RestartPolicy::backoff(retry_settings(&self.signals.settings).socket_retry_delay())
+    }
+
+    async fn run(&mut self, status: &StatusReporter) -> DR2G27Result {
+        let osc_settings = |settings: &AppSettings| settings.integrations.osc.clone();
+        let overrides = self.led_overrides;
+        let (settings, style) = self
+            .signals
+            .settings
+            .lock()
+            .map(|settings| (osc_settings(&settings), overrides.apply(settings.leds)))
+            .map_err(|_| DR2G27Error::ConfigError("settings are unavailable".to_string()))?;
+        if !settings.enabled {
+            status.set(ComponentStatus::Waiting("Off".to_string()));
+            self.signals.settings_differ(&settings, osc_settings).await;
+            return Ok(());
+        }
+        
+        let mut sender = OscSender::connect(&settings).await?;
+        let sending = format!("Sending to {}", sender.target());
+        info!("OSC: {}", sending);
+        status.set(ComponentStatus::Running(sending.clone()));
+        
+        // New receiver settings or LED style restart the sender with them
+        let decide = |current: &AppSettings, paused: bool| {
+            if current.integrations.osc != settings || overrides.apply(current.leds) != style {
+                return LoopControl::Stop;
+            }
+            if paused {
+                status.set(ComponentStatus::Running("Paused".to_string()));
+                return LoopControl::Mute;
+            }
+            status.set(ComponentStatus::Running(sending.clone()));
+            LoopControl::Run
+        };
+        let frames = self.bus.subscribe("OSC");
+        self.signals.controlled(decide, |control| osc::send_frames(&mut sender, style, frames, control)).await
+    }
+}
+
+/// Publishes RPM, gear and shift range to an MQTT broker while `[integrations.mqtt]` is enabled
+struct MqttOutput {
+    signals: BridgeSignals,
+    bus: TelemetryBus,
+    led_overrides: LedOverrides,
+}
+
+impl Component for MqttOutput {
+    fn name(&self) -> &'static str {
+        "MQTT"
+    }
+
+    fn restart_policy(&self) -> RestartPolicy {
+        RestartPolicy::backoff(retry_settings(&self.signals.settings).socket_retry_delay())
+    }
+
+    async fn run(&mut self, status: &StatusReporter) -> DR2G27Result {
+        let mqtt_settings = |settings: &AppSettings| settings.integrations.mqtt.clone();
+        let overrides = self.led_overrides;
+        let (settings, style) = self
+            .signals
+            .settings
+            .lock()
+            .map(|settings| (mqtt_settings(&settings), overrides.apply(settings.leds)))
+            .map_err(|_| DR2G27Error::ConfigError("settings are unavailable".to_string()))?;
+        if !settings.enabled {
+            status.set(ComponentStatus::Waiting("Off".to_string()));
+            self.signals.settings_differ(&settings, mqtt_settings).await;
+            return Ok(());
+        }
+        
+        status.set(ComponentStatus::Waiting(format!("Connecting to {}:{}", settings.host.trim(), settings.port)));
+        let mut client = MqttClient::connect(&settings).await?;
+        let publishing = format!("Publishing to {}", client.broker());
+        info!("MQTT: {}", publishing);
+        status.set(ComponentStatus::Running(publishing.clone()));
+        
+        // New broker settings or LED style reconnect with them
+        let decide = |current: &AppSettings, paused: bool| {
+            if current.integrations.mqtt != settings || overrides.apply(current.leds) != style {
+                return LoopControl::Stop;
+            }
+            if paused {
+                status.set(ComponentStatus::Running("Paused".to_string()));
+                return LoopControl::Mute;
+            }
+            status.set(ComponentStatus::Running(publishing.clone()));
+            LoopControl::Run
+        };
+        let frames = self.bus.subscribe("MQTT");
+        self.signals.controlled(decide, |control| mqtt::publish_frames(&mut client, style, frames, control)).await
+    }
+}
+
+/// Streams telemetry frames and the LED bar as JSON to WebSocket clients while `[websocket]` is enabled
+struct WebSocketStream<'a> {
+    signals: BridgeSignals,
+    bus: TelemetryBus,
+    /// LED bar as the wheel shows it, kept up to date by `mirror_leds`
+    virtual_leds: &'a Cell<u8>,
+}
+
+impl Component for WebSocketStream<'_> {
+    fn name(&self) -> &'static str {
+        "WebSocket"
+    }
+
+    fn restart_policy(&self) -> RestartPolicy {
+        RestartPolicy::backoff(retry_settings(&self.signals.settings).socket_retry_delay())
+    }
+
+    async fn run(&mut self, status: &StatusReporter) -> DR2G27Result {
+        let websocket_settings = |settings: &AppSettings| settings.websocket.clone();
+        let settings = self
+            .signals
+            .settings
+            .lock()
+            .map(|settings| websocket_settings(&settings))
+            .map_err(|_| DR2G27Error::ConfigError("settings are unavailable".to_string()))?;
+        if !settings.enabled {
+            status.set(ComponentStatus::Waiting("Off".to_string()));
+            self.signals.settings_differ(&settings, websocket_settings).await;
+            return Ok(());
+        }
+        
+        let addr = settings.listen_addr();
+        let server = WebSocketServer::bind(addr).await.map_err(|source| {
+            warn!("Cannot start the WebSocket server. Try another port under [websocket]");
+            DR2G27Error::BindFailed { addr, source }
+        })?;
+        info!("WebSocket server listening on ws://{}", addr);
+        status.set(ComponentStatus::Running(format!("ws://{}", addr)));
+        
+        let mut frames = self.bus.subscribe("WebSocket");
+        let virtual_leds = self.virtual_leds;
+        let stream = async {
+            loop {
+                tokio::select! {
+                    accepted = server.accept() => {
+                        if let Err(e) = accepted {
+                            debug!("WebSocket connection failed: {}", e);
+                        }
+                    }
+                    frame = frames.recv() => match frame {
+                        // Nothing to serialize while nobody is connected
+                        Some(frame) if server.clients() > 0 => {
+                            server.broadcast(&websocket::telemetry_message(&frame, virtual_leds.get()));
+                        }
+                        Some(_) => {}
+                        None => return,
+                    },
+                }
+            }
+        };
+        tokio::select! {
+            () = stream => {}
+            changed = self.signals.settings_differ(&settings, websocket_settings) => {
+                if changed {
+                    info!("Settings changed - restarting WebSocket server");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Announces the WebSocket server on the LAN over mDNS while `[mdns]` is enabled
+struct MdnsAdvertiser {
+    signals: BridgeSignals,
+}
+
+impl Component for MdnsAdvertiser {
+    fn name(&self) -> &'static str {
+        "mDNS"
+    }
+
+    fn restart_policy(&self) -> RestartPolicy {
+        RestartPolicy::backoff(retry_settings(&self.signals.settings).socket_retry_delay())
+    }
+
+    async fn run(&mut self, status: &StatusReporter) -> DR2G27Result {
+        let advertised = |settings: &AppSettings| (settings.mdns.clone(), settings.websocket.clone());
+        let settings = self
+            .signals
+            .settings
+            .lock()
+            .map(|settings| advertised(&settings))
+            .map_err(|_| DR2G27Error::ConfigError("settings are unavailable".to_string()))?;
+        let (mdns_settings, websocket_settings) = &settings;
+        let advertisement = mdns_settings
+            .enabled
+            .then(|| Advertisement::for_websocket(mdns_settings, websocket_settings))
+            .flatten();
+        let Some(advertisement) = advertisement else {
+            if mdns_settings.enabled {
+                warn!("mDNS: nothing to advertise; enable [websocket] with bind_address = \"0.0.0.0\"");
+            }
+            status.set(ComponentStatus::Waiting("Off".to_string()));
+            self.signals.settings_differ(&settings, advertised).await;
+            return Ok(());
+        };
+        
+        info!("mDNS: advertising {} at {}", advertisement.instance_name(), advertisement.address());
+        status.set(ComponentStatus::Running(advertisement.instance_name()));
+        let changed = self.signals.settings_differ(&settings, advertised);
+        mdns::advertise(&advertisement, changed).await.map_err(|source| {
+            warn!("Cannot advertise over mDNS. Is UDP port {} blocked?", mdns::MDNS_PORT);
+            DR2G27Error::BindFailed {
+                addr: SocketAddr::from((Ipv4Addr::UNSPECIFIED, mdns::MDNS_PORT)),
+                source,
+            }
+        })
+    }
 }
 
-fn read_telemetry_and_update(device: HidDevice, game_type: GameType, port: u16) -> DR2G27Result {
-    let bind_addr = format!("127.0.0.1:{}", port);
-    println!("# Attempting to bind UDP listener to {}", bind_addr);
+/// Report whether telemetry is flowing, for the Receiving / Stale states
+async fn track_telemetry_flow(bus: TelemetryBus, lifecycle: LifecycleReporter, mut shutdown: watch::Receiver<bool>) {
+    let mut frames = bus.subscribe("lifecycle");
+    loop {
+        tokio::select! {
+            frame = tokio::time::timeout(STALE_AFTER, frames.recv()) => match frame {
+                Ok(Some(_)) => lifecycle.report(LifecycleEvent::FrameReceived),
+                Ok(None) => return,
+                Err(_) => lifecycle.report(LifecycleEvent::TelemetryTimeout),
+            },
+            _ = shutdown.wait_for(|exit| *exit) => return,
+        }
+    }
+}
+
+/// Keep the packet statistics of this run in `stats`, warning once if packets arrive too
+/// slowly for the game
+async fn measure_packets(bus: TelemetryBus, stats: &Cell<PacketStatsSummary>, mut shutdown: watch::Receiver<bool>) {
+    let mut frames = bus.subscribe("packet stats");
+    let mut tick = tokio::time::interval(Duration::from_secs(1));
+    let mut packets = PacketStats::new();
+    loop {
+        tokio::select! {
+            frame = frames.recv() => match frame {
+                Some(frame) => packets.observe(&frame),
+                None => return,
+            },
+            _ = tick.tick() => {
+                packets.tick(Instant::now(), bus.malformed());
+                stats.set(packets.summary());
+                if let Some(warning) = packets.low_rate_warning() {
+                    warn!("{}", warning);
+                }
+            }
+            _ = shutdown.wait_for(|exit| *exit) => return,
+        }
+    }
+}
+
+/// Log a summary of each race session as it ends, and add it to `SESSION_HISTORY_FILE` while
+/// `session_history` is on
+async fn track_sessions(
+    bus: TelemetryBus,
+    settings: Arc<Mutex<AppSettings>>,
+    led_overrides: LedOverrides,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let mut frames = bus.subscribe("sessions");
+    let mut sessions = SessionTracker::new();
+    let mut style = LedStyle::default();
+    loop {
+        let summary = tokio::select! {
+            frame = tokio::time::timeout(STALE_AFTER, frames.recv()) => match frame {
+                Ok(Some(frame)) => {
+                    if !sessions.in_session() {
+                        // Settings only change between races
+                        let Ok(settings) = settings.lock() else { return };
+                        style = led_overrides.apply(settings.leds);
+                    }
+                    sessions.observe(&frame, &style)
+                }
+                Ok(None) => return,
+                Err(_) => sessions.telemetry_stopped(),
+            },
+            _ = shutdown.wait_for(|exit| *exit) => {
+                if let Some(summary) = sessions.telemetry_stopped() {
+                    log_session(&summary, &settings);
+                }
+                return;
+            }
+        };
+        if let Some(summary) = summary {
+            log_session(&summary, &settings);
+        }
+    }
+}
+
+fn log_session(summary: &SessionSummary, settings: &Mutex<AppSettings>) {
+    info!("Session finished: {}", summary);
+    if settings.lock().is_ok_and(|settings| settings.session_history) {
+        if let Err(e) = append_session_history(summary) {
+            warn!("Cannot add the session to {}: {}", SESSION_HISTORY_FILE, e);
+        }
+    }
+}
+
+fn append_session_history(summary: &SessionSummary) -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::Write;
+    
+    let path = paths::data_dir()?.join(SESSION_HISTORY_FILE);
+    let mut file = std::fs::OpenOptions::new().create(true).append(true).open(&path)?;
+    if file.metadata()?.len() == 0 {
+        writeln!(file, "{}", SessionSummary::CSV_HEADER)?;
+    }
+    writeln!(file, "{}", summary.csv_row())?;
+    Ok(())
+}
+
+/// Summarize the telemetry every `TOOLTIP_REFRESH` for the tray tooltip, `None` when none arrived
+async fn summarize_telemetry(
+    bus: TelemetryBus,
+    stats: &Cell<PacketStatsSummary>,
+    summaries: WakingSender<Option<LiveTelemetry>>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let mut frames = bus.subscribe("tooltip");
+    let mut tick = tokio::time::interval(TOOLTIP_REFRESH);
+    let mut latest = None;
+    loop {
+        tokio::select! {
+            frame = frames.recv() => match frame {
+                Some(frame) => latest = Some(frame),
+                None => return,
+            },
+            _ = tick.tick() => {
+                let summary = latest.take().map(|frame: TelemetryFrame| LiveTelemetry {
+                    game: frame.game_type,
+                    rpm: frame.rpm,
+                    gear: frame.gear,
+                    stats: stats.get(),
+                });
+                // Nothing shows summaries without a tray
+                if summaries.send(summary).is_err() {
+                    return;
+                }
+            }
+            _ = shutdown.wait_for(|exit| *exit) => return,
+        }
+    }
+}
+
+/// LED output that shows nothing; the tray icon mirror and the dashboard read the state back from `LEDS`
+struct VirtualLeds;
+
+impl LedOutput for VirtualLeds {
+    fn set_state(&mut self, _state: u8) -> DR2G27Result {
+        Ok(())
+    }
+}
+
+/// Map telemetry to the LED bar like the wheel does, keeping it in `virtual_leds` for status
+/// queries and sending it for the tray icon while `[tray] led_mirror` is on: at most every
+/// `MIRROR_REFRESH` when it changed, `None` once the mirror is switched off
+async fn mirror_leds(
+    bus: TelemetryBus,
+    signals: BridgeSignals,
+    led_overrides: LedOverrides,
+    mirror_tx: WakingSender<Option<u8>>,
+    virtual_leds: &Cell<u8>,
+) {
+    let BridgeSignals {
+        settings,
+        paused,
+        mut shutdown,
+        ..
+    } = signals;
+    let mut frames = bus.subscribe("tray icon");
+    let mut leds = LEDS::new(VirtualLeds);
+    let mut tick = tokio::time::interval(MIRROR_REFRESH);
+    tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+    let mut last_frame = None;
+    let mut shown = None;
+    loop {
+        tokio::select! {
+            frame = frames.recv() => match frame {
+                Some(frame) => {
+                    let _ = leds.update_frame(&frame);
+                    last_frame = Some(frame.received_at);
+                }
+                None => return,
+            },
+            _ = tick.tick() => {
+                let Ok((enabled, style)) = settings
+                    .lock()
+                    .map(|settings| (settings.tray.led_mirror, led_overrides.apply(settings.leds)))
+                else {
+                    return;
+                };
+                leds.set_style(style);
+                // Dark like the wheel while paused, and once telemetry stops
+                let receiving = last_frame.is_some_and(|at: Instant| at.elapsed() < STALE_AFTER);
+                let state = if receiving && !*paused.borrow() { leds.state() } else { 0 };
+                virtual_leds.set(state);
+                let mirrored = enabled.then_some(state);
+                if mirrored != shown {
+                    shown = mirrored;
+                    // Nothing shows the mirror without a tray; keep going for status queries
+                    let _ = mirror_tx.send(mirrored);
+                }
+            }
+            _ = shutdown.wait_for(|exit| *exit) => return,
+        }
+    }
+}
+
+/// Answer `status` queries and a second launch's arguments over the named pipe, and the HTTP and
+/// gRPC APIs while they are enabled, until shutdown
+async fn serve_status(
+    bus: TelemetryBus,
+    signals: BridgeSignals,
+    lifecycle: LifecycleReporter,
+    virtual_leds: &Cell<u8>,
+    sender_filter: &SenderFilter,
+    packet_stats: &Cell<PacketStatsSummary>,
+) {
+    let mut http_signals = signals.clone();
+    let mut grpc_signals = signals.clone();
+    let BridgeSignals {
+        settings,
+        notify_settings_changed,
+        paused,
+        pause_control,
+        mut shutdown,
+        ..
+    } = signals;
+    let latest: Cell<Option<TelemetryFrame>> = Cell::new(None);
+    let recent_sizes = std::cell::RefCell::new(std::collections::VecDeque::with_capacity(RECENT_PACKET_SIZES));
+    let serve_shutdown = shutdown.clone();
+    
+    let keep_latest = async {
+        let mut frames = bus.subscribe("status");
+        loop {
+            tokio::select! {
+                frame = frames.recv() => match frame {
+                    Some(frame) => {
+                        let mut sizes = recent_sizes.borrow_mut();
+                        if sizes.len() == RECENT_PACKET_SIZES {
+                            sizes.pop_front();
+                        }
+                        sizes.push_back(frame.packet.len());
+                        latest.set(Some(frame));
+                    }
+                    None => return,
+                },
+                _ = shutdown.wait_for(|exit| *exit) => return,
+            }
+        }
+    };
+    
+    let status = || {
+        let (game, listen_address) = {
+            let settings = settings.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+            (settings.game_type, settings.listen_addr())
+        };
+        let (state, wheel_connected, last_error) = lifecycle.snapshot();
+        let frame = latest.take();
+        let live = frame.as_ref().filter(|frame| frame.received_at.elapsed() < STALE_AFTER).map(|frame| LiveReadout {
+            rpm: frame.rpm,
+            max_rpm: frame.max_rpm,
+            gear: frame.gear,
+            leds: virtual_leds.get(),
+        });
+        latest.set(frame);
+        let stats = packet_stats.get();
+        InstanceStatus {
+            mode: if service::is_service_process() { "service" } else { "tray" }.to_string(),
+            game,
+            listen_address,
+            state: state.to_string(),
+            wheel_connected,
+            paused: *paused.borrow(),
+            packets_per_second: stats.packets_per_second,
+            jitter_ms: stats.jitter_ms,
+            packet_gaps: stats.gaps,
+            longest_gap_ms: stats.longest_gap_ms,
+            malformed_packets: stats.malformed,
+            rejected_packets: sender_filter.rejected(),
+            last_error,
+            recent_packet_sizes: recent_sizes.borrow().iter().copied().collect(),
+            live,
+        }
+    };
+    // A second launch of the tray app with --profile, --game, --port or --bind
+    let apply = |request| match request {
+        Request::Apply {
+            profile,
+            game,
+            auto_detect_game,
+            port,
+            bind,
+        } => {
+            {
+                let mut settings = settings.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+                if let Some(profile) = profile {
+                    settings.apply_profile(&profile)?;
+                }
+                if let Some(game) = game {
+                    settings.set_game_type(game);
+                }
+                if auto_detect_game {
+                    settings.set_auto_detect_game(true);
+                }
+                if let Some(port) = port {
+                    settings.port = port;
+                }
+                if let Some(bind) = bind {
+                    settings.bind_address = bind;
+                }
+            }
+            info!("Applied the arguments of another launch");
+            notify_settings_changed.raise();
+            Ok(())
+        }
+    };
+    let serve = async {
+        if let Err(e) = ipc::serve(&status, apply, serve_shutdown).await {
+            warn!("Status queries from `g27-led-bridge status` are unavailable: {}", e);
+        }
+    };
+    
+    let switch_profile = |name: &str| {
+        let applied = settings.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).apply_profile(name);
+        if applied.is_ok() {
+            notify_settings_changed.raise();
+        }
+        applied
+    };
+    
+    // Stream decks and scripts on this PC
+    let set_paused = |pause: bool| {
+        if pause_control.send_replace(pause) != pause {
+            info!("LED bridge {} through the HTTP API", if pause { "paused" } else { "resumed" });
+        }
+        Response::ok(&serde_json::json!({ "paused": pause }))
+    };
+    let control = |command| match command {
+        Command::Status => Response::ok(&status()),
+        Command::Pause => set_paused(true),
+        Command::Resume => set_paused(false),
+        Command::Profile(name) => match switch_profile(&name) {
+            Ok(()) => Response::ok(&serde_json::json!({ "profile": name })),
+            Err(e) => Response::error(404, &e),
+        },
+        Command::Dashboard => Response::dashboard(),
+    };
+    let serve_http = async {
+        let http_settings = |settings: &AppSettings| settings.http_api.clone();
+        loop {
+            let current = http_signals.settings.lock().map(|settings| http_settings(&settings)).unwrap_or_default();
+            let listener = if current.enabled {
+                let addr = current.listen_addr();
+                match tokio::net::TcpListener::bind(addr).await {
+                    Ok(listener) => {
+                        info!("HTTP API listening on http://{}", addr);
+                        if current.lan_dashboard {
+                            info!("Phone dashboard: open http://<this PC's address>:{}/ on the phone", addr.port());
+                        }
+                        Some(listener)
+                    }
+                    Err(e) => {
+                        warn!("HTTP API is unavailable on {}: {}. Try another port under [http_api]", addr, e);
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+            // Off or failed to bind: wait for the settings to change
+            let answer = async {
+                match &listener {
+                    Some(listener) => http_api::serve(listener, &control).await,
+                    None => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                () = answer => {}
+                changed = http_signals.settings_differ(&current, http_settings) => {
+                    if !changed {
+                        return;
+                    }
+                }
+            }
+        }
+    };
+    
+    // Other applications on this PC
+    let serve_grpc = async {
+        let grpc_settings = |settings: &AppSettings| settings.grpc.clone();
+        loop {
+            let current = grpc_signals.settings.lock().map(|settings| grpc_settings(&settings)).unwrap_or_default();
+            let answer = async {
+                if current.enabled {
+                    let addr = current.listen_addr();
+                    if let Err(e) = grpc::serve(addr, &bus, virtual_leds, &status, &switch_profile).await {
+                        warn!("gRPC API is unavailable on {}: {}", addr, e);
+                    }
+                }
+                // Off or failed: wait for the settings to change
+                std::future::pending().await
+            };
+            tokio::select! {
+                () = answer => {}
+                changed = grpc_signals.settings_differ(&current, grpc_settings) => {
+                    if !changed {
+                        return;
+                    }
+                }
+            }
+        }
+    };
+    
+    tokio::join!(keep_latest, serve, serve_http, serve_grpc);
+}
+
+/// Report the outcome of a bounded run (--duration/--packets) to the tray loop, which shuts the bridge down
+async fn stop_at_bounds(
+    bus: TelemetryBus,
+    bounds: RunBounds,
+    outcome_tx: WakingSender<BoundOutcome>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    if !bounds.is_bounded() {
+        return;
+    }
+    let frames = bus.subscribe("run bounds");
+    tokio::select! {
+        outcome = wait_for_bounds(bounds, frames) => {
+            let _ = outcome_tx.send(outcome);
+        }
+        _ = shutdown.wait_for(|exit| *exit) => {}
+    }
+}
+
+/// Record telemetry to the file set in `recording` (from the tray) until it is cleared
+async fn record_on_request(
+    bus: TelemetryBus,
+    recording: Arc<watch::Sender<Option<PathBuf>>>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let mut requests = recording.subscribe();
+    loop {
+        let path = tokio::select! {
+            request = requests.wait_for(Option::is_some) => match request {
+                Ok(path) => path.clone(),
+                Err(_) => return,
+            },
+            _ = shutdown.wait_for(|exit| *exit) => return,
+        };
+        let Some(path) = path else { continue };
+        
+        let mut recorder = match Recorder::create(&path, None) {
+            Ok(recorder) => recorder,
+            Err(e) => {
+                error!("Failed to start recording to {}: {}", path.display(), e);
+                recording.send_replace(None);
+                continue;
+            }
+        };
+        info!("Recording telemetry to {}", path.display());
+        
+        let result = {
+            let (control_tx, control_rx) = watch::channel(LoopControl::Run);
+            let record = record_frames(&mut recorder, bus.subscribe("recorder"), control_rx);
+            tokio::pin!(record);
+            tokio::select! {
+                result = &mut record => result,
+                _ = requests.wait_for(|request| request.as_ref() != Some(&path)) => {
+                    control_tx.send_replace(LoopControl::Stop);
+                    (&mut record).await
+                }
+                _ = shutdown.wait_for(|exit| *exit) => {
+                    control_tx.send_replace(LoopControl::Stop);
+                    (&mut record).await
+                }
+            }
+        };
+        match result {
+            Ok(()) => info!("Recorded {} packets to {}", recorder.recorded(), path.display()),
+            Err(e) => error!("Recording to {} stopped: {}", path.display(), e),
+        }
+        
+        // Let the tray offer a new recording, unless it already asked for one
+        recording.send_if_modified(|request| {
+            let finished = request.as_ref() == Some(&path);
+            if finished {
+                *request = None;
+            }
+            finished
+        });
+    }
+}
+
+/// Capture the packets of the next `CAPTURE_DURATION` to the file set in `capture` (from the tray
+/// or `--capture`), then clear it
+async fn capture_on_request(
+    bus: TelemetryBus,
+    capture: Arc<watch::Sender<Option<PathBuf>>>,
+    settings: Arc<Mutex<AppSettings>>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let mut requests = capture.subscribe();
+    loop {
+        let path = tokio::select! {
+            request = requests.wait_for(Option::is_some) => match request {
+                Ok(path) => path.clone(),
+                Err(_) => return,
+            },
+            _ = shutdown.wait_for(|exit| *exit) => return,
+        };
+        let Some(path) = path else { continue };
+        
+        let listen_addr = match settings.lock() {
+            Ok(settings) => settings.listen_addr(),
+            Err(_) => return,
+        };
+        match Capture::create(&path, listen_addr) {
+            Ok(mut packets) => {
+                info!("Capturing telemetry packets for {}s to {}", CAPTURE_DURATION.as_secs(), path.display());
+                let (control_tx, control_rx) = watch::channel(LoopControl::Run);
+                let result = {
+                    let run = capture_frames(&mut packets, CAPTURE_DURATION, bus.subscribe("capture"), control_rx);
+                    tokio::pin!(run);
+                    tokio::select! {
+                        result = &mut run => result,
+                        _ = shutdown.wait_for(|exit| *exit) => {
+                            control_tx.send_replace(LoopControl::Stop);
+                            (&mut run).await
+                        }
+                    }
+                };
+                match result {
+                    Ok(()) => info!("Captured {} packets to {}", packets.captured(), path.display()),
+                    Err(e) => error!("Capture to {} stopped: {}", path.display(), e),
+                }
+            }
+            Err(e) => error!("Failed to start capture to {}: {}", path.display(), e),
+        }
+        capture.send_replace(None);
+    }
+}
+
+fn device_connected(hid: &HidApi) -> bool {
+    for device in hid.device_list() {
+        if device.product_id() == G27_PID && device.vendor_id() == G27_VID {
+            return true;
+        }
+    }
+
+    false
+}
+
+fn test_led_functionality(continuous: bool, duration: Option<Duration>) -> DR2G27Result {
+    info!("Looking for G27 for LED test");
+    let hid = HidApi::new().map_err(DR2G27Error::HidInit)?;
+    
+    if !device_connected(&hid) {
+        error!("G27 not found. Please connect your G27 racing wheel.");
+        // A timed test is a rig check, which a missing wheel fails
+        if duration.is_some() {
+            ExitCode::WheelNotFound.exit();
+        }
+        return Ok(());
+    }
+    
+    let device = hid.open(G27_VID, G27_PID).map_err(DR2G27Error::hid_open)?;
+    info!("G27 connected - Starting LED test");
+    
+    if let Some(duration) = duration {
+        info!("Running LED test for {:?}", duration);
+        let started = Instant::now();
+        while started.elapsed() < duration {
+            run_led_test_cycle(&device)?;
+        }
+        write_g27_leds(&device, 0)?;
+        info!("LED test completed");
+    } else if continuous {
+        info!("Running continuous LED test (Press Ctrl+C to stop)");
+        loop {
+            run_led_test_cycle(&device)?;
+        }
+    } else {
+        info!("Running single LED test cycle");
+        run_led_test_cycle(&device)?;
+        // Turn off all LEDs at the end
+        write_g27_leds(&device, 0)?;
+        info!("LED test completed");
+    }
+    
+    Ok(())
+}
+
+fn run_led_test_cycle(device: &impl WheelDevice) -> DR2G27Result {
+    // LED states: 0=off, 1=green1, 3=green1+2, 7=green1+2+orange1, 15=green1+2+orange1+2, 31=all
+    info!("Testing LED progression: Off -> Green -> Orange -> Red");
+    
+    // Progressive LED activation
+    let led_states = vec![0, 1, 3, 7, 15, 31];
+    for state in &led_states {
+        write_g27_leds(device, *state)?;
+        sleep(Duration::from_millis(500));
+    }
+    
+    info!("Testing reverse LED progression: Red -> Orange -> Green -> Off");
+    
+    // Reverse LED deactivation
+    for state in led_states.iter().rev() {
+        write_g27_leds(device, *state)?;
+        sleep(Duration::from_millis(500));
+    }
+    
+    Ok(())
+}
+
+fn run_service_command(action: ServiceCommands) -> Result<(), Box<dyn std::error::Error>> {
+    let result = match action {
+        ServiceCommands::Install => service::install(),
+        ServiceCommands::Uninstall => service::uninstall(),
+        ServiceCommands::Start => service::start(),
+        ServiceCommands::Stop => service::stop(),
+        ServiceCommands::Run { data_dir } => {
+            paths::use_data_dir(data_dir);
+            service::run(run_service_bridge)
+        }
+    };
+    if result.is_err() {
+        info!("Installing and removing the service needs an administrator prompt");
+    }
+    result
+}
+
+/// The bridge as the Windows service runs it: no tray, settings reloaded when the file changes
+fn run_service_bridge(shutdown: watch::Receiver<bool>) {
+    info!("Starting G27 LED Bridge as a Windows service");
+    let (loaded, report) = AppSettings::load_with_report();
+    report.print();
+    let settings = Arc::new(Mutex::new(loaded));
+    let settings_changed = Arc::new(SettingsChanged::new());
+    // Problems in an edited file are logged by the watcher; there is no one to show them to
+    let _watcher = SettingsWatcher::start(settings.clone(), settings_changed.clone(), Arc::new(Mutex::new(None)))
+        .map_err(|e| error!("Failed to start settings watcher: {}", e))
+        .ok();
+    
+    // There is no tray to pause the service from; only the HTTP API can
+    let paused = Arc::new(watch::Sender::new(false));
+    let signals = BridgeSignals {
+        settings,
+        settings_changed: settings_changed.subscribe(),
+        notify_settings_changed: settings_changed,
+        paused: paused.subscribe(),
+        pause_control: paused,
+        shutdown,
+    };
+    // States and component status are logged as they change; nothing else displays them
+    let nobody = Waker::default();
+    let (status_tx, _) = waker::channel::<StatusUpdate>(&nobody);
+    let (state_tx, _) = waker::channel::<BridgeState>(&nobody);
+    let (live_tx, _) = waker::channel::<Option<LiveTelemetry>>(&nobody);
+    let (mirror_tx, _) = waker::channel::<Option<u8>>(&nobody);
+    let (bounds_tx, _) = waker::channel::<BoundOutcome>(&nobody);
+    let reports = BridgeReports {
+        status_tx,
+        lifecycle: LifecycleReporter::new(state_tx),
+        live_tx,
+        mirror_tx,
+        bounds_tx,
+    };
+    let recording = Arc::new(watch::Sender::new(None));
+    let capture = Arc::new(watch::Sender::new(None));
     
-    let socket = match UdpSocket::bind(&bind_addr) {
-        Ok(socket) => {
-            println!("# Successfully bound to {}", bind_addr);
-            socket
+    match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(runtime) => runtime.block_on(run_bridge(
+            signals,
+            reports,
+            recording,
+            capture,
+            WheelOptions::default(),
+            RunBounds::default(),
+            false,
+        )),
+        Err(e) => error!("Failed to start bridge runtime: {}", e),
+    }
+}
+
+/// The bridge without a tray for `--headless`: like the service, but stopped from the console or by SIGTERM
+fn run_headless(
+    settings: AppSettings,
+    wheel: WheelOptions,
+    bounds: RunBounds,
+    capture: Option<PathBuf>,
+    dump_unknown: bool,
+) {
+    info!("Starting G27 LED Bridge headless");
+    if service::is_running() {
+        error!("The {} service already drives the wheel; stop it first", service::SERVICE_NAME);
+        ExitCode::Failure.exit();
+    }
+    let settings = Arc::new(Mutex::new(settings));
+    let settings_changed = Arc::new(SettingsChanged::new());
+    let _watcher = SettingsWatcher::start(settings.clone(), settings_changed.clone(), Arc::new(Mutex::new(None)))
+        .map_err(|e| error!("Failed to start settings watcher: {}", e))
+        .ok();
+    
+    // Ctrl+C, closing the console, a bounded run ending and SIGTERM all stop the bridge the same way
+    let shutdown = Arc::new(watch::Sender::new(false));
+    let request_exit = shutdown.clone();
+    console::handle_close(move || {
+        request_exit.send_replace(true);
+    });
+    let stop = shutdown.clone();
+    let (bounds_tx, bounds_rx) = waker::channel::<BoundOutcome>(&Waker::new(move || {
+        stop.send_replace(true);
+    }));
+    
+    let paused = Arc::new(watch::Sender::new(false));
+    let signals = BridgeSignals {
+        settings,
+        settings_changed: settings_changed.subscribe(),
+        notify_settings_changed: settings_changed,
+        paused: paused.subscribe(),
+        pause_control: paused,
+        shutdown: shutdown.subscribe(),
+    };
+    let nobody = Waker::default();
+    let (status_tx, _) = waker::channel::<StatusUpdate>(&nobody);
+    let (state_tx, _) = waker::channel::<BridgeState>(&nobody);
+    let (live_tx, _) = waker::channel::<Option<LiveTelemetry>>(&nobody);
+    let (mirror_tx, _) = waker::channel::<Option<u8>>(&nobody);
+    let reports = BridgeReports {
+        status_tx,
+        lifecycle: LifecycleReporter::new(state_tx),
+        live_tx,
+        mirror_tx,
+        bounds_tx,
+    };
+    let recording = Arc::new(watch::Sender::new(None));
+    let capture = Arc::new(watch::Sender::new(capture));
+    
+    match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+        Ok(runtime) => runtime.block_on(async {
+            #[cfg(unix)]
+            tokio::spawn(stop_on_sigterm(shutdown.clone()));
+            run_bridge(signals, reports, recording, capture, wheel, bounds, dump_unknown).await
+        }),
+        Err(e) => {
+            error!("Failed to start bridge runtime: {}", e);
+            ExitCode::Failure.exit();
+        }
+    }
+    console::finished();
+    
+    if let Ok(outcome) = bounds_rx.try_recv() {
+        exit_with(outcome);
+    }
+}
+
+/// Stop the headless bridge when a process manager sends SIGTERM
+#[cfg(unix)]
+async fn stop_on_sigterm(shutdown: Arc<watch::Sender<bool>>) {
+    use tokio::signal::unix::{signal, SignalKind};
+    
+    match signal(SignalKind::terminate()) {
+        Ok(mut terminate) => {
+            if terminate.recv().await.is_some() {
+                info!("SIGTERM received - shutting down");
+                shutdown.send_replace(true);
+            }
+        }
+        Err(e) => warn!("Cannot handle SIGTERM: {}", e),
+    }
+}
+
+fn run_config_command(action: ConfigCommands) -> Result<(), Box<dyn std::error::Error>> {
+    match action {
+        ConfigCommands::Init { force } => AppSettings::init_file(force).map(|_| ()),
+        ConfigCommands::List => {
+            for (key, value) in config_keys::list(&AppSettings::load()).map_err(DR2G27Error::ConfigError)? {
+                println!("{} = {}", key, value);
+            }
+            Ok(())
+        }
+        ConfigCommands::Get { key } => {
+            println!("{}", config_keys::get(&AppSettings::load(), &key).map_err(DR2G27Error::ConfigError)?);
+            Ok(())
+        }
+        ConfigCommands::Set { key, value } => {
+            let settings = config_keys::set(&AppSettings::load(), &key, &value).map_err(DR2G27Error::ConfigError)?;
+            autostart::set_enabled(settings.autostart)?;
+            settings.save()
+        }
+        ConfigCommands::Export { file } => AppSettings::load().export_to(&file),
+        ConfigCommands::Import { file } => {
+            let settings = AppSettings::import_from(&file)?;
+            autostart::sync(settings.autostart);
+            Ok(())
+        }
+    }
+}
+
+/// Product ID of a Logitech wheel in "Driving Force EX" compatibility mode, which is how a
+/// G27 shows up until the Logitech driver switches it to native mode
+const DRIVING_FORCE_EX_PID: u16 = 0xC294;
+
+/// Print the connected Logitech HID devices for `list-devices`, marking the G27 the bridge would open
+fn run_list_devices_command(output: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    let hid = HidApi::new().map_err(DR2G27Error::HidInit)?;
+    let devices: Vec<_> = hid.device_list().filter(|device| device.vendor_id() == G27_VID).collect();
+    
+    if output == OutputFormat::Json {
+        let first_g27 = devices.iter().position(|device| device.product_id() == G27_PID);
+        let devices: Vec<_> = devices
+            .iter()
+            .enumerate()
+            .map(|(index, device)| {
+                serde_json::json!({
+                    "product": device.product_string(),
+                    "manufacturer": device.manufacturer_string(),
+                    "serial": device.serial_number(),
+                    "path": device.path().to_string_lossy(),
+                    "vendor_id": device.vendor_id(),
+                    "product_id": device.product_id(),
+                    "supported": device.product_id() == G27_PID,
+                    "selected": Some(index) == first_g27,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&devices)?);
+        return Ok(());
+    }
+    
+    if devices.is_empty() {
+        println!("No Logitech HID devices found. Check the USB connection and Windows Device Manager.");
+        return Ok(());
+    }
+
+    // The bridge opens the first G27 in enumeration order
+    let mut g27_found = false;
+    for device in devices {
+        let status = match device.product_id() {
+            G27_PID if !g27_found => "G27 - would be opened",
+            G27_PID => "G27 - not used, another G27 comes first",
+            _ => "not supported",
+        };
+        g27_found |= device.product_id() == G27_PID;
+
+        println!(
+            "{} [{:04x}:{:04x}] - {}",
+            device.product_string().unwrap_or("(unknown product)"),
+            device.vendor_id(),
+            device.product_id(),
+            status
+        );
+        println!("    Manufacturer: {}", device.manufacturer_string().unwrap_or("-"));
+        println!("    Serial:       {}", device.serial_number().unwrap_or("-"));
+        println!("    Path:         {}", device.path().to_string_lossy());
+        if device.product_id() == DRIVING_FORCE_EX_PID {
+            println!("    This may be a G27 in compatibility mode - install Logitech Gaming Software so it reports as a G27");
+        }
+    }
+
+    if !g27_found {
+        println!("No G27 found among them.");
+    }
+    Ok(())
+}
+
+/// Print the supported games for `list-games`
+fn list_games(output: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    if output == OutputFormat::Json {
+        let games: Vec<_> = GameType::ALL
+            .iter()
+            .map(|game| {
+                serde_json::json!({
+                    "id": game,
+                    "name": game.parser().game_name(),
+                    "aliases": game.aliases(),
+                    "packet_sizes": game.packet_sizes(),
+                    "default_port": game.default_port(),
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&games)?);
+        return Ok(());
+    }
+    
+    let rows: Vec<[String; 4]> = GameType::ALL
+        .iter()
+        .map(|game| {
+            let sizes: Vec<_> = game.packet_sizes().iter().map(|size| size.to_string()).collect();
+            [
+                game.parser().game_name().to_string(),
+                game.aliases().join(", "),
+                sizes.join(", "),
+                game.default_port().to_string(),
+            ]
+        })
+        .collect();
+    let header = ["Game", "--game names", "Packet sizes (bytes)", "Default port"].map(String::from);
+
+    let mut widths = [0; 4];
+    for row in std::iter::once(&header).chain(&rows) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    for row in std::iter::once(&header).chain(&rows) {
+        println!(
+            "{:<w0$}  {:<w1$}  {:<w2$}  {}",
+            row[0],
+            row[1],
+            row[2],
+            row[3],
+            w0 = widths[0],
+            w1 = widths[1],
+            w2 = widths[2]
+        );
+    }
+    Ok(())
+}
+
+/// Print the fields `games`' parsers read, from the same definitions they parse with
+fn print_schema(games: &[GameType], output: OutputFormat) -> Result<(), Box<dyn std::error::Error>> {
+    if output == OutputFormat::Json {
+        let games: Vec<_> = games
+            .iter()
+            .map(|game| {
+                serde_json::json!({
+                    "id": game,
+                    "name": game.parser().game_name(),
+                    "packet_sizes": game.packet_sizes(),
+                    "fields": game.parser().fields(),
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&games)?);
+        return Ok(());
+    }
+    
+    for (index, game) in games.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        let sizes: Vec<_> = game.packet_sizes().iter().map(|size| size.to_string()).collect();
+        println!("{} ({} byte packets)", game.parser().game_name(), sizes.join(", "));
+        
+        let rows: Vec<[String; 6]> = game
+            .parser()
+            .fields()
+            .iter()
+            .map(|field| {
+                let packets: Vec<_> = field.packet_sizes.iter().map(|size| size.to_string()).collect();
+                [
+                    field.offset.to_string(),
+                    field.name.to_string(),
+                    field.field_type.name().to_string(),
+                    field.unit.to_string(),
+                    if packets.is_empty() { "all".to_string() } else { packets.join(", ") },
+                    field.description.to_string(),
+                ]
+            })
+            .collect();
+        let header = ["Offset", "Field", "Type", "Unit", "Packets", "Notes"].map(String::from);
+        
+        let mut widths = [0; 6];
+        for row in std::iter::once(&header).chain(&rows) {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
         }
-        Err(e) => {
-            println!("# Failed to bind to port {}: {}", port, e);
-            println!("# Port may already be in use. Try a different port with --port <PORT>");
-            return Err(e.into());
+        for row in std::iter::once(&header).chain(&rows) {
+            let line = format!(
+                "  {:>w0$}  {:<w1$}  {:<w2$}  {:<w3$}  {:<w4$}  {}",
+                row[0],
+                row[1],
+                row[2],
+                row[3],
+                row[4],
+                row[5],
+                w0 = widths[0],
+                w1 = widths[1],
+                w2 = widths[2],
+                w3 = widths[3],
+                w4 = widths[4]
+            );
+            println!("{}", line.trim_end());
         }
-    };
+    }
+    Ok(())
+}
+
+/// Print what `status` can tell without the tray app: effective settings, whether the
+/// G27 is connected and whether something (e.g. a running bridge) already holds the port
+fn run_status_command(
+    settings: &AppSettings,
+    listen_addr: SocketAddr,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let wheel_connected = HidApi::new().map(|hid| device_connected(&hid)).unwrap_or(false);
+    let port_in_use = UdpSocket::bind(listen_addr).is_err_and(|e| e.kind() == ErrorKind::AddrInUse);
+    let settings_file = AppSettings::config_path()?;
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+    let instance = runtime.block_on(ipc::query_status()).unwrap_or_else(|e| {
+        warn!("Could not query the running bridge: {}", e);
+        None
+    });
     
-    let mut leds = LEDS::new(device);
-    let parser = game_type.parser();
-    let expected_size = parser.expected_packet_size();
-    let mut data = vec![0u8; expected_size.max(512)]; // Ensure buffer is large enough
+    if output == OutputFormat::Json {
+        let status = serde_json::json!({
+            "running_instance": instance,
+            "game": settings.game_type,
+            "game_name": settings.game_type.parser().game_name(),
+            "auto_detect_game": settings.auto_detect_game,
+            "listen_address": listen_addr.to_string(),
+            "port_in_use": port_in_use,
+            "wheel_connected": wheel_connected,
+            "profile": settings.active_profile,
+            "autostart": settings.autostart,
+            "portable": paths::is_portable(),
+            "settings_file": settings_file,
+        });
+        println!("{}", serde_json::to_string_pretty(&status)?);
+        return Ok(());
+    }
     
-    println!("# Listening for {} telemetry on port {} (expecting {} byte packets)", 
-             parser.game_name(), port, expected_size);
-    println!("# Waiting for telemetry data from the game...");
-
-    loop {
-        match socket.recv(&mut data) {
-            Ok(received_size) => {
-                if received_size >= expected_size {
-                    leds.update(&data[..received_size], parser.as_ref())?;
-                } else {
-                    println!("# Received packet too small: {} bytes (expected {})", received_size, expected_size);
-                }
+    match &instance {
+        Some(instance) => {
+            println!("Running:       {} - {}", instance.mode, instance.state);
+            println!("Game:          {}", instance.game.parser().game_name());
+            println!("Listening on:  {}", instance.listen_address);
+            println!("G27:           {}", if instance.wheel_connected { "connected" } else { "not connected" });
+            if instance.paused {
+                println!("Paused:        LEDs stay off until resumed");
             }
-            Err(e) => {
-                println!("# UDP receive error: {}", e);
-                return Err(e.into());
+            println!("Packet rate:   {:.1}/s", instance.packets_per_second);
+            println!("Jitter:        {:.1} ms", instance.jitter_ms);
+            if instance.packet_gaps > 0 {
+                println!("Gaps:          {} (longest {:.0} ms)", instance.packet_gaps, instance.longest_gap_ms);
+            }
+            if instance.malformed_packets > 0 {
+                println!("Malformed:     {} packets too short for the game", instance.malformed_packets);
             }
+            if instance.rejected_packets > 0 {
+                println!("Rejected:      {} packets from senders not in allowed_senders", instance.rejected_packets);
+            }
+            println!("Last error:    {}", instance.last_error.as_deref().unwrap_or("-"));
+        }
+        None => {
+            println!("Running:       no");
+            println!(
+                "Game:          {}{}",
+                settings.game_type.parser().game_name(),
+                if settings.auto_detect_game { " (auto-detected)" } else { "" }
+            );
+            println!(
+                "Listening on:  {} ({})",
+                listen_addr,
+                if port_in_use { "in use by another program" } else { "free" }
+            );
+            println!("G27:           {}", if wheel_connected { "connected" } else { "not found" });
         }
     }
+    println!("Profile:       {}", settings.active_profile.as_deref().unwrap_or("-"));
+    println!("Autostart:     {}", if settings.autostart { "on" } else { "off" });
+    println!("Settings file: {}", settings_file.display());
+    Ok(())
 }
 
-fn device_connected(hid: &HidApi) -> bool {
-    for device in hid.device_list() {
-        if device.product_id() == G27_PID && device.vendor_id() == G27_VID {
-            return true;
-        }
-    }
+/// How often `monitor` redraws its status line
+const MONITOR_REFRESH: Duration = Duration::from_millis(250);
 
-    false
+/// One status line for `monitor`: the latest frame, the packet rate and jitter
+fn monitor_line(frame: Option<&TelemetryFrame>, stats: &PacketStatsSummary) -> String {
+    let Some(frame) = frame else {
+        return format!("Waiting for telemetry... ({:.0} packets/s)", stats.packets_per_second);
+    };
+    let gear = match frame.gear {
+        Some(-1) => "R".to_string(),
+        Some(0) => "N".to_string(),
+        Some(gear) => gear.to_string(),
+        None => "-".to_string(),
+    };
+    let speed = frame.speed.map_or("-".to_string(), |speed| format!("{:.0} km/h", speed * 3.6));
+    format!(
+        concat!(
+            "RPM {:>5.0} / {:>5.0} (idle {:>4.0}) | Gear {:>2} | Speed {:>8} | Race {:<3} ",
+            "| {:>3.0} pkt/s ±{:.1} ms | {} gaps | {} bytes"
+        ),
+        frame.rpm,
+        frame.max_rpm,
+        frame.idle_rpm,
+        gear,
+        speed,
+        if frame.race_active { "yes" } else { "no" },
+        stats.packets_per_second,
+        stats.jitter_ms,
+        stats.gaps,
+        frame.packet.len()
+    )
 }
 
-fn connect_and_bridge(
-    game_type: GameType, 
-    port: u16,
-    wheel_status_tx: Option<&std::sync::mpsc::Sender<(bool, Option<String>)>>,
-    require_wheel: bool,
-) -> DR2G27Result {
-    println!("# Looking for G27");
-    
-    if let Some(tx) = wheel_status_tx {
-        let _ = tx.send((false, Some("Searching...".to_string())));
+/// One JSON line for `monitor --output json`; `frame` is null until telemetry arrives
+fn monitor_json(frame: Option<&TelemetryFrame>, stats: &PacketStatsSummary) -> serde_json::Value {
+    serde_json::json!({
+        "packets_per_second": stats.packets_per_second,
+        "stats": stats,
+        "frame": frame.map(|frame| serde_json::json!({
+            "game": frame.game_type,
+            "rpm": frame.rpm,
+            "max_rpm": frame.max_rpm,
+            "idle_rpm": frame.idle_rpm,
+            "gear": frame.gear,
+            "speed_mps": frame.speed,
+            "race_active": frame.race_active,
+            "packet_size": frame.packet.len(),
+        })),
+    })
+}
+
+/// Log how a bounded run ended and exit with its status code
+fn exit_with(outcome: BoundOutcome) -> ! {
+    match outcome {
+        BoundOutcome::Completed { .. } => info!("Run finished: {}", outcome),
+        BoundOutcome::MissingPackets { .. } => error!("Run finished: {}", outcome),
     }
+    ExitCode::from(outcome).exit()
+}
+
+/// Returns how the run ended if `bounds` stopped it, `None` if Ctrl+C did
+fn run_monitor_command(
+    game_type: GameType,
+    listen_addr: SocketAddr,
+    output: OutputFormat,
+    bounds: RunBounds,
+    dump_unknown: bool,
+) -> Result<Option<BoundOutcome>, Box<dyn std::error::Error>> {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
     
-    let mut hid = HidApi::new()?;
-    let mut found = device_connected(&hid);
-    
-    if !found {
-        println!("# G27 not found...");
-        if let Some(tx) = wheel_status_tx {
-            let _ = tx.send((false, Some("Not found".to_string())));
-        }
+    let (_, _, outcome) = runtime.block_on(async {
+        let source = AsyncUdpSource::bind(listen_addr)
+            .await
+            .map_err(|source| DR2G27Error::BindFailed { addr: listen_addr, source })?;
+        let bus = TelemetryBus::new();
+        let mut frames = bus.subscribe("monitor");
+        let bounded_frames = bus.subscribe("run bounds");
+        let (control_tx, control_rx) = watch::channel(LoopControl::Run);
+        info!(
+            "Monitoring {} telemetry on {} - press Ctrl+C to stop",
+            game_type.parser().game_name(),
+            listen_addr
+        );
         
-        if require_wheel {
-            println!("# Exiting: G27 wheel required but not found");
-            std::process::exit(1);
-        }
+        let stop = async {
+            let outcome = tokio::select! {
+                _ = tokio::signal::ctrl_c() => None,
+                outcome = wait_for_bounds(bounds, bounded_frames) => Some(outcome),
+            };
+            control_tx.send_replace(LoopControl::Stop);
+            Ok(outcome)
+        };
+        let mut control = control_rx.clone();
+        let counters = bus.clone();
+        let display = async move {
+            let mut refresh = tokio::time::interval(MONITOR_REFRESH);
+            let mut latest = None;
+            let mut stats = PacketStats::new();
+            let mut counting_since = Instant::now();
+            
+            loop {
+                tokio::select! {
+                    frame = frames.recv() => match frame {
+                        Some(frame) => {
+                            stats.observe(&frame);
+                            latest = Some(frame);
+                        }
+                        None => break,
+                    },
+                    _ = refresh.tick() => {
+                        if counting_since.elapsed() >= Duration::from_secs(1) {
+                            counting_since = Instant::now();
+                            stats.tick(counting_since, counters.malformed());
+                            if let Some(warning) = stats.low_rate_warning() {
+                                // Below the status line rather than through it
+                                println!();
+                                warn!("{}", warning);
+                            }
+                        }
+                        if output == OutputFormat::Json {
+                            println!("{}", monitor_json(latest.as_ref(), &stats.summary()));
+                            continue;
+                        }
+                        // Redraw in place; padding clears what is left of a longer line
+                        print!("\r{:<120}", monitor_line(latest.as_ref(), &stats.summary()));
+                        let _ = std::io::Write::flush(&mut std::io::stdout());
+                    }
+                    _ = control.wait_for(|state| *state == LoopControl::Stop) => break,
+                }
+            }
+            if output == OutputFormat::Text {
+                println!();
+            }
+            info!("Session: {}", stats.summary());
+            Ok(())
+        };
+        tokio::try_join!(
+            publish_frames(&source, game_type, dump_unknown, &bus, control_rx),
+            display,
+            stop,
+        )
+    })?;
+    
+    Ok(outcome)
+}
+
+/// Largest UDP datagram, so packets of any game arrive whole
+const CAPTURE_BUFFER_SIZE: usize = 65536;
+
+/// Show the packets arriving on `listen_addr` word by word, whatever game sends them
+fn run_capture_offsets_command(
+    listen_addr: SocketAddr,
+    filter: ExplorerFilter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !packet_explorer::enable_ansi_escapes() {
+        warn!("This console cannot highlight changes; use Windows Terminal or a newer Windows version");
     }
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
     
-    loop {
-        if found {
-            if let Ok(device) = hid.open(G27_VID, G27_PID) {
-                println!("# G27 connected");
-                if let Some(tx) = wheel_status_tx {
-                    let _ = tx.send((true, None));
+    runtime.block_on(async {
+        let socket = tokio::net::UdpSocket::bind(listen_addr)
+            .await
+            .map_err(|source| DR2G27Error::BindFailed { addr: listen_addr, source })?;
+        info!("Capturing packets on {} - press Ctrl+C to stop", listen_addr);
+        
+        let mut explorer = PacketExplorer::new();
+        let mut data = vec![0u8; CAPTURE_BUFFER_SIZE];
+        let mut refresh = tokio::time::interval(MONITOR_REFRESH);
+        let mut packets = 0u32;
+        let mut counting_since = Instant::now();
+        let mut packets_per_second = 0.0;
+        let ctrl_c = tokio::signal::ctrl_c();
+        tokio::pin!(ctrl_c);
+        
+        loop {
+            tokio::select! {
+                received = socket.recv(&mut data) => {
+                    let size = received.map_err(DR2G27Error::Receive)?;
+                    explorer.observe(&data[..size]);
+                    packets += 1;
                 }
-                return read_telemetry_and_update(device, game_type, port);
-            } else {
-                println!("# Found G27 but failed to open connection");
-                if let Some(tx) = wheel_status_tx {
-                    let _ = tx.send((false, Some("Connection failed".to_string())));
+                _ = refresh.tick() => {
+                    if counting_since.elapsed() >= Duration::from_secs(1) {
+                        packets_per_second = packets as f32 / counting_since.elapsed().as_secs_f32();
+                        packets = 0;
+                        counting_since = Instant::now();
+                    }
+                    print!("{}", explorer.render(&filter, packets_per_second));
+                    let _ = std::io::Write::flush(&mut std::io::stdout());
                 }
+                _ = &mut ctrl_c => break,
             }
-        } 
+        }
+        info!("Captured {} packets", explorer.packets());
+        Ok(())
+    })
+}
 
-        sleep(Duration::from_secs(5));
-        hid.refresh_devices()?;
-        found = device_connected(&hid);
-    }
+fn run_record_command(
+    game_type: GameType,
+    listen_addr: SocketAddr,
+    output: &Path,
+    frame_log: Option<&Path>,
+    dump_unknown: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut recorder = Recorder::create(output, frame_log)?;
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+    
+    runtime.block_on(async {
+        let source = AsyncUdpSource::bind(listen_addr)
+            .await
+            .map_err(|source| DR2G27Error::BindFailed { addr: listen_addr, source })?;
+        let bus = TelemetryBus::new();
+        let frames = bus.subscribe("recorder");
+        let (control_tx, control_rx) = watch::channel(LoopControl::Run);
+        info!("Recording to {} - press Ctrl+C to stop", output.display());
+        
+        let stop_on_ctrl_c = async {
+            let _ = tokio::signal::ctrl_c().await;
+            control_tx.send_replace(LoopControl::Stop);
+            Ok(())
+        };
+        tokio::try_join!(
+            publish_frames(&source, game_type, dump_unknown, &bus, control_rx.clone()),
+            record_frames(&mut recorder, frames, control_rx),
+            stop_on_ctrl_c,
+        )
+    })?;
+    
+    info!("Recorded {} packets to {}", recorder.recorded(), output.display());
+    Ok(())
 }
 
+/// Address for `send --to`: HOST:PORT, or HOST with `default_port`
+fn resolve_target(to: &str, default_port: u16) -> Result<SocketAddr, Box<dyn std::error::Error>> {
+    let mut addrs = match to.to_socket_addrs() {
+        Ok(addrs) => addrs,
+        Err(_) => (to, default_port)
+            .to_socket_addrs()
+            .map_err(|e| format!("cannot resolve '{}': {}", to, e))?,
+    };
+    addrs.next().ok_or_else(|| format!("'{}' has no address", to).into())
+}
 
-fn test_led_functionality(continuous: bool) -> DR2G27Result {
-    println!("# Looking for G27 for LED test");
-    let hid = HidApi::new()?;
-    
-    if !device_connected(&hid) {
-        println!("# Error: G27 not found. Please connect your G27 racing wheel.");
-        return Ok(());
+/// `send --to` destination: HOST[:PORT] with an optional =FORMAT, raw packets without one
+fn parse_destination(to: &str, default_port: u16) -> Result<(SocketAddr, ForwardFormat), Box<dyn std::error::Error>> {
+    let (host, format) = match to.rsplit_once('=') {
+        Some((host, format)) => (host, format.parse()?),
+        None => (to, ForwardFormat::Raw),
+    };
+    Ok((resolve_target(host, default_port)?, format))
+}
+
+/// Relay the packets received on `listen_addr` to each destination in `to`, e.g. from the
+/// gaming PC to the machine the wheel is plugged into, in the format the destination asks for
+fn run_send_command(
+    game_type: GameType,
+    auto_detect: bool,
+    listen_addr: SocketAddr,
+    to: &[String],
+    dump_unknown: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let destinations = to
+        .iter()
+        .map(|to| parse_destination(to, listen_addr.port()))
+        .collect::<Result<Vec<_>, _>>()?;
+    for (target, _) in &destinations {
+        if target.port() == listen_addr.port() && (target.ip().is_loopback() || target.ip() == listen_addr.ip()) {
+            let hint = "send to the machine running the bridge";
+            return Err(format!("{} is where the game's packets arrive; {}", target, hint).into());
+        }
     }
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
     
-    let device = hid.open(G27_VID, G27_PID)?;
-    println!("# G27 connected - Starting LED test");
-    
-    if continuous {
-        println!("# Running continuous LED test (Press Ctrl+C to stop)");
-        loop {
-            run_led_test_cycle(&device)?;
+    runtime.block_on(async {
+        let source = AsyncUdpSource::bind(listen_addr)
+            .await
+            .map_err(|source| DR2G27Error::BindFailed { addr: listen_addr, source })?;
+        let bus = TelemetryBus::new();
+        let game = watch::Sender::new(game_type);
+        let (control_tx, control_rx) = watch::channel(LoopControl::Run);
+        let mut forwarders = tokio::task::JoinSet::new();
+        for &(target, format) in &destinations {
+            let local_ip = match target {
+                SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+            };
+            let socket = tokio::net::UdpSocket::bind(SocketAddr::new(local_ip, 0)).await?;
+            let frames = bus.subscribe("forwarder");
+            let control = control_rx.clone();
+            forwarders.spawn(async move { forward_frames(&socket, target, format, frames, control).await });
         }
-    } else {
-        println!("# Running single LED test cycle");
-        run_led_test_cycle(&device)?;
-        // Turn off all LEDs at the end
-        device.write(&[0x00, 0xF8, 0x12, 0, 0x00, 0x00, 0x00, 0x01])?;
-        println!("# LED test completed");
+        let targets: Vec<_> = destinations.iter().map(|(target, format)| format!("{} ({})", target, format)).collect();
+        info!("Forwarding telemetry from {} to {} - press Ctrl+C to stop", listen_addr, targets.join(", "));
+        
+        // Only packets the game's parser accepts are forwarded, so stray traffic stays local
+        let publish = async {
+            if auto_detect {
+                publish_frames_detecting(&source, &game, dump_unknown, &bus, control_rx.clone()).await
+            } else {
+                publish_frames(&source, game_type, dump_unknown, &bus, control_rx.clone()).await
+            }
+        };
+        let stop_on_ctrl_c = async {
+            let _ = tokio::signal::ctrl_c().await;
+            control_tx.send_replace(LoopControl::Stop);
+            Ok(())
+        };
+        let forward = async {
+            while let Some(forwarded) = forwarders.join_next().await {
+                forwarded.unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))?;
+            }
+            Ok(())
+        };
+        tokio::try_join!(publish, forward, stop_on_ctrl_c)?;
+        Ok::<_, Box<dyn std::error::Error>>(())
+    })
+}
+
+/// Whether `file` is a pcap or pcapng capture rather than a session recording, by its extension
+fn is_capture(file: &Path) -> bool {
+    file.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| [CAPTURE_EXTENSION, PCAP_EXTENSION].iter().any(|c| extension.eq_ignore_ascii_case(c)))
+}
+
+/// Replay a session recording, or the packets to `port` in a capture as `game` (detected from
+/// their sizes without one)
+fn run_replay_command(
+    file: &Path,
+    speed: f32,
+    style: LedStyle,
+    game: Option<GameType>,
+    port: u16,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if !(speed.is_finite() && speed > 0.0) {
+        return Err(format!("invalid speed {} - use a positive number such as 0.5 or 2", speed).into());
+    }
+    if !is_capture(file) {
+        return replay_on_wheel(&mut RecordingReader::open(file)?, file, speed, style);
     }
     
+    let mut capture = CaptureReader::open(file, port)?;
+    if let Some(game_type) = game {
+        capture = capture.with_game(game_type);
+    }
+    info!("Replaying the UDP packets to port {} from the capture", port);
+    replay_on_wheel(&mut capture, file, speed, style)?;
+    if capture.matched() == 0 {
+        warn!("{} holds no UDP packets to port {}; pick the port the game sent to with --port", file.display(), port);
+    }
     Ok(())
 }
 
-fn run_led_test_cycle(device: &HidDevice) -> DR2G27Result {
-    // LED states: 0=off, 1=green1, 3=green1+2, 7=green1+2+orange1, 15=green1+2+orange1+2, 31=all
-    println!("# Testing LED progression: Off -> Green -> Orange -> Red");
-    
-    // Progressive LED activation
-    let led_states = vec![0, 1, 3, 7, 15, 31];
-    for state in &led_states {
-        device.write(&[0x00, 0xF8, 0x12, *state, 0x00, 0x00, 0x00, 0x01])?;
-        sleep(Duration::from_millis(500));
+fn replay_on_wheel(
+    recording: &mut impl ReplaySource,
+    file: &Path,
+    speed: f32,
+    style: LedStyle,
+) -> Result<(), Box<dyn std::error::Error>> {
+    info!("Looking for G27 for replay");
+    let hid = HidApi::new().map_err(DR2G27Error::HidInit)?;
+    if !device_connected(&hid) {
+        return Err("G27 not found. Please connect your G27 racing wheel.".into());
     }
+    let mut leds = LEDS::new(hid.open(G27_VID, G27_PID).map_err(DR2G27Error::hid_open)?);
+    leds.set_style(style);
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
     
-    println!("# Testing reverse LED progression: Red -> Orange -> Green -> Off");
+    runtime.block_on(async {
+        let bus = TelemetryBus::new();
+        let frames = bus.subscribe("wheel LEDs");
+        let (control_tx, control_rx) = watch::channel(LoopControl::Run);
+        info!("Replaying {} at {}x speed - press Ctrl+C to stop", file.display(), speed);
+        
+        // The LEDs stop with the replay, whether it ended or was interrupted
+        let replay_control = control_rx.clone();
+        let replay = async move {
+            let result = tokio::select! {
+                result = replay_frames(recording, speed, &bus, replay_control) => result,
+                _ = tokio::signal::ctrl_c() => Ok(()),
+            };
+            control_tx.send_replace(LoopControl::Stop);
+            result
+        };
+        tokio::try_join!(replay, drive_leds(&mut leds, frames, control_rx))
+    })?;
     
-    // Reverse LED deactivation
-    for state in led_states.iter().rev() {
-        device.write(&[0x00, 0xF8, 0x12, *state, 0x00, 0x00, 0x00, 0x01])?;
-        sleep(Duration::from_millis(500));
+    leds.clear()?;
+    info!("Replay finished");
+    Ok(())
+}
+
+fn run_simulate_command(
+    game_type: GameType,
+    target: SocketAddr,
+    pattern: Pattern,
+    rate: u32,
+) -> Result<(), Box<dyn std::error::Error>> {
+    if rate == 0 {
+        return Err("rate must be at least 1 packet per second".into());
     }
+    let local_ip = match target {
+        SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+        SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+    };
+    let socket = UdpSocket::bind(SocketAddr::new(local_ip, 0))?;
+    let simulator = Simulator::new(game_type, pattern);
+    let interval = Duration::from_secs(1) / rate;
     
-    Ok(())
+    info!(
+        "Sending simulated {} telemetry ({}) to {} at {} packets/s - press Ctrl+C to stop",
+        game_type.parser().game_name(),
+        pattern,
+        target,
+        rate
+    );
+    let started = Instant::now();
+    loop {
+        socket.send_to(&simulator.packet_at(started.elapsed()), target)?;
+        sleep(interval);
+    }
 }
 
 fn main() {
     let cli = Cli::parse();
+    logging::init();
+    crash::install();
+    logging::set_verbosity(cli.verbose);
+    let bounds = RunBounds {
+        duration: cli.duration,
+        packets: cli.packets,
+    };
+    let led_overrides = LedOverrides {
+        profile: cli.led_profile,
+        pattern: cli.pattern,
+    };
+    let wheel = WheelOptions {
+        require_wheel: cli.require_wheel,
+        led_overrides,
+    };
+    
+    // Keep stdout for the JSON document or completion script
+    if matches!(
+        cli.command,
+        Some(
+            Commands::ListGames { output: OutputFormat::Json }
+                | Commands::Schema { output: OutputFormat::Json }
+                | Commands::ListDevices { output: OutputFormat::Json }
+                | Commands::Status { output: OutputFormat::Json }
+                | Commands::Monitor { output: OutputFormat::Json }
+                | Commands::Completions { .. }
+        )
+    ) {
+        logging::use_stderr_only();
+    }
+    
+    // Must happen before anything touches the settings file
+    if cli.portable {
+        paths::enable_portable_mode();
+    }
+    if paths::is_portable() {
+        if let Ok(dir) = paths::data_dir() {
+            info!("Portable mode: storing data in {:?}", dir);
+        }
+    }
     
     // Handle subcommands first
     match cli.command {
         Some(Commands::Test { continuous }) => {
-            match test_led_functionality(continuous) {
+            match test_led_functionality(continuous, bounds.duration) {
                 Ok(_) => {},
                 Err(e) => {
-                    eprintln!("# LED test failed: {:?}", e);
-                    std::process::exit(1);
+                    error!("LED test failed: {:?}", e);
+                    ExitCode::for_error(&e).exit();
+                }
+            }
+            return;
+        }
+        Some(Commands::ListGames { output }) => {
+            if let Err(e) = list_games(output) {
+                error!("Listing games failed: {}", e);
+                ExitCode::for_error(&*e).exit();
+            }
+            return;
+        }
+        Some(Commands::Schema { output }) => {
+            let games = match cli.game.as_deref().and_then(GameType::parse_game_name) {
+                Some(game) => vec![game],
+                None => GameType::ALL.to_vec(),
+            };
+            if let Err(e) = print_schema(&games, output) {
+                error!("Printing the schema failed: {}", e);
+                ExitCode::for_error(&*e).exit();
+            }
+            return;
+        }
+        Some(Commands::Completions { shell }) => {
+            clap_complete::generate(shell, &mut Cli::command(), "g27-led-bridge", &mut std::io::stdout());
+            return;
+        }
+        Some(Commands::ListDevices { output }) => {
+            if let Err(e) = run_list_devices_command(output) {
+                error!("Listing devices failed: {}", e);
+                ExitCode::for_error(&*e).exit();
+            }
+            return;
+        }
+        Some(Commands::Replay { file, speed }) => {
+            let settings = AppSettings::load();
+            let style = led_overrides.apply(settings.leds);
+            // Packets in a capture are not marked with their game; --game overrides detecting it
+            let game = cli.game.as_deref().and_then(GameType::parse_game_name);
+            let port = settings.get_effective_port(cli.port);
+            if let Err(e) = run_replay_command(&file, speed, style, game, port) {
+                error!("Replay failed: {}", e);
+                ExitCode::for_error(&*e).exit();
+            }
+            return;
+        }
+        Some(Commands::Doctor { report }) => {
+            // Opens the configured log file, which the report includes
+            AppSettings::load();
+            let Some(path) = report else {
+                print!("{}", diagnostics::summary());
+                return;
+            };
+            match diagnostics::write_report(path.as_deref()) {
+                Ok(path) => info!("Diagnostic report written to {} - attach it to your bug report", path.display()),
+                Err(e) => {
+                    error!("Writing the diagnostic report failed: {}", e);
+                    ExitCode::for_error(&*e).exit();
                 }
             }
             return;
         }
-        None => {}
+        Some(Commands::Dashboard) => {
+            if let Err(e) = dashboard::run() {
+                error!("Dashboard failed: {}", e);
+                ExitCode::Failure.exit();
+            }
+            return;
+        }
+        Some(Commands::Config { action }) => {
+            if let Err(e) = run_config_command(action) {
+                error!("Config command failed: {}", e);
+                ExitCode::for_error(&*e).exit();
+            }
+            return;
+        }
+        Some(Commands::Service { action }) => {
+            if let Err(e) = run_service_command(action) {
+                error!("Service command failed: {}", e);
+                ExitCode::for_error(&*e).exit();
+            }
+            return;
+        }
+        Some(
+            Commands::Monitor { .. }
+            | Commands::CaptureOffsets { .. }
+            | Commands::Status { .. }
+            | Commands::Record { .. }
+            | Commands::Send { .. }
+            | Commands::Simulate { .. },
+        )
+        | None => {}
     }
     
+    // A second tray app would fight the first one over the wheel and the port
+    let _instance_lock = match cli.command {
+        None => match instance::acquire() {
+            Some(lock) => Some(lock),
+            None => {
+                hand_off_to_running_instance(&cli);
+                return;
+            }
+        },
+        Some(_) => None,
+    };
+    
     // Load settings
     let mut settings = AppSettings::load();
     
+    // Keep the startup entry pointing at this executable in case it was moved
+    if settings.autostart {
+        autostart::sync(true);
+    }
+    
+    // Switch profile first so --game and --port can still override it
+    if let Some(ref profile) = cli.profile {
+        if let Err(e) = settings.apply_profile(profile) {
+            error!("{}", e);
+            ExitCode::InvalidSettings.exit();
+        }
+    }
+    
     // Override settings with CLI arguments if provided
     if let Some(ref game_str) = cli.game {
         match GameType::parse_game_name(game_str) {
+            None if game_str.eq_ignore_ascii_case(AUTO_DETECT_GAME) => {
+                settings.set_auto_detect_game(true);
+            }
             Some(game) => {
                 settings.set_game_type(game);
             }
             None => {
-                println!("# Error: Unknown game '{}'. Supported games: dirt-rally-2, forza-horizon-5", game_str);
-                println!("# Use --help for more information");
-                return;
+                let names: Vec<_> = GameType::ALL.iter().map(|game| game.aliases()[0]).collect();
+                error!("Unknown game '{}'. Supported games: {}", game_str, names.join(", "));
+                info!("Run `g27-led-bridge list-games` for every accepted name");
+                ExitCode::InvalidSettings.exit();
             }
         }
     }
     
+    let integrations = settings.integrations.enabled_names();
+    if !integrations.is_empty() {
+        info!("Enabled integrations: {}", integrations.join(", "));
+    }
+    
     let port = settings.get_effective_port(cli.port);
+    let bind_address = settings.get_effective_bind_address(cli.bind);
+    
+    if let Some(Commands::Status { output }) = cli.command {
+        if let Err(e) = run_status_command(&settings, SocketAddr::new(bind_address, port), output) {
+            error!("Status failed: {}", e);
+            ExitCode::for_error(&*e).exit();
+        }
+        return;
+    }
+    
+    if let Some(Commands::Monitor { output }) = cli.command {
+        let listen_addr = SocketAddr::new(bind_address, port);
+        match run_monitor_command(settings.game_type, listen_addr, output, bounds, cli.dump_unknown) {
+            Ok(Some(outcome)) => exit_with(outcome),
+            Ok(None) => {}
+            Err(e) => {
+                error!("Monitoring failed: {}", e);
+                ExitCode::for_error(&*e).exit();
+            }
+        }
+        return;
+    }
+    
+    if let Some(Commands::CaptureOffsets { changing, min, max }) = cli.command {
+        let filter = ExplorerFilter { changing, min, max };
+        if let Err(e) = run_capture_offsets_command(SocketAddr::new(bind_address, port), filter) {
+            error!("Capturing packets failed: {}", e);
+            ExitCode::for_error(&*e).exit();
+        }
+        return;
+    }
+    
+    if let Some(Commands::Record { output, frames }) = cli.command {
+        let listen_addr = SocketAddr::new(bind_address, port);
+        let dump_unknown = cli.dump_unknown;
+        if let Err(e) = run_record_command(settings.game_type, listen_addr, &output, frames.as_deref(), dump_unknown) {
+            error!("Recording failed: {}", e);
+            ExitCode::for_error(&*e).exit();
+        }
+        return;
+    }
     
-    run(settings.game_type, port, cli.console, cli.require_wheel);
+    if let Some(Commands::Send { to }) = cli.command {
+        let listen_addr = SocketAddr::new(bind_address, port);
+        let auto_detect = settings.auto_detect_game;
+        if let Err(e) = run_send_command(settings.game_type, auto_detect, listen_addr, &to, cli.dump_unknown) {
+            error!("Forwarding failed: {}", e);
+            ExitCode::for_error(&*e).exit();
+        }
+        return;
+    }
+    
+    if let Some(Commands::Simulate { pattern, rate }) = cli.command {
+        // A bridge listening on all interfaces is reachable on loopback
+        let target_ip = match bind_address {
+            IpAddr::V4(ip) if ip.is_unspecified() => IpAddr::V4(Ipv4Addr::LOCALHOST),
+            IpAddr::V6(ip) if ip.is_unspecified() => IpAddr::V6(Ipv6Addr::LOCALHOST),
+            ip => ip,
+        };
+        if let Err(e) = run_simulate_command(settings.game_type, SocketAddr::new(target_ip, port), pattern, rate) {
+            error!("Simulation failed: {}", e);
+            ExitCode::for_error(&*e).exit();
+        }
+        return;
+    }
+    
+    // Reopen the wheel after the PC wakes from sleep; the service gets these events from Windows instead
+    let _power = power::watch();
+    if cli.headless {
+        settings.port = port;
+        settings.bind_address = bind_address;
+        run_headless(settings, wheel, bounds, cli.capture, cli.dump_unknown);
+        return;
+    }
+    let listen_addr = SocketAddr::new(bind_address, port);
+    run(settings.game_type, listen_addr, cli.console, wheel, bounds, cli.capture, cli.dump_unknown);
 }
 
-fn run(initial_game_type: GameType, initial_port: u16, _keep_console: bool, require_wheel: bool) {
-    use std::sync::mpsc;
-    use std::sync::atomic::{AtomicBool, Ordering};
+/// Pass this launch's --profile, --game, --port and --bind to the tray app that is already running
+fn hand_off_to_running_instance(cli: &Cli) {
+    if !cli.console && !cli.headless {
+        hide_console_window();
+    }
+    let game = cli.game.as_deref().and_then(GameType::parse_game_name);
+    let auto_detect_game = cli.game.as_deref().is_some_and(|game| game.eq_ignore_ascii_case(AUTO_DETECT_GAME));
+    if cli.profile.is_none() && game.is_none() && !auto_detect_game && cli.port.is_none() && cli.bind.is_none() {
+        info!("G27 LED Bridge is already running");
+        show_info_dialog(
+            "G27 LED Bridge is already running.\n\nUse its icon in the notification area to change games and settings.",
+        );
+        return;
+    }
     
-    if !_keep_console {
+    let request = Request::Apply {
+        profile: cli.profile.clone(),
+        game,
+        auto_detect_game,
+        port: cli.port,
+        bind: cli.bind,
+    };
+    let result = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .and_then(|runtime| runtime.block_on(ipc::send_request(&request)));
+    match result {
+        Ok(Some(Ok(()))) => info!("G27 LED Bridge is already running - passed the new settings to it"),
+        Ok(Some(Err(e))) => {
+            error!("The running G27 LED Bridge rejected the new settings: {}", e);
+            ExitCode::InvalidSettings.exit();
+        }
+        Ok(None) => {
+            error!("G27 LED Bridge is already running but does not answer; exit it and try again");
+            ExitCode::Failure.exit();
+        }
+        Err(e) => {
+            error!("Cannot pass the new settings to the running G27 LED Bridge: {}", e);
+            ExitCode::Failure.exit();
+        }
+    }
+}
+
+/// Apply `[exit] console_close` to the console window; returns whether it minimizes to the tray
+fn apply_console_close(settings: &AppSettings) -> bool {
+    let to_tray = settings.exit.console_close == ConsoleClose::MinimizeToTray;
+    console::set_close_enabled(!to_tray);
+    to_tray
+}
+
+fn run(
+    initial_game_type: GameType,
+    initial_listen_addr: SocketAddr,
+    keep_console: bool,
+    wheel: WheelOptions,
+    bounds: RunBounds,
+    capture: Option<PathBuf>,
+    dump_unknown: bool,
+) {
+    if !keep_console {
         hide_console_window();
     }
     
-    println!("# Starting G27 LED Bridge in system tray mode");
-    println!("# Right-click system tray icon to change games or exit");
+    info!("Starting G27 LED Bridge in system tray mode");
+    info!("Right-click system tray icon to change games or exit");
     
     // Create system tray
-    let tray = match SystemTray::new() {
+    let tray = match SystemTray::new(keep_console) {
         Ok(tray) => tray,
         Err(e) => {
-            eprintln!("Failed to create system tray: {}", e);
-            println!("# Falling back to console mode");
-            run(initial_game_type, initial_port, false, require_wheel);
+            error!("Failed to create system tray: {}", e);
+            info!("Falling back to console mode");
+            run(initial_game_type, initial_listen_addr, false, wheel, bounds, capture, dump_unknown);
             return;
         }
     };
     
-    // Create shared flags and channels
-    let exit_flag = Arc::new(AtomicBool::new(false));
-    let (status_tx, status_rx) = mpsc::channel::<String>();
-    let (wheel_status_tx, wheel_status_rx) = mpsc::channel::<(bool, Option<String>)>();
+    // The tray loads the saved settings; keep the flag/environment overrides in effect
+    if let Ok(mut settings) = tray.settings.lock() {
+        settings.game_type = initial_game_type;
+        settings.port = initial_listen_addr.port();
+        settings.bind_address = initial_listen_addr.ip();
+    }
+    tray.update_menu_display();
+    let console_to_tray = Cell::new(keep_console && apply_console_close(&tray.get_current_settings()));
     
-    // Start the bridge in a background thread with dynamic settings
-    let exit_flag_clone = Arc::clone(&exit_flag);
-    let tray_settings_clone = tray.settings.clone();
-    let _bridge_handle = thread::spawn(move || {
-        let mut current_game_type = initial_game_type;
-        let mut current_port = initial_port;
-        
-        loop {
-            if exit_flag_clone.load(Ordering::Relaxed) {
-                break;
-            }
-            
-            // Check for settings changes
-            if let Ok(settings) = tray_settings_clone.lock() {
-                let new_game_type = settings.game_type;
-                let new_port = settings.port;
-                
-                if new_game_type != current_game_type || new_port != current_port {
-                    current_game_type = new_game_type;
-                    current_port = new_port;
-                    let parser = new_game_type.parser();
-                    let _ = status_tx.send(format!("Switched to {} on port {}", parser.game_name(), new_port));
-                }
-            }
-            
-            match connect_and_bridge(current_game_type, current_port, Some(&wheel_status_tx), require_wheel) {
-                Err(error) => {
-                    let msg = match error {
-                        DR2G27Error::DR2UdpSocketError => {
-                            let _ = wheel_status_tx.send((false, Some("UDP Error".to_string())));
-                            "UDP Socket Error - retrying in 5 seconds...".to_string()
-                        }
-                        DR2G27Error::G27ConnectionLostError => {
-                            let _ = wheel_status_tx.send((false, Some("Disconnected".to_string())));
-                            "G27 connection lost - retrying in 2 seconds...".to_string()
-                        }
-                    };
-                    let _ = status_tx.send(msg);
-                    
-                    // Sleep with periodic exit checks
-                    for _ in 0..50 { // Check every 100ms for 5 seconds max
-                        if exit_flag_clone.load(Ordering::Relaxed) {
-                            return;
-                        }
-                        sleep(Duration::from_millis(100));
-                    }
-                }
-                Ok(()) => {
-                    let _ = status_tx.send("Bridge stopped unexpectedly, restarting...".to_string());
-                    sleep(Duration::from_secs(1));
-                }
-            }
+    // The service already drives the wheel and holds the port; only edit its settings
+    let service_running = service::is_running();
+    if service_running {
+        info!("The {} service is running - the tray only changes its settings", service::SERVICE_NAME);
+        tray.show_service_mode();
+        if capture.is_some() {
+            warn!("Packets cannot be captured while the service holds the port; stop the service first");
         }
+    } else if let Some(path) = capture {
+        tray.capture.send_replace(Some(path));
+    }
+    
+    // The tray loop sleeps until the bridge sends it something
+    let event_loop = create_event_loop();
+    let proxy = event_loop.create_proxy();
+    let wake_tray = Waker::new(move || {
+        let _ = proxy.send_event(());
+    });
+    
+    // Create shared signals and channels
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let (status_tx, status_rx) = waker::channel::<StatusUpdate>(&wake_tray);
+    let (state_tx, state_rx) = waker::channel::<BridgeState>(&wake_tray);
+    let (live_tx, live_rx) = waker::channel::<Option<LiveTelemetry>>(&wake_tray);
+    let (mirror_tx, mirror_rx) = waker::channel::<Option<u8>>(&wake_tray);
+    let (bounds_tx, bounds_rx) = waker::channel::<BoundOutcome>(&wake_tray);
+    let reports = BridgeReports {
+        status_tx,
+        lifecycle: LifecycleReporter::new(state_tx),
+        live_tx,
+        mirror_tx,
+        bounds_tx,
+    };
+    let recording = tray.recording.clone();
+    let capture = tray.capture.clone();
+    let signals = BridgeSignals {
+        settings: tray.settings.clone(),
+        settings_changed: tray.settings_changed.subscribe(),
+        notify_settings_changed: tray.settings_changed.clone(),
+        paused: tray.paused.subscribe(),
+        pause_control: tray.paused.clone(),
+        shutdown: shutdown_rx,
+    };
+    
+    // Start the bridge on its own async runtime in a background thread
+    let bridge_handle = (!service_running).then(|| {
+        thread::spawn(move || {
+            let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    error!("Failed to start bridge runtime: {}", e);
+                    return;
+                }
+            };
+            runtime.block_on(run_bridge(signals, reports, recording, capture, wheel, bounds, dump_unknown));
+        })
     });
     
     // Run the event loop for system tray
-    let event_loop = create_event_loop();
-    let _ = event_loop.run(move |event, elwt| {
-        elwt.set_control_flow(winit::event_loop::ControlFlow::Wait);
+    let finished = Cell::new(None);
+    
+    // Ctrl+C or closing the console exits like the tray menu, so the LEDs are turned off
+    if keep_console {
+        let should_exit = tray.should_exit.clone();
+        let wake = wake_tray.clone();
+        console::handle_close(move || {
+            if let Ok(mut should_exit) = should_exit.lock() {
+                *should_exit = true;
+            }
+            wake.wake();
+        });
+    }
+    
+    let _ = event_loop.run(|event, elwt| {
+        // Bridge reports wake the loop; the idle poll only catches changes nothing sends
+        elwt.set_control_flow(winit::event_loop::ControlFlow::wait_duration(TRAY_IDLE_POLL));
         
         if let winit::event::Event::WindowEvent { event: WindowEvent::CloseRequested, .. } = event {
-            exit_flag.store(true, Ordering::Relaxed);
+            shutdown_tx.send_replace(true);
             elwt.exit();
         }
         
-        // Check for status messages
-        while let Ok(status) = status_rx.try_recv() {
-            println!("# {}", status);
+        // Show bridge state changes (already logged by the lifecycle)
+        while let Ok(state) = state_rx.try_recv() {
+            tray.update_lifecycle(&state);
+        }
+        
+        // Show the latest telemetry in the tooltip
+        if let Some(live) = live_rx.try_iter().last() {
+            tray.update_live_telemetry(live);
         }
+        if let Some(mirror) = mirror_rx.try_iter().last() {
+            tray.update_led_mirror(mirror);
+        }
+        tray.refresh_icon_theme();
+        
+        tray.update_recording_display();
+        tray.update_capture_display();
+        tray.update_pause_display();
         
-        // Check for wheel status updates
-        while let Ok((connected, error_msg)) = wheel_status_rx.try_recv() {
-            tray.update_wheel_status(connected, error_msg.as_deref());
+        // Show component status updates (already logged by the supervisor)
+        while let Ok((component, status)) = status_rx.try_recv() {
+            tray.update_component_status(component, &status);
         }
         
+        // Surface settings problems from startup, reloads or watcher edits
+        tray.show_settings_report();
+        
+        // Mark the About entry once an update check found a newer release
+        tray.show_available_update();
+        
         // Check for settings changes (menu)
         if tray.settings_changed() {
-            println!("# Settings changed - bridge will update automatically");
+            info!("Settings changed - bridge will update automatically");
             tray.update_menu_display();
+            if keep_console {
+                console_to_tray.set(apply_console_close(&tray.get_current_settings()));
+            }
+        }
+        
+        // A minimized console goes to the tray while closing it is disabled
+        if console_to_tray.get() {
+            console::hide_if_minimized();
         }
         
         // Check if we should exit
         if tray.should_exit() {
-            exit_flag.store(true, Ordering::Relaxed);
+            shutdown_tx.send_replace(true);
+            elwt.exit();
+        }
+        
+        // A bounded run (--duration/--packets) ends like Exit
+        if let Ok(outcome) = bounds_rx.try_recv() {
+            finished.set(Some(outcome));
+            shutdown_tx.send_replace(true);
             elwt.exit();
         }
     });
+    
+    // Let the bridge turn the LEDs off before the process ends, without hanging
+    // on a wheel that stopped responding
+    if let Some(bridge_handle) = bridge_handle {
+        let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+        while !bridge_handle.is_finished() && Instant::now() < deadline {
+            sleep(Duration::from_millis(10));
+        }
+        if bridge_handle.is_finished() {
+            let _ = bridge_handle.join();
+        } else {
+            warn!("Bridge did not stop within {:?} - exiting anyway", SHUTDOWN_TIMEOUT);
+        }
+    }
+    console::finished();
+    
+    if let Some(outcome) = finished.get() {
+        exit_with(outcome);
+    }
+}
+
+/// Where the bridge reports what it is doing; the tray shows it, the service only logs it
+struct BridgeReports {
+    status_tx: WakingSender<StatusUpdate>,
+    lifecycle: LifecycleReporter,
+    /// Telemetry summaries for the tray tooltip
+    live_tx: WakingSender<Option<LiveTelemetry>>,
+    /// LED bar for the tray icon mirror
+    mirror_tx: WakingSender<Option<u8>>,
+    /// End of a run bounded by --duration or --packets
+    bounds_tx: WakingSender<BoundOutcome>,
+}
+
+/// Bridge task: runs the telemetry listener and wheel LEDs as supervised components until shutdown
+async fn run_bridge(
+    signals: BridgeSignals,
+    reports: BridgeReports,
+    recording: Arc<watch::Sender<Option<PathBuf>>>,
+    capture: Arc<watch::Sender<Option<PathBuf>>>,
+    wheel: WheelOptions,
+    bounds: RunBounds,
+    dump_unknown: bool,
+) {
+    let BridgeReports {
+        status_tx,
+        lifecycle,
+        live_tx,
+        mirror_tx,
+        bounds_tx,
+    } = reports;
+    // Held until the bridge stops; the setting applies from the next start
+    let _low_latency = signals.settings.lock().is_ok_and(|settings| settings.low_latency).then(LowLatency::enter);
+    // Outlives component restarts so consumers keep their subscription across game/port changes
+    let bus = TelemetryBus::new();
+    let shutdown = signals.shutdown.clone();
+    // LED bar as the wheel shows it, for status queries
+    let virtual_leds = Cell::new(0);
+    let sender_filter = Arc::new(SenderFilter::default());
+    let packet_stats = Cell::new(PacketStatsSummary::default());
+    
+    let telemetry = TelemetryListener {
+        signals: signals.clone(),
+        bus: bus.clone(),
+        lifecycle: lifecycle.clone(),
+        sender_filter: sender_filter.clone(),
+        dump_unknown,
+    };
+    let osc = OscOutput {
+        signals: signals.clone(),
+        bus: bus.clone(),
+        led_overrides: wheel.led_overrides,
+    };
+    let mqtt = MqttOutput {
+        signals: signals.clone(),
+        bus: bus.clone(),
+        led_overrides: wheel.led_overrides,
+    };
+    let websocket = WebSocketStream {
+        signals: signals.clone(),
+        bus: bus.clone(),
+        virtual_leds: &virtual_leds,
+    };
+    let mdns = MdnsAdvertiser {
+        signals: signals.clone(),
+    };
+    let status_signals = signals.clone();
+    let capture_settings = signals.settings.clone();
+    let session_settings = signals.settings.clone();
+    let mirror_signals = signals.clone();
+    let led_overrides = wheel.led_overrides;
+    let wheel = WheelLeds {
+        signals,
+        bus: bus.clone(),
+        lifecycle: lifecycle.clone(),
+        require_wheel: wheel.require_wheel,
+        led_overrides: wheel.led_overrides,
+    };
+    
+    tokio::join!(
+        supervise(telemetry, shutdown.clone(), status_tx.clone()),
+        supervise(wheel, shutdown.clone(), status_tx.clone()),
+        supervise(osc, shutdown.clone(), status_tx.clone()),
+        supervise(mqtt, shutdown.clone(), status_tx.clone()),
+        supervise(websocket, shutdown.clone(), status_tx.clone()),
+        supervise(mdns, shutdown.clone(), status_tx),
+        track_telemetry_flow(bus.clone(), lifecycle.clone(), shutdown.clone()),
+        stop_at_bounds(bus.clone(), bounds, bounds_tx, shutdown.clone()),
+        measure_packets(bus.clone(), &packet_stats, shutdown.clone()),
+        track_sessions(bus.clone(), session_settings, led_overrides, shutdown.clone()),
+        serve_status(bus.clone(), status_signals, lifecycle, &virtual_leds, &sender_filter, &packet_stats),
+        summarize_telemetry(bus.clone(), &packet_stats, live_tx, shutdown.clone()),
+        mirror_leds(bus.clone(), mirror_signals, led_overrides, mirror_tx, &virtual_leds),
+        record_on_request(bus.clone(), recording, shutdown.clone()),
+        capture_on_request(bus, capture, capture_settings, shutdown),
+    );
 }
 
 #[test]
+#[ignore = "needs a G27 connected; run with `cargo test -- --ignored`"]
 fn test_device_leds() -> DR2G27Result {
-    let device = HidApi::new()?.open(G27_VID, G27_PID)?;
+    let device = HidApi::new()
+        .map_err(DR2G27Error::HidInit)?
+        .open(G27_VID, G27_PID)
+        .map_err(DR2G27Error::hid_open)?;
 
     for state in vec![0, 1, 3, 7, 15, 31] {
-        device.write(&[0x00, 0xF8, 0x12, state, 0x00, 0x00, 0x00, 0x01])?;
+        write_g27_leds(&device, state)?;
         sleep(Duration::from_millis(200));
     }
 
     sleep(Duration::from_secs(1));
 
     for state in vec![31, 15, 7, 3, 1, 0] {
-        device.write(&[0x00, 0xF8, 0x12, state, 0x00, 0x00, 0x00, 0x01])?;
+        write_g27_leds(&device, state)?;
         sleep(Duration::from_millis(200));
     }
 
@@ -1,8 +1,43 @@
 pub mod common {
-    pub mod leds;
-    pub mod rpm;
+    // Telemetry, RPM and LED logic live in the core crate; re-exported at their old paths
+    pub use g27_led_bridge_core::{capture, leds, recording, rpm, telemetry, util};
+
+    pub mod autostart;
+    pub mod config_keys;
+    pub mod console;
+    pub mod crash;
+    pub mod dashboard;
+    pub mod diagnostics;
+    pub mod exit_code;
+    pub mod grpc;
+    pub mod hotkey;
+    pub mod http_api;
+    pub mod i18n;
+    pub mod instance;
+    pub mod integrations;
+    pub mod ipc;
+    pub mod latency;
+    pub mod lifecycle;
+    pub mod log_viewer;
+    pub mod logging;
+    pub mod mdns;
+    pub mod migrations;
+    pub mod mqtt;
+    pub mod notifications;
+    pub mod osc;
+    pub mod outputs;
+    pub mod packet_explorer;
+    pub mod paths;
+    pub mod power;
+    pub mod service;
     pub mod settings;
+    pub mod settings_dialog;
+    pub mod settings_template;
+    pub mod supervisor;
     pub mod systray;
-    pub mod telemetry;
-    pub mod util;
+    pub mod updates;
+    pub mod validation;
+    pub mod waker;
+    pub mod watcher;
+    pub mod websocket;
 }